@@ -306,3 +306,50 @@ pub async fn export_search_to_jsonl(_query: &str, results: &[agent_v_store::Sear
 
     Ok(lines.join("\n"))
 }
+
+/// Export a session's events to CSV, one row per event
+pub fn export_session_to_csv(events: &[agent_v_store::EventRow]) -> String {
+    let mut csv = csv_row(&["id", "kind", "role", "timestamp", "content"]);
+    for event in events {
+        csv.push_str(&csv_row(&[
+            &event.id,
+            &event.kind,
+            event.role.as_deref().unwrap_or_default(),
+            &event.timestamp,
+            event.content.as_deref().unwrap_or_default(),
+        ]));
+    }
+    csv
+}
+
+/// Export search results to CSV, one row per result; results without a snippet use the event content
+pub fn export_search_to_csv(results: &[agent_v_store::SearchResult]) -> String {
+    let mut csv = csv_row(&["event_id", "session_id", "kind", "rank", "snippet"]);
+    for result in results {
+        let snippet = result.snippet.as_deref().or(result.event.content.as_deref());
+        csv.push_str(&csv_row(&[
+            &result.event.id,
+            &result.event.session_id,
+            &result.event.kind,
+            &format!("{:.4}", result.rank),
+            snippet.unwrap_or_default(),
+        ]));
+    }
+    csv
+}
+
+/// A CSV record terminated by CRLF, per RFC 4180
+fn csv_row(fields: &[&str]) -> String {
+    let mut row = fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",");
+    row.push_str("\r\n");
+    row
+}
+
+/// Quote a field when it holds a comma, quote, or line break, doubling embedded quotes
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -1,19 +1,31 @@
 use super::models::IngestResult;
 
-use agent_v_adapters::{ClaudeAdapter, CodexAdapter, CrushAdapter, OpenCodeAdapter};
 use agent_v_core::Source;
+use agent_v_ingest::{CancellationToken, discover_external_ids, ingest_one};
 use agent_v_store::Database;
 
-/// Ingest sessions from a single source
-pub async fn ingest_single_source(db: &Database, source: Source) -> Result<IngestResult, String> {
+/// Ingest sessions from a single source, stopping between sessions once `cancel` fires
+pub async fn ingest_single_source(
+    db: &Database, source: Source, cancel: &CancellationToken,
+) -> Result<IngestResult, String> {
     let start = std::time::Instant::now();
 
-    let (imported, failed) = match source {
-        Source::Claude => ingest_claude(db).await,
-        Source::Codex => ingest_codex(db).await,
-        Source::OpenCode => ingest_opencode(db).await,
-        Source::Crush => ingest_crush(db).await,
-    };
+    let mut known = db
+        .get_external_ids(&source.to_string())
+        .await
+        .map_err(|e| format!("Failed to list existing sessions: {}", e))?;
+    let before = known.len();
+
+    let outcome = ingest_one(source, db, &mut known, cancel).await;
+    let new = known.len() - before;
+
+    if outcome.cancelled {
+        log::info!(
+            "Ingest {} cancelled after {} sessions",
+            source,
+            outcome.imported + outcome.failed
+        );
+    }
 
     if let Ok(pruned) = db.prune_duplicate_sessions().await {
         if pruned > 0 {
@@ -23,99 +35,15 @@ pub async fn ingest_single_source(db: &Database, source: Source) -> Result<Inges
 
     let duration = start.elapsed().as_millis() as u64;
 
-    Ok(IngestResult { imported, failed, total: imported + failed, source: source.to_string(), duration_ms: duration })
-}
-
-/// Ingest from Claude source
-async fn ingest_claude(db: &Database) -> (usize, usize) {
-    let adapter = ClaudeAdapter::new();
-    let sessions = adapter.discover_sessions().await;
-    let mut imported = 0;
-    let mut failed = 0;
-
-    for session_file in sessions {
-        match adapter.parse_session(&session_file).await {
-            Ok((session, events)) => {
-                if db.insert_session_with_events(&session, &events).await.is_ok() {
-                    imported += 1;
-                } else {
-                    failed += 1;
-                }
-            }
-            Err(_) => failed += 1,
-        }
-    }
-
-    (imported, failed)
-}
-
-/// Ingest from Codex source
-async fn ingest_codex(db: &Database) -> (usize, usize) {
-    let adapter = CodexAdapter::new();
-    let sessions = adapter.discover_sessions().await;
-    let mut imported = 0;
-    let mut failed = 0;
-
-    for session_file in sessions {
-        match adapter.parse_session(&session_file).await {
-            Ok((session, events)) => {
-                if db.insert_session_with_events(&session, &events).await.is_ok() {
-                    imported += 1;
-                } else {
-                    failed += 1;
-                }
-            }
-            Err(_) => failed += 1,
-        }
-    }
-
-    (imported, failed)
-}
-
-/// Ingest from OpenCode source
-async fn ingest_opencode(db: &Database) -> (usize, usize) {
-    let adapter = OpenCodeAdapter::new();
-    let sessions = adapter.discover_sessions().await;
-    let mut imported = 0;
-    let mut failed = 0;
-
-    for session in sessions {
-        match adapter.parse_session(&session).await {
-            Ok((session, events)) => {
-                if db.insert_session_with_events(&session, &events).await.is_ok() {
-                    imported += 1;
-                } else {
-                    failed += 1;
-                }
-            }
-            Err(_) => failed += 1,
-        }
-    }
-
-    (imported, failed)
-}
-
-/// Ingest from Crush source
-async fn ingest_crush(db: &Database) -> (usize, usize) {
-    let adapter = CrushAdapter::new();
-    let sessions = adapter.discover_sessions().await;
-    let mut imported = 0;
-    let mut failed = 0;
-
-    for session_file in sessions {
-        match adapter.parse_session(&session_file).await {
-            Ok((session, events)) => {
-                if db.insert_session_with_events(&session, &events).await.is_ok() {
-                    imported += 1;
-                } else {
-                    failed += 1;
-                }
-            }
-            Err(_) => failed += 1,
-        }
-    }
-
-    (imported, failed)
+    Ok(IngestResult {
+        imported: outcome.imported,
+        failed: outcome.failed,
+        total: outcome.imported + outcome.failed,
+        new,
+        source: source.to_string(),
+        duration_ms: duration,
+        cancelled: outcome.cancelled,
+    })
 }
 
 /// Check if new sessions are available in any source
@@ -129,31 +57,12 @@ pub async fn check_new_sessions_available(db: &Database) -> Result<bool, String>
         .map(|s| (s.source, s.external_id))
         .collect();
 
-    let adapter = ClaudeAdapter::new();
-    for session_file in adapter.discover_sessions().await {
-        if !existing_ids.contains(&("claude".to_string(), session_file.session_id.clone())) {
-            return Ok(true);
-        }
-    }
-
-    let adapter = CodexAdapter::new();
-    for session_file in adapter.discover_sessions().await {
-        if !existing_ids.contains(&("codex".to_string(), session_file.session_id.clone())) {
-            return Ok(true);
-        }
-    }
-
-    let adapter = OpenCodeAdapter::new();
-    for session in adapter.discover_sessions().await {
-        if !existing_ids.contains(&("opencode".to_string(), session.id.clone())) {
-            return Ok(true);
-        }
-    }
-
-    let adapter = CrushAdapter::new();
-    for session_file in adapter.discover_sessions().await {
-        if !existing_ids.contains(&("crush".to_string(), session_file.session_id.clone())) {
-            return Ok(true);
+    for &source in Source::all() {
+        let source_name = source.to_string();
+        for external_id in discover_external_ids(source).await {
+            if !existing_ids.contains(&(source_name.clone(), external_id)) {
+                return Ok(true);
+            }
         }
     }
 
@@ -2,15 +2,40 @@ mod export;
 mod ingest;
 mod models;
 
-use agent_v_core::Source;
+use agent_v_core::{SessionId, Source, parse_duration};
+use agent_v_ingest::CancellationToken;
 use agent_v_store::SearchFacets as DbSearchFacets;
-use agent_v_store::{check_sources_health, Database};
-use chrono::{Duration, Utc};
+use agent_v_store::{Database, check_sources_health};
+use chrono::Utc;
 use std::str::FromStr;
 use tauri::State;
 
 pub use models::*;
 
+/// Cancellation shared by long-running commands (ingest, search) so the frontend can abort them
+#[derive(Default)]
+pub struct OperationCancel(std::sync::Mutex<CancellationToken>);
+
+impl OperationCancel {
+    /// Token for an operation starting now
+    fn token(&self) -> CancellationToken {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Cancel every running operation and arm a fresh token for the next ones
+    fn cancel(&self) {
+        let mut token = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        token.cancel();
+        *token = CancellationToken::new();
+    }
+}
+
+/// Abort any in-flight ingest or search
+#[tauri::command]
+pub fn cancel_operation(cancel: State<'_, OperationCancel>) {
+    cancel.cancel();
+}
+
 /// List all sessions
 #[tauri::command]
 pub async fn list_sessions(db: State<'_, Database>) -> Result<Vec<SessionData>, String> {
@@ -38,6 +63,7 @@ pub async fn list_sessions(db: State<'_, Database>) -> Result<Vec<SessionData>,
 /// Get events for a session
 #[tauri::command]
 pub async fn get_session_events(db: State<'_, Database>, session_id: String) -> Result<Vec<EventData>, String> {
+    let session_id: SessionId = session_id.parse()?;
     let rows = db
         .get_session_events(session_id)
         .await
@@ -60,31 +86,56 @@ pub async fn get_session_events(db: State<'_, Database>, session_id: String) ->
 
 /// Trigger ingestion from a source
 #[tauri::command]
-pub async fn ingest_source(db: State<'_, Database>, source: String) -> Result<IngestResult, String> {
+pub async fn ingest_source(
+    db: State<'_, Database>, cancel: State<'_, OperationCancel>, source: String,
+) -> Result<IngestResult, String> {
     let source = Source::from_str(&source)?;
 
-    ingest::ingest_single_source(&db, source).await
+    ingest::ingest_single_source(&db, source, &cancel.token()).await
 }
 
 /// Search events with FTS5 and faceted filtering
 #[tauri::command]
 pub async fn search_events(
-    db: State<'_, Database>, query: String, facets: SearchFacets, limit: i64,
-) -> Result<Vec<SearchResult>, String> {
+    db: State<'_, Database>, cancel: State<'_, OperationCancel>, query: String, facets: SearchFacets, limit: i64,
+    offset: Option<i64>,
+) -> Result<SearchPage, String> {
+    let cancel = cancel.token();
     let since_dt = facets
         .since
         .and_then(|s| parse_duration(&s))
         .map(|dur| Utc::now() - dur);
+    let until_dt = facets
+        .until
+        .and_then(|s| parse_duration(&s))
+        .map(|dur| Utc::now() - dur);
 
-    let db_facets =
-        DbSearchFacets { source: facets.source, project: facets.project, kind: facets.kind, since: since_dt };
-
-    let results = db
-        .search_events(&query, &db_facets, limit, 0)
-        .await
-        .map_err(|e| format!("Failed to search events: {}", e))?;
+    let db_facets = DbSearchFacets {
+        source: facets.source,
+        project: facets.project,
+        kind: facets.kind,
+        since: since_dt,
+        until: until_dt,
+        snippet_len: None,
+        match_any: false,
+        category: None,
+        thinking: false,
+        tag: facets.tag,
+        session_id: facets.session_id,
+    };
+
+    let (results, total) = tokio::select! {
+        page = db.search_events_paged(&query, &db_facets, limit, offset.unwrap_or(0)) => {
+            page.map_err(|e| format!("Failed to search events: {}", e))?
+        }
+        _ = cancel.cancelled() => {
+            // Dropping the search future leaves its statement running on the shared connection
+            db.interrupt();
+            return Err("Search cancelled".to_string());
+        }
+    };
 
-    Ok(results
+    let results = results
         .into_iter()
         .map(|r| SearchResult {
             event: EventData {
@@ -98,6 +149,57 @@ pub async fn search_events(
             rank: r.rank,
             snippet: r.snippet,
         })
+        .collect();
+    Ok(SearchPage { results, total })
+}
+
+/// Search session titles, best match first
+#[tauri::command]
+pub async fn search_sessions(
+    db: State<'_, Database>, query: String, facets: SearchFacets, limit: i64,
+) -> Result<Vec<SessionSearchResult>, String> {
+    let since_dt = facets
+        .since
+        .and_then(|s| parse_duration(&s))
+        .map(|dur| Utc::now() - dur);
+    let until_dt = facets
+        .until
+        .and_then(|s| parse_duration(&s))
+        .map(|dur| Utc::now() - dur);
+
+    let db_facets = DbSearchFacets {
+        source: facets.source,
+        project: facets.project,
+        kind: None,
+        since: since_dt,
+        until: until_dt,
+        snippet_len: None,
+        match_any: false,
+        category: None,
+        thinking: false,
+        tag: facets.tag,
+        session_id: facets.session_id,
+    };
+
+    let rows = db
+        .search_sessions(&query, &db_facets, limit, 0)
+        .await
+        .map_err(|e| format!("Failed to search sessions: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(row, rank)| SessionSearchResult {
+            session: SessionData {
+                id: row.id,
+                source: row.source,
+                external_id: row.external_id,
+                project: row.project,
+                title: row.title,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            },
+            rank,
+        })
         .collect())
 }
 
@@ -116,7 +218,44 @@ pub async fn get_activity_stats(
 
     Ok(stats
         .into_iter()
-        .map(|s| ActivityStats { day: s.day.to_string(), event_count: s.event_count, session_count: s.session_count })
+        .map(|s| ActivityStats {
+            day: s.day.to_string(),
+            event_count: s.event_count,
+            session_count: s.session_count,
+            rolling_avg_7d: s.rolling_avg_7d,
+        })
+        .collect())
+}
+
+/// Get per-day session and event counts across all sources, with a per-source breakdown
+#[tauri::command]
+pub async fn get_session_timeline(
+    db: State<'_, Database>, since: Option<String>, until: Option<String>,
+) -> Result<Vec<TimelineDayData>, String> {
+    let since_dt = since.and_then(|s| parse_duration(&s)).map(|dur| Utc::now() - dur);
+    let until_dt = until.and_then(|s| parse_duration(&s)).map(|dur| Utc::now() - dur);
+
+    let timeline = db
+        .get_session_timeline(since_dt, until_dt)
+        .await
+        .map_err(|e| format!("Failed to get session timeline: {}", e))?;
+
+    Ok(timeline
+        .into_iter()
+        .map(|d| TimelineDayData {
+            day: d.day.to_string(),
+            event_count: d.event_count,
+            session_count: d.session_count,
+            sources: d
+                .sources
+                .into_iter()
+                .map(|s| SourceActivityData {
+                    source: s.source,
+                    event_count: s.event_count,
+                    session_count: s.session_count,
+                })
+                .collect(),
+        })
         .collect())
 }
 
@@ -172,29 +311,6 @@ pub async fn get_event_kinds(db: State<'_, Database>) -> Result<Vec<String>, Str
     Ok(kinds)
 }
 
-fn parse_duration(s: &str) -> Option<chrono::Duration> {
-    if s.ends_with('d') {
-        s.strip_suffix('d')
-            .and_then(|days| days.parse().ok())
-            .map(Duration::days)
-    } else if s.ends_with('h') {
-        s.strip_suffix('h')
-            .and_then(|hours| hours.parse().ok())
-            .map(Duration::hours)
-    } else if s.ends_with('w') {
-        s.strip_suffix('w')
-            .and_then(|weeks| weeks.parse().ok())
-            .map(Duration::weeks)
-    } else if s.ends_with('m') && !s.ends_with("min") {
-        s[..s.len() - 1]
-            .parse()
-            .ok()
-            .map(|months: i64| Duration::days(months * 30))
-    } else {
-        None
-    }
-}
-
 /// Get health status for all data sources
 #[tauri::command]
 pub async fn get_source_health() -> Result<Vec<agent_v_core::SourceHealth>, String> {
@@ -204,20 +320,30 @@ pub async fn get_source_health() -> Result<Vec<agent_v_core::SourceHealth>, Stri
 
 /// Ingest from all available sources
 #[tauri::command]
-pub async fn ingest_all_sources(db: State<'_, Database>) -> Result<Vec<IngestResult>, String> {
+pub async fn ingest_all_sources(
+    db: State<'_, Database>, cancel: State<'_, OperationCancel>,
+) -> Result<Vec<IngestResult>, String> {
     let mut results = Vec::new();
-    let sources = vec![Source::Claude, Source::Codex, Source::OpenCode, Source::Crush];
-
-    for source in sources {
-        match ingest::ingest_single_source(&db, source).await {
-            Ok(result) => results.push(result),
+    let cancel = cancel.token();
+
+    for &source in Source::all() {
+        match ingest::ingest_single_source(&db, source, &cancel).await {
+            Ok(result) => {
+                let cancelled = result.cancelled;
+                results.push(result);
+                if cancelled {
+                    break;
+                }
+            }
             Err(e) => {
                 results.push(IngestResult {
                     imported: 0,
                     failed: 0,
                     total: 0,
+                    new: 0,
                     source: source.to_string(),
                     duration_ms: 0,
+                    cancelled: false,
                 });
                 eprintln!("Failed to ingest {}: {}", source, e);
             }
@@ -336,32 +462,30 @@ pub async fn get_long_running_tools(
         .collect())
 }
 
-/// Export a session to various formats
+/// Export a session to various formats; `source` picks the session when its external ID exists in several sources
 #[tauri::command]
-pub async fn export_session(db: State<'_, Database>, session_id: String, format: String) -> Result<String, String> {
-    let mut session = None;
-    let mut offset = 0;
-    loop {
-        let sessions = db.list_sessions(100, offset).await.map_err(|e| e.to_string())?;
-        if sessions.is_empty() {
-            break;
-        }
-        if let Some(found) = sessions
-            .into_iter()
-            .find(|s| s.id == session_id || s.external_id == session_id)
-        {
-            session = Some(found);
-            break;
-        }
-        offset += 100;
-    }
-
-    let session = session.ok_or_else(|| format!("Session not found: {}", session_id))?;
-    let events = db
-        .get_session_events(session.id.clone())
+pub async fn export_session(
+    db: State<'_, Database>, session_id: String, source: Option<String>, format: String,
+) -> Result<String, String> {
+    let mut matches = db
+        .find_sessions(&session_id, source.as_deref())
         .await
         .map_err(|e| e.to_string())?;
-    let metrics = db.get_session_metrics(&session.id).await.ok().flatten();
+    let session = match matches.len() {
+        0 => return Err(format!("Session not found: {}", session_id)),
+        1 => matches.remove(0),
+        _ => {
+            let sources: Vec<&str> = matches.iter().map(|s| s.source.as_str()).collect();
+            return Err(format!(
+                "Session ID {} exists in several sources ({}); choose a source",
+                session_id,
+                sources.join(", ")
+            ));
+        }
+    };
+    let session_key = session.session_id()?;
+    let events = db.get_session_events(session_key).await.map_err(|e| e.to_string())?;
+    let metrics = db.get_session_metrics(session_key).await.ok().flatten();
 
     let export_format = ExportFormat::from_str(&format)?;
 
@@ -369,6 +493,7 @@ pub async fn export_session(db: State<'_, Database>, session_id: String, format:
         ExportFormat::Markdown => export::export_session_to_markdown(&session, &events, metrics.as_ref()).await,
         ExportFormat::Json => export::export_session_to_json(&session, &events, metrics.as_ref()).await,
         ExportFormat::Jsonl => export::export_session_to_jsonl(&session, &events, metrics.as_ref()).await,
+        ExportFormat::Csv => Ok(export::export_session_to_csv(&events)),
     }
 }
 
@@ -380,7 +505,19 @@ pub async fn export_search(
 ) -> Result<String, String> {
     let since_dt = since.and_then(|s| parse_duration(&s)).map(|dur| Utc::now() - dur);
 
-    let db_facets = DbSearchFacets { source, project: None, kind, since: since_dt };
+    let db_facets = DbSearchFacets {
+        source,
+        project: None,
+        kind,
+        since: since_dt,
+        until: None,
+        snippet_len: None,
+        match_any: false,
+        category: None,
+        thinking: false,
+        tag: None,
+        session_id: None,
+    };
 
     let results = db
         .search_events(&query, &db_facets, 10000, 0)
@@ -393,6 +530,7 @@ pub async fn export_search(
         ExportFormat::Markdown => export::export_search_to_markdown(&query, &results).await,
         ExportFormat::Json => export::export_search_to_json(&query, &results).await,
         ExportFormat::Jsonl => export::export_search_to_jsonl(&query, &results).await,
+        ExportFormat::Csv => Ok(export::export_search_to_csv(&results)),
     }
 }
 
@@ -414,7 +552,9 @@ pub async fn recompute_all_metrics(db: State<'_, Database>) -> Result<models::Re
         }
 
         for session in &sessions {
-            let _ = db.compute_session_metrics(&session.id).await;
+            if let Ok(session_id) = session.session_id() {
+                let _ = db.compute_session_metrics(session_id).await;
+            }
             total += 1;
         }
 
@@ -429,8 +569,9 @@ pub async fn recompute_all_metrics(db: State<'_, Database>) -> Result<models::Re
 pub async fn get_session_metrics(
     db: State<'_, Database>, session_id: String,
 ) -> Result<Option<models::SessionMetricsData>, String> {
+    let session_id: SessionId = session_id.parse()?;
     let metrics = db
-        .get_session_metrics(&session_id)
+        .get_session_metrics(session_id)
         .await
         .map_err(|e| format!("Failed to get session metrics: {}", e))?;
 
@@ -484,12 +625,13 @@ pub async fn list_session_metrics(
 #[tauri::command]
 pub async fn get_cost_stats_by_source(
     db: State<'_, Database>, source: Option<String>, since: Option<String>, until: Option<String>,
+    exclude_reasoning: Option<bool>, model: Option<String>,
 ) -> Result<Vec<models::CostStats>, String> {
     let since_dt = since.and_then(|s| parse_duration(&s)).map(|dur| Utc::now() - dur);
     let until_dt = until.and_then(|s| parse_duration(&s)).map(|dur| Utc::now() - dur);
 
     let stats = db
-        .get_cost_stats_by_source(source, since_dt, until_dt)
+        .get_cost_stats_by_source(source, since_dt, until_dt, exclude_reasoning.unwrap_or(false), model)
         .await
         .map_err(|e| format!("Failed to get cost stats by source: {}", e))?;
 
@@ -513,12 +655,13 @@ pub async fn get_cost_stats_by_source(
 #[tauri::command]
 pub async fn get_cost_stats_by_project(
     db: State<'_, Database>, source: Option<String>, since: Option<String>, until: Option<String>,
+    exclude_reasoning: Option<bool>, model: Option<String>,
 ) -> Result<Vec<models::CostStats>, String> {
     let since_dt = since.and_then(|s| parse_duration(&s)).map(|dur| Utc::now() - dur);
     let until_dt = until.and_then(|s| parse_duration(&s)).map(|dur| Utc::now() - dur);
 
     let stats = db
-        .get_cost_stats_by_project(source, since_dt, until_dt)
+        .get_cost_stats_by_project(source, since_dt, until_dt, exclude_reasoning.unwrap_or(false), model)
         .await
         .map_err(|e| format!("Failed to get cost stats by project: {}", e))?;
 
@@ -542,12 +685,13 @@ pub async fn get_cost_stats_by_project(
 #[tauri::command]
 pub async fn get_model_usage_stats(
     db: State<'_, Database>, source: Option<String>, since: Option<String>, until: Option<String>,
+    model: Option<String>,
 ) -> Result<Vec<models::ModelUsageStats>, String> {
     let since_dt = since.and_then(|s| parse_duration(&s)).map(|dur| Utc::now() - dur);
     let until_dt = until.and_then(|s| parse_duration(&s)).map(|dur| Utc::now() - dur);
 
     let stats = db
-        .get_model_usage_stats(source, since_dt, until_dt)
+        .get_model_usage_stats(source, since_dt, until_dt, model)
         .await
         .map_err(|e| format!("Failed to get model usage stats: {}", e))?;
 
@@ -610,3 +754,42 @@ pub async fn get_efficiency_stats(
         p95_latency_ms: stats.p95_latency_ms,
     })
 }
+
+/// Get local attachments (images, files) recorded for a session
+#[tauri::command]
+pub async fn get_session_attachments(
+    db: State<'_, Database>, session_id: String,
+) -> Result<Vec<models::AttachmentData>, String> {
+    let session_id: SessionId = session_id.parse()?;
+    let rows = db
+        .get_session_attachments(session_id)
+        .await
+        .map_err(|e| format!("Failed to get session attachments: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|a| models::AttachmentData { id: a.id, event_id: a.event_id, path: a.path, mime_type: a.mime_type })
+        .collect())
+}
+
+/// Get the tokens each event of a session used, oldest first
+#[tauri::command]
+pub async fn get_token_timeline(
+    db: State<'_, Database>, session_id: String,
+) -> Result<Vec<models::TokenUsagePointData>, String> {
+    let session_id: SessionId = session_id.parse()?;
+    let points = db
+        .get_token_timeline(session_id)
+        .await
+        .map_err(|e| format!("Failed to get token timeline: {}", e))?;
+
+    Ok(points
+        .into_iter()
+        .map(|p| models::TokenUsagePointData {
+            event_id: p.event_id,
+            timestamp: p.timestamp,
+            input_tokens: p.input_tokens,
+            output_tokens: p.output_tokens,
+        })
+        .collect())
+}
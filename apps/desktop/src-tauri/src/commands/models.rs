@@ -18,6 +18,7 @@ pub struct EventData {
     pub id: String,
     pub session_id: String,
     pub kind: String,
+    /// user, assistant, system, or tool (tool results), so tool output can be told apart from prose
     pub role: Option<String>,
     pub content: Option<String>,
     pub timestamp: String,
@@ -29,8 +30,12 @@ pub struct IngestResult {
     pub imported: usize,
     pub failed: usize,
     pub total: usize,
+    /// Sessions whose external id was not stored before this ingest
+    pub new: usize,
     pub source: String,
     pub duration_ms: u64,
+    /// Ingest was stopped early by `cancel_operation`; counts cover the sessions processed so far
+    pub cancelled: bool,
 }
 
 /// Search result for the frontend
@@ -41,6 +46,20 @@ pub struct SearchResult {
     pub snippet: Option<String>,
 }
 
+/// A session whose title matched a search, with its FTS rank (lower is better)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSearchResult {
+    pub session: SessionData,
+    pub rank: f64,
+}
+
+/// One page of search results and how many matches there are in all
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    pub total: i64,
+}
+
 /// Search facets for filtering
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchFacets {
@@ -48,6 +67,12 @@ pub struct SearchFacets {
     pub project: Option<String>,
     pub kind: Option<String>,
     pub since: Option<String>,
+    /// Only match events older than this window, e.g. "7d"
+    pub until: Option<String>,
+    /// Only match sessions carrying this tag
+    pub tag: Option<String>,
+    /// Only match within this session (internal ID)
+    pub session_id: Option<String>,
 }
 
 /// Activity stats for the frontend
@@ -56,6 +81,24 @@ pub struct ActivityStats {
     pub day: String,
     pub event_count: i64,
     pub session_count: i64,
+    pub rolling_avg_7d: f64,
+}
+
+/// Per-source activity within a timeline day for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceActivityData {
+    pub source: String,
+    pub event_count: i64,
+    pub session_count: i64,
+}
+
+/// A day in the cross-source session timeline for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineDayData {
+    pub day: String,
+    pub event_count: i64,
+    pub session_count: i64,
+    pub sources: Vec<SourceActivityData>,
 }
 
 /// Error stats for the frontend
@@ -207,6 +250,24 @@ pub struct LatencyDistribution {
     pub session_count: i64,
 }
 
+/// Attachment reference for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentData {
+    pub id: String,
+    pub event_id: String,
+    pub path: String,
+    pub mime_type: Option<String>,
+}
+
+/// Tokens reported by one event, for the session token-usage chart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenUsagePointData {
+    pub event_id: String,
+    pub timestamp: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+}
+
 /// Streaming event payload pushed to the frontend via Tauri events
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamingEventPayload {
@@ -223,6 +284,7 @@ pub enum ExportFormat {
     Markdown,
     Json,
     Jsonl,
+    Csv,
 }
 
 impl ExportFormat {
@@ -231,7 +293,8 @@ impl ExportFormat {
             "md" | "markdown" => Ok(ExportFormat::Markdown),
             "json" => Ok(ExportFormat::Json),
             "jsonl" => Ok(ExportFormat::Jsonl),
-            _ => Err(format!("Unknown format: {}. Use 'md', 'json', or 'jsonl'", s)),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(format!("Unknown format: {}. Use 'md', 'json', 'jsonl', or 'csv'", s)),
         }
     }
 }
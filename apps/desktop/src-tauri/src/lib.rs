@@ -3,13 +3,14 @@ mod commands;
 use agent_v_ingest::{IngestProgress, StreamingEvent, WatcherConfig};
 use agent_v_store::Database;
 use commands::{
-    check_for_new_sessions, export_search, export_session, get_activity_stats, get_cost_stats_by_project,
-    get_cost_stats_by_source, get_efficiency_stats, get_error_stats, get_event_kinds, get_files_leaderboard,
-    get_latency_distribution, get_long_running_tools, get_model_usage_stats, get_patch_churn, get_projects,
-    get_session_events, get_session_metrics, get_source_health, get_sources, get_tool_call_frequency,
-    ingest_all_sources, ingest_source, list_session_metrics, list_sessions, recompute_all_metrics, search_events,
+    cancel_operation, check_for_new_sessions, export_search, export_session, get_activity_stats,
+    get_cost_stats_by_project, get_cost_stats_by_source, get_efficiency_stats, get_error_stats, get_event_kinds,
+    get_files_leaderboard, get_latency_distribution, get_long_running_tools, get_model_usage_stats, get_patch_churn,
+    get_projects, get_session_attachments, get_session_events, get_session_metrics, get_session_timeline,
+    get_source_health, get_sources, get_token_timeline, get_tool_call_frequency, ingest_all_sources, ingest_source,
+    list_session_metrics, list_sessions, recompute_all_metrics, search_events, search_sessions,
 };
-use commands::{EventData, StreamingEventPayload};
+use commands::{EventData, OperationCancel, StreamingEventPayload};
 use std::sync::Arc;
 use tauri::{Emitter, Manager};
 
@@ -44,6 +45,16 @@ pub fn run() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_log::Builder::new().level(log::LevelFilter::Debug).build())
         .setup(|app| {
+            if let Some(path) = agent_v_core::pricing::default_pricing_path() {
+                if let Err(e) = agent_v_core::pricing::load_overrides(&path) {
+                    log::warn!("Ignoring pricing overrides: {}", e);
+                }
+            }
+            if let Some(path) = agent_v_core::config::default_config_path() {
+                if let Err(e) = agent_v_core::config::load_config(&path) {
+                    log::warn!("Ignoring config file: {}", e);
+                }
+            }
             tauri::async_runtime::block_on(async {
                 let db = Database::open_default().await.expect("Failed to open database");
                 db.migrate().await.expect("Failed to run database migrations");
@@ -53,6 +64,7 @@ pub fn run() {
                     }
                 }
                 app.manage(db);
+                app.manage(OperationCancel::default());
             });
 
             let app_handle = app.handle().clone();
@@ -85,6 +97,7 @@ pub fn run() {
             ingest_source,
             ingest_all_sources,
             search_events,
+            search_sessions,
             get_activity_stats,
             get_error_stats,
             get_sources,
@@ -105,7 +118,11 @@ pub fn run() {
             get_cost_stats_by_project,
             get_model_usage_stats,
             get_latency_distribution,
-            get_efficiency_stats
+            get_efficiency_stats,
+            get_session_attachments,
+            get_session_timeline,
+            get_token_timeline,
+            cancel_operation
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -52,9 +52,21 @@ export type EventData = {
 
 export type SearchResult = { event: EventData; rank: number; snippet: string | null };
 
-export type SearchFacets = { source?: string; project?: string; kind?: string; since?: string };
+export type SearchPage = { results: SearchResult[]; total: number };
 
-export type ActivityStats = { day: string; event_count: number; session_count: number };
+export type SessionSearchResult = { session: SessionData; rank: number };
+
+export type SearchFacets = {
+  source?: string;
+  project?: string;
+  kind?: string;
+  since?: string;
+  until?: string;
+  tag?: string;
+  session_id?: string;
+};
+
+export type ActivityStats = { day: string; event_count: number; session_count: number; rolling_avg_7d: number };
 
 export type ErrorStats = { day: string; error_count: number; signature: string | null };
 
@@ -103,16 +115,24 @@ export type StreamingEventPayload = {
 
 export type IngestProgress = { source: string; phase: string; current: number; total: number };
 
-export type ExportFormat = "md" | "json" | "jsonl";
+export type ExportFormat = "md" | "json" | "jsonl" | "csv";
 
 export type SourceHealth = {
-  source: "claude" | "codex" | "opencode" | "crush";
+  source: "claude" | "codex" | "opencode" | "crush" | "gemini";
   status: "healthy" | "degraded" | "unhealthy" | "unknown";
   path: string | null;
   message: string | null;
 };
 
-export type IngestResult = { imported: number; failed: number; total: number; source: string; duration_ms: number };
+export type IngestResult = {
+  imported: number;
+  failed: number;
+  total: number;
+  new: number;
+  source: string;
+  duration_ms: number;
+  cancelled: boolean;
+};
 
 export type ToastNotification = { id: string; type: "success" | "error" | "info"; message: string; duration?: number };
 
@@ -0,0 +1,41 @@
+CREATE TABLE sessions (
+    id TEXT PRIMARY KEY,
+    parent_session_id TEXT,
+    title TEXT NOT NULL,
+    message_count INTEGER NOT NULL DEFAULT 0,
+    prompt_tokens INTEGER NOT NULL DEFAULT 0,
+    completion_tokens INTEGER NOT NULL DEFAULT 0,
+    cost REAL NOT NULL DEFAULT 0.0,
+    updated_at INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    summary_message_id TEXT,
+    todos TEXT
+);
+
+CREATE TABLE messages (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    role TEXT NOT NULL,
+    parts TEXT NOT NULL DEFAULT '[]',
+    model TEXT,
+    provider TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    finished_at INTEGER,
+    is_summary_message INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT INTO sessions (id, title, message_count, prompt_tokens, completion_tokens, cost, updated_at, created_at)
+VALUES ('parse-check', 'List the project files', 4, 120, 40, 0.01, 1735725604, 1735725600);
+
+INSERT INTO messages (id, session_id, role, parts, model, provider, created_at, updated_at, finished_at) VALUES
+    ('m1', 'parse-check', 'user', '[{"type":"text","data":{"text":"list the files in src"}}]',
+        NULL, NULL, 1735725600, 1735725600, NULL),
+    ('m2', 'parse-check', 'assistant',
+        '[{"type":"text","data":{"text":"Listing src now."}},{"type":"tool_use","data":{"id":"call_1","name":"ls","input":"{\"path\":\"src\"}"}},{"type":"finish","data":{"reason":"tool_use","time":1735725602}}]',
+        'claude-sonnet-4', 'anthropic', 1735725601, 1735725602, 1735725602),
+    ('m3', 'parse-check', 'tool', '[{"type":"tool_result","data":{"tool_use_id":"call_1","content":"lib.rs\nmain.rs","is_error":false}}]',
+        NULL, NULL, 1735725603, 1735725603, NULL),
+    ('m4', 'parse-check', 'assistant',
+        '[{"type":"text","data":{"text":"src holds main.rs and lib.rs."}},{"type":"finish","data":{"reason":"end_turn","time":1735725604}}]',
+        'claude-sonnet-4', 'anthropic', 1735725604, 1735725604, 1735725604);
@@ -0,0 +1,46 @@
+CREATE TABLE session (
+    id TEXT PRIMARY KEY,
+    slug TEXT,
+    version TEXT,
+    project_id TEXT,
+    directory TEXT,
+    title TEXT NOT NULL,
+    time_created INTEGER NOT NULL,
+    time_updated INTEGER NOT NULL,
+    summary_additions INTEGER,
+    summary_deletions INTEGER,
+    summary_files INTEGER,
+    summary_diffs TEXT
+);
+
+CREATE TABLE message (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    time_created INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+
+CREATE TABLE part (
+    id TEXT PRIMARY KEY,
+    message_id TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    time_created INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+
+INSERT INTO session (id, slug, version, project_id, directory, title, time_created, time_updated,
+                     summary_additions, summary_deletions, summary_files, summary_diffs)
+VALUES ('parse-check', 'list-files', '1.0.0', 'prj_1', '/work/project', 'List the project files',
+        1735725600000, 1735725604000, 2, 0, 1,
+        '[{"file":"NOTES.md","before":"","after":"main.rs\nlib.rs\n","additions":2,"deletions":0,"status":"added"}]');
+
+INSERT INTO message (id, session_id, time_created, data) VALUES
+    ('msg_1', 'parse-check', 1735725600000, '{"role":"user","time":{"created":1735725600000}}'),
+    ('msg_2', 'parse-check', 1735725601000,
+        '{"role":"assistant","time":{"created":1735725601000,"completed":1735725604000},"providerID":"anthropic","modelID":"claude-sonnet-4"}');
+
+INSERT INTO part (id, message_id, session_id, time_created, data) VALUES
+    ('prt_1', 'msg_1', 'parse-check', 1735725600000, '{"type":"text","text":"list the files in src"}'),
+    ('prt_2', 'msg_2', 'parse-check', 1735725601000, '{"type":"text","text":"Listing src now."}'),
+    ('prt_3', 'msg_2', 'parse-check', 1735725602000,
+        '{"type":"tool","tool":"bash","callID":"call_1","state":{"status":"completed","input":{"command":"ls src"},"output":"lib.rs\nmain.rs"}}');
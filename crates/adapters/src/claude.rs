@@ -1,7 +1,10 @@
-use agent_v_core::{Event, EventKind, Role, Session, Source};
+use agent_v_core::config::{self, Config};
+use agent_v_core::{AdapterError, Event, EventKind, FileEdit, Role, Session, Source};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncBufReadExt;
 use uuid::Uuid;
 
 /// A discovered Claude Code session file
@@ -12,6 +15,27 @@ pub struct ClaudeSessionFile {
     pub session_id: String,
 }
 
+impl ClaudeSessionFile {
+    /// Describe a session file at an arbitrary path. The session ID is the file stem and the project
+    /// defaults to the parent directory name, matching the `projects/<project>/<session>.jsonl` layout.
+    pub fn from_path(path: &Path, project: Option<String>) -> Self {
+        let session_id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let project = project.unwrap_or_else(|| {
+            path.parent()
+                .and_then(|p| p.file_name())
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown")
+                .to_string()
+        });
+
+        Self { path: path.to_path_buf(), project, session_id }
+    }
+}
+
 /// Represents a tool call extracted from assistant message content
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
@@ -77,21 +101,34 @@ pub struct ConversationThread {
 #[derive(Debug, Clone)]
 pub struct ClaudeAdapter {
     projects_dir: PathBuf,
+    capture_source_lines: bool,
 }
 
 impl ClaudeAdapter {
-    /// Create a new Claude adapter with default projects directory
+    /// Create a new Claude adapter with the configured or default projects directory
     pub fn new() -> Self {
-        let projects_dir = dirs::home_dir()
-            .map(|h| h.join(".claude").join("projects"))
+        Self::from_config(config::current())
+    }
+
+    /// Create a Claude adapter rooted at `sources.claude` from `config`, or `~/.claude/projects`
+    pub fn from_config(config: &Config) -> Self {
+        let projects_dir = config
+            .source_path(Source::Claude)
+            .or_else(|| dirs::home_dir().map(|h| h.join(".claude").join("projects")))
             .unwrap_or_else(|| PathBuf::from("."));
 
-        Self { projects_dir }
+        Self { projects_dir, capture_source_lines: false }
     }
 
     /// Create a new Claude adapter with a custom projects directory
     pub fn with_projects_dir(projects_dir: PathBuf) -> Self {
-        Self { projects_dir }
+        Self { projects_dir, capture_source_lines: false }
+    }
+
+    /// Record each event's zero-based line in the session file under `source_line` in its raw payload
+    pub fn with_source_lines(mut self, enabled: bool) -> Self {
+        self.capture_source_lines = enabled;
+        self
     }
 
     /// Get the projects directory path
@@ -139,13 +176,7 @@ impl ClaudeAdapter {
             while let Ok(Some(file_entry)) = jsonl_files.next_entry().await {
                 let file_path = file_entry.path();
                 if file_path.extension().and_then(|e| e.to_str()) == Some("jsonl") {
-                    let session_id = file_path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("unknown")
-                        .to_string();
-
-                    sessions.push(ClaudeSessionFile { path: file_path, project: project_name.clone(), session_id });
+                    sessions.push(ClaudeSessionFile::from_path(&file_path, Some(project_name.clone())));
                 }
             }
         }
@@ -154,33 +185,43 @@ impl ClaudeAdapter {
         sessions
     }
 
-    /// Parse a session file and return a Session with its Events
-    pub async fn parse_session(
-        &self, session_file: &ClaudeSessionFile,
-    ) -> Result<(Session, Vec<Event>), Box<dyn std::error::Error + Send + Sync>> {
+    /// Parse a session file and return a Session with its Events.
+    ///
+    /// Malformed lines are skipped; the file only fails with [`AdapterError::Parse`] when none of
+    /// its lines parse, or [`AdapterError::Empty`] when it has none.
+    pub async fn parse_session(&self, session_file: &ClaudeSessionFile) -> Result<(Session, Vec<Event>), AdapterError> {
         log::debug!("Parsing session file: {:?}", session_file.path);
 
-        let content = tokio::fs::read_to_string(&session_file.path).await?;
-        let lines: Vec<&str> = content.lines().collect();
+        let file = tokio::fs::File::open(&session_file.path).await?;
+        let mut lines = tokio::io::BufReader::new(file).lines();
+        let mut line_count = 0;
 
         let mut session_title = None;
         let mut events = Vec::new();
         let mut first_timestamp: Option<DateTime<Utc>> = None;
         let mut last_timestamp: Option<DateTime<Utc>> = None;
         let mut leaf_uuid: Option<String> = None;
-
-        for (idx, line) in lines.iter().enumerate() {
+        let mut parsed_lines = 0;
+        let mut first_error = None;
+        // Native uuid -> event ID; lines that yield no event stand in for their nearest ancestor
+        let mut event_ids: HashMap<String, Uuid> = HashMap::new();
+
+        while let Some(line) = lines.next_line().await? {
+            let idx = line_count;
+            line_count += 1;
             if line.trim().is_empty() {
                 continue;
             }
 
-            let value: serde_json::Value = match serde_json::from_str(line) {
+            let value: serde_json::Value = match serde_json::from_str(&line) {
                 Ok(v) => v,
                 Err(e) => {
                     log::warn!("Failed to parse line {} in {:?}: {}", idx, session_file.path, e);
+                    first_error.get_or_insert(AdapterError::Parse { line: idx + 1, source: e });
                     continue;
                 }
             };
+            parsed_lines += 1;
 
             if let Some(ts_str) = value.get("timestamp").and_then(|t| t.as_str())
                 && let Ok(ts) = DateTime::parse_from_rfc3339(ts_str)
@@ -202,9 +243,24 @@ impl ClaudeAdapter {
                 continue;
             }
 
-            if let Some(event) = self.parse_event_line(&value, idx) {
+            let parent = value
+                .get("parentUuid")
+                .and_then(|p| p.as_str())
+                .and_then(|p| event_ids.get(p))
+                .copied();
+            let mut resolved = parent;
+            for mut event in self.parse_line_events(&value, Some(idx)) {
+                event.parent_id = resolved;
+                resolved = Some(event.id);
                 events.push(event);
             }
+            if let (Some(uuid), Some(id)) = (value.get("uuid").and_then(|u| u.as_str()), resolved) {
+                event_ids.insert(uuid.to_string(), id);
+            }
+        }
+
+        if parsed_lines == 0 {
+            return Err(first_error.unwrap_or(AdapterError::Empty));
         }
 
         let created_at = first_timestamp.unwrap_or_else(Utc::now);
@@ -217,7 +273,7 @@ impl ClaudeAdapter {
             "project": session_file.project,
             "session_id": external_id,
             "file_path": session_file.path.to_string_lossy().to_string(),
-            "line_count": lines.len(),
+            "line_count": line_count,
             "leaf_uuid": leaf_uuid,
         });
 
@@ -245,11 +301,18 @@ impl ClaudeAdapter {
         Ok((session, events))
     }
 
+    /// Parse a single session file outside the projects directory, e.g. one moved out of the default tree
+    pub async fn parse_file(
+        &self, path: &Path, project: Option<String>,
+    ) -> Result<(Session, Vec<Event>), AdapterError> {
+        self.parse_session(&ClaudeSessionFile::from_path(path, project)).await
+    }
+
     /// Rebuild conversation threads from events
     pub fn rebuild_conversations(&self, events: &[Event]) -> Vec<ConversationThread> {
         let mut nodes: Vec<ConversationNode> = events.iter().filter_map(|event| self.event_to_node(event)).collect();
 
-        nodes.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        nodes.sort_by_key(|a| a.timestamp);
 
         let uuid_set: std::collections::HashSet<_> = nodes.iter().map(|n| &n.uuid).collect();
         let roots: Vec<_> = nodes
@@ -319,6 +382,42 @@ impl ClaudeAdapter {
         value.get("content").and_then(|c| c.as_str()).map(|s| s.to_string())
     }
 
+    /// Extract the `tool_result` blocks of a user message entry as `(tool_use_id, text)` pairs
+    fn extract_tool_results(&self, value: &serde_json::Value) -> Vec<(String, String)> {
+        let Some(blocks) = value.pointer("/message/content").and_then(|c| c.as_array()) else {
+            return Vec::new();
+        };
+
+        blocks
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("tool_result"))
+            .map(|block| {
+                let id = block.get("tool_use_id").and_then(|i| i.as_str()).unwrap_or_default();
+                let text = match block.get("content") {
+                    Some(serde_json::Value::String(text)) => text.clone(),
+                    Some(serde_json::Value::Array(parts)) => parts
+                        .iter()
+                        .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    _ => String::new(),
+                };
+                (id.to_string(), text)
+            })
+            .collect()
+    }
+
+    /// Join the text blocks of a user message entry whose content is an array of blocks
+    fn extract_user_text_blocks(&self, value: &serde_json::Value) -> Option<String> {
+        let blocks = value.pointer("/message/content")?.as_array()?;
+        let texts: Vec<&str> = blocks
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+            .collect();
+        if texts.is_empty() { None } else { Some(texts.join("\n")) }
+    }
+
     /// Extract content, tool calls, and thinking from an assistant message entry
     fn extract_assistant_content(&self, value: &serde_json::Value) -> (Option<String>, Vec<ToolCall>, Option<String>) {
         let mut content_parts = Vec::new();
@@ -365,8 +464,86 @@ impl ClaudeAdapter {
         (content, tool_calls, thinking)
     }
 
-    /// Parse a single JSONL line into an Event
-    fn parse_event_line(&self, value: &serde_json::Value, _line_idx: usize) -> Option<Event> {
+    /// Parse a JSONL line into its events. A user entry with `tool_result` blocks yields one tool
+    /// result per block, followed by a user message holding any text blocks next to them; other
+    /// lines yield at most one event.
+    fn parse_line_events(&self, value: &serde_json::Value, line_idx: Option<usize>) -> Vec<Event> {
+        let Some(event) = self.parse_event_line(value, line_idx) else {
+            return Vec::new();
+        };
+        if event.kind != EventKind::ToolResult {
+            return vec![event];
+        }
+        let timestamp = event.timestamp;
+        let mut events = self.split_tool_results(event, value);
+        let Some(text) = self.extract_user_text_blocks(value) else {
+            return events;
+        };
+
+        let mut raw_payload = value.clone();
+        if self.capture_source_lines
+            && let Some(idx) = line_idx
+            && let Some(obj) = raw_payload.as_object_mut()
+        {
+            obj.insert(Event::SOURCE_LINE_KEY.to_string(), serde_json::json!(idx));
+        }
+        let message = Event {
+            id: Uuid::new_v4(),
+            session_id: Uuid::nil(),
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some(text),
+            timestamp,
+            raw_payload,
+            parent_id: None,
+        };
+        events.push(message);
+        events
+    }
+
+    /// Split a tool result event covering several `tool_result` blocks into one event per block,
+    /// each keeping only its own block, `tool_use_id`, and text. File edits from the line's
+    /// `toolUseResult` stay on the first.
+    fn split_tool_results(&self, event: Event, value: &serde_json::Value) -> Vec<Event> {
+        let results = self.extract_tool_results(value);
+        if results.len() < 2 {
+            return vec![event];
+        }
+        let blocks = value
+            .pointer("/message/content")
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("tool_result"));
+
+        results
+            .into_iter()
+            .zip(blocks)
+            .enumerate()
+            .map(|(idx, ((tool_use_id, text), block))| {
+                let mut raw_payload = event.raw_payload.clone();
+                if let Some(content) = raw_payload.pointer_mut("/message/content") {
+                    *content = serde_json::json!([block]);
+                }
+                if let Some(obj) = raw_payload.as_object_mut() {
+                    obj.insert("tool_use_id".to_string(), serde_json::json!(tool_use_id));
+                    if idx > 0 {
+                        obj.remove(FileEdit::PAYLOAD_KEY);
+                    }
+                }
+                Event {
+                    id: if idx == 0 { event.id } else { Uuid::new_v4() },
+                    content: Some(text),
+                    raw_payload,
+                    ..event.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Parse a single JSONL line into an Event.
+    /// `line_idx` is recorded when source lines are captured and the line position is known.
+    fn parse_event_line(&self, value: &serde_json::Value, line_idx: Option<usize>) -> Option<Event> {
         let entry_type = value.get("type")?.as_str()?;
 
         let timestamp = value
@@ -375,7 +552,16 @@ impl ClaudeAdapter {
             .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
             .map(|dt| dt.with_timezone(&Utc))?;
 
+        let tool_results = self.extract_tool_results(value);
         let (kind, role, content) = match entry_type {
+            "user" if !tool_results.is_empty() => {
+                let content = tool_results
+                    .iter()
+                    .map(|(_, text)| text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                (EventKind::ToolResult, Some(Role::Tool), Some(content))
+            }
             "user" => {
                 let content = self.extract_user_content(value);
                 (EventKind::Message, Some(Role::User), content)
@@ -413,6 +599,29 @@ impl ClaudeAdapter {
             _ => (EventKind::System, None, Some(format!("Unknown type: {}", entry_type))),
         };
 
+        let mut raw_payload = value.clone();
+        if kind == EventKind::ToolResult
+            && let Some((tool_use_id, _)) = tool_results.first()
+            && let Some(obj) = raw_payload.as_object_mut()
+        {
+            obj.insert("tool_use_id".to_string(), serde_json::json!(tool_use_id));
+        }
+        let file_edits = extract_file_edits(value);
+        if !file_edits.is_empty()
+            && let Some(obj) = raw_payload.as_object_mut()
+        {
+            obj.insert(FileEdit::PAYLOAD_KEY.to_string(), serde_json::json!(file_edits));
+        }
+        if self.capture_source_lines
+            && let Some(idx) = line_idx
+            && let Some(obj) = raw_payload.as_object_mut()
+        {
+            obj.insert(Event::SOURCE_LINE_KEY.to_string(), serde_json::json!(idx));
+        }
+        if kind == EventKind::System {
+            Event::set_category(&mut raw_payload, entry_type);
+        }
+
         Some(Event {
             id: Uuid::new_v4(),
             session_id: Uuid::nil(),
@@ -420,7 +629,8 @@ impl ClaudeAdapter {
             role,
             content,
             timestamp,
-            raw_payload: value.clone(),
+            raw_payload,
+            parent_id: None,
         })
     }
 
@@ -429,7 +639,7 @@ impl ClaudeAdapter {
     /// Skips to the next newline after `byte_offset` to avoid partial lines.
     pub async fn parse_session_incremental(
         &self, session_file: &ClaudeSessionFile, byte_offset: u64,
-    ) -> Result<(Vec<Event>, u64), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(Vec<Event>, u64), AdapterError> {
         use tokio::io::{AsyncReadExt, AsyncSeekExt};
 
         let mut file = tokio::fs::File::open(&session_file.path).await?;
@@ -465,18 +675,14 @@ impl ClaudeAdapter {
                 continue;
             }
 
-            if let Some(event) = self.parse_event_line(&value, 0) {
-                events.push(event);
-            }
+            events.extend(self.parse_line_events(&value, None));
         }
 
         Ok((events, file_len))
     }
 
     /// Get statistics about a session file
-    pub async fn get_session_stats(
-        &self, session_file: &ClaudeSessionFile,
-    ) -> Result<SessionStats, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn get_session_stats(&self, session_file: &ClaudeSessionFile) -> Result<SessionStats, AdapterError> {
         let content = tokio::fs::read_to_string(&session_file.path).await?;
         let lines: Vec<&str> = content.lines().collect();
 
@@ -543,6 +749,52 @@ impl Default for ClaudeAdapter {
     }
 }
 
+/// Extract file edit outcomes from a top-level `toolUseResult` sidecar.
+/// Edit, MultiEdit and Write results carry a `filePath` and a `structuredPatch` of diff hunks;
+/// a Write that creates a file may omit the patch, in which case its content is counted instead.
+fn extract_file_edits(value: &serde_json::Value) -> Vec<FileEdit> {
+    let results = match value.get("toolUseResult") {
+        Some(serde_json::Value::Array(items)) => items.iter().collect(),
+        Some(result @ serde_json::Value::Object(_)) => vec![result],
+        _ => Vec::new(),
+    };
+
+    results
+        .into_iter()
+        .filter_map(|result| {
+            let path = result.get("filePath").and_then(|p| p.as_str())?;
+            let hunks = result.get("structuredPatch").and_then(|p| p.as_array());
+            let content = result.get("content").and_then(|c| c.as_str());
+            let operation = match result.get("type").and_then(|t| t.as_str()) {
+                Some("create") => "create",
+                _ => "edit",
+            };
+            if hunks.is_none() && (operation != "create" || content.is_none()) {
+                return None;
+            }
+
+            let (mut lines_added, mut lines_removed) = (0, 0);
+            for line in hunks
+                .into_iter()
+                .flatten()
+                .filter_map(|h| h.get("lines")?.as_array())
+                .flatten()
+            {
+                match line.as_str().and_then(|l| l.chars().next()) {
+                    Some('+') => lines_added += 1,
+                    Some('-') => lines_removed += 1,
+                    _ => {}
+                }
+            }
+            if operation == "create" && lines_added == 0 {
+                lines_added = content.map_or(0, |c| c.lines().count() as i64);
+            }
+
+            Some(FileEdit { path: path.to_string(), operation: operation.to_string(), lines_added, lines_removed })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -553,6 +805,47 @@ mod tests {
         assert!(adapter.projects_dir().to_string_lossy().contains(".claude/projects"));
     }
 
+    #[test]
+    fn test_config_override_moves_the_projects_dir() {
+        let config = Config::parse("[sources]\nclaude = \"/mnt/work/claude-projects\"").unwrap();
+        let adapter = ClaudeAdapter::from_config(&config);
+        assert_eq!(adapter.projects_dir(), &PathBuf::from("/mnt/work/claude-projects"));
+
+        let adapter = ClaudeAdapter::from_config(&Config::default());
+        assert!(adapter.projects_dir().ends_with(".claude/projects"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_errors_say_why_the_file_failed() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, contents: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, contents).unwrap();
+            path
+        };
+        let adapter = ClaudeAdapter::new();
+
+        let missing = adapter.parse_file(&dir.path().join("missing.jsonl"), None).await;
+        assert!(matches!(missing, Err(AdapterError::Io(_))));
+
+        let garbled = write("garbled.jsonl", "\n{\"type\": \"user\"\nnot json\n");
+        let err = adapter.parse_file(&garbled, None).await.unwrap_err();
+        assert!(matches!(err, AdapterError::Parse { line: 2, .. }), "{}", err);
+
+        let blank = write("blank.jsonl", "\n   \n");
+        assert!(matches!(
+            adapter.parse_file(&blank, None).await,
+            Err(AdapterError::Empty)
+        ));
+
+        let partial = write(
+            "partial.jsonl",
+            "not json\n{\"type\":\"user\",\"uuid\":\"u1\",\"timestamp\":\"2024-01-01T00:00:00Z\",\"content\":\"hi\"}\n",
+        );
+        let (_, events) = adapter.parse_file(&partial, None).await.unwrap();
+        assert_eq!(events.len(), 1);
+    }
+
     #[test]
     fn test_parse_event_line_user_message() {
         let adapter = ClaudeAdapter::new();
@@ -563,7 +856,7 @@ mod tests {
             "content": "Hello, world!"
         });
 
-        let event = adapter.parse_event_line(&value, 0).unwrap();
+        let event = adapter.parse_event_line(&value, None).unwrap();
         assert_eq!(event.kind, EventKind::Message);
         assert_eq!(event.role, Some(Role::User));
         assert_eq!(event.content, Some("Hello, world!".to_string()));
@@ -582,7 +875,7 @@ mod tests {
             }
         });
 
-        let event = adapter.parse_event_line(&value, 0).unwrap();
+        let event = adapter.parse_event_line(&value, None).unwrap();
         assert_eq!(event.kind, EventKind::Message);
         assert_eq!(event.role, Some(Role::User));
         assert_eq!(event.content, Some("Message from object".to_string()));
@@ -601,7 +894,7 @@ mod tests {
             }
         });
 
-        let event = adapter.parse_event_line(&value, 0).unwrap();
+        let event = adapter.parse_event_line(&value, None).unwrap();
         assert_eq!(event.kind, EventKind::Message);
         assert_eq!(event.role, Some(Role::Assistant));
         assert_eq!(event.content, Some("How can I help?".to_string()));
@@ -623,7 +916,7 @@ mod tests {
             }
         });
 
-        let event = adapter.parse_event_line(&value, 0).unwrap();
+        let event = adapter.parse_event_line(&value, None).unwrap();
         assert_eq!(event.kind, EventKind::Message);
         assert_eq!(event.role, Some(Role::Assistant));
         assert_eq!(event.content, Some("Let me check that file.".to_string()));
@@ -649,7 +942,7 @@ mod tests {
             }
         });
 
-        let event = adapter.parse_event_line(&value, 0).unwrap();
+        let event = adapter.parse_event_line(&value, None).unwrap();
         assert_eq!(event.content, Some("Here's my analysis.".to_string()));
 
         let (_, _, thinking) = adapter.extract_assistant_content(&value);
@@ -666,7 +959,7 @@ mod tests {
             "content": "System message"
         });
 
-        let event = adapter.parse_event_line(&value, 0).unwrap();
+        let event = adapter.parse_event_line(&value, None).unwrap();
         assert_eq!(event.kind, EventKind::System);
         assert_eq!(event.role, Some(Role::System));
     }
@@ -685,9 +978,10 @@ mod tests {
             }
         });
 
-        let event = adapter.parse_event_line(&value, 0).unwrap();
+        let event = adapter.parse_event_line(&value, None).unwrap();
         assert_eq!(event.kind, EventKind::System);
         assert_eq!(event.content, Some("Processing...".to_string()));
+        assert_eq!(Event::category(&event.raw_payload), Some("progress"));
     }
 
     #[test]
@@ -700,7 +994,7 @@ mod tests {
             "message": "Something went wrong"
         });
 
-        let event = adapter.parse_event_line(&value, 0).unwrap();
+        let event = adapter.parse_event_line(&value, None).unwrap();
         assert_eq!(event.kind, EventKind::Error);
         assert_eq!(event.content, Some("Something went wrong".to_string()));
     }
@@ -715,7 +1009,7 @@ mod tests {
             "data": "some data"
         });
 
-        let event = adapter.parse_event_line(&value, 0).unwrap();
+        let event = adapter.parse_event_line(&value, None).unwrap();
         assert_eq!(event.kind, EventKind::System);
         assert!(event.content.is_some());
     }
@@ -737,6 +1031,7 @@ mod tests {
                     "parentUuid": null,
                     "timestamp": "2024-01-01T00:00:00Z"
                 }),
+                parent_id: None,
             },
             Event {
                 id: Uuid::new_v4(),
@@ -751,6 +1046,7 @@ mod tests {
                     "parentUuid": "uuid-1",
                     "timestamp": "2024-01-01T00:00:01Z"
                 }),
+                parent_id: None,
             },
         ];
 
@@ -759,4 +1055,241 @@ mod tests {
         assert_eq!(threads[0].root_uuid, "uuid-1");
         assert_eq!(threads[0].nodes.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_parse_session_records_source_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let lines = [
+            r#"{"type":"user","timestamp":"2024-01-01T00:00:00Z","content":"first"}"#,
+            r#"{"type":"assistant","timestamp":"2024-01-01T00:00:01Z","message":{"content":"second"}}"#,
+            r#"{"type":"user","timestamp":"2024-01-01T00:00:02Z","content":"third"}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let session_file =
+            ClaudeSessionFile { path: path.clone(), project: "test".to_string(), session_id: "lines".to_string() };
+        let adapter = ClaudeAdapter::with_projects_dir(dir.path().to_path_buf()).with_source_lines(true);
+        let (_, events) = adapter.parse_session(&session_file).await.unwrap();
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2].content.as_deref(), Some("third"));
+        assert_eq!(Event::source_line(&events[2].raw_payload), Some(2));
+
+        let (_, events) = ClaudeAdapter::with_projects_dir(dir.path().to_path_buf())
+            .parse_session(&session_file)
+            .await
+            .unwrap();
+        assert_eq!(Event::source_line(&events[2].raw_payload), None);
+    }
+
+    #[tokio::test]
+    async fn test_parse_file_by_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_dir = dir.path().join("moved-project");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        let path = project_dir.join("abc-123.jsonl");
+        let lines = [
+            r#"{"type":"summary","summary":"Moved session","leafUuid":"uuid-2"}"#,
+            r#"{"type":"user","uuid":"uuid-1","timestamp":"2024-01-01T00:00:00Z","content":"hello"}"#,
+            r#"{"type":"assistant","uuid":"uuid-2","timestamp":"2024-01-01T00:00:01Z","message":{"content":"hi"}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let adapter = ClaudeAdapter::new();
+        let (session, events) = adapter.parse_file(&path, None).await.unwrap();
+
+        assert_eq!(session.external_id, "abc-123");
+        assert_eq!(session.project.as_deref(), Some("moved-project"));
+        assert_eq!(session.title.as_deref(), Some("Moved session"));
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.session_id == session.id));
+
+        let (session, _) = adapter.parse_file(&path, Some("explicit".to_string())).await.unwrap();
+        assert_eq!(session.project.as_deref(), Some("explicit"));
+    }
+
+    #[tokio::test]
+    async fn test_parent_uuids_resolve_to_event_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("branched.jsonl");
+        let lines = [
+            r#"{"type":"user","uuid":"u1","parentUuid":null,"timestamp":"2024-01-01T00:00:00Z","content":"hi"}"#,
+            r#"{"type":"assistant","uuid":"a1","parentUuid":"u1","timestamp":"2024-01-01T00:00:01Z","message":{"content":"hello"}}"#,
+            r#"{"type":"user","uuid":"u2","parentUuid":"a1","timestamp":"2024-01-01T00:00:02Z","content":"first try"}"#,
+            r#"{"type":"user","uuid":"u3","parentUuid":"a1","timestamp":"2024-01-01T00:00:03Z","content":"edited"}"#,
+            r#"{"type":"user","uuid":"u4","parentUuid":"gone","timestamp":"2024-01-01T00:00:04Z","content":"orphan"}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let (_, events) = ClaudeAdapter::new().parse_file(&path, None).await.unwrap();
+        let parent = |i: usize| events[i].parent_id;
+
+        assert_eq!(parent(0), None);
+        assert_eq!(parent(1), Some(events[0].id));
+        assert_eq!(parent(2), Some(events[1].id));
+        assert_eq!(parent(3), Some(events[1].id));
+        assert_eq!(parent(4), None);
+    }
+
+    #[tokio::test]
+    async fn test_large_file_streams_to_the_same_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("long.jsonl");
+        let mut contents = String::new();
+        for i in 0..20_000 {
+            let parent = if i == 0 { "null".to_string() } else { format!("\"m{}\"", i - 1) };
+            contents.push_str(&format!(
+                r#"{{"type":"user","uuid":"m{i}","parentUuid":{parent},"timestamp":"2024-01-01T00:00:00Z","content":"message {i}"}}"#
+            ));
+            // Blank and CRLF-terminated lines behave as they did when the file was read whole
+            contents.push_str(if i % 1000 == 0 { "\r\n\n" } else { "\n" });
+        }
+        contents.push_str(r#"{"type":"summary","summary":"Long session","leafUuid":"m19999"}"#);
+        std::fs::write(&path, &contents).unwrap();
+
+        let (session, events) = ClaudeAdapter::new().parse_file(&path, None).await.unwrap();
+
+        assert_eq!(session.title.as_deref(), Some("Long session"));
+        assert_eq!(session.raw_payload["line_count"], contents.lines().count());
+        assert_eq!(events.len(), 20_000);
+        assert_eq!(events[19_999].content.as_deref(), Some("message 19999"));
+        assert_eq!(events[19_999].parent_id, Some(events[19_998].id));
+    }
+
+    #[test]
+    fn test_user_tool_result_block_becomes_tool_result_event() {
+        let adapter = ClaudeAdapter::new();
+        let value = serde_json::json!({
+            "type": "user",
+            "uuid": "u3",
+            "timestamp": "2024-01-01T00:00:05Z",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "toolu_1", "content": [{"type": "text", "text": "main.rs\nlib.rs"}]}
+            ]}
+        });
+
+        let event = adapter.parse_event_line(&value, None).unwrap();
+        assert_eq!(event.kind, EventKind::ToolResult);
+        assert_eq!(event.role, Some(Role::Tool));
+        assert_eq!(event.content.as_deref(), Some("main.rs\nlib.rs"));
+        assert_eq!(event.raw_payload["tool_use_id"], "toolu_1");
+
+        let plain = serde_json::json!({
+            "type": "user",
+            "timestamp": "2024-01-01T00:00:06Z",
+            "message": {"role": "user", "content": "thanks"}
+        });
+        let event = adapter.parse_event_line(&plain, None).unwrap();
+        assert_eq!(event.kind, EventKind::Message);
+        assert_eq!(event.role, Some(Role::User));
+    }
+
+    #[tokio::test]
+    async fn test_user_text_next_to_tool_results_becomes_its_own_message() {
+        let value = serde_json::json!({
+            "type": "user",
+            "uuid": "u3",
+            "timestamp": "2024-01-01T00:00:05Z",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "toolu_1", "content": "main.rs"},
+                {"type": "text", "text": "Also check lib.rs"}
+            ]}
+        });
+        let events = ClaudeAdapter::new().parse_line_events(&value, None);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, EventKind::ToolResult);
+        assert_eq!(events[0].content.as_deref(), Some("main.rs"));
+        assert_eq!(events[1].kind, EventKind::Message);
+        assert_eq!(events[1].role, Some(Role::User));
+        assert_eq!(events[1].content.as_deref(), Some("Also check lib.rs"));
+        assert!(events[1].raw_payload.get("tool_use_id").is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mixed.jsonl");
+        let next = r#"{"type":"assistant","uuid":"a4","parentUuid":"u3","timestamp":"2024-01-01T00:00:06Z","message":{"role":"assistant","content":"Checked"}}"#;
+        std::fs::write(&path, format!("{}\n{}\n", value, next)).unwrap();
+        let (_, events) = ClaudeAdapter::new().parse_file(&path, None).await.unwrap();
+        let kinds: Vec<EventKind> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![EventKind::ToolResult, EventKind::Message, EventKind::Message]
+        );
+        assert_eq!(events[1].parent_id, Some(events[0].id));
+        assert_eq!(events[2].parent_id, Some(events[1].id));
+    }
+
+    #[test]
+    fn test_each_tool_result_block_becomes_its_own_event() {
+        let value = serde_json::json!({
+            "type": "user",
+            "timestamp": "2024-01-01T00:00:05Z",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "toolu_1", "content": "main.rs"},
+                {"type": "tool_result", "tool_use_id": "toolu_2", "content": [{"type": "text", "text": "lib.rs"}]}
+            ]}
+        });
+        let events = ClaudeAdapter::new().parse_line_events(&value, None);
+        assert_eq!(events.len(), 2);
+        for (event, (id, text)) in events.iter().zip([("toolu_1", "main.rs"), ("toolu_2", "lib.rs")]) {
+            assert_eq!(event.kind, EventKind::ToolResult);
+            assert_eq!(event.content.as_deref(), Some(text));
+            assert_eq!(event.raw_payload["tool_use_id"], id);
+            let blocks = event
+                .raw_payload
+                .pointer("/message/content")
+                .unwrap()
+                .as_array()
+                .unwrap();
+            assert_eq!(blocks.len(), 1);
+            assert_eq!(blocks[0]["tool_use_id"], id);
+        }
+        assert_ne!(events[0].id, events[1].id);
+    }
+
+    #[test]
+    fn test_tool_use_result_records_file_edits() {
+        let adapter = ClaudeAdapter::new();
+        let value = serde_json::json!({
+            "type": "user",
+            "timestamp": "2024-01-01T00:00:00Z",
+            "message": {"role": "user", "content": [{"type": "tool_result", "tool_use_id": "t1", "content": "ok"}]},
+            "toolUseResult": {
+                "filePath": "/repo/src/main.rs",
+                "oldString": "a",
+                "newString": "b",
+                "structuredPatch": [
+                    {"oldStart": 1, "oldLines": 3, "newStart": 1, "newLines": 4, "lines": [" fn main() {", "-    a();", "+    b();", "+    c();", " }"]},
+                    {"oldStart": 10, "oldLines": 1, "newStart": 11, "newLines": 1, "lines": ["-x", "+y"]}
+                ]
+            }
+        });
+
+        let event = adapter.parse_event_line(&value, None).unwrap();
+        let edits = FileEdit::from_payload(&event.raw_payload);
+        assert_eq!(
+            edits,
+            vec![FileEdit {
+                path: "/repo/src/main.rs".to_string(),
+                operation: "edit".to_string(),
+                lines_added: 3,
+                lines_removed: 2,
+            }]
+        );
+
+        let created = serde_json::json!({
+            "type": "user",
+            "timestamp": "2024-01-01T00:00:00Z",
+            "toolUseResult": {"type": "create", "filePath": "/repo/new.txt", "content": "one\ntwo\nthree", "structuredPatch": []}
+        });
+        let event = adapter.parse_event_line(&created, None).unwrap();
+        let edits = FileEdit::from_payload(&event.raw_payload);
+        assert_eq!(edits[0].operation, "create");
+        assert_eq!(edits[0].lines_added, 3);
+
+        let plain =
+            serde_json::json!({"type": "user", "timestamp": "2024-01-01T00:00:00Z", "toolUseResult": "Error: denied"});
+        let event = adapter.parse_event_line(&plain, None).unwrap();
+        assert!(event.raw_payload.get(FileEdit::PAYLOAD_KEY).is_none());
+    }
 }
@@ -1,7 +1,9 @@
-use agent_v_core::{Event, EventKind, Role, Session, Source};
+use agent_v_core::config::{self, Config};
+use agent_v_core::{AdapterError, Event, EventKind, Role, Session, Source, ToolOutcome};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use tokio::io::AsyncBufReadExt;
 
 /// A discovered Codex rollout session file
 #[derive(Debug, Clone)]
@@ -62,6 +64,9 @@ struct ResponseItem {
     call_id: Option<String>,
     #[serde(default)]
     output: Option<String>,
+    /// Readable summary of a `reasoning` item
+    #[serde(default)]
+    summary: Option<Vec<ContentBlock>>,
 }
 
 /// Content block in messages
@@ -72,10 +77,32 @@ enum ContentBlock {
     InputText { text: String },
     #[serde(rename = "output_text")]
     OutputText { text: String },
+    #[serde(rename = "summary_text")]
+    SummaryText { text: String },
+    #[serde(rename = "reasoning_text")]
+    ReasoningText { text: String },
     #[serde(other)]
     Other,
 }
 
+/// Readable text of a `reasoning` item: its full reasoning content when recorded, otherwise its
+/// summary. `None` when the rollout only kept the encrypted form.
+fn reasoning_text(item: &ResponseItem) -> Option<String> {
+    let join = |blocks: &Option<Vec<ContentBlock>>| {
+        let texts: Vec<&str> = blocks
+            .iter()
+            .flatten()
+            .filter_map(|block| match block {
+                ContentBlock::ReasoningText { text } | ContentBlock::SummaryText { text } => Some(text.trim()),
+                _ => None,
+            })
+            .filter(|text| !text.is_empty())
+            .collect();
+        (!texts.is_empty()).then(|| texts.join("\n\n"))
+    };
+    join(&item.content).or_else(|| join(&item.summary))
+}
+
 /// Event message payload
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct EventMessage {
@@ -101,11 +128,17 @@ pub struct CodexAdapter {
 }
 
 impl CodexAdapter {
-    /// Create a new Codex adapter with default sessions directory
+    /// Create a new Codex adapter with the configured or default sessions directory
     pub fn new() -> Self {
-        let sessions_dir = std::env::var("CODEX_HOME")
-            .ok()
-            .map(PathBuf::from)
+        Self::from_config(config::current())
+    }
+
+    /// Create a Codex adapter reading `sources.codex` from `config`, then `$CODEX_HOME`, then
+    /// `~/.codex/sessions`
+    pub fn from_config(config: &Config) -> Self {
+        let sessions_dir = config
+            .source_path(Source::Codex)
+            .or_else(|| std::env::var("CODEX_HOME").ok().map(PathBuf::from))
             .or_else(|| dirs::home_dir().map(|h| h.join(".codex").join("sessions")))
             .unwrap_or_else(|| PathBuf::from(".codex/sessions"));
 
@@ -214,14 +247,17 @@ impl CodexAdapter {
         sessions
     }
 
-    /// Parse a rollout session file and return a Session with its Events
-    pub async fn parse_session(
-        &self, session_file: &CodexSessionFile,
-    ) -> Result<(Session, Vec<Event>), Box<dyn std::error::Error + Send + Sync>> {
+    /// Parse a rollout session file and return a Session with its Events.
+    ///
+    /// Malformed lines are skipped as in [`crate::claude::ClaudeAdapter::parse_session`]; the file
+    /// fails when it can't be read, with [`AdapterError::Parse`] when none of its lines parse, or
+    /// [`AdapterError::Empty`] when it has none.
+    pub async fn parse_session(&self, session_file: &CodexSessionFile) -> Result<(Session, Vec<Event>), AdapterError> {
         log::debug!("Parsing session file: {:?}", session_file.path);
 
-        let content = tokio::fs::read_to_string(&session_file.path).await?;
-        let lines: Vec<&str> = content.lines().collect();
+        let file = tokio::fs::File::open(&session_file.path).await?;
+        let mut lines = tokio::io::BufReader::new(file).lines();
+        let mut line_count = 0;
 
         let mut session_meta: Option<CodexSessionMeta> = None;
         let mut events = Vec::new();
@@ -230,19 +266,25 @@ impl CodexAdapter {
         let mut project: Option<String> = None;
         let session_title: Option<String> = None;
         let mut model_name: Option<String> = None;
+        let mut parsed_lines = 0;
+        let mut first_error = None;
 
-        for (idx, line) in lines.iter().enumerate() {
+        while let Some(line) = lines.next_line().await? {
+            let idx = line_count;
+            line_count += 1;
             if line.trim().is_empty() {
                 continue;
             }
 
-            let codex_event: CodexEvent = match serde_json::from_str(line) {
+            let codex_event: CodexEvent = match serde_json::from_str(&line) {
                 Ok(e) => e,
                 Err(e) => {
                     log::warn!("Failed to parse line {} in {:?}: {}", idx, session_file.path, e);
+                    first_error.get_or_insert(AdapterError::Parse { line: idx + 1, source: e });
                     continue;
                 }
             };
+            parsed_lines += 1;
 
             let timestamp = DateTime::parse_from_rfc3339(&codex_event.timestamp)
                 .map(|dt| dt.with_timezone(&Utc))
@@ -295,6 +337,10 @@ impl CodexAdapter {
             }
         }
 
+        if parsed_lines == 0 {
+            return Err(first_error.unwrap_or(AdapterError::Empty));
+        }
+
         let created_at = first_timestamp.unwrap_or_else(Utc::now);
         let updated_at = last_timestamp.unwrap_or(created_at);
 
@@ -305,7 +351,7 @@ impl CodexAdapter {
             "session_id": external_id,
             "date": session_file.date,
             "file_path": session_file.path.to_string_lossy().to_string(),
-            "line_count": lines.len(),
+            "line_count": line_count,
             "meta": session_meta,
             "model": model_name,
         });
@@ -321,6 +367,8 @@ impl CodexAdapter {
             raw_payload,
         };
 
+        resolve_tool_names(&mut events);
+        link_parents(&mut events);
         let events: Vec<Event> = events
             .into_iter()
             .map(|mut e| {
@@ -357,7 +405,7 @@ impl CodexAdapter {
                         .filter_map(|block| match block {
                             ContentBlock::InputText { text } => Some(text),
                             ContentBlock::OutputText { text } => Some(text),
-                            ContentBlock::Other => None,
+                            _ => None,
                         })
                         .collect::<Vec<_>>()
                         .join("\n")
@@ -372,6 +420,7 @@ impl CodexAdapter {
                     content,
                     timestamp,
                     raw_payload: serde_json::to_value(codex_event).unwrap_or_default(),
+                    parent_id: None,
                 })
             }
             "function_call" => {
@@ -388,19 +437,31 @@ impl CodexAdapter {
                     content,
                     timestamp,
                     raw_payload: serde_json::to_value(codex_event).unwrap_or_default(),
+                    parent_id: None,
                 })
             }
             "function_call_output" => {
                 let output = item.output.unwrap_or_else(|| "".to_string());
 
+                let mut raw_payload = serde_json::to_value(codex_event).unwrap_or_default();
+                if let Some(outcome) = parse_shell_outcome(&output)
+                    && let Some(obj) = raw_payload.as_object_mut()
+                {
+                    obj.insert(
+                        ToolOutcome::PAYLOAD_KEY.to_string(),
+                        serde_json::to_value(outcome).unwrap_or_default(),
+                    );
+                }
+
                 Some(Event {
                     id: uuid::Uuid::new_v4(),
                     session_id: uuid::Uuid::nil(),
                     kind: EventKind::ToolResult,
-                    role: None,
+                    role: Some(Role::Tool),
                     content: Some(output),
                     timestamp,
-                    raw_payload: serde_json::to_value(codex_event).unwrap_or_default(),
+                    raw_payload,
+                    parent_id: None,
                 })
             }
             "reasoning" => Some(Event {
@@ -408,9 +469,13 @@ impl CodexAdapter {
                 session_id: uuid::Uuid::nil(),
                 kind: EventKind::System,
                 role: Some(Role::Assistant),
-                content: Some("[Reasoning content encrypted by Codex]".to_string()),
+                content: Some(match reasoning_text(&item) {
+                    Some(text) => format!("[Thinking] {}", text),
+                    None => "[Reasoning content encrypted by Codex]".to_string(),
+                }),
                 timestamp,
-                raw_payload: serde_json::to_value(codex_event).unwrap_or_default(),
+                raw_payload: categorized_payload(codex_event, "reasoning"),
+                parent_id: None,
             }),
             _ => None,
         }
@@ -429,6 +494,7 @@ impl CodexAdapter {
                 content: msg.message,
                 timestamp,
                 raw_payload: serde_json::to_value(codex_event).unwrap_or_default(),
+                parent_id: None,
             }),
             "agent_reasoning" => Some(Event {
                 id: uuid::Uuid::new_v4(),
@@ -437,7 +503,8 @@ impl CodexAdapter {
                 role: Some(Role::Assistant),
                 content: msg.message.map(|m| format!("[Thinking] {}", m)),
                 timestamp,
-                raw_payload: serde_json::to_value(codex_event).unwrap_or_default(),
+                raw_payload: categorized_payload(codex_event, "agent_reasoning"),
+                parent_id: None,
             }),
             "token_count" => None,
             _ => None,
@@ -449,7 +516,7 @@ impl CodexAdapter {
     /// Skips to the next newline after `byte_offset` to avoid partial lines.
     pub async fn parse_session_incremental(
         &self, session_file: &CodexSessionFile, byte_offset: u64,
-    ) -> Result<(Vec<Event>, u64), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(Vec<Event>, u64), AdapterError> {
         use tokio::io::{AsyncReadExt, AsyncSeekExt};
 
         let mut file = tokio::fs::File::open(&session_file.path).await?;
@@ -504,9 +571,7 @@ impl CodexAdapter {
     }
 
     /// Get statistics about a rollout session file
-    pub async fn get_session_stats(
-        &self, session_file: &CodexSessionFile,
-    ) -> Result<SessionStats, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn get_session_stats(&self, session_file: &CodexSessionFile) -> Result<SessionStats, AdapterError> {
         let content = tokio::fs::read_to_string(&session_file.path).await?;
         let lines: Vec<&str> = content.lines().collect();
 
@@ -579,6 +644,109 @@ impl Default for CodexAdapter {
     }
 }
 
+/// Serialize a rollout entry as a raw payload tagged with its Codex sub-type
+fn categorized_payload(codex_event: &CodexEvent, category: &str) -> serde_json::Value {
+    let mut payload = serde_json::to_value(codex_event).unwrap_or_default();
+    Event::set_category(&mut payload, category);
+    payload
+}
+
+/// Extract the exit code of a shell tool from its output.
+///
+/// Codex has reported shell results both as a JSON envelope
+/// (`{"output": ..., "metadata": {"exit_code": N, "duration_seconds": S}}`)
+/// and as plain text headed by `Exit code: N` / `Wall time: S seconds`.
+/// Outputs matching neither shape (e.g. non-shell tools) yield `None`.
+fn parse_shell_outcome(output: &str) -> Option<ToolOutcome> {
+    if let Ok(envelope) = serde_json::from_str::<serde_json::Value>(output)
+        && let Some(metadata) = envelope.get("metadata")
+    {
+        let exit_code = metadata.get("exit_code")?.as_i64()?;
+        let duration_ms = metadata
+            .get("duration_seconds")
+            .and_then(|secs| secs.as_f64())
+            .map(|secs| (secs * 1000.0).round() as i64);
+        return Some(ToolOutcome::from_exit_code(exit_code as i32, duration_ms));
+    }
+
+    let mut exit_code = None;
+    let mut duration_ms = None;
+    for line in output.lines().take(4) {
+        let line = line.trim();
+        if let Some(code) = line
+            .strip_prefix("Exit code:")
+            .or_else(|| line.strip_prefix("Process exited with code"))
+        {
+            exit_code = code.trim().parse::<i32>().ok();
+        } else if let Some(wall) = line.strip_prefix("Wall time:") {
+            duration_ms = wall
+                .trim()
+                .trim_end_matches("seconds")
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .map(|secs| (secs * 1000.0).round() as i64);
+        }
+    }
+
+    exit_code.map(|code| ToolOutcome::from_exit_code(code, duration_ms))
+}
+
+/// Fill in tool names on recorded outcomes by matching each result's `call_id` to its function call
+fn resolve_tool_names(events: &mut [Event]) {
+    let call_id = |event: &Event| {
+        event
+            .raw_payload
+            .pointer("/payload/call_id")
+            .and_then(|id| id.as_str())
+            .map(str::to_string)
+    };
+
+    let mut names = std::collections::HashMap::new();
+    for event in events.iter_mut() {
+        match event.kind {
+            EventKind::ToolCall => {
+                if let (Some(id), Some(name)) = (call_id(event), event.raw_payload.pointer("/payload/name")) {
+                    names.insert(id, name.clone());
+                }
+            }
+            EventKind::ToolResult => {
+                if let Some(name) = call_id(event).and_then(|id| names.get(&id))
+                    && let Some(outcome) = event.raw_payload.get_mut(ToolOutcome::PAYLOAD_KEY)
+                    && let Some(obj) = outcome.as_object_mut()
+                {
+                    obj.insert("tool_name".to_string(), name.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Record each event's parent: a function call's output hangs off the call with the same
+/// `call_id`, and everything else follows the event before it, since rollouts are linear
+fn link_parents(events: &mut [Event]) {
+    let mut calls = std::collections::HashMap::new();
+    let mut previous: Option<uuid::Uuid> = None;
+    for event in events.iter_mut() {
+        let call_id = event
+            .raw_payload
+            .pointer("/payload/call_id")
+            .and_then(|id| id.as_str())
+            .map(str::to_string);
+        let parent = match (event.kind, call_id) {
+            (EventKind::ToolCall, Some(id)) => {
+                calls.insert(id, event.id);
+                previous
+            }
+            (EventKind::ToolResult, Some(id)) => calls.get(&id).copied().or(previous),
+            _ => previous,
+        };
+        event.parent_id = parent;
+        previous = Some(event.id);
+    }
+}
+
 /// Statistics about a Codex rollout session
 #[derive(Debug, Clone, Default)]
 pub struct SessionStats {
@@ -646,6 +814,60 @@ mod tests {
         assert!(event.content.is_some());
     }
 
+    #[test]
+    fn test_function_call_output_nonzero_exit_marks_failure() {
+        let adapter = CodexAdapter::new();
+        let call = CodexEvent {
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            event_type: "response_item".to_string(),
+            payload: serde_json::json!({
+                "type": "function_call",
+                "name": "shell",
+                "arguments": "{\"command\": [\"cargo\", \"test\"]}",
+                "call_id": "call_456"
+            }),
+        };
+        let output = |output: &str| CodexEvent {
+            timestamp: "2024-01-01T00:00:02Z".to_string(),
+            event_type: "response_item".to_string(),
+            payload: serde_json::json!({
+                "type": "function_call_output",
+                "call_id": "call_456",
+                "output": output
+            }),
+        };
+
+        let failed = r#"{"output":"error: test failed","metadata":{"exit_code":101,"duration_seconds":1.5}}"#;
+        let mut events = vec![
+            adapter.parse_response_item(&call, Utc::now()).unwrap(),
+            adapter.parse_response_item(&output(failed), Utc::now()).unwrap(),
+        ];
+        resolve_tool_names(&mut events);
+        assert_eq!(events[0].role, Some(Role::Assistant));
+        assert_eq!(
+            (events[1].kind, events[1].role),
+            (EventKind::ToolResult, Some(Role::Tool))
+        );
+
+        let outcome = ToolOutcome::from_payload(&events[1].raw_payload).unwrap();
+        assert!(!outcome.success);
+        assert_eq!(outcome.exit_code, Some(101));
+        assert_eq!(outcome.duration_ms, Some(1500));
+        assert_eq!(outcome.tool_name.as_deref(), Some("shell"));
+
+        let text = adapter
+            .parse_response_item(&output("Exit code: 0\nWall time: 0.2 seconds\nOutput:\nok"), Utc::now())
+            .unwrap();
+        let outcome = ToolOutcome::from_payload(&text.raw_payload).unwrap();
+        assert!(outcome.success);
+        assert_eq!(outcome.duration_ms, Some(200));
+
+        let plain = adapter
+            .parse_response_item(&output("file contents"), Utc::now())
+            .unwrap();
+        assert!(ToolOutcome::from_payload(&plain.raw_payload).is_none());
+    }
+
     #[test]
     fn test_parse_event_msg_user_message() {
         let adapter = CodexAdapter::new();
@@ -665,4 +887,99 @@ mod tests {
         assert_eq!(event.role, Some(Role::User));
         assert_eq!(event.content, Some("Test message".to_string()));
     }
+
+    #[test]
+    fn test_readable_reasoning_is_kept_and_encrypted_reasoning_is_a_placeholder() {
+        let adapter = CodexAdapter::new();
+        let reasoning = |payload: serde_json::Value| {
+            let codex_event = CodexEvent {
+                timestamp: "2024-01-01T00:00:00Z".to_string(),
+                event_type: "response_item".to_string(),
+                payload,
+            };
+            adapter.parse_response_item(&codex_event, Utc::now()).unwrap()
+        };
+
+        let summarized = reasoning(serde_json::json!({
+            "type": "reasoning",
+            "summary": [
+                {"type": "summary_text", "text": "**Listing files**"},
+                {"type": "summary_text", "text": "Then reading the config."}
+            ],
+            "content": null,
+            "encrypted_content": "gAAAAB..."
+        }));
+        assert_eq!(summarized.kind, EventKind::System);
+        assert_eq!(summarized.role, Some(Role::Assistant));
+        assert_eq!(
+            summarized.content.as_deref(),
+            Some("[Thinking] **Listing files**\n\nThen reading the config.")
+        );
+
+        let full = reasoning(serde_json::json!({
+            "type": "reasoning",
+            "summary": [{"type": "summary_text", "text": "Short version"}],
+            "content": [{"type": "reasoning_text", "text": "The long version"}]
+        }));
+        assert_eq!(full.content.as_deref(), Some("[Thinking] The long version"));
+
+        let encrypted = reasoning(serde_json::json!({
+            "type": "reasoning",
+            "summary": [],
+            "encrypted_content": "gAAAAB..."
+        }));
+        assert_eq!(
+            encrypted.content.as_deref(),
+            Some("[Reasoning content encrypted by Codex]")
+        );
+        assert_eq!(encrypted.kind, EventKind::System);
+    }
+
+    #[tokio::test]
+    async fn test_large_rollout_streams_to_the_same_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        let mut contents = String::new();
+        for i in 0..20_000 {
+            contents.push_str(&format!(
+                r#"{{"timestamp":"2024-01-01T00:00:00Z","type":"response_item","payload":{{"type":"message","role":"user","content":[{{"type":"input_text","text":"turn {i}"}}]}}}}"#
+            ));
+            contents.push('\n');
+        }
+        std::fs::write(&path, &contents).unwrap();
+
+        let session_file = CodexSessionFile { path, session_id: "rollout".to_string(), date: "2024/01/01".to_string() };
+        let (session, events) = CodexAdapter::new().parse_session(&session_file).await.unwrap();
+
+        assert_eq!(session.raw_payload["line_count"], 20_000);
+        assert_eq!(events.len(), 20_000);
+        assert_eq!(events[0].content.as_deref(), Some("turn 0"));
+        assert_eq!(events[19_999].content.as_deref(), Some("turn 19999"));
+    }
+
+    #[test]
+    fn test_outputs_link_to_their_calls_and_the_rest_is_linear() {
+        let adapter = CodexAdapter::new();
+        let item = |payload: serde_json::Value| {
+            let codex_event = CodexEvent {
+                timestamp: "2024-01-01T00:00:00Z".to_string(),
+                event_type: "response_item".to_string(),
+                payload,
+            };
+            adapter.parse_response_item(&codex_event, Utc::now()).unwrap()
+        };
+        let call = |id: &str| item(serde_json::json!({"type": "function_call", "name": "shell", "call_id": id}));
+        let output = |id: &str| item(serde_json::json!({"type": "function_call_output", "call_id": id, "output": ""}));
+        let message = item(serde_json::json!({"type": "message", "role": "user", "content": []}));
+
+        let mut events = vec![message, call("a"), call("b"), output("b"), output("a")];
+        link_parents(&mut events);
+        let parent = |i: usize| events[i].parent_id;
+
+        assert_eq!(parent(0), None);
+        assert_eq!(parent(1), Some(events[0].id));
+        assert_eq!(parent(2), Some(events[1].id));
+        assert_eq!(parent(3), Some(events[2].id));
+        assert_eq!(parent(4), Some(events[1].id));
+    }
 }
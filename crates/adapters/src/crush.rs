@@ -1,5 +1,8 @@
-use agent_v_core::{Event, EventKind, Role, Session, Source};
+use crate::sqlite_error;
+use agent_v_core::config::{self, Config};
+use agent_v_core::{AdapterError, Attachment, Event, EventKind, Role, Session, Source};
 use chrono::{DateTime, TimeZone, Utc};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use rayon::prelude::*;
 use rusqlite::{Connection, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
@@ -186,25 +189,111 @@ struct SchemaFeatures {
     has_read_files_table: bool,
 }
 
+/// Include/exclude path globs applied while searching for Crush databases.
+/// A leading `~/` in a pattern expands to the home directory, and relative patterns are anchored
+/// to the directory being searched.
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl DiscoveryFilter {
+    /// Build a filter from glob patterns; empty lists impose no restriction
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, globset::Error> {
+        let filter = Self {
+            include: include.iter().map(|p| expand_home(p)).collect(),
+            exclude: exclude.iter().map(|p| expand_home(p)).collect(),
+        };
+        filter.anchored(Path::new("/"))?;
+        Ok(filter)
+    }
+
+    /// Compile the patterns for a walk of `root`, joining relative ones onto it
+    fn anchored(&self, root: &Path) -> Result<AnchoredFilter, globset::Error> {
+        let include: Vec<String> = self.include.iter().map(|p| anchor_pattern(p, root)).collect();
+        let exclude: Vec<String> = self.exclude.iter().map(|p| anchor_pattern(p, root)).collect();
+        Ok(AnchoredFilter {
+            include: build_glob_set(&include)?,
+            include_prefixes: include.iter().map(|p| literal_prefix(p)).collect(),
+            exclude: build_glob_set(&exclude)?,
+        })
+    }
+}
+
+/// A [`DiscoveryFilter`] compiled against one search root
+struct AnchoredFilter {
+    include: Option<GlobSet>,
+    /// Literal leading directories of each include pattern; only paths on the way to or under one
+    /// of them can hold a match
+    include_prefixes: Vec<PathBuf>,
+    exclude: Option<GlobSet>,
+}
+
+impl AnchoredFilter {
+    /// Whether the walk should descend into or keep this path
+    fn allows_entry(&self, path: &Path) -> bool {
+        let excluded = self.exclude.as_ref().is_some_and(|set| set.is_match(path));
+        let reachable = self.include.is_none()
+            || self
+                .include_prefixes
+                .iter()
+                .any(|prefix| path.starts_with(prefix) || prefix.starts_with(path));
+        !excluded && reachable
+    }
+
+    /// Whether a discovered database should be read
+    fn allows_db(&self, path: &Path) -> bool {
+        self.allows_entry(path) && self.include.as_ref().is_none_or(|set| set.is_match(path))
+    }
+}
+
 /// Adapter for Crush databases
 #[derive(Debug, Clone)]
 pub struct CrushAdapter {
     db_path: PathBuf,
+    /// Directory searched for `.crush/crush.db` files; the home directory when unset
+    search_root: Option<PathBuf>,
+    capture_attachments: bool,
+    discovery_filter: DiscoveryFilter,
 }
 
 impl CrushAdapter {
-    /// Create a new Crush adapter with default database path
+    /// Create a new Crush adapter with default database path and the configured search root
     pub fn new() -> Self {
+        Self::from_config(config::current())
+    }
+
+    /// Create a Crush adapter that searches `sources.crush` from `config` instead of the home
+    /// directory
+    pub fn from_config(config: &Config) -> Self {
         let db_path = dirs::home_dir()
             .map(|h| h.join(".crush").join("crush.db"))
             .unwrap_or_else(|| PathBuf::from(".crush/crush.db"));
 
-        Self { db_path }
+        Self {
+            db_path,
+            search_root: config.source_path(Source::Crush),
+            capture_attachments: false,
+            discovery_filter: DiscoveryFilter::default(),
+        }
     }
 
     /// Create a new Crush adapter with a custom database path
     pub fn with_db_path(db_path: PathBuf) -> Self {
-        Self { db_path }
+        Self { db_path, search_root: None, capture_attachments: false, discovery_filter: DiscoveryFilter::default() }
+    }
+
+    /// Limit database discovery to paths passing the include/exclude globs
+    pub fn with_discovery_filter(mut self, filter: DiscoveryFilter) -> Self {
+        self.discovery_filter = filter;
+        self
+    }
+
+    /// Record local image paths from message parts as event attachments
+    pub fn with_attachments(mut self, enabled: bool) -> Self {
+        self.capture_attachments = enabled;
+        self
     }
 
     /// Get the database path
@@ -212,43 +301,26 @@ impl CrushAdapter {
         &self.db_path
     }
 
+    /// Directory searched for Crush databases
+    pub fn search_root(&self) -> Option<PathBuf> {
+        self.search_root.clone().or_else(dirs::home_dir)
+    }
+
     /// Discover all Crush databases and their sessions
     pub async fn discover_sessions(&self) -> Vec<CrushSessionFile> {
         let mut sessions = Vec::new();
 
-        let home_dir = match dirs::home_dir() {
-            Some(home) => home,
+        let root = match self.search_root() {
+            Some(root) => root,
             None => {
                 log::warn!("Could not determine home directory");
                 return sessions;
             }
         };
 
-        log::info!("Searching for Crush databases in: {:?}", home_dir);
+        log::info!("Searching for Crush databases in: {:?}", root);
 
-        let db_paths: Vec<PathBuf> = WalkDir::new(&home_dir)
-            .max_depth(6)
-            .into_iter()
-            .filter_entry(|entry| {
-                let name = entry.file_name().to_string_lossy();
-                let is_common_skip = matches!(
-                    name.as_ref(),
-                    "node_modules" | "target" | "vendor" | "build" | "dist" | ".git" | "Cache"
-                );
-                !is_common_skip || entry.file_type().is_file()
-            })
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-            .filter(|e| {
-                e.file_name() == "crush.db"
-                    && e.path()
-                        .parent()
-                        .and_then(|p| p.file_name())
-                        .map(|n| n == ".crush")
-                        .unwrap_or(false)
-            })
-            .map(|e| e.path().to_path_buf())
-            .collect();
+        let db_paths = self.find_databases(&root);
 
         log::info!("Found {} Crush database(s)", db_paths.len());
 
@@ -273,13 +345,47 @@ impl CrushAdapter {
         sessions
     }
 
+    /// Walk `root` for `.crush/crush.db` files that pass the discovery filter
+    fn find_databases(&self, root: &Path) -> Vec<PathBuf> {
+        let filter = match self.discovery_filter.anchored(root) {
+            Ok(filter) => filter,
+            Err(e) => {
+                log::warn!("Invalid Crush discovery filter for {:?}: {}", root, e);
+                return Vec::new();
+            }
+        };
+
+        WalkDir::new(root)
+            .max_depth(6)
+            .into_iter()
+            .filter_entry(|entry| {
+                let name = entry.file_name().to_string_lossy();
+                let is_common_skip = matches!(
+                    name.as_ref(),
+                    "node_modules" | "target" | "vendor" | "build" | "dist" | ".git" | "Cache"
+                );
+                (!is_common_skip || entry.file_type().is_file()) && filter.allows_entry(entry.path())
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter(|e| {
+                e.file_name() == "crush.db"
+                    && e.path()
+                        .parent()
+                        .and_then(|p| p.file_name())
+                        .map(|n| n == ".crush")
+                        .unwrap_or(false)
+            })
+            .filter(|e| filter.allows_db(e.path()))
+            .map(|e| e.path().to_path_buf())
+            .collect()
+    }
+
     /// Discover sessions within a specific Crush database
-    fn discover_sessions_in_db(
-        &self, db_path: &Path,
-    ) -> Result<Vec<CrushSessionFile>, Box<dyn std::error::Error + Send + Sync>> {
+    fn discover_sessions_in_db(&self, db_path: &Path) -> Result<Vec<CrushSessionFile>, AdapterError> {
         let mut sessions = Vec::new();
 
-        let conn = Connection::open(db_path)?;
+        let conn = Connection::open(db_path).map_err(sqlite_error)?;
 
         let has_sessions: i64 = conn.query_row(CHECK_SESSIONS_TABLE, [], |row| row.get(0)).unwrap_or(0);
 
@@ -288,12 +394,14 @@ impl CrushAdapter {
             return Ok(sessions);
         }
 
-        let mut stmt = conn.prepare(LIST_SESSIONS)?;
+        let mut stmt = conn.prepare(LIST_SESSIONS).map_err(sqlite_error)?;
 
-        let session_ids = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let session_ids = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(sqlite_error)?;
 
         for session_id in session_ids {
-            let session_id = session_id?;
+            let session_id = session_id.map_err(sqlite_error)?;
             sessions.push(CrushSessionFile { path: db_path.to_path_buf(), session_id });
         }
 
@@ -301,19 +409,21 @@ impl CrushAdapter {
     }
 
     /// Parse a session from the Crush database
-    pub async fn parse_session(
-        &self, session_file: &CrushSessionFile,
-    ) -> Result<(Session, Vec<Event>), Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn parse_session(&self, session_file: &CrushSessionFile) -> Result<(Session, Vec<Event>), AdapterError> {
         log::debug!(
             "Parsing Crush session: {} from {:?}",
             session_file.session_id,
             session_file.path
         );
 
-        let conn = Connection::open(&session_file.path)?;
-        let features = self.detect_schema_features(&conn)?;
-        let crush_session = self.get_session(&conn, &session_file.session_id, &features)?;
-        let messages = self.get_session_messages(&conn, &session_file.session_id, &features)?;
+        let conn = Connection::open(&session_file.path).map_err(sqlite_error)?;
+        let features = self.detect_schema_features(&conn).map_err(sqlite_error)?;
+        let crush_session = self
+            .get_session(&conn, &session_file.session_id, &features)
+            .map_err(sqlite_error)?;
+        let messages = self
+            .get_session_messages(&conn, &session_file.session_id, &features)
+            .map_err(sqlite_error)?;
         let read_files = self.get_read_files(&conn, &session_file.session_id, &features);
 
         let created_at = timestamp_to_datetime(crush_session.created_at);
@@ -471,16 +581,18 @@ impl CrushAdapter {
 
         let parts: Vec<ContentPart> = serde_json::from_str(&msg.parts).unwrap_or_default();
 
+        let (kind, content) = self.extract_content_from_parts(&parts, &msg.role);
+
         let role = match msg.role.as_str() {
+            _ if kind == EventKind::ToolResult => Some(Role::Tool),
             "user" => Some(Role::User),
             "assistant" => Some(Role::Assistant),
             "system" => Some(Role::System),
+            "tool" => Some(Role::Tool),
             _ => None,
         };
 
-        let (kind, content) = self.extract_content_from_parts(&parts, &msg.role);
-
-        let raw_payload = serde_json::json!({
+        let mut raw_payload = serde_json::json!({
             "id": msg.id,
             "session_id": msg.session_id,
             "role": msg.role,
@@ -493,13 +605,33 @@ impl CrushAdapter {
             "is_summary_message": msg.is_summary_message,
         });
 
-        Some(Event { id: Uuid::new_v4(), session_id: Uuid::nil(), kind, role, content, timestamp, raw_payload })
+        if self.capture_attachments {
+            let attachments = extract_attachments(&parts);
+            if !attachments.is_empty() {
+                raw_payload[Attachment::PAYLOAD_KEY] = serde_json::to_value(attachments).unwrap_or_default();
+            }
+        }
+
+        Some(Event {
+            id: Uuid::new_v4(),
+            session_id: Uuid::nil(),
+            kind,
+            role,
+            content,
+            timestamp,
+            raw_payload,
+            parent_id: None,
+        })
     }
 
     /// Extract content and event kind from message parts
+    ///
+    /// Assistant messages with tool use are tool calls, and messages carrying only tool results
+    /// (Crush stores these under the `tool` role) are tool results.
     fn extract_content_from_parts(&self, parts: &[ContentPart], role: &str) -> (EventKind, Option<String>) {
         let mut content_parts = Vec::new();
         let mut tool_calls = Vec::new();
+        let mut has_tool_results = false;
 
         for part in parts {
             match part {
@@ -514,6 +646,7 @@ impl CrushAdapter {
                     content_parts.push(format!("[Tool: {}]", data.name));
                 }
                 ContentPart::ToolResult { data } => {
+                    has_tool_results = true;
                     let prefix = if data.is_error.unwrap_or(false) { "[Error]" } else { "[Result]" };
                     content_parts.push(format!("{} {}", prefix, data.content));
                 }
@@ -527,7 +660,13 @@ impl CrushAdapter {
             }
         }
 
-        let kind = if role == "assistant" && !tool_calls.is_empty() { EventKind::ToolCall } else { EventKind::Message };
+        let kind = if role == "assistant" && !tool_calls.is_empty() {
+            EventKind::ToolCall
+        } else if has_tool_results && tool_calls.is_empty() {
+            EventKind::ToolResult
+        } else {
+            EventKind::Message
+        };
 
         let content = if content_parts.is_empty() { None } else { Some(content_parts.join("\n")) };
 
@@ -538,9 +677,9 @@ impl CrushAdapter {
     /// Returns new events and the new last_created_at value.
     pub async fn parse_session_incremental(
         &self, session_file: &CrushSessionFile, last_created_at: i64,
-    ) -> Result<(Vec<Event>, i64), Box<dyn std::error::Error + Send + Sync>> {
-        let conn = Connection::open(&session_file.path)?;
-        let features = self.detect_schema_features(&conn)?;
+    ) -> Result<(Vec<Event>, i64), AdapterError> {
+        let conn = Connection::open(&session_file.path).map_err(sqlite_error)?;
+        let features = self.detect_schema_features(&conn).map_err(sqlite_error)?;
 
         let query = if features.has_provider_column && features.has_is_summary_message {
             "SELECT id, session_id, role, parts, model, provider, created_at, updated_at, finished_at, is_summary_message \
@@ -556,7 +695,7 @@ impl CrushAdapter {
              FROM messages WHERE session_id = ? AND created_at > ? ORDER BY created_at ASC"
         };
 
-        let mut stmt = conn.prepare(query)?;
+        let mut stmt = conn.prepare(query).map_err(sqlite_error)?;
         let messages: Vec<CrushMessage> = stmt
             .query_map(rusqlite::params![&session_file.session_id, last_created_at], |row| {
                 Ok(CrushMessage {
@@ -571,8 +710,9 @@ impl CrushAdapter {
                     finished_at: row.get(8)?,
                     is_summary_message: row.get(9)?,
                 })
-            })?
-            .collect::<SqliteResult<Vec<_>>>()?;
+            })
+            .and_then(|rows| rows.collect::<SqliteResult<Vec<_>>>())
+            .map_err(sqlite_error)?;
 
         let mut new_last = last_created_at;
         let events: Vec<Event> = messages
@@ -595,6 +735,60 @@ impl Default for CrushAdapter {
     }
 }
 
+/// Compile glob patterns into a set; `None` when there are none
+fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>, globset::Error> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    builder.build().map(Some)
+}
+
+/// Join a relative glob pattern onto `root`, escaping any glob syntax in the root itself
+fn anchor_pattern(pattern: &str, root: &Path) -> String {
+    if Path::new(pattern).is_absolute() {
+        return pattern.to_string();
+    }
+    let root = globset::escape(&root.to_string_lossy());
+    format!("{}/{}", root.trim_end_matches('/'), pattern)
+}
+
+/// Expand a leading `~/` in a glob pattern to the home directory
+fn expand_home(pattern: &str) -> String {
+    match (pattern.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().into_owned(),
+        _ => pattern.to_string(),
+    }
+}
+
+/// The leading path components of a glob pattern that contain no glob syntax; empty when the
+/// pattern starts with a wildcard
+fn literal_prefix(pattern: &str) -> PathBuf {
+    Path::new(pattern)
+        .components()
+        .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[', '{', '\\']))
+        .collect()
+}
+
+/// Collect image parts that point at a local file
+fn extract_attachments(parts: &[ContentPart]) -> Vec<Attachment> {
+    parts
+        .iter()
+        .filter_map(|part| match part {
+            ContentPart::Image { data } => {
+                let path = data.get("path").and_then(|v| v.as_str())?;
+                let mime_type = data.get("mime_type").and_then(|v| v.as_str()).map(String::from);
+                Attachment::from_local(path, mime_type)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 /// Convert Crush Unix timestamp (milliseconds) to DateTime<Utc>
 fn timestamp_to_datetime(ts_millis: i64) -> DateTime<Utc> {
     let secs = if ts_millis > 1_000_000_000_000 { ts_millis / 1000 } else { ts_millis };
@@ -614,6 +808,21 @@ mod tests {
         assert!(path_str.contains(".crush") || path_str.contains("crush.db"));
     }
 
+    #[tokio::test]
+    async fn test_database_without_crush_tables_is_a_schema_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("crush.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch("CREATE TABLE notes (body TEXT);")
+            .unwrap();
+
+        let session = CrushSessionFile { path, session_id: "s1".to_string() };
+        let err = CrushAdapter::new().parse_session(&session).await.unwrap_err();
+        assert!(matches!(err, AdapterError::Schema(_)), "{}", err);
+        assert!(err.to_string().contains("sessions"), "{}", err);
+    }
+
     #[test]
     fn test_timestamp_conversion() {
         let ts = 1704067200;
@@ -651,6 +860,48 @@ mod tests {
         assert!(content.unwrap().contains("read_file"));
     }
 
+    #[test]
+    fn test_tool_result_message_has_the_tool_role() {
+        let parts = serde_json::json!([
+            {"type": "tool_result", "data": {"tool_use_id": "call_123", "content": "file contents", "is_error": false}}
+        ]);
+        let msg = CrushMessage {
+            id: "msg_2".to_string(),
+            session_id: "s1".to_string(),
+            role: "tool".to_string(),
+            parts: parts.to_string(),
+            model: None,
+            provider: None,
+            created_at: 1704067200,
+            updated_at: 1704067200,
+            finished_at: None,
+            is_summary_message: 0,
+        };
+
+        let event = CrushAdapter::new()
+            .message_to_event(msg, &SchemaFeatures::default())
+            .unwrap();
+        assert_eq!(event.kind, EventKind::ToolResult);
+        assert_eq!(event.role, Some(Role::Tool));
+        assert_eq!(event.content.as_deref(), Some("[Result] file contents"));
+    }
+
+    #[test]
+    fn test_extract_attachments_from_image_part() {
+        let parts: Vec<ContentPart> = serde_json::from_value(serde_json::json!([
+            {"type": "text", "data": {"text": "What's in this screenshot?"}},
+            {"type": "image", "data": {"path": "/home/user/shot.png", "mime_type": "image/png"}},
+            {"type": "image", "data": {"data": "aGVsbG8=", "mime_type": "image/jpeg"}},
+        ]))
+        .unwrap();
+
+        let attachments = extract_attachments(&parts);
+        assert_eq!(
+            attachments,
+            vec![Attachment { path: "/home/user/shot.png".to_string(), mime_type: Some("image/png".to_string()) }]
+        );
+    }
+
     #[test]
     fn test_content_part_serialization() {
         let part = ContentPart::Text { data: TextData { text: "Test content".to_string() } };
@@ -658,4 +909,70 @@ mod tests {
         assert!(json.contains("text"));
         assert!(json.contains("Test content"));
     }
+
+    #[test]
+    fn test_discovery_exclude_skips_matching_database() {
+        let root = tempfile::tempdir().unwrap();
+        for project in ["keep", "skip"] {
+            let dir = root.path().join(project).join(".crush");
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("crush.db"), b"").unwrap();
+        }
+
+        let adapter = CrushAdapter::new();
+        assert_eq!(adapter.find_databases(root.path()).len(), 2);
+
+        let filter = DiscoveryFilter::new(&[], &["**/skip/**".to_string()]).unwrap();
+        let adapter = CrushAdapter::new().with_discovery_filter(filter);
+        let found = adapter.find_databases(root.path());
+        assert_eq!(found, vec![root.path().join("keep").join(".crush").join("crush.db")]);
+
+        let include = format!("{}/skip/**", root.path().display());
+        let filter = DiscoveryFilter::new(&[include], &[]).unwrap();
+        let adapter = CrushAdapter::new().with_discovery_filter(filter);
+        let found = adapter.find_databases(root.path());
+        assert_eq!(found, vec![root.path().join("skip").join(".crush").join("crush.db")]);
+    }
+
+    #[test]
+    fn test_discovery_include_prunes_directories_outside_its_prefix() {
+        assert_eq!(
+            literal_prefix("/home/me/src/*/.crush/crush.db"),
+            PathBuf::from("/home/me/src")
+        );
+        assert_eq!(literal_prefix("/home/me/pro[jk]ects/**"), PathBuf::from("/home/me"));
+        assert_eq!(literal_prefix("**/.crush/crush.db"), PathBuf::new());
+
+        let root = Path::new("/home/me");
+        let filter = DiscoveryFilter::new(&["/home/me/src/**".to_string()], &[])
+            .unwrap()
+            .anchored(root)
+            .unwrap();
+        assert!(filter.allows_entry(Path::new("/home")));
+        assert!(filter.allows_entry(Path::new("/home/me")));
+        assert!(filter.allows_entry(Path::new("/home/me/src/app/.crush")));
+        assert!(!filter.allows_entry(Path::new("/home/me/Downloads")));
+        assert!(!filter.allows_entry(Path::new("/home/other")));
+
+        let anywhere = DiscoveryFilter::new(&["**/src/**".to_string()], &[])
+            .unwrap()
+            .anchored(Path::new("/"))
+            .unwrap();
+        assert!(anywhere.allows_entry(Path::new("/home/me/Downloads")));
+    }
+
+    #[test]
+    fn test_discovery_relative_include_is_anchored_to_the_search_root() {
+        let root = tempfile::tempdir().unwrap();
+        for project in ["projects/app", "scratch"] {
+            let dir = root.path().join(project).join(".crush");
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("crush.db"), b"").unwrap();
+        }
+
+        let filter = DiscoveryFilter::new(&["projects/**".to_string()], &[]).unwrap();
+        let adapter = CrushAdapter::new().with_discovery_filter(filter);
+        let found = adapter.find_databases(root.path());
+        assert_eq!(found, vec![root.path().join("projects/app/.crush/crush.db")]);
+    }
 }
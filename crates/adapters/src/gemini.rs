@@ -0,0 +1,436 @@
+use agent_v_core::config::{self, Config};
+use agent_v_core::{AdapterError, Event, EventKind, Role, Session, Source, ToolOutcome};
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+
+/// A discovered Gemini CLI chat file
+#[derive(Debug, Clone)]
+pub struct GeminiSessionFile {
+    pub path: PathBuf,
+    pub session_id: String,
+    /// Hash of the project directory the chat was recorded in
+    pub project_hash: String,
+}
+
+/// Adapter for Gemini CLI sessions, saved as one JSON file per chat under
+/// `~/.gemini/tmp/<project hash>/chats/`
+#[derive(Debug, Clone)]
+pub struct GeminiAdapter {
+    tmp_dir: PathBuf,
+}
+
+impl GeminiAdapter {
+    /// Create a new Gemini adapter with the configured or default `~/.gemini/tmp` directory
+    pub fn new() -> Self {
+        Self::from_config(config::current())
+    }
+
+    /// Create a Gemini adapter rooted at `sources.gemini` from `config`, or `~/.gemini/tmp`
+    pub fn from_config(config: &Config) -> Self {
+        let tmp_dir = config
+            .source_path(Source::Gemini)
+            .or_else(|| dirs::home_dir().map(|h| h.join(".gemini").join("tmp")))
+            .unwrap_or_else(|| PathBuf::from(".gemini/tmp"));
+
+        Self { tmp_dir }
+    }
+
+    /// Create a new Gemini adapter with a custom tmp directory
+    pub fn with_tmp_dir(tmp_dir: PathBuf) -> Self {
+        Self { tmp_dir }
+    }
+
+    /// Get the tmp directory path
+    pub fn tmp_dir(&self) -> &PathBuf {
+        &self.tmp_dir
+    }
+
+    /// Discover all chat files across project directories
+    pub async fn discover_sessions(&self) -> Vec<GeminiSessionFile> {
+        let mut sessions = Vec::new();
+
+        if !self.tmp_dir.exists() {
+            log::warn!("Gemini tmp directory not found: {:?}", self.tmp_dir);
+            return sessions;
+        }
+
+        let mut project_entries = match tokio::fs::read_dir(&self.tmp_dir).await {
+            Ok(e) => e,
+            Err(e) => {
+                log::error!("Failed to read Gemini tmp directory: {}", e);
+                return sessions;
+            }
+        };
+
+        while let Ok(Some(project_entry)) = project_entries.next_entry().await {
+            let chats_dir = project_entry.path().join("chats");
+            if !chats_dir.is_dir() {
+                continue;
+            }
+            let project_hash = project_entry.file_name().to_string_lossy().to_string();
+
+            let mut chat_files = match tokio::fs::read_dir(&chats_dir).await {
+                Ok(f) => f,
+                Err(e) => {
+                    log::warn!("Failed to read chats directory {:?}: {}", chats_dir, e);
+                    continue;
+                }
+            };
+
+            while let Ok(Some(file_entry)) = chat_files.next_entry().await {
+                let path = file_entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                    continue;
+                }
+                if let Some(session_id) = session_id_from_path(&path) {
+                    sessions.push(GeminiSessionFile { path, session_id, project_hash: project_hash.clone() });
+                }
+            }
+        }
+
+        log::info!("Discovered {} Gemini sessions", sessions.len());
+        sessions
+    }
+
+    /// Parse a chat file and return a Session with its Events.
+    ///
+    /// User and model turns become messages, model thoughts become system events, and each
+    /// recorded tool call becomes a call/result pair.
+    pub async fn parse_session(&self, session_file: &GeminiSessionFile) -> Result<(Session, Vec<Event>), AdapterError> {
+        log::debug!("Parsing Gemini chat file: {:?}", session_file.path);
+
+        let content = tokio::fs::read_to_string(&session_file.path).await?;
+        if content.trim().is_empty() {
+            return Err(AdapterError::Empty);
+        }
+        let chat: serde_json::Value = serde_json::from_str(&content)?;
+        let messages = chat
+            .get("messages")
+            .or_else(|| chat.get("history"))
+            .and_then(|m| m.as_array())
+            .or_else(|| chat.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        let start_time = chat.get("startTime").and_then(parse_timestamp);
+        let mut events = Vec::new();
+        let mut last_timestamp = start_time;
+        let mut model_name: Option<String> = None;
+        let mut title: Option<String> = None;
+
+        for message in &messages {
+            let timestamp = message
+                .get("timestamp")
+                .and_then(parse_timestamp)
+                .or(last_timestamp)
+                .unwrap_or_else(Utc::now);
+            last_timestamp = Some(timestamp);
+
+            if model_name.is_none() {
+                model_name = message.get("model").and_then(|m| m.as_str()).map(str::to_string);
+            }
+
+            let turn = message
+                .get("type")
+                .or_else(|| message.get("role"))
+                .and_then(|t| t.as_str());
+            let text = message_text(message);
+            match turn {
+                Some("user") => {
+                    if title.is_none() {
+                        title = text.as_deref().and_then(|t| t.lines().next()).map(str::to_string);
+                    }
+                    events.push(event(
+                        EventKind::Message,
+                        Some(Role::User),
+                        text,
+                        timestamp,
+                        message.clone(),
+                    ));
+                }
+                Some("gemini") | Some("model") => {
+                    events.extend(thought_events(message, timestamp));
+                    if text.is_some() {
+                        events.push(event(
+                            EventKind::Message,
+                            Some(Role::Assistant),
+                            text,
+                            timestamp,
+                            message.clone(),
+                        ));
+                    }
+                    events.extend(tool_call_events(message, timestamp));
+                }
+                Some("error") => events.push(event(EventKind::Error, None, text, timestamp, message.clone())),
+                Some(_) => events.push(event(
+                    EventKind::System,
+                    Some(Role::System),
+                    text,
+                    timestamp,
+                    message.clone(),
+                )),
+                None => log::trace!("Skipping Gemini message without a type in {:?}", session_file.path),
+            }
+        }
+
+        let created_at = start_time
+            .or_else(|| events.first().map(|e| e.timestamp))
+            .unwrap_or_else(Utc::now);
+        let updated_at = chat
+            .get("lastUpdated")
+            .and_then(parse_timestamp)
+            .or(last_timestamp)
+            .unwrap_or(created_at);
+        let session = Session {
+            id: uuid::Uuid::new_v4(),
+            source: Source::Gemini,
+            external_id: session_file.session_id.clone(),
+            project: None,
+            title,
+            created_at,
+            updated_at,
+            raw_payload: serde_json::json!({
+                "source": "gemini",
+                "session_id": chat.get("sessionId"),
+                "project_hash": session_file.project_hash,
+                "file_path": session_file.path.to_string_lossy().to_string(),
+                "message_count": messages.len(),
+                "model": model_name,
+            }),
+        };
+
+        let events: Vec<Event> = events
+            .into_iter()
+            .map(|mut e| {
+                e.session_id = session.id;
+                e
+            })
+            .collect();
+
+        log::info!(
+            "Parsed Gemini session {} with {} events",
+            session.external_id,
+            events.len()
+        );
+
+        Ok((session, events))
+    }
+}
+
+impl Default for GeminiAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Session ID from a `session-<id>.json` chat file name
+fn session_id_from_path(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let id = stem.strip_prefix("session-").unwrap_or(stem);
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+fn parse_timestamp(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.as_str()?)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Text of a message, whether stored as a plain `content` string or as `parts` / `content` text parts
+fn message_text(message: &serde_json::Value) -> Option<String> {
+    if let Some(text) = message.get("content").and_then(|c| c.as_str()) {
+        return (!text.trim().is_empty()).then(|| text.to_string());
+    }
+
+    let parts = message
+        .get("parts")
+        .or_else(|| message.get("content"))
+        .and_then(|p| p.as_array())?;
+    let text = parts
+        .iter()
+        .filter(|part| !part.get("thought").and_then(|t| t.as_bool()).unwrap_or(false))
+        .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// A model's recorded thoughts, as `[Thinking]` system events
+fn thought_events(message: &serde_json::Value, timestamp: DateTime<Utc>) -> Vec<Event> {
+    let thoughts = message.get("thoughts").and_then(|t| t.as_array());
+    thoughts
+        .into_iter()
+        .flatten()
+        .filter_map(|thought| {
+            let subject = thought.get("subject").and_then(|s| s.as_str()).unwrap_or_default();
+            let description = thought.get("description").and_then(|d| d.as_str()).unwrap_or_default();
+            let text = [subject, description]
+                .into_iter()
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(": ");
+            if text.is_empty() {
+                return None;
+            }
+            let mut payload = thought.clone();
+            Event::set_category(&mut payload, "thought");
+            Some(event(
+                EventKind::System,
+                Some(Role::Assistant),
+                Some(format!("[Thinking] {}", text)),
+                timestamp,
+                payload,
+            ))
+        })
+        .collect()
+}
+
+/// A call event and, once the call has finished, a result event for each of a model turn's `toolCalls`
+fn tool_call_events(message: &serde_json::Value, timestamp: DateTime<Utc>) -> Vec<Event> {
+    let mut events = Vec::new();
+    let calls = message.get("toolCalls").and_then(|c| c.as_array());
+
+    for call in calls.into_iter().flatten() {
+        let name = call.get("name").and_then(|n| n.as_str()).unwrap_or("unknown");
+        let args = call
+            .get("args")
+            .map(|a| a.to_string())
+            .unwrap_or_else(|| "{}".to_string());
+        let called_at = call.get("timestamp").and_then(parse_timestamp).unwrap_or(timestamp);
+        events.push(event(
+            EventKind::ToolCall,
+            Some(Role::Assistant),
+            Some(format!("Called {} with arguments: {}", name, args)),
+            called_at,
+            call.clone(),
+        ));
+
+        let Some(status) = call.get("status").and_then(|s| s.as_str()) else {
+            continue;
+        };
+        let success = status == "success";
+        let output = call
+            .get("resultDisplay")
+            .and_then(|r| r.as_str())
+            .map(str::to_string)
+            .or_else(|| call.get("result").map(|r| r.to_string()));
+        let outcome = ToolOutcome {
+            tool_name: Some(name.to_string()),
+            success,
+            exit_code: None,
+            duration_ms: None,
+            error_message: (!success).then(|| output.clone().unwrap_or_else(|| status.to_string())),
+        };
+        let mut payload = call.clone();
+        if let Some(obj) = payload.as_object_mut() {
+            obj.insert(
+                ToolOutcome::PAYLOAD_KEY.to_string(),
+                serde_json::to_value(outcome).unwrap_or_default(),
+            );
+        }
+        events.push(event(
+            EventKind::ToolResult,
+            Some(Role::Tool),
+            output,
+            called_at,
+            payload,
+        ));
+    }
+
+    events
+}
+
+fn event(
+    kind: EventKind, role: Option<Role>, content: Option<String>, timestamp: DateTime<Utc>,
+    raw_payload: serde_json::Value,
+) -> Event {
+    Event {
+        id: uuid::Uuid::new_v4(),
+        session_id: uuid::Uuid::nil(),
+        kind,
+        role,
+        content,
+        timestamp,
+        raw_payload,
+        parent_id: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAT: &str = r#"{
+        "sessionId": "3b44bc68-5d3c-4a3f-9a51-0c1d2e3f4a5b",
+        "projectHash": "9f2c",
+        "startTime": "2025-09-18T02:45:00.000Z",
+        "lastUpdated": "2025-09-18T02:46:10.000Z",
+        "messages": [
+            {"id": "m1", "timestamp": "2025-09-18T02:45:01.000Z", "type": "user", "content": "list the files in src"},
+            {"id": "m2", "timestamp": "2025-09-18T02:45:05.000Z", "type": "gemini", "content": "",
+             "model": "gemini-2.5-pro",
+             "thoughts": [{"subject": "Listing", "description": "I'll run ls on src."}],
+             "toolCalls": [{"id": "ls-1", "name": "list_directory", "args": {"path": "src"},
+                            "status": "success", "resultDisplay": "main.rs\nlib.rs"}]},
+            {"id": "m3", "timestamp": "2025-09-18T02:45:09.000Z", "type": "gemini",
+             "content": "src holds main.rs and lib.rs.", "model": "gemini-2.5-pro"},
+            {"id": "m4", "timestamp": "2025-09-18T02:46:10.000Z", "type": "info", "content": "Request cancelled."}
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn test_discover_and_parse_chat_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let chats = dir.path().join("9f2c").join("chats");
+        std::fs::create_dir_all(&chats).unwrap();
+        std::fs::write(chats.join("session-2025-09-18T02-45-3b44bc68.json"), CHAT).unwrap();
+        std::fs::write(dir.path().join("9f2c").join("logs.json"), "[]").unwrap();
+
+        let adapter = GeminiAdapter::with_tmp_dir(dir.path().to_path_buf());
+        let files = adapter.discover_sessions().await;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].session_id, "2025-09-18T02-45-3b44bc68");
+        assert_eq!(files[0].project_hash, "9f2c");
+
+        let (session, events) = adapter.parse_session(&files[0]).await.unwrap();
+        assert_eq!(session.source, Source::Gemini);
+        assert_eq!(session.external_id, "2025-09-18T02-45-3b44bc68");
+        assert_eq!(
+            session.raw_payload["session_id"],
+            "3b44bc68-5d3c-4a3f-9a51-0c1d2e3f4a5b"
+        );
+        assert_eq!(session.title.as_deref(), Some("list the files in src"));
+        assert_eq!(session.raw_payload["model"], "gemini-2.5-pro");
+        assert_eq!(session.updated_at.to_rfc3339(), "2025-09-18T02:46:10+00:00");
+
+        let kinds: Vec<(EventKind, Option<Role>)> = events.iter().map(|e| (e.kind, e.role)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (EventKind::Message, Some(Role::User)),
+                (EventKind::System, Some(Role::Assistant)),
+                (EventKind::ToolCall, Some(Role::Assistant)),
+                (EventKind::ToolResult, Some(Role::Tool)),
+                (EventKind::Message, Some(Role::Assistant)),
+                (EventKind::System, Some(Role::System)),
+            ]
+        );
+        assert_eq!(
+            events[1].content.as_deref(),
+            Some("[Thinking] Listing: I'll run ls on src.")
+        );
+        assert_eq!(events[3].content.as_deref(), Some("main.rs\nlib.rs"));
+        assert!(ToolOutcome::from_payload(&events[3].raw_payload).unwrap().success);
+        assert!(events.iter().all(|e| e.session_id == session.id));
+    }
+
+    #[test]
+    fn test_message_text_reads_model_parts() {
+        let turn = serde_json::json!({"role": "model", "parts": [
+            {"text": "thinking it over", "thought": true},
+            {"text": "Here is the answer."}
+        ]});
+        assert_eq!(message_text(&turn).as_deref(), Some("Here is the answer."));
+        assert_eq!(message_text(&serde_json::json!({"role": "model", "parts": []})), None);
+    }
+}
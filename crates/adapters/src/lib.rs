@@ -1,9 +1,66 @@
 pub mod claude;
 pub mod codex;
 pub mod crush;
+pub mod gemini;
 pub mod opencode;
+pub mod parse_check;
 
 pub use claude::ClaudeAdapter;
 pub use codex::CodexAdapter;
 pub use crush::CrushAdapter;
+pub use gemini::GeminiAdapter;
 pub use opencode::OpenCodeAdapter;
+
+/// Classify a SQLite error from a source database: a file that can't be opened, read, or locked, or
+/// isn't a database at all, is an I/O error; anything else means the database isn't shaped the way
+/// the adapter expects
+pub(crate) fn sqlite_error(err: rusqlite::Error) -> agent_v_core::AdapterError {
+    use rusqlite::ErrorCode;
+
+    match err.sqlite_error_code() {
+        Some(
+            ErrorCode::CannotOpen
+            | ErrorCode::DatabaseBusy
+            | ErrorCode::DatabaseLocked
+            | ErrorCode::NotADatabase
+            | ErrorCode::PermissionDenied
+            | ErrorCode::SystemIoFailure,
+        ) => agent_v_core::AdapterError::Io(std::io::Error::other(err)),
+        _ => agent_v_core::AdapterError::Schema(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_v_core::AdapterError;
+    use rusqlite::{Connection, ErrorCode, ffi};
+
+    #[test]
+    fn test_sqlite_access_failures_are_io_errors() {
+        for code in [
+            ffi::SQLITE_BUSY,
+            ffi::SQLITE_LOCKED,
+            ffi::SQLITE_NOTADB,
+            ffi::SQLITE_PERM,
+            ffi::SQLITE_IOERR,
+        ] {
+            let err = rusqlite::Error::SqliteFailure(ffi::Error::new(code), None);
+            assert!(matches!(sqlite_error(err), AdapterError::Io(_)), "code {}", code);
+        }
+
+        let missing_table = rusqlite::Error::SqliteFailure(ffi::Error::new(ffi::SQLITE_ERROR), None);
+        assert!(matches!(sqlite_error(missing_table), AdapterError::Schema(_)));
+    }
+
+    #[test]
+    fn test_a_file_that_is_not_a_database_is_an_io_error() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "definitely not sqlite, just long enough to have a header").unwrap();
+
+        let conn = Connection::open(file.path()).unwrap();
+        let err = conn.prepare("SELECT name FROM sqlite_master").unwrap_err();
+        assert_eq!(err.sqlite_error_code(), Some(ErrorCode::NotADatabase));
+        assert!(matches!(sqlite_error(err), AdapterError::Io(_)));
+    }
+}
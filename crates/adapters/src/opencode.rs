@@ -1,4 +1,6 @@
-use agent_v_core::{Event, EventKind, Role, Session, Source};
+use crate::sqlite_error;
+use agent_v_core::config::{self, Config};
+use agent_v_core::{AdapterError, Attachment, Event, EventKind, Role, Session, Source};
 use chrono::{DateTime, Utc};
 use rusqlite::{Connection, OpenFlags, OptionalExtension};
 use serde::{Deserialize, Serialize};
@@ -54,6 +56,9 @@ struct OpenCodeMessageStorage {
     #[serde(rename = "parentID")]
     #[serde(default)]
     parent_id: Option<String>,
+    /// Token usage of an assistant message (`input`, `output`, `reasoning`, `cache`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tokens: Option<serde_json::Value>,
 }
 
 /// OpenCode part payload format reconstructed from DB rows.
@@ -72,10 +77,15 @@ struct OpenCodePartStorage {
     filename: Option<String>,
     #[serde(default)]
     tool: Option<String>,
+    #[serde(rename = "callID")]
+    #[serde(default)]
+    call_id: Option<String>,
     #[serde(default)]
     state: Option<OpenCodePartState>,
     #[serde(default)]
     url: Option<String>,
+    #[serde(default)]
+    mime: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,6 +98,12 @@ struct OpenCodePartState {
     output: Option<serde_json::Value>,
     #[serde(default)]
     metadata: Option<serde_json::Value>,
+    /// Error text when `status` is `error`
+    #[serde(default)]
+    error: Option<String>,
+    /// Tool start/end times in epoch milliseconds
+    #[serde(default)]
+    time: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -187,6 +203,7 @@ pub struct OpenCodeAdapter {
     auth_path: PathBuf,
     log_path: PathBuf,
     db_path: PathBuf,
+    capture_attachments: bool,
 }
 
 impl OpenCodeAdapter {
@@ -229,15 +246,23 @@ impl OpenCodeAdapter {
             .unwrap_or_else(|| PathBuf::from("~/.local/share/opencode"))
     }
 
-    /// Create a new OpenCode adapter with default paths
+    /// Create a new OpenCode adapter with the configured or default paths
     pub fn new() -> Self {
-        let base_path = Self::default_base_path();
+        Self::from_config(config::current())
+    }
+
+    /// Create an OpenCode adapter whose base directory is `sources.opencode` from `config`, or the
+    /// first existing candidate base path
+    pub fn from_config(config: &Config) -> Self {
+        let base_path = config
+            .source_path(Source::OpenCode)
+            .unwrap_or_else(Self::default_base_path);
         let storage_path = base_path.join("storage");
         let auth_path = base_path.join("auth.json");
         let log_path = base_path.join("log");
         let db_path = base_path.join("opencode.db");
 
-        Self { storage_path, auth_path, log_path, db_path }
+        Self { storage_path, auth_path, log_path, db_path, capture_attachments: false }
     }
 
     /// Create a new OpenCode adapter with custom paths
@@ -249,7 +274,13 @@ impl OpenCodeAdapter {
         let log_path = base_path.join("log");
         let db_path = base_path.join("opencode.db");
 
-        Self { storage_path, auth_path, log_path, db_path }
+        Self { storage_path, auth_path, log_path, db_path, capture_attachments: false }
+    }
+
+    /// Record local `file` parts (images, documents) as event attachments
+    pub fn with_attachments(mut self, enabled: bool) -> Self {
+        self.capture_attachments = enabled;
+        self
     }
 
     /// Get the storage path
@@ -395,30 +426,28 @@ impl OpenCodeAdapter {
     }
 
     /// Parse a session from OpenCode DB rows.
-    pub async fn parse_session(
-        &self, session: &OpenCodeSession,
-    ) -> Result<(Session, Vec<Event>), Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn parse_session(&self, session: &OpenCodeSession) -> Result<(Session, Vec<Event>), AdapterError> {
         if !self.has_db() {
-            return Err(format!("OpenCode db not found at {:?}", self.db_path).into());
+            return Err(AdapterError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("OpenCode db not found at {:?}", self.db_path),
+            )));
         }
         self.parse_session_from_db(session).await
     }
 
-    async fn load_db_session_row(
-        &self, session_id: &str,
-    ) -> Result<Option<OpenCodeDbSessionRow>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn load_db_session_row(&self, session_id: &str) -> Result<Option<OpenCodeDbSessionRow>, AdapterError> {
         let db_path = self.db_path.clone();
         let sid = session_id.to_string();
-        let row = tokio::task::spawn_blocking(move || -> Result<Option<OpenCodeDbSessionRow>, String> {
-            let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
-                .map_err(|e| format!("open opencode db: {e}"))?;
+        let row = tokio::task::spawn_blocking(move || -> Result<Option<OpenCodeDbSessionRow>, AdapterError> {
+            let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(sqlite_error)?;
             let mut stmt = conn
                 .prepare(
                     "SELECT id, slug, version, project_id, directory, title, time_created, time_updated, \
                      summary_additions, summary_deletions, summary_files \
                      FROM session WHERE id = ?1 LIMIT 1",
                 )
-                .map_err(|e| format!("prepare session row query: {e}"))?;
+                .map_err(sqlite_error)?;
 
             stmt.query_row([sid], |r| {
                 Ok(OpenCodeDbSessionRow {
@@ -436,22 +465,19 @@ impl OpenCodeAdapter {
                 })
             })
             .optional()
-            .map_err(|e| format!("query session row: {e}"))
+            .map_err(sqlite_error)
         })
         .await
-        .map_err(|e| format!("OpenCode db session row task failed: {e}"))?
-        .map_err(|e| format!("OpenCode db session row query failed: {e}"))?;
+        .map_err(std::io::Error::from)??;
 
         Ok(row)
     }
 
-    async fn parse_session_from_db(
-        &self, session: &OpenCodeSession,
-    ) -> Result<(Session, Vec<Event>), Box<dyn std::error::Error + Send + Sync>> {
+    async fn parse_session_from_db(&self, session: &OpenCodeSession) -> Result<(Session, Vec<Event>), AdapterError> {
         let row = self
             .load_db_session_row(&session.id)
             .await?
-            .ok_or_else(|| format!("Session not found in opencode db: {}", session.id))?;
+            .ok_or_else(|| AdapterError::Schema(format!("Session not found in opencode db: {}", session.id)))?;
 
         let created_at = Self::timestamp_from_millis(row.time_created);
         let updated_at = Self::timestamp_from_millis(row.time_updated);
@@ -486,6 +512,7 @@ impl OpenCodeAdapter {
 
         let messages = self.load_session_messages(&session.id).await?;
         let mut events = Vec::new();
+        let mut message_events: HashMap<String, Uuid> = HashMap::new();
 
         for message in messages {
             let timestamp = Self::timestamp_from_millis(message.time.created);
@@ -498,15 +525,25 @@ impl OpenCodeAdapter {
             let event_kind = if role.is_some() { EventKind::Message } else { EventKind::System };
             let parts = self.load_message_parts(&message.id).await.unwrap_or_default();
             let content = self.format_message_content(&parts, &message);
+            let mut raw_payload = serde_json::to_value(&message)?;
+            self.record_attachments(&mut raw_payload, &parts);
+            let parent_id = message
+                .parent_id
+                .as_ref()
+                .and_then(|id| message_events.get(id))
+                .copied();
+            let message_event_id = Uuid::new_v4();
+            message_events.insert(message.id.clone(), message_event_id);
 
             events.push(Event {
-                id: Uuid::new_v4(),
+                id: message_event_id,
                 session_id: session_obj.id,
                 kind: event_kind,
                 role,
                 content: Some(content),
                 timestamp,
-                raw_payload: serde_json::to_value(&message)?,
+                raw_payload,
+                parent_id,
             });
 
             for part in &parts {
@@ -529,7 +566,8 @@ impl OpenCodeAdapter {
                         role: Some(Role::Assistant),
                         content: tool_content.or_else(|| part.tool.clone()),
                         timestamp,
-                        raw_payload: serde_json::to_value(part)?,
+                        raw_payload: Self::tool_event_payload(part, &message),
+                        parent_id: Some(message_event_id),
                     });
                 }
             }
@@ -551,20 +589,17 @@ impl OpenCodeAdapter {
     }
 
     /// Load all messages for a session
-    async fn load_session_messages(
-        &self, session_id: &str,
-    ) -> Result<Vec<OpenCodeMessageStorage>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn load_session_messages(&self, session_id: &str) -> Result<Vec<OpenCodeMessageStorage>, AdapterError> {
         let db_path = self.db_path.clone();
         let sid = session_id.to_string();
-        let messages = tokio::task::spawn_blocking(move || -> Result<Vec<OpenCodeMessageStorage>, String> {
-            let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
-                .map_err(|e| format!("open opencode db: {e}"))?;
+        let messages = tokio::task::spawn_blocking(move || -> Result<Vec<OpenCodeMessageStorage>, AdapterError> {
+            let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(sqlite_error)?;
             let mut stmt = conn
                 .prepare(
                     "SELECT id, session_id, time_created, data \
                      FROM message WHERE session_id = ?1 ORDER BY time_created ASC",
                 )
-                .map_err(|e| format!("prepare message query: {e}"))?;
+                .map_err(sqlite_error)?;
 
             let rows = stmt
                 .query_map([sid], |row| {
@@ -575,11 +610,11 @@ impl OpenCodeAdapter {
                         row.get::<_, String>(3)?,
                     ))
                 })
-                .map_err(|e| format!("query messages: {e}"))?;
+                .map_err(sqlite_error)?;
 
             let mut out = Vec::new();
             for row in rows {
-                let (id, session_id, created_ms, data_json) = row.map_err(|e| format!("read message row: {e}"))?;
+                let (id, session_id, created_ms, data_json) = row.map_err(sqlite_error)?;
                 if let Some(message) = Self::build_message_from_db_row(id, session_id, created_ms, data_json) {
                     out.push(message);
                 }
@@ -587,27 +622,23 @@ impl OpenCodeAdapter {
             Ok(out)
         })
         .await
-        .map_err(|e| format!("OpenCode db message task failed: {e}"))?
-        .map_err(|e| format!("OpenCode db message query failed: {e}"))?;
+        .map_err(std::io::Error::from)??;
 
         Ok(messages)
     }
 
     /// Load all parts for a message
-    async fn load_message_parts(
-        &self, message_id: &str,
-    ) -> Result<Vec<OpenCodePartStorage>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn load_message_parts(&self, message_id: &str) -> Result<Vec<OpenCodePartStorage>, AdapterError> {
         let db_path = self.db_path.clone();
         let mid = message_id.to_string();
-        let parts = tokio::task::spawn_blocking(move || -> Result<Vec<OpenCodePartStorage>, String> {
-            let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
-                .map_err(|e| format!("open opencode db: {e}"))?;
+        let parts = tokio::task::spawn_blocking(move || -> Result<Vec<OpenCodePartStorage>, AdapterError> {
+            let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(sqlite_error)?;
             let mut stmt = conn
                 .prepare(
                     "SELECT id, message_id, session_id, data \
                      FROM part WHERE message_id = ?1 ORDER BY time_created ASC",
                 )
-                .map_err(|e| format!("prepare part query: {e}"))?;
+                .map_err(sqlite_error)?;
 
             let rows = stmt
                 .query_map([mid], |row| {
@@ -618,11 +649,11 @@ impl OpenCodeAdapter {
                         row.get::<_, String>(3)?,
                     ))
                 })
-                .map_err(|e| format!("query parts: {e}"))?;
+                .map_err(sqlite_error)?;
 
             let mut out = Vec::new();
             for row in rows {
-                let (id, message_id, session_id, data_json) = row.map_err(|e| format!("read part row: {e}"))?;
+                let (id, message_id, session_id, data_json) = row.map_err(sqlite_error)?;
                 if let Some(part) = Self::build_part_from_db_row(id, session_id, message_id, data_json) {
                     out.push(part);
                 }
@@ -630,33 +661,29 @@ impl OpenCodeAdapter {
             Ok(out)
         })
         .await
-        .map_err(|e| format!("OpenCode db part task failed: {e}"))?
-        .map_err(|e| format!("OpenCode db part query failed: {e}"))?;
+        .map_err(std::io::Error::from)??;
 
         Ok(parts)
     }
 
     /// Load session-level diffs from `session.summary_diffs` in OpenCode DB.
-    async fn load_session_diffs(
-        &self, session_id: &str,
-    ) -> Result<Vec<OpenCodeDiff>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn load_session_diffs(&self, session_id: &str) -> Result<Vec<OpenCodeDiff>, AdapterError> {
         if !self.has_db() {
             return Ok(Vec::new());
         }
 
         let db_path = self.db_path.clone();
         let sid = session_id.to_string();
-        let diffs = tokio::task::spawn_blocking(move || -> Result<Vec<OpenCodeDiff>, String> {
-            let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
-                .map_err(|e| format!("open opencode db: {e}"))?;
+        let diffs = tokio::task::spawn_blocking(move || -> Result<Vec<OpenCodeDiff>, AdapterError> {
+            let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(sqlite_error)?;
             let mut stmt = conn
                 .prepare("SELECT summary_diffs FROM session WHERE id = ?1 LIMIT 1")
-                .map_err(|e| format!("prepare diff query: {e}"))?;
+                .map_err(sqlite_error)?;
 
             let summary: Option<String> = stmt
                 .query_row([sid], |row| row.get::<_, Option<String>>(0))
                 .optional()
-                .map_err(|e| format!("query summary_diffs: {e}"))?
+                .map_err(sqlite_error)?
                 .flatten();
 
             let Some(summary) = summary else {
@@ -670,8 +697,7 @@ impl OpenCodeAdapter {
             Ok(serde_json::from_str(&summary).unwrap_or_default())
         })
         .await
-        .map_err(|e| format!("OpenCode db diff task failed for {session_id}: {e}"))?
-        .map_err(|e| format!("OpenCode db diff query failed for {session_id}: {e}"))?;
+        .map_err(std::io::Error::from)??;
 
         Ok(diffs)
     }
@@ -732,6 +758,7 @@ impl OpenCodeAdapter {
             content: Some(self.format_session_diff_content(diffs)),
             timestamp,
             raw_payload: serde_json::to_value(diffs).unwrap_or_default(),
+            parent_id: None,
         })
     }
 
@@ -852,11 +879,55 @@ impl OpenCodeAdapter {
         content_parts.join("\n\n")
     }
 
+    /// Add local file parts to a message payload when attachment capture is enabled
+    fn record_attachments(&self, payload: &mut serde_json::Value, parts: &[OpenCodePartStorage]) {
+        if !self.capture_attachments {
+            return;
+        }
+
+        let attachments: Vec<Attachment> = parts
+            .iter()
+            .filter(|part| part.part_type == "file")
+            .filter_map(|part| Attachment::from_local(part.url.as_deref()?, part.mime.clone()))
+            .collect();
+
+        if !attachments.is_empty()
+            && let Some(obj) = payload.as_object_mut()
+        {
+            obj.insert(
+                Attachment::PAYLOAD_KEY.to_string(),
+                serde_json::to_value(attachments).unwrap_or_default(),
+            );
+        }
+    }
+
+    /// Serialize a tool part, carrying over the provider and model of the message it belongs to
+    fn tool_event_payload(part: &OpenCodePartStorage, message: &OpenCodeMessageStorage) -> serde_json::Value {
+        let mut payload = serde_json::to_value(part).unwrap_or_default();
+        let provider_id = message
+            .model
+            .as_ref()
+            .map(|model| model.provider_id.clone())
+            .or_else(|| message.provider_id.clone());
+
+        if let Some(obj) = payload.as_object_mut() {
+            if let Some(provider_id) = provider_id {
+                obj.insert("providerID".to_string(), serde_json::Value::String(provider_id));
+            }
+            if let Some(model) = &message.model {
+                obj.insert("modelID".to_string(), serde_json::Value::String(model.model_id.clone()));
+                obj.insert("model".to_string(), serde_json::to_value(model).unwrap_or_default());
+            }
+        }
+
+        payload
+    }
+
     /// Parse only new events from a session by tracking known message keys.
     /// Returns new events and the updated key set.
     pub async fn parse_session_incremental(
         &self, session: &OpenCodeSession, known_files: &HashSet<String>,
-    ) -> Result<(Vec<Event>, HashSet<String>), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(Vec<Event>, HashSet<String>), AdapterError> {
         let mut new_known = known_files.clone();
         let mut new_events = Vec::new();
 
@@ -879,6 +950,8 @@ impl OpenCodeAdapter {
             let event_kind = if role.is_some() { EventKind::Message } else { EventKind::System };
             let parts = self.load_message_parts(&message.id).await.unwrap_or_default();
             let content_str = self.format_message_content(&parts, &message);
+            let mut raw_payload = serde_json::to_value(&message).unwrap_or_default();
+            self.record_attachments(&mut raw_payload, &parts);
 
             new_events.push(Event {
                 id: Uuid::new_v4(),
@@ -887,7 +960,8 @@ impl OpenCodeAdapter {
                 role,
                 content: Some(content_str),
                 timestamp,
-                raw_payload: serde_json::to_value(&message).unwrap_or_default(),
+                raw_payload,
+                parent_id: None,
             });
 
             for part in &parts {
@@ -909,7 +983,8 @@ impl OpenCodeAdapter {
                         role: Some(Role::Assistant),
                         content: tool_content.or_else(|| part.tool.clone()),
                         timestamp,
-                        raw_payload: serde_json::to_value(part).unwrap_or_default(),
+                        raw_payload: Self::tool_event_payload(part, &message),
+                        parent_id: None,
                     });
                 }
             }
@@ -985,6 +1060,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_config_override_moves_every_opencode_path() {
+        let config = Config::parse("[sources]\nopencode = \"/opt/opencode\"").unwrap();
+        let adapter = OpenCodeAdapter::from_config(&config);
+        assert_eq!(adapter.storage_path(), &PathBuf::from("/opt/opencode/storage"));
+        assert_eq!(adapter.auth_path, PathBuf::from("/opt/opencode/auth.json"));
+        assert_eq!(adapter.db_path, PathBuf::from("/opt/opencode/opencode.db"));
+    }
+
+    #[test]
+    fn test_record_attachments_from_file_parts() {
+        let parts: Vec<OpenCodePartStorage> = serde_json::from_value(serde_json::json!([
+            {
+                "id": "prt_1", "sessionID": "ses_1", "messageID": "msg_1", "type": "file",
+                "filename": "shot.png", "mime": "image/png", "url": "file:///home/user/shot.png"
+            },
+            {
+                "id": "prt_2", "sessionID": "ses_1", "messageID": "msg_1", "type": "file",
+                "filename": "inline.png", "mime": "image/png", "url": "data:image/png;base64,aGVsbG8="
+            },
+        ]))
+        .unwrap();
+
+        let mut payload = serde_json::json!({"id": "msg_1"});
+        OpenCodeAdapter::new().record_attachments(&mut payload, &parts);
+        assert!(payload.get("attachments").is_none(), "capture is opt-in");
+
+        OpenCodeAdapter::new()
+            .with_attachments(true)
+            .record_attachments(&mut payload, &parts);
+        assert_eq!(
+            Attachment::from_payload(&payload),
+            vec![Attachment { path: "/home/user/shot.png".to_string(), mime_type: Some("image/png".to_string()) }]
+        );
+    }
+
+    #[test]
+    fn test_tool_event_inherits_message_provider() {
+        let message: OpenCodeMessageStorage = serde_json::from_value(serde_json::json!({
+            "id": "msg_1", "sessionID": "ses_1", "role": "assistant", "time": {"created": 1704067200000i64},
+            "model": {"providerID": "anthropic", "modelID": "claude-sonnet-4"}
+        }))
+        .unwrap();
+        let part: OpenCodePartStorage = serde_json::from_value(serde_json::json!({
+            "id": "prt_1", "sessionID": "ses_1", "messageID": "msg_1", "type": "tool", "tool": "bash",
+            "state": {"status": "completed", "input": {"command": "ls"}}
+        }))
+        .unwrap();
+
+        let payload = OpenCodeAdapter::tool_event_payload(&part, &message);
+        assert_eq!(payload["providerID"], "anthropic");
+        assert_eq!(payload["modelID"], "claude-sonnet-4");
+        assert_eq!(payload["tool"], "bash");
+
+        let bare: OpenCodeMessageStorage = serde_json::from_value(serde_json::json!({
+            "id": "msg_2", "sessionID": "ses_1", "role": "assistant", "time": {"created": 1704067200000i64},
+            "providerID": "openai"
+        }))
+        .unwrap();
+        let payload = OpenCodeAdapter::tool_event_payload(&part, &bare);
+        assert_eq!(payload["providerID"], "openai");
+        assert!(payload.get("modelID").is_none());
+    }
+
     #[test]
     fn test_timestamp_conversion() {
         let ts_millis = 1704067200000i64;
@@ -0,0 +1,217 @@
+//! Known-good session fixtures for each source, used by `doctor --parse-check`.
+//!
+//! Agent tools change their on-disk formats between releases. Parsing a small embedded fixture per
+//! source and comparing the result with the events it is known to contain catches an adapter that
+//! has quietly stopped understanding a format, before it shows up as empty or missing sessions.
+
+use crate::claude::ClaudeAdapter;
+use crate::codex::{CodexAdapter, CodexSessionFile};
+use crate::crush::{CrushAdapter, CrushSessionFile};
+use crate::gemini::{GeminiAdapter, GeminiSessionFile};
+use crate::opencode::{OpenCodeAdapter, OpenCodeSession};
+use agent_v_core::{Event, EventKind, Source};
+use chrono::Utc;
+use std::path::Path;
+
+/// A session in one source's native format, plus the events it should parse into
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub source: Source,
+    /// The session ID inside the fixture
+    pub session_id: &'static str,
+    /// JSONL (or JSON) for file-based sources, a SQL script for database-backed ones
+    pub contents: &'static str,
+    /// Expected event kinds in order, each with a snippet its content must contain
+    pub expected: &'static [(EventKind, &'static str)],
+}
+
+/// Outcome of parsing one fixture
+#[derive(Debug, Clone)]
+pub struct ParseCheck {
+    pub source: Source,
+    /// Number of events parsed on success, or what went wrong
+    pub result: Result<usize, String>,
+}
+
+impl ParseCheck {
+    pub fn passed(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// The bundled fixture for every supported source
+pub fn fixtures() -> Vec<Fixture> {
+    vec![
+        Fixture {
+            source: Source::Claude,
+            session_id: "parse-check",
+            contents: include_str!("../fixtures/parse-check/claude.jsonl"),
+            expected: &[
+                (EventKind::Message, "list the files in src"),
+                (EventKind::Message, "Listing src now."),
+                (EventKind::Message, "thanks"),
+                (EventKind::Message, "src holds main.rs and lib.rs."),
+            ],
+        },
+        Fixture {
+            source: Source::Codex,
+            session_id: "parse-check",
+            contents: include_str!("../fixtures/parse-check/codex.jsonl"),
+            expected: &[
+                (EventKind::Message, "list the files in src"),
+                (EventKind::ToolCall, "Called shell"),
+                (EventKind::ToolResult, "main.rs"),
+                (EventKind::System, "[Thinking] Summarizing the listing"),
+                (EventKind::Message, "src holds main.rs and lib.rs."),
+            ],
+        },
+        Fixture {
+            source: Source::OpenCode,
+            session_id: "parse-check",
+            contents: include_str!("../fixtures/parse-check/opencode.sql"),
+            expected: &[
+                (EventKind::Message, "list the files in src"),
+                (EventKind::Message, "Listing src now."),
+                (EventKind::ToolCall, "\"tool\":\"bash\""),
+                (EventKind::System, "Session diffs: 1 files changed"),
+            ],
+        },
+        Fixture {
+            source: Source::Crush,
+            session_id: "parse-check",
+            contents: include_str!("../fixtures/parse-check/crush.sql"),
+            expected: &[
+                (EventKind::Message, "list the files in src"),
+                (EventKind::ToolCall, "[Tool: ls]"),
+                (EventKind::ToolResult, "[Result] lib.rs"),
+                (EventKind::Message, "src holds main.rs and lib.rs."),
+            ],
+        },
+        Fixture {
+            source: Source::Gemini,
+            session_id: "parse-check",
+            contents: include_str!("../fixtures/parse-check/gemini.json"),
+            expected: &[
+                (EventKind::Message, "list the files in src"),
+                (EventKind::Message, "Listing src now."),
+                (EventKind::ToolCall, "Called list_directory"),
+                (EventKind::ToolResult, "main.rs"),
+                (EventKind::Message, "src holds main.rs and lib.rs."),
+            ],
+        },
+    ]
+}
+
+/// Parse every bundled fixture
+pub async fn run_all() -> Vec<ParseCheck> {
+    let mut checks = Vec::new();
+    for fixture in fixtures() {
+        checks.push(check(&fixture).await);
+    }
+    checks
+}
+
+/// Parse a fixture with its source's adapter and compare the events with what it should contain
+pub async fn check(fixture: &Fixture) -> ParseCheck {
+    let dir = std::env::temp_dir().join(format!(
+        "agent-viz-parse-check-{}-{}",
+        fixture.source,
+        uuid::Uuid::new_v4()
+    ));
+    let result = match std::fs::create_dir_all(&dir) {
+        Ok(()) => parse_fixture(fixture, &dir)
+            .await
+            .map_err(|e| format!("parse failed: {}", e))
+            .and_then(|events| compare_events(fixture.expected, &events).map(|()| events.len())),
+        Err(e) => Err(format!("could not create scratch directory: {}", e)),
+    };
+    let _ = std::fs::remove_dir_all(&dir);
+
+    ParseCheck { source: fixture.source, result }
+}
+
+/// Write the fixture under `dir` in the layout its adapter expects and parse it
+async fn parse_fixture(fixture: &Fixture, dir: &Path) -> Result<Vec<Event>, Box<dyn std::error::Error + Send + Sync>> {
+    let session_id = fixture.session_id.to_string();
+
+    let (_, events) = match fixture.source {
+        Source::Claude => {
+            let path = dir.join(format!("{}.jsonl", session_id));
+            std::fs::write(&path, fixture.contents)?;
+            ClaudeAdapter::new()
+                .parse_file(&path, Some("parse-check".to_string()))
+                .await?
+        }
+        Source::Codex => {
+            let path = dir.join(format!("rollout-{}.jsonl", session_id));
+            std::fs::write(&path, fixture.contents)?;
+            let file = CodexSessionFile { path, session_id, date: "2025-01-01".to_string() };
+            CodexAdapter::new().parse_session(&file).await?
+        }
+        Source::Crush => {
+            let path = dir.join("crush.db");
+            rusqlite::Connection::open(&path)?.execute_batch(fixture.contents)?;
+            let file = CrushSessionFile { path: path.clone(), session_id };
+            CrushAdapter::with_db_path(path).parse_session(&file).await?
+        }
+        Source::Gemini => {
+            let path = dir.join(format!("session-{}.json", session_id));
+            std::fs::write(&path, fixture.contents)?;
+            let file = GeminiSessionFile { path, session_id, project_hash: "parse-check".to_string() };
+            GeminiAdapter::with_tmp_dir(dir.to_path_buf()).parse_session(&file).await?
+        }
+        Source::OpenCode => {
+            rusqlite::Connection::open(dir.join("opencode.db"))?.execute_batch(fixture.contents)?;
+            let adapter = OpenCodeAdapter::with_paths(dir.join("storage"), dir.join("auth.json"));
+            let session = OpenCodeSession {
+                id: session_id,
+                title: String::new(),
+                directory: None,
+                project_id: None,
+                created: Utc::now(),
+                updated: Utc::now(),
+            };
+            adapter.parse_session(&session).await?
+        }
+    };
+
+    Ok(events)
+}
+
+/// Check parsed events against the expected kinds and content snippets, in order
+fn compare_events(expected: &[(EventKind, &str)], events: &[Event]) -> Result<(), String> {
+    if events.len() != expected.len() {
+        return Err(format!("expected {} events, parsed {}", expected.len(), events.len()));
+    }
+
+    for (idx, ((kind, snippet), event)) in expected.iter().zip(events).enumerate() {
+        if event.kind != *kind {
+            return Err(format!("event {}: expected {}, parsed {}", idx + 1, kind, event.kind));
+        }
+        if !event.content.as_deref().unwrap_or_default().contains(snippet) {
+            return Err(format!("event {}: content no longer contains {:?}", idx + 1, snippet));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bundled_fixtures_pass_and_a_broken_one_fails() {
+        for check in run_all().await {
+            assert!(check.passed(), "{} fixture failed: {:?}", check.source, check.result);
+        }
+
+        let broken = Fixture {
+            contents: r#"{"type":"user","uuid":"u1","ts":"2025-01-01T10:00:00.000Z","msg":{"text":"hi"}}"#,
+            ..fixtures().remove(0)
+        };
+        let check = check(&broken).await;
+        assert!(!check.passed());
+        assert_eq!(check.result.unwrap_err(), "expected 4 events, parsed 0");
+    }
+}
@@ -1,3 +1,4 @@
+use agent_v_core::SessionId;
 use agent_v_store::Database;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -108,6 +109,7 @@ pub async fn get_session_events(session_id: String) -> Result<Vec<EventData>, Co
 
     db.migrate().await.map_err(|e| CommandError::Migration(e.to_string()))?;
 
+    let session_id: SessionId = session_id.parse().map_err(CommandError::GetEvents)?;
     let rows = db
         .get_session_events(session_id)
         .await
@@ -132,10 +134,9 @@ pub async fn get_session_events(session_id: String) -> Result<Vec<EventData>, Co
 /// Trigger ingestion from a source
 #[tauri::command]
 pub async fn ingest_source(source: String) -> Result<IngestResult, CommandError> {
-    use agent_v_adapters::{
-        claude::ClaudeAdapter, codex::CodexAdapter, crush::CrushAdapter, opencode::OpenCodeAdapter,
-    };
     use agent_v_core::Source;
+    use agent_v_ingest::{CancellationToken, ingest_one};
+    use std::collections::HashSet;
     use std::str::FromStr;
 
     let db = Database::open_default()
@@ -146,92 +147,8 @@ pub async fn ingest_source(source: String) -> Result<IngestResult, CommandError>
 
     let source = Source::from_str(&source).map_err(|e| CommandError::InvalidSource(e.to_string()))?;
 
-    match source {
-        Source::Claude => {
-            let adapter = ClaudeAdapter::new();
-            let sessions = adapter.discover_sessions().await;
-            let mut imported = 0;
-            let mut failed = 0;
-
-            for session_file in sessions {
-                match adapter.parse_session(&session_file).await {
-                    Ok((session, events)) => {
-                        if db.insert_session_with_events(&session, &events).await.is_ok() {
-                            imported += 1;
-                        } else {
-                            failed += 1;
-                        }
-                    }
-                    Err(_) => failed += 1,
-                }
-            }
-
-            Ok(IngestResult { imported, failed, total: imported + failed })
-        }
-        Source::Codex => {
-            let adapter = CodexAdapter::new();
-            let sessions = adapter.discover_sessions().await;
-            let mut imported = 0;
-            let mut failed = 0;
-
-            for session_file in sessions {
-                match adapter.parse_session(&session_file).await {
-                    Ok((session, events)) => {
-                        if db.insert_session_with_events(&session, &events).await.is_ok() {
-                            imported += 1;
-                        } else {
-                            failed += 1;
-                        }
-                    }
-                    Err(_) => failed += 1,
-                }
-            }
-
-            Ok(IngestResult { imported, failed, total: imported + failed })
-        }
-        Source::OpenCode => {
-            let adapter = OpenCodeAdapter::new();
-            let sessions = adapter.discover_sessions().await;
-            let mut imported = 0;
-            let mut failed = 0;
-
-            for session_file in sessions {
-                match adapter.parse_session(&session_file).await {
-                    Ok((session, events)) => {
-                        if db.insert_session_with_events(&session, &events).await.is_ok() {
-                            imported += 1;
-                        } else {
-                            failed += 1;
-                        }
-                    }
-                    Err(_) => failed += 1,
-                }
-            }
-
-            Ok(IngestResult { imported, failed, total: imported + failed })
-        }
-        Source::Crush => {
-            let adapter = CrushAdapter::new();
-            let sessions = adapter.discover_sessions().await;
-            let mut imported = 0;
-            let mut failed = 0;
-
-            for session_file in sessions {
-                match adapter.parse_session(&session_file).await {
-                    Ok((session, events)) => {
-                        if db.insert_session_with_events(&session, &events).await.is_ok() {
-                            imported += 1;
-                        } else {
-                            failed += 1;
-                        }
-                    }
-                    Err(_) => failed += 1,
-                }
-            }
-
-            Ok(IngestResult { imported, failed, total: imported + failed })
-        }
-    }
+    let outcome = ingest_one(source, &db, &mut HashSet::new(), &CancellationToken::new()).await;
+    Ok(IngestResult { imported: outcome.imported, failed: outcome.failed, total: outcome.imported + outcome.failed })
 }
 
 /// Result of an ingestion operation
@@ -266,7 +183,9 @@ pub async fn recompute_all_metrics() -> Result<RecomputeResult, CommandError> {
         }
 
         for session in &sessions {
-            let _ = db.compute_session_metrics(&session.id).await;
+            if let Ok(session_id) = session.session_id() {
+                let _ = db.compute_session_metrics(session_id).await;
+            }
             total += 1;
         }
 
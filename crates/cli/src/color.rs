@@ -0,0 +1,71 @@
+use anstream::ColorChoice;
+use owo_colors::Style;
+
+/// When to emit ANSI colors on stdout/stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Color only when writing to a terminal (honors `NO_COLOR` and `CLICOLOR_FORCE`)
+    Auto,
+    /// Always color, even through pipes (e.g. into `less -R`)
+    Always,
+    /// Never color
+    Never,
+}
+
+impl ColorMode {
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => Err(format!("Unknown color mode: {}. Use 'auto', 'always' or 'never'", s)),
+        }
+    }
+
+    fn choice(self) -> ColorChoice {
+        match self {
+            ColorMode::Auto => ColorChoice::Auto,
+            ColorMode::Always => ColorChoice::Always,
+            ColorMode::Never => ColorChoice::Never,
+        }
+    }
+
+    /// Apply this mode to all command output, overriding terminal detection
+    pub fn apply(self) {
+        self.choice().write_global();
+    }
+}
+
+/// Style for an event's header, by kind and, for messages, by role: assistant cyan, user green,
+/// tool calls and results yellow, errors red, system events dimmed
+pub fn event_style(kind: &str, role: Option<&str>) -> Style {
+    match (kind, role) {
+        ("error", _) => Style::new().red(),
+        ("tool_call" | "tool_result", _) => Style::new().yellow(),
+        ("system", _) | (_, Some("system")) => Style::new().dimmed(),
+        (_, Some("assistant")) => Style::new().cyan(),
+        (_, Some("user")) => Style::new().green(),
+        _ => Style::new().dimmed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use owo_colors::OwoColorize;
+    use std::io::Write;
+
+    fn render(mode: ColorMode) -> String {
+        let mut out = anstream::AutoStream::new(Vec::new(), mode.choice());
+        write!(out, "{}", "colored".red()).unwrap();
+        String::from_utf8(out.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_color_mode_controls_ansi_output() {
+        // A Vec<u8> is never a terminal, so `always` must force escapes through
+        assert!(render(ColorMode::Always).contains('\x1b'));
+        assert_eq!(render(ColorMode::Never), "colored");
+        assert!(ColorMode::from_str("sometimes").is_err());
+    }
+}
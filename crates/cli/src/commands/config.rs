@@ -0,0 +1,74 @@
+use crate::error::AgentVizError;
+use agent_v_core::config::{Config, default_config_path, read_config, save_config};
+use anstream::println;
+use owo_colors::OwoColorize;
+use std::path::PathBuf;
+
+fn config_path() -> Result<PathBuf, AgentVizError> {
+    default_config_path().ok_or_else(|| AgentVizError::Other("Could not determine home directory".into()))
+}
+
+/// Print the config file location and every setting, marking the ones left at their default
+pub async fn show() -> Result<(), Box<dyn std::error::Error>> {
+    let path = config_path()?;
+    let config = read_config(&path).map_err(AgentVizError::Usage)?;
+
+    println!("{} {}", "Config:".bold().underline(), path.display().to_string().cyan());
+    if !path.exists() {
+        println!("  {}", "(not created yet; `agent-viz config set` writes it)".dimmed());
+    }
+    println!();
+    for line in setting_lines(&config) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Set one key in the config file, creating it if needed
+pub async fn set(key: String, value: String) -> Result<(), Box<dyn std::error::Error>> {
+    let path = config_path()?;
+    let mut config = read_config(&path).map_err(AgentVizError::Usage)?;
+    config.set(&key, &value).map_err(AgentVizError::Usage)?;
+    save_config(&path, &config).map_err(|e| AgentVizError::Other(e.into()))?;
+
+    if value.trim().is_empty() {
+        println!("{} {} (back to the default)", "Cleared".green(), key.cyan());
+    } else {
+        println!("{} {} = {}", "Set".green(), key.cyan(), value);
+    }
+    println!("{}", format!("Saved to {}", path.display()).dimmed());
+    Ok(())
+}
+
+/// `key  value` rows, with `(default)` for unset keys
+fn setting_lines(config: &Config) -> Vec<String> {
+    let entries = config.entries();
+    let width = entries.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    entries
+        .into_iter()
+        .map(|(key, value)| match value {
+            Some(path) => format!("  {:width$}  {}", key, path.display()),
+            None => format!("  {:width$}  {}", key, "(default)".dimmed()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setting_lines_mark_unset_keys_as_default() {
+        let mut config = Config::default();
+        config.set("sources.claude", "/mnt/claude").unwrap();
+        let lines = setting_lines(&config);
+
+        assert_eq!(lines.len(), 6);
+        assert!(
+            lines
+                .iter()
+                .any(|l| l.contains("sources.claude") && l.contains("/mnt/claude"))
+        );
+        assert!(lines[0].starts_with("  db_path") && lines[0].contains("(default)"));
+    }
+}
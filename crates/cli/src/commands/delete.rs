@@ -0,0 +1,152 @@
+use super::export::find_session;
+use agent_v_store::{BACKUP_KEEP, BackupError, Database};
+use anstream::{print, println};
+use owo_colors::OwoColorize;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Delete a session by internal or external ID, asking first unless `yes` is set and backing up
+/// the database first when `backup` is set
+pub async fn run(
+    session_id: String, source: Option<String>, yes: bool, backup: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
+    delete(&db, &session_id, source.as_deref(), yes, backup).await
+}
+
+async fn delete(
+    db: &Database, session_id: &str, source: Option<&str>, yes: bool, backup: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let session = find_session(db, session_id, source).await?;
+    let title = session.title.as_deref().unwrap_or("Untitled");
+
+    println!(
+        "{} {} {} {}",
+        session.id.dimmed(),
+        session.source.cyan(),
+        session.external_id,
+        title.bold()
+    );
+    if !yes && !confirm("Delete this session and all of its events?")? {
+        println!("Nothing deleted.");
+        return Ok(());
+    }
+
+    let backup = backup_if(db, backup).await?;
+    db.delete_session(session.session_id()?).await?;
+
+    println!("{} {}", "Deleted session".green(), session.id);
+    print_backup(backup.as_deref());
+    Ok(())
+}
+
+/// Back up the database ahead of a destructive command unless `--no-backup` was given
+pub(crate) async fn backup_if(db: &Database, backup: bool) -> Result<Option<PathBuf>, BackupError> {
+    if !backup {
+        return Ok(None);
+    }
+    db.backup_before_destructive(BACKUP_KEEP).await.map(Some)
+}
+
+/// Say where the backup went, or that there is none
+pub(crate) fn print_backup(backup: Option<&Path>) {
+    match backup {
+        Some(path) => println!("{}", format!("Backup saved to {}", path.display()).dimmed()),
+        None => println!("{}", "No backup taken (--no-backup)".dimmed()),
+    }
+}
+
+/// Ask a yes/no question on stdin; anything but `y` or `yes` declines
+pub(crate) fn confirm(question: &str) -> io::Result<bool> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(confirmed(&input))
+}
+
+fn confirmed(input: &str) -> bool {
+    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_v_core::{Event, EventKind, Role, Session, Source};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_only_yes_confirms() {
+        assert!(confirmed("y\n"));
+        assert!(confirmed(" YES "));
+        assert!(!confirmed("\n"));
+        assert!(!confirmed("no"));
+    }
+
+    async fn stored_session(db: &Database, external_id: &str) -> Session {
+        let session = Session {
+            id: Uuid::new_v4(),
+            source: Source::Codex,
+            external_id: external_id.to_string(),
+            project: None,
+            title: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_payload: serde_json::json!({}),
+        };
+        let event = Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some("hello".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        };
+        db.insert_session_with_events(&session, &[event]).await.unwrap();
+        session
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_external_id_removes_session_and_events() {
+        let db = Database::open_in_memory().await.unwrap();
+        let session = stored_session(&db, "rollout-doomed").await;
+
+        let found = find_session(&db, "rollout-doomed", None).await.unwrap();
+        assert!(db.delete_session(found.session_id().unwrap()).await.unwrap());
+
+        assert!(find_session(&db, "rollout-doomed", None).await.is_err());
+        assert!(db.get_session_events(session.id.into()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_backs_up_first_unless_told_not_to() {
+        let dir = std::env::temp_dir().join(format!("agent-viz-delete-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = Database::open(dir.join("agent-viz.db")).await.unwrap();
+        db.migrate().await.unwrap();
+        let kept_in_backup = stored_session(&db, "rollout-backed-up").await;
+        let unbacked = stored_session(&db, "rollout-unbacked").await;
+
+        delete(&db, "rollout-backed-up", None, true, true).await.unwrap();
+        delete(&db, &unbacked.id.to_string(), None, true, false).await.unwrap();
+        assert!(db.list_sessions(10, 0).await.unwrap().is_empty());
+
+        let backups: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().contains(".bak-"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        let restored = Database::open(&backups[0]).await.unwrap();
+        let sessions = restored.list_sessions(10, 0).await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions.iter().any(|s| s.id == kept_in_backup.id.to_string()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,56 +1,265 @@
-use agent_v_core::HealthStatus;
-use agent_v_store::{Database, check_sources_health};
+use agent_v_adapters::parse_check::{self, ParseCheck};
+use agent_v_core::{HealthStatus, Source, SourceHealth};
+use agent_v_store::{Database, ExternalIdCollision, check_sources_health};
+use anstream::println;
 use owo_colors::OwoColorize;
+use serde::Serialize;
+
+/// Database state as reported by `doctor`
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseHealth {
+    pub status: HealthStatus,
+    pub path: Option<String>,
+    /// Why the database couldn't be opened or migrated
+    pub error: Option<String>,
+    /// Migrations already applied when `doctor` opened the database
+    pub applied_migrations: usize,
+    /// Migrations that were still pending when `doctor` opened the database (it applies them)
+    pub pending_migrations: Vec<String>,
+}
+
+/// A bundled fixture's parse result, as reported by `doctor --json --parse-check`
+#[derive(Debug, Clone, Serialize)]
+pub struct ParseCheckReport {
+    pub source: Source,
+    pub events: Option<usize>,
+    pub error: Option<String>,
+}
+
+impl From<&ParseCheck> for ParseCheckReport {
+    fn from(check: &ParseCheck) -> Self {
+        Self {
+            source: check.source,
+            events: check.result.as_ref().ok().copied(),
+            error: check.result.as_ref().err().cloned(),
+        }
+    }
+}
+
+/// Everything `doctor --json` prints
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    pub database: DatabaseHealth,
+    pub sources: Vec<SourceHealth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_check: Option<Vec<ParseCheckReport>>,
+}
+
+impl DoctorReport {
+    /// What makes `doctor` exit non-zero: an unhealthy database or source, or a failed fixture.
+    /// Degraded and unknown sources (e.g. a tool that isn't installed) don't count.
+    pub fn failures(&self) -> Vec<String> {
+        let mut failures = Vec::new();
+        if self.database.status == HealthStatus::Unhealthy {
+            failures.push("database is unhealthy".to_string());
+        }
+        for health in &self.sources {
+            if health.status == HealthStatus::Unhealthy {
+                failures.push(format!("{} is unhealthy", health.source));
+            }
+        }
+        let failed = self
+            .parse_check
+            .iter()
+            .flatten()
+            .filter(|check| check.error.is_some())
+            .count();
+        if failed > 0 {
+            failures.push(format!(
+                "{} source(s) failed the parse check; their session format may have changed",
+                failed
+            ));
+        }
+        failures
+    }
+}
+
+pub async fn run(parse_check: bool, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if json {
+        let report = DoctorReport {
+            database: check_database().await,
+            sources: check_sources_health().await,
+            parse_check: if parse_check {
+                Some(
+                    parse_check::run_all()
+                        .await
+                        .iter()
+                        .map(ParseCheckReport::from)
+                        .collect(),
+                )
+            } else {
+                None
+            },
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return finish(&report);
+    }
 
-pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", "Agent V Doctor".bold().underline());
     println!();
 
     println!("{}", "Checking database...".dimmed());
-    match check_database().await {
-        Ok(status) => {
-            println!("  Database: {}", status);
-        }
+    let database = check_database().await;
+    print_database_health(&database);
+    println!();
+
+    println!("{}", "Checking external IDs...".dimmed());
+    match check_external_ids().await {
+        Ok(collisions) => print_external_id_collisions(&collisions),
         Err(e) => {
-            log::error!("Database check failed: {}", e);
-            println!("  Database: {} - {}", "FAILED".red().bold(), e);
+            log::error!("External ID check failed: {}", e);
+            println!("  External IDs: {} - {}", "FAILED".red().bold(), e);
         }
     }
     println!();
 
     println!("{}", "Checking data sources...".dimmed());
-    let health_results = check_sources_health().await;
-    for health in health_results {
-        print_source_health(&health);
+    let sources = check_sources_health().await;
+    for health in &sources {
+        print_source_health(health);
     }
     println!();
 
+    let mut checks = None;
+    if parse_check {
+        println!("{}", "Parsing bundled fixtures...".dimmed());
+        let results = parse_check::run_all().await;
+        for check in &results {
+            print_parse_check(check);
+        }
+        println!();
+        checks = Some(results.iter().map(ParseCheckReport::from).collect());
+    }
+
+    let report = DoctorReport { database, sources, parse_check: checks };
+    finish(&report)?;
     println!("{}", "Doctor check complete.".green().bold());
     Ok(())
 }
 
-async fn check_database() -> Result<String, Box<dyn std::error::Error>> {
-    let db = Database::open_default().await?;
+/// Fail the command when the report has any failures
+fn finish(report: &DoctorReport) -> Result<(), Box<dyn std::error::Error>> {
+    let failures = report.failures();
+    if failures.is_empty() {
+        return Ok(());
+    }
+    Err(failures.join("; ").into())
+}
 
-    db.migrate().await?;
+async fn check_database() -> DatabaseHealth {
+    let opened = async {
+        let db = Database::open_default().await?;
+        let applied = db.applied_migrations().await?.len();
+        let pending = db.pending_migrations().await?;
+        db.migrate().await?;
+        Ok::<_, Box<dyn std::error::Error>>((db, applied, pending))
+    };
 
-    let health = db.health_check().await;
+    match opened.await {
+        Ok((db, applied, pending)) => DatabaseHealth {
+            status: db.health_check().await,
+            path: Some(db.path().display().to_string()),
+            error: None,
+            applied_migrations: applied,
+            pending_migrations: pending.into_iter().map(str::to_string).collect(),
+        },
+        Err(e) => {
+            log::error!("Database check failed: {}", e);
+            DatabaseHealth {
+                status: HealthStatus::Unhealthy,
+                path: None,
+                error: Some(e.to_string()),
+                applied_migrations: 0,
+                pending_migrations: Vec::new(),
+            }
+        }
+    }
+}
 
-    let path = db.path().display().to_string();
+fn print_database_health(database: &DatabaseHealth) {
+    match &database.error {
+        Some(e) => println!("  Database: {} - {}", "FAILED".red().bold(), e),
+        None => {
+            println!(
+                "  Database: {} ({})",
+                status_label(database.status),
+                database.path.as_deref().unwrap_or("?").dimmed()
+            );
+            print_migrations(database);
+        }
+    }
+}
 
-    let status_str = match health {
-        HealthStatus::Healthy => "healthy".green().bold().to_string(),
-        HealthStatus::Degraded => "degraded".yellow().bold().to_string(),
-        HealthStatus::Unhealthy => "unhealthy".red().bold().to_string(),
-        HealthStatus::Unknown => "unknown".dimmed().to_string(),
-    };
+fn print_migrations(database: &DatabaseHealth) {
+    let pending = &database.pending_migrations;
+    if pending.is_empty() {
+        println!(
+            "  Migrations: {} applied, {} pending",
+            database.applied_migrations.to_string().green().bold(),
+            0
+        );
+        return;
+    }
 
-    Ok(format!("{} ({})", status_str, path.dimmed()))
+    println!(
+        "  Migrations: {} applied, {} pending",
+        database.applied_migrations,
+        pending.len().to_string().yellow().bold()
+    );
+    println!("      {}", format!("Applied now: {}", pending.join(", ")).italic());
+}
+
+/// Find external IDs shared across sources, which make ID-only session lookups ambiguous
+async fn check_external_ids() -> Result<Vec<ExternalIdCollision>, Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
+    Ok(db.get_external_id_collisions().await?)
+}
+
+fn print_external_id_collisions(collisions: &[ExternalIdCollision]) {
+    if collisions.is_empty() {
+        println!("  External IDs: {}", "unique across sources".green().bold());
+        return;
+    }
+
+    println!(
+        "  External IDs: {} shared across sources",
+        collisions.len().to_string().yellow().bold()
+    );
+    for collision in collisions {
+        println!(
+            "      {} ({})",
+            collision.external_id,
+            collision.sources.join(", ").dimmed()
+        );
+    }
+    println!("      {}", "Use --source with export --session to pick one".italic());
+}
+
+fn print_parse_check(check: &ParseCheck) {
+    match &check.result {
+        Ok(events) => println!(
+            "  [{}] {}: {} ({} events)",
+            "✓".green().bold(),
+            check.source.to_string().cyan(),
+            "parses".green().bold(),
+            events
+        ),
+        Err(e) => {
+            println!(
+                "  [{}] {}: {}",
+                "✗".red().bold(),
+                check.source.to_string().cyan(),
+                "FAILED".red().bold()
+            );
+            println!("      {}", e.italic());
+        }
+    }
 }
 
 type IconStyler = Box<dyn Fn(&str) -> String>;
 
-fn print_source_health(health: &agent_v_core::SourceHealth) {
+fn print_source_health(health: &SourceHealth) {
     let (icon, icon_style): (&str, IconStyler) = match health.status {
         HealthStatus::Healthy => ("✓", Box::new(|s: &str| s.green().bold().to_string())),
         HealthStatus::Degraded => ("~", Box::new(|s: &str| s.yellow().bold().to_string())),
@@ -58,18 +267,11 @@ fn print_source_health(health: &agent_v_core::SourceHealth) {
         HealthStatus::Unknown => ("?", Box::new(|s: &str| s.dimmed().to_string())),
     };
 
-    let status_str: String = match health.status {
-        HealthStatus::Healthy => "healthy".green().bold().to_string(),
-        HealthStatus::Degraded => "degraded".yellow().bold().to_string(),
-        HealthStatus::Unhealthy => "unhealthy".red().bold().to_string(),
-        HealthStatus::Unknown => "unknown".dimmed().to_string(),
-    };
-
     println!(
         "  [{}] {}: {}",
         icon_style(icon),
         health.source.to_string().cyan(),
-        status_str
+        status_label(health.status)
     );
 
     if let Some(path) = &health.path {
@@ -80,3 +282,55 @@ fn print_source_health(health: &agent_v_core::SourceHealth) {
         println!("      {}", msg.italic());
     }
 }
+
+fn status_label(status: HealthStatus) -> String {
+    match status {
+        HealthStatus::Healthy => "healthy".green().bold().to_string(),
+        HealthStatus::Degraded => "degraded".yellow().bold().to_string(),
+        HealthStatus::Unhealthy => "unhealthy".red().bold().to_string(),
+        HealthStatus::Unknown => "unknown".dimmed().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(source: Source, status: HealthStatus) -> SourceHealth {
+        SourceHealth { source, status, path: None, message: None }
+    }
+
+    #[test]
+    fn test_report_json_shape_and_failures() {
+        let mut report = DoctorReport {
+            database: DatabaseHealth {
+                status: HealthStatus::Healthy,
+                path: Some("/data/agent-viz.db".to_string()),
+                error: None,
+                applied_migrations: 17,
+                pending_migrations: Vec::new(),
+            },
+            sources: vec![
+                source(Source::Claude, HealthStatus::Healthy),
+                source(Source::Codex, HealthStatus::Unknown),
+                source(Source::Gemini, HealthStatus::Degraded),
+            ],
+            parse_check: None,
+        };
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["database"]["status"], "healthy");
+        assert_eq!(json["database"]["path"], "/data/agent-viz.db");
+        assert_eq!(json["database"]["applied_migrations"], 17);
+        assert_eq!(json["database"]["pending_migrations"], serde_json::json!([]));
+        assert_eq!(json["sources"][1]["source"], "codex");
+        assert_eq!(json["sources"][1]["status"], "unknown");
+        assert!(json.get("parse_check").is_none());
+        assert!(report.failures().is_empty());
+
+        report.sources[1].status = HealthStatus::Unhealthy;
+        report.database.status = HealthStatus::Unhealthy;
+        assert_eq!(report.failures(), ["database is unhealthy", "codex is unhealthy"]);
+        assert!(finish(&report).is_err());
+    }
+}
@@ -1,58 +1,131 @@
+use crate::error::AgentVizError;
+use crate::util::parse_since;
+use agent_v_core::SessionId;
 use agent_v_store::{Database, EventRow, SearchFacets, SessionMetricsRow, SessionRow};
+use anstream::println;
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
-/// Export a single session
+/// Export a session; `last_n_events` keeps only its most recent events (metrics still cover the whole session)
+/// and `merge_turns` coalesces consecutive same-role messages into one block
 pub async fn export_session(
-    session_id: String, format: ExportFormat, output: Option<String>,
+    session_id: String, source: Option<String>, format: ExportFormat, output: Option<String>,
+    last_n_events: Option<usize>, merge_turns: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let db = Database::open_default().await?;
     db.migrate().await?;
 
-    let mut session = None;
-    let mut offset = 0;
-    loop {
-        let sessions = db.list_sessions(100, offset).await?;
-        if sessions.is_empty() {
-            break;
-        }
-        if let Some(found) = sessions
-            .into_iter()
-            .find(|s| s.id == session_id || s.external_id == session_id)
+    let session = find_session(&db, &session_id, source.as_deref()).await?;
+
+    let session_key = session.session_id()?;
+    let mut events = tail_events(db.get_session_events(session_key).await?, last_n_events);
+    if merge_turns {
+        events = merge_consecutive_turns(events);
+    }
+    let metrics = db.get_session_metrics(session_key).await?;
+
+    let content = render_session(&session, &events, metrics.as_ref(), format).await?;
+    write_output(&content, output.as_deref())?;
+
+    Ok(())
+}
+
+/// Keep the last `n` events of a timestamp-ordered list, or all of them when `n` is `None`
+fn tail_events(mut events: Vec<EventRow>, n: Option<usize>) -> Vec<EventRow> {
+    if let Some(n) = n
+        && events.len() > n
+    {
+        events.drain(..events.len() - n);
+    }
+    events
+}
+
+/// Coalesce runs of adjacent message events with the same role into a single event whose
+/// content is joined with blank lines. The first event of a run keeps its ID and timestamp.
+/// Only messages merge, so tool calls and results always split a turn.
+fn merge_consecutive_turns(events: Vec<EventRow>) -> Vec<EventRow> {
+    let mut merged: Vec<EventRow> = Vec::with_capacity(events.len());
+
+    for event in events {
+        if let Some(last) = merged.last_mut()
+            && last.kind == "message"
+            && event.kind == "message"
+            && last.role.is_some()
+            && last.role == event.role
         {
-            session = Some(found);
-            break;
+            if let Some(next) = event.content {
+                match last.content {
+                    Some(ref mut existing) => {
+                        existing.push_str("\n\n");
+                        existing.push_str(&next);
+                    }
+                    None => last.content = Some(next),
+                }
+            }
+            continue;
         }
-        offset += 100;
+        merged.push(event);
     }
 
-    let session = match session {
-        Some(s) => s,
+    merged
+}
+
+/// Export only the computed metrics for one session, or for every session when none is given
+pub async fn export_metrics(
+    session_id: Option<String>, source: Option<String>, format: ExportFormat, output: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
+
+    let content = match session_id {
+        Some(session_id) => {
+            let session = find_session(&db, &session_id, source.as_deref()).await?;
+            let metrics = db.get_session_metrics(session.session_id()?).await?;
+            render_metrics_only(&[(session, metrics)], format, true)?
+        }
         None => {
-            return Err(format!("Session not found: {}", session_id).into());
+            let mut entries = Vec::new();
+            let mut offset = 0;
+            loop {
+                let page = db.get_sessions_with_metrics(500, offset).await?;
+                if page.is_empty() {
+                    break;
+                }
+                offset += page.len() as i64;
+                entries.extend(page);
+            }
+            render_metrics_only(&entries, format, false)?
         }
     };
+    write_output(&content, output.as_deref())?;
 
-    let events = db.get_session_events(session.id.clone()).await?;
-    let metrics = db.get_session_metrics(&session.id).await?;
+    Ok(())
+}
 
-    match format {
-        ExportFormat::Markdown => {
-            let md = export_session_to_markdown(&session, &events, metrics.as_ref()).await?;
-            write_output(&md, output.as_deref())?;
-        }
-        ExportFormat::Json => {
-            let json = export_session_to_json(&session, &events, metrics.as_ref()).await?;
-            write_output(&json, output.as_deref())?;
-        }
-        ExportFormat::Jsonl => {
-            let jsonl = export_session_to_jsonl(&session, &events).await?;
-            write_output(&jsonl, output.as_deref())?;
+/// Look up a session by internal or external ID, scoped to `source` when given
+///
+/// An internal ID match wins; anything else is treated as an external ID. External IDs are only unique per source, so an ID shared across sources needs
+/// `--source` to pick one.
+pub(crate) async fn find_session(
+    db: &Database, session_id: &str, source: Option<&str>,
+) -> Result<SessionRow, Box<dyn std::error::Error>> {
+    let mut matches = db.find_sessions(session_id, source).await?;
+    match matches.len() {
+        0 => Err(AgentVizError::NotFound(format!("Session not found: {}", session_id)).into()),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let sources: Vec<&str> = matches.iter().map(|s| s.source.as_str()).collect();
+            Err(format!(
+                "Session ID {} exists in several sources ({}); pass --source to choose one",
+                session_id,
+                sources.join(", ")
+            )
+            .into())
         }
     }
-
-    Ok(())
 }
 
 /// Export search results
@@ -63,8 +136,19 @@ pub async fn export_search(
     let db = Database::open_default().await?;
     db.migrate().await?;
 
-    let facets =
-        SearchFacets { source, project: None, kind, since: since.and_then(|s| parse_since(&s).ok().flatten()) };
+    let facets = SearchFacets {
+        source,
+        project: None,
+        kind,
+        since: since.and_then(|s| parse_since(&s).ok()),
+        until: None,
+        snippet_len: None,
+        match_any: false,
+        category: None,
+        thinking: false,
+        tag: None,
+        session_id: None,
+    };
 
     let results = db.search_events(&query, &facets, 10000, 0).await?;
 
@@ -81,16 +165,187 @@ pub async fn export_search(
             let jsonl = export_search_to_jsonl(&query, &results).await?;
             write_output(&jsonl, output.as_deref())?;
         }
+        ExportFormat::Csv => {
+            let csv = export_search_to_csv(&results);
+            write_output(&csv, output.as_deref())?;
+        }
     }
 
     Ok(())
 }
 
+/// Export search results as one file per matching session
+///
+/// Each file contains the matched events of a single session, or the full
+/// session transcript (with metrics) when `full_context` is set.
+pub async fn export_search_split(
+    query: String, source: Option<String>, since: Option<String>, kind: Option<String>, format: ExportFormat,
+    output_dir: String, full_context: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
+
+    let facets = SearchFacets {
+        source,
+        project: None,
+        kind,
+        since: since.and_then(|s| parse_since(&s).ok()),
+        until: None,
+        snippet_len: None,
+        match_any: false,
+        category: None,
+        thinking: false,
+        tag: None,
+        session_id: None,
+    };
+
+    let results = db.search_events(&query, &facets, 10000, 0).await?;
+
+    let mut exports = Vec::new();
+    for (session_id, matched) in group_results_by_session(&results) {
+        let session_id: SessionId = session_id.parse()?;
+        let Some(session) = db.get_session(session_id).await? else {
+            continue;
+        };
+
+        if full_context {
+            let events = db.get_session_events(session_id).await?;
+            let metrics = db.get_session_metrics(session_id).await?;
+            exports.push((session, events, metrics));
+        } else {
+            exports.push((session, matched, None));
+        }
+    }
+
+    let written = write_session_files(Path::new(&output_dir), &exports, format).await?;
+    println!("Exported {} sessions to: {}", written.len(), output_dir);
+
+    Ok(())
+}
+
+/// Export every session into its own file under `output_dir`
+///
+/// With `since_session`, only sessions updated after the referenced session are written, so a sync
+/// can pass the last session it exported to pick up where it left off.
+pub async fn export_all(
+    source: Option<String>, since_session: Option<String>, format: ExportFormat, output_dir: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
+
+    let mut exports = Vec::new();
+    for session in sessions_since(&db, source.as_deref(), since_session.as_deref()).await? {
+        let session_id = session.session_id()?;
+        let events = db.get_session_events(session_id).await?;
+        let metrics = db.get_session_metrics(session_id).await?;
+        exports.push((session, events, metrics));
+    }
+
+    let written = write_session_files(Path::new(&output_dir), &exports, format).await?;
+    println!("Exported {} sessions to: {}", written.len(), output_dir);
+
+    Ok(())
+}
+
+/// List sessions (newest first) updated strictly after the `since_session` reference, or all of them
+async fn sessions_since(
+    db: &Database, source: Option<&str>, since_session: Option<&str>,
+) -> Result<Vec<SessionRow>, Box<dyn std::error::Error>> {
+    let cutoff = match since_session {
+        Some(id) => {
+            let reference = find_session(db, id, source).await?;
+            Some(DateTime::parse_from_rfc3339(&reference.updated_at)?.with_timezone(&Utc))
+        }
+        None => None,
+    };
+
+    let mut sessions = Vec::new();
+    let mut offset = 0;
+    'pages: loop {
+        let page = db.list_sessions_filtered(source, 500, offset).await?;
+        if page.is_empty() {
+            break;
+        }
+        offset += page.len() as i64;
+
+        for session in page {
+            if let Some(cutoff) = cutoff {
+                let updated_at = DateTime::parse_from_rfc3339(&session.updated_at)?.with_timezone(&Utc);
+                if updated_at <= cutoff {
+                    break 'pages;
+                }
+            }
+            sessions.push(session);
+        }
+    }
+
+    Ok(sessions)
+}
+
+/// Group search hits by session, ordering each session's events by timestamp
+fn group_results_by_session(results: &[agent_v_store::SearchResult]) -> BTreeMap<String, Vec<EventRow>> {
+    let mut grouped: BTreeMap<String, Vec<EventRow>> = BTreeMap::new();
+
+    for result in results {
+        grouped
+            .entry(result.event.session_id.clone())
+            .or_default()
+            .push(result.event.clone());
+    }
+
+    for events in grouped.values_mut() {
+        events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+    }
+
+    grouped
+}
+
+/// Write each session export into its own file under `dir`
+async fn write_session_files(
+    dir: &Path, exports: &[(SessionRow, Vec<EventRow>, Option<SessionMetricsRow>)], format: ExportFormat,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(dir)?;
+
+    let mut written = Vec::new();
+    for (session, events, metrics) in exports {
+        let content = render_session(session, events, metrics.as_ref(), format).await?;
+        let path = dir.join(session_file_name(session, format));
+        std::fs::write(&path, content)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// Build a filesystem-safe file name for a session export. The short session ID keeps sessions
+/// whose external IDs only differ in characters replaced here from overwriting each other.
+fn session_file_name(session: &SessionRow, format: ExportFormat) -> String {
+    let short_id: String = session.id.chars().take(8).collect();
+    let stem: String = format!("{}-{}-{}", session.source, session.external_id, short_id)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+        .collect();
+
+    format!("{}.{}", stem, format.extension())
+}
+
+async fn render_session(
+    session: &SessionRow, events: &[EventRow], metrics: Option<&SessionMetricsRow>, format: ExportFormat,
+) -> Result<String, Box<dyn std::error::Error>> {
+    match format {
+        ExportFormat::Markdown => export_session_to_markdown(session, events, metrics).await,
+        ExportFormat::Json => export_session_to_json(session, events, metrics).await,
+        ExportFormat::Jsonl => export_session_to_jsonl(session, events).await,
+        ExportFormat::Csv => Ok(export_session_to_csv(events)),
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum ExportFormat {
     Markdown,
     Json,
     Jsonl,
+    Csv,
 }
 
 impl ExportFormat {
@@ -99,7 +354,28 @@ impl ExportFormat {
             "md" | "markdown" => Ok(ExportFormat::Markdown),
             "json" => Ok(ExportFormat::Json),
             "jsonl" => Ok(ExportFormat::Jsonl),
-            _ => Err(format!("Unknown format: {}. Use 'md', 'json', or 'jsonl'", s)),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(format!("Unknown format: {}. Use 'md', 'json', 'jsonl', or 'csv'", s)),
+        }
+    }
+
+    /// All export formats supported by this build
+    pub fn all() -> &'static [ExportFormat] {
+        &[
+            ExportFormat::Markdown,
+            ExportFormat::Json,
+            ExportFormat::Jsonl,
+            ExportFormat::Csv,
+        ]
+    }
+
+    /// File extension used when writing this format to disk
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Csv => "csv",
         }
     }
 }
@@ -219,6 +495,74 @@ struct SessionMetricsExport {
     p95_latency_ms: Option<i64>,
 }
 
+impl From<&SessionMetricsRow> for SessionMetricsExport {
+    fn from(m: &SessionMetricsRow) -> Self {
+        SessionMetricsExport {
+            total_events: m.total_events,
+            message_count: m.message_count,
+            tool_call_count: m.tool_call_count,
+            tool_result_count: m.tool_result_count,
+            error_count: m.error_count,
+            user_messages: m.user_messages,
+            assistant_messages: m.assistant_messages,
+            duration_seconds: m.duration_seconds,
+            files_touched: m.files_touched,
+            lines_added: m.lines_added,
+            lines_removed: m.lines_removed,
+            model: m.model.clone(),
+            provider: m.provider.clone(),
+            input_tokens: m.input_tokens,
+            output_tokens: m.output_tokens,
+            estimated_cost: m.estimated_cost,
+            total_latency_ms: m.total_latency_ms,
+            avg_latency_ms: m.avg_latency_ms,
+            p50_latency_ms: m.p50_latency_ms,
+            p95_latency_ms: m.p95_latency_ms,
+        }
+    }
+}
+
+/// Session identity plus metrics, without any event content
+#[derive(Serialize)]
+struct SessionMetricsOnlyExport {
+    id: String,
+    source: String,
+    external_id: String,
+    project: Option<String>,
+    title: Option<String>,
+    metrics: Option<SessionMetricsExport>,
+}
+
+/// Render metrics-only records as JSON (an object when `single`, otherwise an array) or JSON lines
+fn render_metrics_only(
+    entries: &[(SessionRow, Option<SessionMetricsRow>)], format: ExportFormat, single: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let records: Vec<SessionMetricsOnlyExport> = entries
+        .iter()
+        .map(|(session, metrics)| SessionMetricsOnlyExport {
+            id: session.id.clone(),
+            source: session.source.clone(),
+            external_id: session.external_id.clone(),
+            project: session.project.clone(),
+            title: session.title.clone(),
+            metrics: metrics.as_ref().map(SessionMetricsExport::from),
+        })
+        .collect();
+
+    match format {
+        ExportFormat::Json if single && records.len() == 1 => Ok(serde_json::to_string_pretty(&records[0])?),
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(&records)?),
+        ExportFormat::Jsonl => Ok(records
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n")),
+        ExportFormat::Markdown | ExportFormat::Csv => {
+            Err("Metrics-only export supports 'json' or 'jsonl' formats".into())
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct EventExport {
     id: String,
@@ -229,31 +573,10 @@ struct EventExport {
     raw_payload: serde_json::Value,
 }
 
-async fn export_session_to_json(
+pub(crate) async fn export_session_to_json(
     session: &SessionRow, events: &[EventRow], metrics: Option<&SessionMetricsRow>,
 ) -> Result<String, Box<dyn std::error::Error>> {
-    let metrics_export = metrics.map(|m| SessionMetricsExport {
-        total_events: m.total_events,
-        message_count: m.message_count,
-        tool_call_count: m.tool_call_count,
-        tool_result_count: m.tool_result_count,
-        error_count: m.error_count,
-        user_messages: m.user_messages,
-        assistant_messages: m.assistant_messages,
-        duration_seconds: m.duration_seconds,
-        files_touched: m.files_touched,
-        lines_added: m.lines_added,
-        lines_removed: m.lines_removed,
-        model: m.model.clone(),
-        provider: m.provider.clone(),
-        input_tokens: m.input_tokens,
-        output_tokens: m.output_tokens,
-        estimated_cost: m.estimated_cost,
-        total_latency_ms: m.total_latency_ms,
-        avg_latency_ms: m.avg_latency_ms,
-        p50_latency_ms: m.p50_latency_ms,
-        p95_latency_ms: m.p95_latency_ms,
-    });
+    let metrics_export = metrics.map(SessionMetricsExport::from);
 
     let export = SessionExport {
         id: session.id.clone(),
@@ -389,34 +712,364 @@ async fn export_search_to_jsonl(
     Ok(lines.join("\n"))
 }
 
-fn parse_since(since: &str) -> Result<Option<DateTime<Utc>>, String> {
-    if since.is_empty() {
-        return Ok(None);
-    }
-
-    let err = Err(format!("Invalid duration format: {}. Use Nd, Nh, Nw", since));
-
-    let duration = if since.ends_with('d') {
-        since
-            .strip_suffix('d')
-            .and_then(|days| days.parse().ok())
-            .map(chrono::Duration::days)
-    } else if since.ends_with('h') {
-        since
-            .strip_suffix('h')
-            .and_then(|hours| hours.parse().ok())
-            .map(chrono::Duration::hours)
-    } else if since.ends_with('w') {
-        since
-            .strip_suffix('w')
-            .and_then(|weeks| weeks.parse().ok())
-            .map(chrono::Duration::weeks)
+/// One CSV row per event: `id,kind,role,timestamp,content`
+fn export_session_to_csv(events: &[EventRow]) -> String {
+    let mut csv = csv_row(&["id", "kind", "role", "timestamp", "content"]);
+    for event in events {
+        csv.push_str(&csv_row(&[
+            &event.id,
+            &event.kind,
+            event.role.as_deref().unwrap_or_default(),
+            &event.timestamp,
+            event.content.as_deref().unwrap_or_default(),
+        ]));
+    }
+    csv
+}
+
+/// One CSV row per search hit: `event_id,session_id,kind,rank,snippet`; hits without a
+/// snippet fall back to the event's full content
+fn export_search_to_csv(results: &[agent_v_store::SearchResult]) -> String {
+    let mut csv = csv_row(&["event_id", "session_id", "kind", "rank", "snippet"]);
+    for result in results {
+        let snippet = result.snippet.as_deref().or(result.event.content.as_deref());
+        csv.push_str(&csv_row(&[
+            &result.event.id,
+            &result.event.session_id,
+            &result.event.kind,
+            &format!("{:.4}", result.rank),
+            snippet.unwrap_or_default(),
+        ]));
+    }
+    csv
+}
+
+/// A CSV record terminated by CRLF, per RFC 4180
+fn csv_row(fields: &[&str]) -> String {
+    let mut row = fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",");
+    row.push_str("\r\n");
+    row
+}
+
+/// Quote a field when it holds a comma, quote, or line break, doubling embedded quotes
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
     } else {
-        return err;
-    };
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, external_id: &str) -> SessionRow {
+        SessionRow {
+            id: id.to_string(),
+            source: "claude".to_string(),
+            external_id: external_id.to_string(),
+            project: None,
+            title: Some(format!("Session {}", external_id)),
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+            updated_at: "2025-01-01T00:00:00Z".to_string(),
+            raw_payload: "{}".to_string(),
+        }
+    }
+
+    fn hit(id: &str, session_id: &str, timestamp: &str) -> agent_v_store::SearchResult {
+        agent_v_store::SearchResult {
+            event: EventRow {
+                id: id.to_string(),
+                session_id: session_id.to_string(),
+                kind: "message".to_string(),
+                role: Some("user".to_string()),
+                content: Some("needle".to_string()),
+                timestamp: timestamp.to_string(),
+                raw_payload: "{}".to_string(),
+                category: None,
+                parent_id: None,
+            },
+            rank: -1.0,
+            snippet: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_export_writes_one_file_per_session() {
+        let results = vec![
+            hit("e3", "s1", "2025-01-01T00:02:00Z"),
+            hit("e1", "s2", "2025-01-01T00:00:00Z"),
+            hit("e2", "s1", "2025-01-01T00:01:00Z"),
+        ];
+
+        let grouped = group_results_by_session(&results);
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(
+            grouped["s1"].iter().map(|e| e.id.as_str()).collect::<Vec<_>>(),
+            vec!["e2", "e3"]
+        );
+
+        let exports: Vec<_> = grouped
+            .into_iter()
+            .map(|(id, events)| (session(&id, &format!("ext/{}", id)), events, None))
+            .collect();
+
+        let dir = std::env::temp_dir().join(format!("agent-viz-split-{}", std::process::id()));
+        let written = write_session_files(&dir, &exports, ExportFormat::Jsonl).await.unwrap();
+
+        assert_eq!(written.len(), 2);
+        assert!(dir.join("claude-ext_s1-s1.jsonl").exists());
+        assert!(dir.join("claude-ext_s2-s2.jsonl").exists());
+
+        let s1 = std::fs::read_to_string(dir.join("claude-ext_s1-s1.jsonl")).unwrap();
+        assert_eq!(s1.lines().count(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_split_export_keeps_sessions_with_the_same_name_apart() {
+        let mut first = session("0b6f3a1c-0000-4000-8000-000000000001", "task:1");
+        let mut second = session("7d2e9c44-0000-4000-8000-000000000002", "task/1");
+        first.title = Some("Fix the build".to_string());
+        second.title = Some("Fix the build".to_string());
+        let exports = vec![(first, Vec::new(), None), (second, Vec::new(), None)];
+
+        let dir = std::env::temp_dir().join(format!("agent-viz-split-names-{}", std::process::id()));
+        let written = write_session_files(&dir, &exports, ExportFormat::Markdown)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            written,
+            vec![
+                dir.join("claude-task_1-0b6f3a1c.md"),
+                dir.join("claude-task_1-7d2e9c44.md")
+            ]
+        );
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn metrics(session_id: &str) -> SessionMetricsRow {
+        SessionMetricsRow {
+            session_id: session_id.to_string(),
+            total_events: 12,
+            message_count: 8,
+            tool_call_count: 2,
+            tool_result_count: 2,
+            error_count: 0,
+            user_messages: 4,
+            assistant_messages: 4,
+            duration_seconds: Some(90),
+            files_touched: 1,
+            lines_added: 10,
+            lines_removed: 3,
+            computed_at: "2025-01-01T00:00:00Z".to_string(),
+            model: Some("claude-sonnet".to_string()),
+            provider: Some("anthropic".to_string()),
+            input_tokens: Some(1000),
+            output_tokens: Some(200),
+            estimated_cost: Some(0.01),
+            total_latency_ms: None,
+            avg_latency_ms: None,
+            p50_latency_ms: None,
+            p95_latency_ms: None,
+            reasoning_tokens: None,
+            reasoning_cost: None,
+            tokens_per_line: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_last_n_events_keeps_the_tail_in_every_format() {
+        let events: Vec<EventRow> = (0..10)
+            .map(|i| EventRow {
+                id: format!("e{}", i),
+                session_id: "s1".to_string(),
+                kind: "message".to_string(),
+                role: Some("user".to_string()),
+                content: Some(format!("event {}", i)),
+                timestamp: format!("2025-01-01T00:00:{:02}Z", i),
+                raw_payload: "{}".to_string(),
+                category: None,
+                parent_id: None,
+            })
+            .collect();
+        let tail = tail_events(events.clone(), Some(3));
+        assert_eq!(
+            tail.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(),
+            vec!["e7", "e8", "e9"]
+        );
+        assert_eq!(tail_events(events.clone(), None).len(), 10);
+        assert_eq!(tail_events(events, Some(50)).len(), 10);
+
+        let s = session("s1", "ext1");
+        let m = metrics("s1");
+
+        let json = render_session(&s, &tail, Some(&m), ExportFormat::Json).await.unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["events"].as_array().unwrap().len(), 3);
+        assert_eq!(value["metrics"]["total_events"], 12);
+
+        let jsonl = render_session(&s, &tail, Some(&m), ExportFormat::Jsonl).await.unwrap();
+        assert_eq!(jsonl.lines().count(), 3);
+
+        let md = render_session(&s, &tail, Some(&m), ExportFormat::Markdown)
+            .await
+            .unwrap();
+        assert_eq!(md.matches("\n### ").count(), 3);
+        assert!(md.contains("Cost & Efficiency"));
+    }
+
+    #[tokio::test]
+    async fn test_merge_turns_renders_adjacent_assistant_messages_as_one_block() {
+        let event = |id: &str, kind: &str, role: &str, content: &str| EventRow {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            kind: kind.to_string(),
+            role: Some(role.to_string()),
+            content: Some(content.to_string()),
+            timestamp: format!("2025-01-01T00:00:0{}Z", &id[1..]),
+            raw_payload: "{}".to_string(),
+            category: None,
+            parent_id: None,
+        };
+        let events = vec![
+            event("e1", "message", "user", "fix the build"),
+            event("e2", "message", "assistant", "Looking at the error."),
+            event("e3", "message", "assistant", "It is a missing import."),
+            event("e4", "tool_call", "assistant", "Edit src/lib.rs"),
+            event("e5", "message", "assistant", "Done."),
+        ];
+
+        let merged = merge_consecutive_turns(events);
+        assert_eq!(
+            merged.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(),
+            vec!["e1", "e2", "e4", "e5"]
+        );
+        assert_eq!(
+            merged[1].content.as_deref(),
+            Some("Looking at the error.\n\nIt is a missing import.")
+        );
+
+        let md = render_session(&session("s1", "ext1"), &merged, None, ExportFormat::Markdown)
+            .await
+            .unwrap();
+        assert_eq!(md.matches("\n### ").count(), 4);
+        assert_eq!(md.matches("**Role**: assistant").count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_since_session_keeps_only_newer_sessions() {
+        use agent_v_core::{Session, Source};
+        use chrono::TimeZone;
+
+        let db = Database::open_in_memory().await.unwrap();
+
+        for day in 1..=5 {
+            let at = Utc.with_ymd_and_hms(2025, 1, day, 12, 0, 0).unwrap();
+            let session = Session {
+                id: uuid::Uuid::new_v4(),
+                source: Source::Claude,
+                external_id: format!("day-{}", day),
+                project: None,
+                title: None,
+                created_at: at,
+                updated_at: at,
+                raw_payload: serde_json::json!({}),
+            };
+            db.insert_session(&session).await.unwrap();
+        }
+
+        let newer = sessions_since(&db, None, Some("day-3")).await.unwrap();
+        assert_eq!(
+            newer.iter().map(|s| s.external_id.as_str()).collect::<Vec<_>>(),
+            vec!["day-5", "day-4"]
+        );
+
+        assert!(sessions_since(&db, None, Some("day-5")).await.unwrap().is_empty());
+        assert_eq!(sessions_since(&db, None, None).await.unwrap().len(), 5);
+        assert!(sessions_since(&db, None, Some("missing")).await.is_err());
+    }
+
+    #[test]
+    fn test_metrics_only_export_has_no_events() {
+        let entries = vec![
+            (session("s1", "ext1"), Some(metrics("s1"))),
+            (session("s2", "ext2"), None),
+        ];
+
+        let json = render_metrics_only(&entries, ExportFormat::Json, false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let records = value.as_array().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["metrics"]["total_events"], 12);
+        assert_eq!(records[0]["metrics"]["input_tokens"], 1000);
+        assert!(records[1]["metrics"].is_null());
+        assert!(records.iter().all(|r| r.get("events").is_none()));
+
+        let single = render_metrics_only(&entries[..1], ExportFormat::Json, true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&single).unwrap();
+        assert_eq!(value["id"], "s1");
+        assert!(value.get("events").is_none());
+
+        assert!(render_metrics_only(&entries, ExportFormat::Markdown, false).is_err());
+    }
+
+    /// Minimal RFC 4180 reader, enough to check that exported rows parse back intact
+    fn parse_csv(text: &str) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        let (mut row, mut field) = (Vec::new(), String::new());
+        let mut chars = text.chars().peekable();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') => quoted = true,
+                (false, ',') => row.push(std::mem::take(&mut field)),
+                (false, '\r') => {}
+                (false, '\n') => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                (false, c) => field.push(c),
+            }
+        }
+        rows
+    }
+
+    #[tokio::test]
+    async fn test_csv_export_round_trips_quotes_commas_and_newlines() {
+        let tricky = "she said \"hi\", then\nleft\r\nfor good";
+        let mut event = hit("e1", "s1", "2025-01-01T00:00:00Z").event;
+        event.content = Some(tricky.to_string());
+        let events = vec![event, hit("e2", "s1", "2025-01-01T00:01:00Z").event];
+
+        let csv = render_session(&session("s1", "ext1"), &events, None, ExportFormat::Csv)
+            .await
+            .unwrap();
+        let rows = parse_csv(&csv);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], ["id", "kind", "role", "timestamp", "content"]);
+        assert_eq!(rows[1], ["e1", "message", "user", "2025-01-01T00:00:00Z", tricky]);
+        assert_eq!(rows[2][4], "needle");
 
-    match duration {
-        Some(duration) => Ok(Some(Utc::now() - duration)),
-        None => Ok(None),
+        let mut snippet_hit = hit("e1", "s1", "2025-01-01T00:00:00Z");
+        snippet_hit.snippet = Some(tricky.to_string());
+        let rows = parse_csv(&export_search_to_csv(&[
+            snippet_hit,
+            hit("e2", "s2", "2025-01-01T00:01:00Z"),
+        ]));
+        assert_eq!(rows[0], ["event_id", "session_id", "kind", "rank", "snippet"]);
+        assert_eq!(rows[1], ["e1", "s1", "message", "-1.0000", tricky]);
+        assert_eq!(rows[2], ["e2", "s2", "message", "-1.0000", "needle"]);
     }
 }
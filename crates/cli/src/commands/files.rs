@@ -0,0 +1,43 @@
+use agent_v_store::Database;
+use anstream::println;
+use owo_colors::OwoColorize;
+
+/// List sessions that touched files matching a glob or substring pattern
+pub async fn run(pattern: String, limit: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
+
+    let matches = db.get_sessions_by_file(&pattern, limit).await?;
+
+    println!("{} {}", "Sessions touching:".bold().underline(), pattern.cyan());
+    println!();
+
+    if matches.is_empty() {
+        println!("{}", "No sessions touched a matching file.".yellow());
+        return Ok(());
+    }
+
+    for m in &matches {
+        let title = m.title.as_deref().unwrap_or("Untitled");
+        let updated = &m.updated_at[..19.min(m.updated_at.len())];
+        println!(
+            "  {} {:10} {} {}",
+            m.session_id[..8.min(m.session_id.len())].to_string().cyan(),
+            m.source.dimmed(),
+            updated.dimmed(),
+            title.bold()
+        );
+        println!(
+            "     {} touches across {} file(s)  {} {}",
+            m.match_count,
+            m.file_count,
+            format!("+{}", m.lines_added).green(),
+            format!("-{}", m.lines_removed).red()
+        );
+    }
+
+    println!();
+    println!("  {}: {} sessions", "Total".bold(), matches.len());
+
+    Ok(())
+}
@@ -1,29 +1,291 @@
-use agent_v_adapters::{claude::ClaudeAdapter, codex::CodexAdapter, crush::CrushAdapter, opencode::OpenCodeAdapter};
-use agent_v_core::Source;
+use crate::error::AgentVizError;
+use crate::util::truncate_chars;
+use agent_v_adapters::{
+    claude::{ClaudeAdapter, ClaudeSessionFile},
+    codex::{CodexAdapter, CodexSessionFile},
+    crush::{CrushAdapter, DiscoveryFilter},
+    gemini::{GeminiAdapter, GeminiSessionFile},
+    opencode::OpenCodeAdapter,
+};
+use agent_v_core::{AdapterError, Event, ExternalId, KindRemap, Session, Source};
 use agent_v_ingest::Watcher;
-use agent_v_store::Database;
+use agent_v_store::{Database, detect_source_in_dir};
+use anstream::{print, println};
+use futures::stream::{self, Stream, StreamExt};
 use owo_colors::OwoColorize;
+use std::any::Any;
+use std::fmt;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::task::Poll;
+use std::time::Instant;
 
-pub async fn run(source: Option<String>, watch: bool) -> Result<(), Box<dyn std::error::Error>> {
+/// Adapter options for a one-shot ingest
+#[derive(Debug, Clone, Default)]
+pub struct IngestOptions {
+    /// Record local image/file attachments (opencode, crush)
+    pub attachments: bool,
+    /// Record session file line numbers (claude)
+    pub source_lines: bool,
+    /// Crush database path globs to include
+    pub include: Vec<String>,
+    /// Crush database path globs to exclude
+    pub exclude: Vec<String>,
+    /// Abort a source's ingest on its first failed session
+    pub fail_fast: bool,
+    /// Event kind rewrites applied after parsing
+    pub kind_remap: KindRemap,
+    /// Single session file to ingest instead of discovering sessions (claude)
+    pub path: Option<PathBuf>,
+    /// Store event raw payloads zstd-compressed
+    pub compress_raw: bool,
+    /// Re-ingest session files (and Crush sessions) even when unchanged since the last ingest
+    pub force: bool,
+    /// Sessions parsed at once; inserts still happen one at a time
+    pub concurrency: usize,
+    /// Discover and parse sessions but write nothing to the database
+    pub dry_run: bool,
+}
+
+impl IngestOptions {
+    /// The options that change what gets stored for a session file, recorded with its ingest stamp
+    /// so changing any of them re-ingests files that are otherwise unchanged
+    pub fn fingerprint(&self) -> String {
+        format!(
+            "remap-kind={};compress-raw={};source-lines={};attachments={}",
+            self.kind_remap.fingerprint(),
+            self.compress_raw,
+            self.source_lines,
+            self.attachments
+        )
+    }
+}
+
+/// Default `--concurrency`: one parse per available CPU
+pub fn default_concurrency() -> usize {
+    std::thread::available_parallelism().map_or(1, std::num::NonZero::get)
+}
+
+/// Run `parse` over `items` with up to `concurrency` parses in flight, yielding results as they
+/// finish. Parsing is independent file and process I/O, so only the database writes that
+/// consume the stream need to stay serialized.
+fn parse_concurrently<T, F, Fut>(items: Vec<T>, concurrency: usize, parse: F) -> impl Stream<Item = Fut::Output>
+where
+    F: FnMut(T) -> Fut,
+    Fut: Future,
+{
+    stream::iter(items).map(parse).buffer_unordered(concurrency.max(1))
+}
+
+/// Sessions imported and failed by an ingest pass
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct IngestCounts {
+    imported: usize,
+    failed: usize,
+    causes: FailureCauses,
+    /// Events in the imported sessions
+    events: usize,
+}
+
+impl IngestCounts {
+    fn add(&mut self, other: IngestCounts) {
+        self.imported += other.imported;
+        self.failed += other.failed;
+        self.causes.add(other.causes);
+        self.events += other.events;
+    }
+}
+
+/// Failed sessions broken down by what went wrong
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct FailureCauses {
+    io: usize,
+    parse: usize,
+    schema: usize,
+    empty: usize,
+    /// Parsed but couldn't be stored
+    database: usize,
+    /// Adapter panics and anything else that isn't an [`AdapterError`]
+    other: usize,
+}
+
+impl FailureCauses {
+    /// Count a session that failed to parse
+    fn record(&mut self, err: &(dyn std::error::Error + 'static)) {
+        match err.downcast_ref::<AdapterError>() {
+            Some(AdapterError::Io(_)) => self.io += 1,
+            Some(AdapterError::Parse { .. }) => self.parse += 1,
+            Some(AdapterError::Schema(_)) => self.schema += 1,
+            Some(AdapterError::Empty) => self.empty += 1,
+            None => self.other += 1,
+        }
+    }
+
+    fn add(&mut self, other: FailureCauses) {
+        self.io += other.io;
+        self.parse += other.parse;
+        self.schema += other.schema;
+        self.empty += other.empty;
+        self.database += other.database;
+        self.other += other.other;
+    }
+}
+
+/// e.g. `3 parse errors, 1 IO error`
+impl fmt::Display for FailureCauses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let causes: Vec<String> = [
+            (self.parse, "parse error"),
+            (self.io, "IO error"),
+            (self.schema, "schema error"),
+            (self.empty, "empty session"),
+            (self.database, "database error"),
+            (self.other, "other error"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{} {}{}", count, label, if count == 1 { "" } else { "s" }))
+        .collect();
+        write!(f, "{}", causes.join(", "))
+    }
+}
+
+/// Print the failure count with its causes, if anything failed
+fn print_failed(failed: usize, causes: &FailureCauses) {
+    if failed > 0 {
+        println!(
+            "  {} Failed: {} {}",
+            "✗".red(),
+            failed.to_string().bold(),
+            format!("({})", causes).dimmed()
+        );
+    }
+}
+
+/// Run a session parse, turning an adapter panic into a parse error so one malformed session
+/// is counted as failed instead of aborting the whole ingest
+async fn parse_guarded<T>(
+    parse: impl Future<Output = Result<T, AdapterError>>,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    let mut parse = std::pin::pin!(parse);
+    std::future::poll_fn(
+        |cx| match std::panic::catch_unwind(AssertUnwindSafe(|| parse.as_mut().poll(cx))) {
+            Ok(poll) => poll.map_err(Into::into),
+            Err(panic) => Poll::Ready(Err(
+                format!("adapter panicked: {}", panic_message(panic.as_ref())).into()
+            )),
+        },
+    )
+    .await
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Aggregated result of one ingest pass over several sources
+#[derive(Debug, Default)]
+struct OncePassSummary {
+    totals: IngestCounts,
+    /// Sources whose ingest was aborted, with the reason
+    errors: Vec<(Source, String)>,
+}
+
+/// Parse `FROM=TO` remap rules, e.g. `tool_result=system` or `reasoning=drop`
+pub fn parse_kind_remap(specs: &[String]) -> Result<KindRemap, String> {
+    let rules = specs
+        .iter()
+        .map(|spec| {
+            spec.split_once('=')
+                .map(|(from, to)| (from.trim().to_string(), to.trim().to_string()))
+                .ok_or_else(|| format!("Invalid kind remap: {}. Use FROM=TO", spec))
+        })
+        .collect::<Result<_, _>>()?;
+    KindRemap::new(&rules)
+}
+
+/// Sessions attempted before the failure-rate guard can abort an ingest
+const FAILURE_GUARD_MIN_SESSIONS: usize = 20;
+/// Share of failed sessions above which an ingest is aborted
+const FAILURE_GUARD_MAX_RATE: f64 = 0.5;
+
+/// Stop ingesting a source once failures show it is systematically unreadable,
+/// or on the first failure with `fail_fast`.
+fn check_failure_budget(source: Source, fail_fast: bool, attempted: usize, failed: usize) -> Result<(), String> {
+    if failed == 0 {
+        return Ok(());
+    }
+    if fail_fast {
+        return Err(format!(
+            "Aborting {} ingest after the first failed session (--fail-fast)",
+            source
+        ));
+    }
+    if attempted >= FAILURE_GUARD_MIN_SESSIONS && failed as f64 / attempted as f64 > FAILURE_GUARD_MAX_RATE {
+        return Err(format!(
+            "Aborting {} ingest: {} of {} sessions failed (over {:.0}%); the source format may have changed",
+            source,
+            failed,
+            attempted,
+            FAILURE_GUARD_MAX_RATE * 100.0
+        ));
+    }
+    Ok(())
+}
+
+pub async fn run(
+    source: Option<String>, watch: bool, once: bool, options: IngestOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if watch && once {
+        return run_once(source, &options).await;
+    }
     if watch {
         return run_watch_mode(source).await;
     }
 
-    let db = Database::open_default().await?;
+    let db = Database::open_default()
+        .await?
+        .with_compressed_payloads(options.compress_raw);
     db.migrate().await?;
 
+    let source = match source {
+        Some(src) => Some(Source::from_str(&src)?),
+        None => {
+            let detected = detect_source_in_dir(&std::env::current_dir()?);
+            if let Some(source) = detected {
+                log::info!("Detected {} markers in current directory", source);
+                println!(
+                    "{} {} {}",
+                    "Detected:".bold(),
+                    source.to_string().cyan(),
+                    "(from current directory)".dimmed()
+                );
+            }
+            detected
+        }
+    };
+
+    if options.path.is_some() && source != Some(Source::Claude) {
+        return Err("--path is only supported with --source claude".into());
+    }
+
     match source {
-        Some(src) => {
-            let source = Source::from_str(&src)?;
+        Some(source) => {
             log::info!("Ingesting from source: {}", source);
-            println!("{} {}", "Ingesting from:".bold(), src.cyan());
+            println!("{} {}", "Ingesting from:".bold(), source.to_string().cyan());
 
-            match source {
-                Source::Claude => ingest_claude(&db).await?,
-                Source::Codex => ingest_codex(&db).await?,
-                Source::OpenCode => ingest_opencode(&db).await?,
-                Source::Crush => ingest_crush(&db).await?,
+            let started = Instant::now();
+            ingest_source(&db, source, &options).await?;
+            print_duration(started);
+            if options.dry_run {
+                println!("  {} {}", "→".dimmed(), "Dry run: nothing was written".yellow());
             }
         }
         None => {
@@ -36,15 +298,98 @@ pub async fn run(source: Option<String>, watch: bool) -> Result<(), Box<dyn std:
             println!("  {}     - Codex CLI rollouts", "codex".green());
             println!("  {}  - OpenCode logs", "opencode".green());
             println!("  {}     - Crush database", "crush".green());
+            println!("  {}    - Gemini CLI chats", "gemini".green());
             println!();
             println!("{}", "Options:".bold());
-            println!("  {}   Continuously watch for new sessions", "--watch".cyan());
+            println!("  {}         Continuously watch for new sessions", "--watch".cyan());
+            println!("  {}  Ingest every source once and exit", "--watch --once".cyan());
+            println!("  {}   Record local image/file attachments", "--attachments".cyan());
+            println!("  {}  Record session file line numbers", "--source-lines".cyan());
+            println!("  {}  Only scan matching Crush paths", "--include <GLOB>".cyan());
+            println!("  {}  Skip matching Crush paths", "--exclude <GLOB>".cyan());
+            println!("  {}     Stop at the first failed session", "--fail-fast".cyan());
+            println!("  {}  Remap or drop event kinds", "--remap-kind <FROM=TO>".cyan());
+            println!("  {}  Ingest one Claude session file", "--path <FILE>".cyan());
+            println!("  {}         Re-ingest sessions unchanged since the last run", "--force".cyan());
+            println!("  {}  Parse N sessions at once (default: CPUs)", "--concurrency <N>".cyan());
+            println!("  {}       Count what would be imported without writing", "--dry-run".cyan());
         }
     }
 
     Ok(())
 }
 
+/// Discover and ingest every session of one source
+async fn ingest_source(
+    db: &Database, source: Source, options: &IngestOptions,
+) -> Result<IngestCounts, Box<dyn std::error::Error>> {
+    match source {
+        Source::Claude => ingest_claude(db, options).await,
+        Source::Codex => ingest_codex(db, options).await,
+        Source::OpenCode => ingest_opencode(db, options).await,
+        Source::Crush => ingest_crush(db, options).await,
+        Source::Gemini => ingest_gemini(db, options).await,
+    }
+}
+
+/// Run a single discovery and ingest pass over every source (or just `source`) and exit,
+/// for cron-driven setups that don't want the long-lived watcher.
+async fn run_once(source: Option<String>, options: &IngestOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default()
+        .await?
+        .with_compressed_payloads(options.compress_raw);
+    db.migrate().await?;
+
+    let sources = match source {
+        Some(src) => vec![Source::from_str(&src)?],
+        None => Source::all().to_vec(),
+    };
+
+    let db = &db;
+    let started = Instant::now();
+    let summary = ingest_once(&sources, move |source| {
+        println!();
+        println!("{} {}", "Ingesting from:".bold(), source.to_string().cyan());
+        ingest_source(db, source, options)
+    })
+    .await;
+
+    println!();
+    println!("{}", "Sync complete".bold().underline());
+    println!("  {} Sources: {}", "→".dimmed(), sources.len().to_string().bold());
+    print_imported(summary.totals.imported, summary.totals.events, false);
+    print_failed(summary.totals.failed, &summary.totals.causes);
+    print_duration(started);
+    for (source, err) in &summary.errors {
+        println!("  {} {}: {}", "✗".red(), source.to_string().cyan(), err.dimmed());
+    }
+
+    if !summary.errors.is_empty() {
+        return Err(format!("{} of {} sources failed to ingest", summary.errors.len(), sources.len()).into());
+    }
+    Ok(())
+}
+
+/// Ingest each source exactly once, summing their counts. A source that aborts is
+/// recorded and skipped so the remaining sources still run.
+async fn ingest_once<F, Fut>(sources: &[Source], mut ingest: F) -> OncePassSummary
+where
+    F: FnMut(Source) -> Fut,
+    Fut: std::future::Future<Output = Result<IngestCounts, Box<dyn std::error::Error>>>,
+{
+    let mut summary = OncePassSummary::default();
+    for &source in sources {
+        match ingest(source).await {
+            Ok(counts) => summary.totals.add(counts),
+            Err(e) => {
+                log::error!("Ingest of {} failed: {}", source, e);
+                summary.errors.push((source, e.to_string()));
+            }
+        }
+    }
+    summary
+}
+
 async fn run_watch_mode(source: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", "Watch Mode".bold().underline());
     println!();
@@ -87,8 +432,18 @@ async fn run_watch_mode(source: Option<String>) -> Result<(), Box<dyn std::error
     Ok(())
 }
 
-async fn ingest_claude(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
-    let adapter = ClaudeAdapter::new();
+async fn ingest_claude(db: &Database, options: &IngestOptions) -> Result<IngestCounts, Box<dyn std::error::Error>> {
+    let adapter = ClaudeAdapter::new().with_source_lines(options.source_lines);
+    ingest_claude_with(db, &adapter, options).await
+}
+
+/// Ingest the Claude sessions `adapter` discovers, or just `options.path` when set
+async fn ingest_claude_with(
+    db: &Database, adapter: &ClaudeAdapter, options: &IngestOptions,
+) -> Result<IngestCounts, Box<dyn std::error::Error>> {
+    if let Some(path) = &options.path {
+        return ingest_claude_file(db, adapter, path, options).await;
+    }
 
     println!("  {} Discovering sessions...", "→".dimmed());
     let sessions = adapter.discover_sessions().await;
@@ -97,7 +452,7 @@ async fn ingest_claude(db: &Database) -> Result<(), Box<dyn std::error::Error>>
         println!("  {} No Claude Code sessions found", "✗".red());
         println!();
         println!("{}", "Make sure Claude Code is installed and has sessions.".dimmed());
-        return Ok(());
+        return Ok(IngestCounts::default());
     }
 
     println!(
@@ -107,43 +462,267 @@ async fn ingest_claude(db: &Database) -> Result<(), Box<dyn std::error::Error>>
     );
     println!();
 
+    let parse = |session_file: ClaudeSessionFile| async move { adapter.parse_session(&session_file).await };
+    ingest_session_files(db, Source::Claude, sessions, parse, options).await
+}
+
+/// A discovered file holding exactly one session
+trait SessionFile: Clone {
+    fn path(&self) -> &Path;
+    fn session_id(&self) -> &str;
+}
+
+impl SessionFile for ClaudeSessionFile {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn session_id(&self) -> &str {
+        &self.session_id
+    }
+}
+
+impl SessionFile for CodexSessionFile {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn session_id(&self) -> &str {
+        &self.session_id
+    }
+}
+
+impl SessionFile for GeminiSessionFile {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn session_id(&self) -> &str {
+        &self.session_id
+    }
+}
+
+/// Parse and store a source's discovered session files with `parse`, skipping files unchanged
+/// since the last ingest
+async fn ingest_session_files<F, P, Fut>(
+    db: &Database, source: Source, sessions: Vec<F>, parse: P, options: &IngestOptions,
+) -> Result<IngestCounts, Box<dyn std::error::Error>>
+where
+    F: SessionFile,
+    P: Fn(F) -> Fut,
+    Fut: Future<Output = Result<(Session, Vec<Event>), AdapterError>>,
+{
     let mut imported = 0;
+    let mut event_count = 0;
     let mut failed = 0;
+    let mut causes = FailureCauses::default();
+    let mut unchanged = 0;
 
+    let mut pending = Vec::new();
     for session_file in sessions {
-        print!("  {} {} ... ", "→".dimmed(), session_file.session_id.cyan());
+        let stamp = file_stamp(session_file.path());
+        if !options.force && file_unchanged(db, source, session_file.path(), stamp, options).await {
+            unchanged += 1;
+            continue;
+        }
+        pending.push((session_file, stamp));
+    }
 
-        match adapter.parse_session(&session_file).await {
-            Ok((session, events)) => match db.insert_session_with_events(&session, &events).await {
+    let mut parsed = parse_concurrently(pending, options.concurrency, |(session_file, stamp)| {
+        let parsing = parse(session_file.clone());
+        async move { (session_file, stamp, parse_guarded(parsing).await) }
+    });
+
+    while let Some((session_file, stamp, result)) = parsed.next().await {
+        print!("  {} {} ... ", "→".dimmed(), session_file.session_id().cyan());
+
+        match result.map(|(session, events)| (session, options.kind_remap.apply(events))) {
+            Ok((session, events)) => match store_session(db, &session, &events, options).await {
                 Ok(_) => {
                     println!("{} ({} events)", "✓".green(), events.len().to_string().dimmed());
                     imported += 1;
+                    event_count += events.len();
+                    if !options.dry_run {
+                        record_ingested_file(db, source, session_file.path(), stamp, options).await;
+                    }
                 }
                 Err(e) => {
                     println!("{} {}", "✗".red(), e.to_string().dimmed());
                     log::error!("Failed to insert session {}: {}", session.external_id, e);
+                    causes.database += 1;
                     failed += 1;
                 }
             },
             Err(e) => {
                 println!("{} {}", "✗".red(), e.to_string().dimmed());
-                log::error!("Failed to parse session {:?}: {}", session_file.path, e);
+                log::error!("Failed to parse session {:?}: {}", session_file.path(), e);
+                causes.record(e.as_ref());
                 failed += 1;
             }
         }
+
+        check_failure_budget(source, options.fail_fast, imported + failed, failed)?;
     }
 
     println!();
     println!("{}", "Ingest complete".bold().underline());
-    println!("  {} Imported: {}", "✓".green(), imported.to_string().bold());
-    if failed > 0 {
-        println!("  {} Failed: {}", "✗".red(), failed.to_string().bold());
+    print_imported(imported, event_count, options.dry_run);
+    print_failed(failed, &causes);
+    print_unchanged(unchanged);
+
+    Ok(IngestCounts { imported, failed, causes, events: event_count })
+}
+
+/// Modification time (ms since the epoch) and size of a session file
+fn file_stamp(path: &Path) -> Option<(i64, i64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    let modified = meta.modified().ok()?.duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some((modified.as_millis() as i64, meta.len() as i64))
+}
+
+/// Whether a session file has the same modification time and size as when it was last ingested,
+/// under the same ingest options
+async fn file_unchanged(
+    db: &Database, source: Source, path: &Path, stamp: Option<(i64, i64)>, options: &IngestOptions,
+) -> bool {
+    let Some((mtime, size)) = stamp else {
+        return false;
+    };
+    match db
+        .should_reingest(
+            &source.to_string(),
+            &path.to_string_lossy(),
+            mtime,
+            size,
+            &options.fingerprint(),
+        )
+        .await
+    {
+        Ok(reingest) => !reingest,
+        Err(e) => {
+            log::warn!("Could not check ingest cache for {}: {}", path.display(), e);
+            false
+        }
     }
+}
 
-    Ok(())
+/// Remember an ingested session file so the next run can skip it while it and the options stay
+/// unchanged
+async fn record_ingested_file(
+    db: &Database, source: Source, path: &Path, stamp: Option<(i64, i64)>, options: &IngestOptions,
+) {
+    let Some((mtime, size)) = stamp else {
+        return;
+    };
+    if let Err(e) = db
+        .upsert_ingested_file(
+            &source.to_string(),
+            &path.to_string_lossy(),
+            mtime,
+            size,
+            &options.fingerprint(),
+        )
+        .await
+    {
+        log::warn!("Could not record ingested file {}: {}", path.display(), e);
+    }
+}
+
+/// Ingest stamp key for a session read from a shared database (like Crush), which has no file of
+/// its own: `<db_path>#<session_id>`
+fn database_session_key(db_path: &Path, session_id: &str) -> PathBuf {
+    PathBuf::from(format!("{}#{}", db_path.to_string_lossy(), session_id))
+}
+
+/// Stamp for a session from a shared database: its `updated_at` stands in for a file's mtime
+fn database_session_stamp(session: &Session) -> Option<(i64, i64)> {
+    Some((session.updated_at.timestamp_millis(), 0))
+}
+
+/// Whether a session (from a source without per-session files, like Crush) is already stored with
+/// the same `updated_at`, ingested under the same options
+async fn session_unchanged(db: &Database, key: &Path, session: &Session, options: &IngestOptions) -> bool {
+    let external_id = ExternalId::from(session.external_id.as_str());
+    let stored = match db
+        .get_session_id_by_external(&session.source.to_string(), &external_id)
+        .await
+    {
+        Ok(Some(id)) => matches!(
+            db.get_session(id).await,
+            Ok(Some(row)) if row.updated_at == session.updated_at.to_rfc3339()
+        ),
+        _ => false,
+    };
+    stored && file_unchanged(db, session.source, key, database_session_stamp(session), options).await
 }
 
-async fn ingest_codex(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+/// Store a parsed session, or with `--dry-run` leave the database untouched
+async fn store_session(
+    db: &Database, session: &Session, events: &[Event], options: &IngestOptions,
+) -> Result<(), tokio_rusqlite::Error> {
+    if options.dry_run {
+        return Ok(());
+    }
+    db.insert_session_with_events(session, events).await.map(|_| ())
+}
+
+/// Report the sessions imported and their events, or what would be imported with `--dry-run`
+fn print_imported(imported: usize, events: usize, dry_run: bool) {
+    let label = if dry_run { "Would import" } else { "Imported" };
+    println!(
+        "  {} {}: {} {}",
+        "✓".green(),
+        label,
+        imported.to_string().bold(),
+        format!("({} events)", events).dimmed()
+    );
+}
+
+/// Report how long an ingest took
+fn print_duration(started: Instant) {
+    println!("  {} Duration: {:.2?}", "→".dimmed(), started.elapsed());
+}
+
+/// Report the sessions skipped because they were unchanged since the last ingest
+fn print_unchanged(unchanged: usize) {
+    if unchanged > 0 {
+        println!(
+            "  {} Unchanged: {} {}",
+            "·".dimmed(),
+            unchanged.to_string().bold(),
+            "(use --force to re-ingest)".dimmed()
+        );
+    }
+}
+
+/// Ingest one Claude session file given by path, e.g. a file moved out of `~/.claude/projects`
+async fn ingest_claude_file(
+    db: &Database, adapter: &ClaudeAdapter, path: &Path, options: &IngestOptions,
+) -> Result<IngestCounts, Box<dyn std::error::Error>> {
+    if !path.is_file() {
+        return Err(AgentVizError::NotFound(format!("Session file not found: {}", path.display())).into());
+    }
+
+    print!("  {} {} ... ", "→".dimmed(), path.display().to_string().cyan());
+    let (session, events) = match parse_guarded(adapter.parse_file(path, None)).await {
+        Ok((session, events)) => (session, options.kind_remap.apply(events)),
+        Err(e) => {
+            println!("{} {}", "✗".red(), e.to_string().dimmed());
+            return Err(format!("Failed to parse session {}: {}", path.display(), e).into());
+        }
+    };
+    store_session(db, &session, &events, options).await?;
+    println!("{} ({} events)", "✓".green(), events.len().to_string().dimmed());
+
+    println!();
+    println!("{}", "Ingest complete".bold().underline());
+    let label = if options.dry_run { "Would import" } else { "Imported" };
+    println!("  {} {}: {}", "✓".green(), label, session.external_id.bold());
+
+    Ok(IngestCounts { imported: 1, events: events.len(), ..IngestCounts::default() })
+}
+
+async fn ingest_codex(db: &Database, options: &IngestOptions) -> Result<IngestCounts, Box<dyn std::error::Error>> {
     let adapter = CodexAdapter::new();
 
     println!("  {} Discovering sessions...", "→".dimmed());
@@ -157,7 +736,7 @@ async fn ingest_codex(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
             "{}",
             "Sessions should be in $CODEX_HOME/sessions/ or ~/.codex/sessions/".dimmed()
         );
-        return Ok(());
+        return Ok(IngestCounts::default());
     }
 
     println!(
@@ -167,50 +746,48 @@ async fn ingest_codex(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
     );
     println!();
 
-    let mut imported = 0;
-    let mut failed = 0;
+    let adapter = &adapter;
+    let parse = move |session_file: CodexSessionFile| async move { adapter.parse_session(&session_file).await };
+    ingest_session_files(db, Source::Codex, sessions, parse, options).await
+}
 
-    for session_file in sessions {
-        print!("  {} {} ... ", "→".dimmed(), session_file.session_id.cyan());
+async fn ingest_gemini(db: &Database, options: &IngestOptions) -> Result<IngestCounts, Box<dyn std::error::Error>> {
+    let adapter = GeminiAdapter::new();
 
-        match adapter.parse_session(&session_file).await {
-            Ok((session, events)) => match db.insert_session_with_events(&session, &events).await {
-                Ok(_) => {
-                    println!("{} ({} events)", "✓".green(), events.len().to_string().dimmed());
-                    imported += 1;
-                }
-                Err(e) => {
-                    println!("{} {}", "✗".red(), e.to_string().dimmed());
-                    log::error!("Failed to insert session {}: {}", session.external_id, e);
-                    failed += 1;
-                }
-            },
-            Err(e) => {
-                println!("{} {}", "✗".red(), e.to_string().dimmed());
-                log::error!("Failed to parse session {:?}: {}", session_file.path, e);
-                failed += 1;
-            }
-        }
+    println!("  {} Discovering sessions...", "→".dimmed());
+    let sessions = adapter.discover_sessions().await;
+
+    if sessions.is_empty() {
+        println!("  {} No Gemini chats found", "✗".red());
+        println!();
+        println!(
+            "{}",
+            "Make sure Gemini CLI is installed and has saved sessions.".dimmed()
+        );
+        println!("{}", "Sessions should be in ~/.gemini/tmp/<project>/chats/".dimmed());
+        return Ok(IngestCounts::default());
     }
 
+    println!(
+        "  {} Found {} chat files",
+        "✓".green(),
+        sessions.len().to_string().bold()
+    );
     println!();
-    println!("{}", "Ingest complete".bold().underline());
-    println!("  {} Imported: {}", "✓".green(), imported.to_string().bold());
-    if failed > 0 {
-        println!("  {} Failed: {}", "✗".red(), failed.to_string().bold());
-    }
 
-    Ok(())
+    let adapter = &adapter;
+    let parse = move |session_file: GeminiSessionFile| async move { adapter.parse_session(&session_file).await };
+    ingest_session_files(db, Source::Gemini, sessions, parse, options).await
 }
 
-async fn ingest_opencode(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
-    let adapter = OpenCodeAdapter::new();
+async fn ingest_opencode(db: &Database, options: &IngestOptions) -> Result<IngestCounts, Box<dyn std::error::Error>> {
+    let adapter = OpenCodeAdapter::new().with_attachments(options.attachments);
 
     if !adapter.is_available() {
         println!("  {} OpenCode CLI not found", "✗".red());
         println!();
         println!("{}", "Make sure OpenCode is installed and in PATH.".dimmed());
-        return Ok(());
+        return Ok(IngestCounts::default());
     }
 
     println!("  {} Discovering sessions...", "→".dimmed());
@@ -220,7 +797,7 @@ async fn ingest_opencode(db: &Database) -> Result<(), Box<dyn std::error::Error>
         println!("  {} No OpenCode sessions found", "✗".red());
         println!();
         println!("{}", "Make sure OpenCode has sessions.".dimmed());
-        return Ok(());
+        return Ok(IngestCounts::default());
     }
 
     println!("  {} Found {} sessions", "✓".green(), sessions.len().to_string().bold());
@@ -237,47 +814,57 @@ async fn ingest_opencode(db: &Database) -> Result<(), Box<dyn std::error::Error>
     }
 
     let mut imported = 0;
+    let mut event_count = 0;
     let mut failed = 0;
+    let mut causes = FailureCauses::default();
 
-    for session in sessions {
-        print!(
-            "  {} {} ... ",
-            "→".dimmed(),
-            session.title.chars().take(50).collect::<String>().cyan()
-        );
+    let adapter = &adapter;
+    let mut parsed = parse_concurrently(sessions, options.concurrency, move |session| async move {
+        let result = parse_guarded(adapter.parse_session(&session)).await;
+        (session, result)
+    });
+
+    while let Some((session, result)) = parsed.next().await {
+        print!("  {} {} ... ", "→".dimmed(), truncate_chars(&session.title, 50).cyan());
 
-        match adapter.parse_session(&session).await {
-            Ok((session_obj, events)) => match db.insert_session_with_events(&session_obj, &events).await {
+        match result.map(|(session, events)| (session, options.kind_remap.apply(events))) {
+            Ok((session_obj, events)) => match store_session(db, &session_obj, &events, options).await {
                 Ok(_) => {
                     println!("{} ({} events)", "✓".green(), events.len().to_string().dimmed());
                     imported += 1;
+                    event_count += events.len();
                 }
                 Err(e) => {
                     println!("{} {}", "✗".red(), e.to_string().dimmed());
                     log::error!("Failed to insert session {}: {}", session_obj.external_id, e);
+                    causes.database += 1;
                     failed += 1;
                 }
             },
             Err(e) => {
                 println!("{} {}", "✗".red(), e.to_string().dimmed());
                 log::error!("Failed to parse session {}: {}", session.id, e);
+                causes.record(e.as_ref());
                 failed += 1;
             }
         }
+
+        check_failure_budget(Source::OpenCode, options.fail_fast, imported + failed, failed)?;
     }
 
     println!();
     println!("{}", "Ingest complete".bold().underline());
-    println!("  {} Imported: {}", "✓".green(), imported.to_string().bold());
-    if failed > 0 {
-        println!("  {} Failed: {}", "✗".red(), failed.to_string().bold());
-    }
+    print_imported(imported, event_count, options.dry_run);
+    print_failed(failed, &causes);
 
-    Ok(())
+    Ok(IngestCounts { imported, failed, causes, events: event_count })
 }
 
-async fn ingest_crush(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
-    let adapter = CrushAdapter::new();
+async fn ingest_crush(db: &Database, options: &IngestOptions) -> Result<IngestCounts, Box<dyn std::error::Error>> {
+    let filter = DiscoveryFilter::new(&options.include, &options.exclude)?;
+    let adapter = CrushAdapter::new()
+        .with_attachments(options.attachments)
+        .with_discovery_filter(filter);
 
     println!("  {} Discovering sessions...", "→".dimmed());
     let sessions = adapter.discover_sessions().await;
@@ -290,44 +877,326 @@ async fn ingest_crush(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
             "{}",
             "Sessions should be in ~/.crush/crush.db or ./.crush/crush.db".dimmed()
         );
-        return Ok(());
+        return Ok(IngestCounts::default());
     }
 
     println!("  {} Found {} sessions", "✓".green(), sessions.len().to_string().bold());
     println!();
 
     let mut imported = 0;
+    let mut event_count = 0;
     let mut failed = 0;
+    let mut causes = FailureCauses::default();
+    let mut unchanged = 0;
 
-    for session_file in sessions {
+    let adapter = &adapter;
+    let mut parsed = parse_concurrently(sessions, options.concurrency, move |session_file| async move {
+        let result = parse_guarded(adapter.parse_session(&session_file)).await;
+        (session_file, result)
+    });
+
+    while let Some((session_file, result)) = parsed.next().await {
         print!("  {} {} ... ", "→".dimmed(), session_file.session_id.cyan());
+        let key = database_session_key(&session_file.path, &session_file.session_id);
 
-        match adapter.parse_session(&session_file).await {
-            Ok((session, events)) => match db.insert_session_with_events(&session, &events).await {
+        match result.map(|(session, events)| (session, options.kind_remap.apply(events))) {
+            Ok((session, _)) if !options.force && session_unchanged(db, &key, &session, options).await => {
+                println!("{}", "unchanged".dimmed());
+                unchanged += 1;
+            }
+            Ok((session, events)) => match store_session(db, &session, &events, options).await {
                 Ok(_) => {
                     println!("{} ({} events)", "✓".green(), events.len().to_string().dimmed());
                     imported += 1;
+                    event_count += events.len();
+                    if !options.dry_run {
+                        record_ingested_file(db, Source::Crush, &key, database_session_stamp(&session), options).await;
+                    }
                 }
                 Err(e) => {
                     println!("{} {}", "✗".red(), e.to_string().dimmed());
                     log::error!("Failed to insert session {}: {}", session.external_id, e);
+                    causes.database += 1;
                     failed += 1;
                 }
             },
             Err(e) => {
                 println!("{} {}", "✗".red(), e.to_string().dimmed());
                 log::error!("Failed to parse session {:?}: {}", session_file.path, e);
+                causes.record(e.as_ref());
                 failed += 1;
             }
         }
+
+        check_failure_budget(Source::Crush, options.fail_fast, imported + failed, failed)?;
     }
 
     println!();
     println!("{}", "Ingest complete".bold().underline());
-    println!("  {} Imported: {}", "✓".green(), imported.to_string().bold());
-    if failed > 0 {
-        println!("  {} Failed: {}", "✗".red(), failed.to_string().bold());
+    print_imported(imported, event_count, options.dry_run);
+    print_failed(failed, &causes);
+    print_unchanged(unchanged);
+
+    Ok(IngestCounts { imported, failed, causes, events: event_count })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_v_core::EventKind;
+    use chrono::Utc;
+    use std::collections::HashSet;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_kind_remap_changes_stored_kind() {
+        let db = Database::open_in_memory().await.unwrap();
+
+        let remap = parse_kind_remap(&["tool_result=system".to_string()]).unwrap();
+        let session = Session {
+            id: Uuid::new_v4(),
+            source: Source::Codex,
+            external_id: "remap-1".to_string(),
+            project: None,
+            title: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_payload: serde_json::json!({}),
+        };
+        let events = vec![Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::ToolResult,
+            role: None,
+            content: Some("ok".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        }];
+        db.insert_session_with_events(&session, &remap.apply(events))
+            .await
+            .unwrap();
+
+        let stored = db.get_session_events(session.id.into()).await.unwrap();
+        assert_eq!(stored[0].kind, "system");
+
+        assert!(parse_kind_remap(&["tool_result".to_string()]).is_err());
+        assert!(parse_kind_remap(&["tool_result=bogus".to_string()]).is_err());
     }
 
-    Ok(())
+    #[test]
+    fn test_failure_budget_aborts_when_every_session_fails() {
+        let aborted_at =
+            (1..=100).find(|&attempted| check_failure_budget(Source::Claude, false, attempted, attempted).is_err());
+        assert_eq!(aborted_at, Some(FAILURE_GUARD_MIN_SESSIONS));
+
+        let err = check_failure_budget(Source::Claude, false, 20, 20).unwrap_err();
+        assert!(err.contains("claude"));
+        assert!(err.contains("20 of 20 sessions failed"));
+    }
+
+    #[test]
+    fn test_failure_budget_tolerates_occasional_failures() {
+        assert!(check_failure_budget(Source::Codex, false, 100, 10).is_ok());
+        assert!(check_failure_budget(Source::Codex, false, 10, 10).is_ok());
+        assert!(check_failure_budget(Source::Codex, true, 10, 0).is_ok());
+        assert!(check_failure_budget(Source::Codex, true, 10, 1).is_err());
+    }
+
+    #[test]
+    fn test_failure_causes_summarize_adapter_errors() {
+        let mut causes = FailureCauses::default();
+        let json_error = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let errors: Vec<Box<dyn std::error::Error + Send + Sync>> = vec![
+            Box::new(AdapterError::from(json_error)),
+            Box::new(AdapterError::Parse { line: 3, source: serde_json::from_str::<u8>("x").unwrap_err() }),
+            Box::new(AdapterError::Io(std::io::Error::from(std::io::ErrorKind::NotFound))),
+            Box::new(AdapterError::Schema("no such table: sessions".to_string())),
+            Box::new(AdapterError::Empty),
+            Box::from("adapter panicked: index out of range"),
+        ];
+        for err in &errors {
+            causes.record(err.as_ref());
+        }
+
+        assert_eq!(
+            causes.to_string(),
+            "2 parse errors, 1 IO error, 1 schema error, 1 empty session, 1 other error"
+        );
+        assert_eq!(FailureCauses::default().to_string(), "");
+    }
+
+    #[tokio::test]
+    async fn test_once_pass_visits_each_source_once_and_sums_counts() {
+        let parse_failure = FailureCauses { parse: 1, ..FailureCauses::default() };
+        let mut visited = Vec::new();
+        let summary = ingest_once(Source::all(), |source| {
+            visited.push(source);
+            async move {
+                match source {
+                    Source::Claude => {
+                        Ok(IngestCounts { imported: 3, failed: 1, causes: parse_failure, ..IngestCounts::default() })
+                    }
+                    Source::Codex => Ok(IngestCounts { imported: 2, ..IngestCounts::default() }),
+                    Source::OpenCode => Err(Box::<dyn std::error::Error>::from("OpenCode CLI crashed")),
+                    Source::Crush | Source::Gemini => Ok(IngestCounts::default()),
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(visited, Source::all().to_vec());
+        assert_eq!(
+            summary.totals,
+            IngestCounts { imported: 5, failed: 1, causes: parse_failure, ..IngestCounts::default() }
+        );
+        assert_eq!(summary.errors.len(), 1);
+        assert_eq!(summary.errors[0].0, Source::OpenCode);
+        assert!(summary.errors[0].1.contains("crashed"));
+    }
+
+    #[tokio::test]
+    async fn test_session_unchanged_compares_updated_at_and_options() {
+        let db = Database::open_in_memory().await.unwrap();
+        let key = database_session_key(Path::new("/repo/.crush/crush.db"), "crush-1");
+        let options = IngestOptions::default();
+
+        let mut session = Session {
+            id: Uuid::new_v4(),
+            source: Source::Crush,
+            external_id: "crush-1".to_string(),
+            project: None,
+            title: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_payload: serde_json::json!({}),
+        };
+        assert_eq!(key, PathBuf::from("/repo/.crush/crush.db#crush-1"));
+        assert!(!session_unchanged(&db, &key, &session, &options).await);
+
+        db.insert_session_with_events(&session, &[]).await.unwrap();
+        assert!(!session_unchanged(&db, &key, &session, &options).await);
+        record_ingested_file(&db, Source::Crush, &key, database_session_stamp(&session), &options).await;
+        assert!(session_unchanged(&db, &key, &session, &options).await);
+
+        let with_attachments = IngestOptions { attachments: true, ..IngestOptions::default() };
+        assert!(!session_unchanged(&db, &key, &session, &with_attachments).await);
+
+        session.updated_at += chrono::Duration::seconds(5);
+        assert!(!session_unchanged(&db, &key, &session, &options).await);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_counts_a_session_without_storing_it() {
+        let db = Database::open_in_memory().await.unwrap();
+        let path = std::env::temp_dir().join(format!("agent-viz-dry-run-{}.jsonl", Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"type":"user","uuid":"u1","timestamp":"2024-01-01T00:00:00Z","content":"list the files"}"#,
+                "\n",
+                r#"{"type":"user","uuid":"u2","timestamp":"2024-01-01T00:00:05Z","content":"now the tests"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let options = IngestOptions { path: Some(path.clone()), dry_run: true, ..IngestOptions::default() };
+        let counts = ingest_claude(&db, &options).await;
+        std::fs::remove_file(&path).unwrap();
+
+        let counts = counts.unwrap();
+        assert_eq!(counts.imported, 1);
+        assert_eq!(counts.events, 2);
+        assert!(db.list_sessions(10, 0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_parse_imports_as_many_sessions_as_serial() {
+        let projects = std::env::temp_dir().join(format!("agent-viz-concurrent-{}", Uuid::new_v4()));
+        let mut paths = Vec::new();
+        for project in ["alpha", "beta"] {
+            std::fs::create_dir_all(projects.join(project)).unwrap();
+            for i in 0..20 {
+                let path = projects.join(project).join(format!("{}-{}.jsonl", project, i));
+                let line = format!(
+                    r#"{{"type":"user","uuid":"{}-{}","timestamp":"2024-01-01T00:00:{:02}Z","content":"task {}"}}"#,
+                    project, i, i, i
+                );
+                std::fs::write(&path, line).unwrap();
+                paths.push(path);
+            }
+        }
+        // One malformed file, which must fail the same way at any concurrency
+        std::fs::write(projects.join("alpha").join("broken.jsonl"), "not json\n").unwrap();
+
+        let adapter = ClaudeAdapter::with_projects_dir(projects.clone());
+        let mut runs = Vec::new();
+        for concurrency in [1, 8] {
+            let db = Database::open_in_memory().await.unwrap();
+            let options = IngestOptions { concurrency, ..IngestOptions::default() };
+            let counts = ingest_claude_with(&db, &adapter, &options).await.unwrap();
+
+            let mut recorded = 0;
+            for path in &paths {
+                let (mtime, size) = file_stamp(path).unwrap();
+                let path = path.to_string_lossy();
+                if !db
+                    .should_reingest("claude", &path, mtime, size, &options.fingerprint())
+                    .await
+                    .unwrap()
+                {
+                    recorded += 1;
+                }
+            }
+            let stored = db.get_external_ids("claude").await.unwrap();
+            runs.push((counts.imported, counts.failed, stored, recorded));
+        }
+        std::fs::remove_dir_all(&projects).unwrap();
+
+        let (imported, failed, stored, recorded) = &runs[0];
+        assert_eq!((*imported, *failed), (40, 1));
+        assert_eq!(stored.len(), 40);
+        assert_eq!(*recorded, 40);
+        assert_eq!(runs[0], runs[1]);
+    }
+
+    #[tokio::test]
+    async fn test_adapter_panic_fails_one_session_and_imports_the_rest() {
+        let projects = std::env::temp_dir().join(format!("agent-viz-panic-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(projects.join("demo")).unwrap();
+        for name in ["first", "malformed", "last"] {
+            let line = format!(
+                r#"{{"type":"user","uuid":"{}","timestamp":"2024-01-01T00:00:00Z","content":"hi"}}"#,
+                name
+            );
+            std::fs::write(projects.join("demo").join(format!("{}.jsonl", name)), line).unwrap();
+        }
+        let adapter = ClaudeAdapter::with_projects_dir(projects.clone());
+        let sessions = adapter.discover_sessions().await;
+        assert_eq!(sessions.len(), 3);
+
+        let db = Database::open_in_memory().await.unwrap();
+        let parse = |session_file: ClaudeSessionFile| {
+            let adapter = &adapter;
+            async move {
+                if session_file.session_id == "malformed" {
+                    let preview = "short";
+                    let _ = &preview[..60];
+                }
+                adapter.parse_session(&session_file).await
+            }
+        };
+        let options = IngestOptions::default();
+        let counts = ingest_session_files(&db, Source::Claude, sessions, parse, &options)
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&projects).unwrap();
+
+        assert_eq!(counts.imported, 2);
+        assert_eq!(counts.failed, 1);
+        assert_eq!(counts.causes.to_string(), "1 other error");
+        let stored = db.get_external_ids("claude").await.unwrap();
+        assert_eq!(stored, HashSet::from(["first".to_string(), "last".to_string()]));
+    }
 }
@@ -1,15 +1,38 @@
-use agent_v_store::Database;
+use crate::util::truncate_chars;
+use agent_v_store::{Database, SessionRow};
+use anstream::{print, println};
 use owo_colors::OwoColorize;
+use std::collections::HashMap;
 
-pub async fn sessions(source_filter: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+/// Number of cells in a session's event-kind sparkline
+const SPARKLINE_WIDTH: usize = 8;
+
+/// Sparkline glyph per event kind, in drawing order; errors get the tallest bar so they stand out
+const KIND_GLYPHS: &[(&str, char)] = &[
+    ("message", '▆'),
+    ("tool_call", '▄'),
+    ("tool_result", '▂'),
+    ("error", '█'),
+    ("system", '▁'),
+];
+
+pub async fn sessions(
+    source_filter: Option<String>, sparkline: bool, limit: i64, offset: i64, json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     let db = Database::open_default().await?;
     db.migrate().await?;
 
-    let sessions = if let Some(ref filter) = source_filter {
-        db.list_sessions_filtered(Some(filter), 100, 0).await?
-    } else {
-        db.list_sessions(100, 0).await?
-    };
+    let sessions = page(&db, source_filter.as_deref(), limit, offset).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&sessions_json(&sessions))?);
+        return Ok(());
+    }
+
+    if sessions.is_empty() && offset > 0 {
+        println!("{}", format!("No sessions past offset {}.", offset).yellow());
+        return Ok(());
+    }
 
     if sessions.is_empty() {
         println!("{}", "No sessions found.".yellow());
@@ -21,27 +44,180 @@ pub async fn sessions(source_filter: Option<String>) -> Result<(), Box<dyn std::
 
     println!("{}", "Sessions".bold().underline());
     println!("{}", "-".repeat(80).dimmed());
-    println!(
-        "{:<36} {:<10} {:<20} {}",
-        "ID".dimmed(),
-        "Source".dimmed(),
-        "Project".dimmed(),
-        "Title".dimmed()
-    );
+    if sparkline {
+        println!(
+            "{:<36} {:<10} {:<20} {:<8} {}",
+            "ID".dimmed(),
+            "Source".dimmed(),
+            "Project".dimmed(),
+            "Shape".dimmed(),
+            "Title".dimmed()
+        );
+    } else {
+        println!(
+            "{:<36} {:<10} {:<20} {}",
+            "ID".dimmed(),
+            "Source".dimmed(),
+            "Project".dimmed(),
+            "Title".dimmed()
+        );
+    }
     println!("{}", "-".repeat(80).dimmed());
 
     for session in sessions {
         let project = session.project.as_deref().unwrap_or("-");
         let title = session.title.as_deref().unwrap_or("Untitled");
 
-        println!(
-            "{:<36} {:<10} {:<20} {}",
+        print!(
+            "{:<36} {:<10} {:<20} ",
             session.id.dimmed(),
             session.source.cyan(),
-            &project[..project.len().min(20)],
-            title.bold()
+            truncate_chars(project, 20),
         );
+        if sparkline {
+            let counts = db.get_session_kind_counts(session.session_id()?).await?;
+            print!("{} ", colorize_sparkline(&kind_sparkline(&counts, SPARKLINE_WIDTH)));
+        }
+        println!("{}", title.bold());
+    }
+
+    if sparkline {
+        println!();
+        let legend: Vec<String> = KIND_GLYPHS
+            .iter()
+            .map(|(kind, glyph)| format!("{} {}", glyph, kind))
+            .collect();
+        println!("{}", legend.join("  ").dimmed());
     }
 
     Ok(())
 }
+
+/// One page of sessions, most recently updated first
+async fn page(
+    db: &Database, source_filter: Option<&str>, limit: i64, offset: i64,
+) -> Result<Vec<SessionRow>, tokio_rusqlite::Error> {
+    match source_filter {
+        Some(filter) => db.list_sessions_filtered(Some(filter), limit, offset).await,
+        None => db.list_sessions(limit, offset).await,
+    }
+}
+
+/// Sessions as a JSON array, without their raw source payloads
+fn sessions_json(sessions: &[SessionRow]) -> serde_json::Value {
+    sessions
+        .iter()
+        .map(|s| {
+            serde_json::json!({
+                "id": s.id,
+                "source": s.source,
+                "external_id": s.external_id,
+                "project": s.project,
+                "title": s.title,
+                "created_at": s.created_at,
+                "updated_at": s.updated_at,
+            })
+        })
+        .collect()
+}
+
+/// Render a session's event-kind mix as `width` glyphs, each kind getting cells in proportion
+/// to its share of events (largest remainder first, so the cells always add up to `width`).
+/// Kinds without a glyph count toward `system`; an empty session renders as blank cells.
+fn kind_sparkline(counts: &HashMap<String, i64>, width: usize) -> String {
+    let mut per_glyph: Vec<i64> = KIND_GLYPHS
+        .iter()
+        .map(|(kind, _)| counts.get(*kind).copied().unwrap_or(0))
+        .collect();
+    let known: i64 = per_glyph.iter().sum();
+    let total: i64 = counts.values().sum();
+    if let Some(system) = per_glyph.last_mut() {
+        *system += total - known;
+    }
+    if total <= 0 {
+        return " ".repeat(width);
+    }
+
+    let mut cells: Vec<usize> = per_glyph
+        .iter()
+        .map(|&count| (count as usize * width) / total as usize)
+        .collect();
+    let mut by_remainder: Vec<usize> = (0..per_glyph.len()).collect();
+    by_remainder.sort_by_key(|&i| std::cmp::Reverse((per_glyph[i] as usize * width) % total as usize));
+    let assigned: usize = cells.iter().sum();
+    for &i in by_remainder.iter().take(width - assigned) {
+        cells[i] += 1;
+    }
+
+    KIND_GLYPHS
+        .iter()
+        .zip(cells)
+        .flat_map(|((_, glyph), n)| std::iter::repeat_n(*glyph, n))
+        .collect()
+}
+
+/// Color the error cells of a sparkline so failing sessions stand out
+fn colorize_sparkline(sparkline: &str) -> String {
+    sparkline
+        .chars()
+        .map(|c| match KIND_GLYPHS.iter().find(|(_, glyph)| *glyph == c) {
+            Some(("error", _)) => c.red().to_string(),
+            Some(_) => c.cyan().to_string(),
+            None => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_v_core::{Session, Source};
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_offset_pages_through_sessions_newest_first() {
+        let db = Database::open_in_memory().await.unwrap();
+        let base = Utc::now() - Duration::hours(1);
+        for i in 0..5 {
+            let at = base + Duration::minutes(i);
+            let session = Session {
+                id: uuid::Uuid::new_v4(),
+                source: Source::Claude,
+                external_id: format!("paged-{}", i),
+                project: None,
+                title: Some(format!("Session {}", i)),
+                created_at: at,
+                updated_at: at,
+                raw_payload: serde_json::json!({}),
+            };
+            db.insert_session(&session).await.unwrap();
+        }
+
+        let ids = |rows: Vec<SessionRow>| rows.into_iter().map(|s| s.external_id).collect::<Vec<_>>();
+        assert_eq!(ids(page(&db, None, 2, 0).await.unwrap()), ["paged-4", "paged-3"]);
+        assert_eq!(ids(page(&db, None, 2, 2).await.unwrap()), ["paged-2", "paged-1"]);
+        assert_eq!(ids(page(&db, Some("claude"), 2, 4).await.unwrap()), ["paged-0"]);
+
+        let json = sessions_json(&page(&db, None, 1, 1).await.unwrap());
+        assert_eq!(json.as_array().unwrap().len(), 1);
+        assert_eq!(json[0]["title"], "Session 3");
+        assert!(json[0].get("raw_payload").is_none());
+    }
+
+    #[test]
+    fn test_all_error_session_sparkline_is_error_glyphs() {
+        let errors = HashMap::from([("error".to_string(), 5)]);
+        assert_eq!(kind_sparkline(&errors, 8), "████████");
+
+        let mixed = HashMap::from([
+            ("message".to_string(), 2),
+            ("tool_call".to_string(), 1),
+            ("error".to_string(), 9),
+        ]);
+        let line = kind_sparkline(&mixed, 8);
+        assert_eq!(line.chars().count(), 8);
+        assert!(line.chars().filter(|&c| c == '█').count() > 4);
+
+        assert_eq!(kind_sparkline(&HashMap::new(), 8), "        ");
+    }
+}
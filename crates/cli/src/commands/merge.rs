@@ -0,0 +1,48 @@
+use super::delete::{backup_if, confirm, print_backup};
+use super::export::find_session;
+use crate::error::AgentVizError;
+use agent_v_store::{Database, SessionRow};
+use anstream::println;
+use owo_colors::OwoColorize;
+
+/// Merge the `secondary` session into `primary` (each an internal or external ID), asking first
+/// unless `yes` is set and backing up the database first when `backup` is set
+pub async fn run(
+    primary: String, secondary: String, yes: bool, backup: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
+
+    let primary = find_session(&db, &primary, None).await?;
+    let secondary = find_session(&db, &secondary, None).await?;
+    if primary.id == secondary.id {
+        return Err(AgentVizError::Usage("Cannot merge a session into itself".to_string()).into());
+    }
+
+    println!("{} {}", "Keep:  ".dimmed(), describe(&primary));
+    println!("{} {}", "Merge: ".dimmed(), describe(&secondary));
+    if !yes && !confirm("Move the second session's events into the first and delete it?")? {
+        println!("Nothing merged.");
+        return Ok(());
+    }
+
+    let backup = backup_if(&db, backup).await?;
+    let moved = db
+        .merge_sessions(primary.session_id()?, secondary.session_id()?)
+        .await?
+        .ok_or_else(|| AgentVizError::NotFound(format!("Session not found: {}", secondary.id)))?;
+
+    println!("{} {} event(s) into {}", "Merged".green(), moved, primary.id);
+    print_backup(backup.as_deref());
+    Ok(())
+}
+
+fn describe(session: &SessionRow) -> String {
+    format!(
+        "{} {} {} {}",
+        session.id.dimmed(),
+        session.source.cyan(),
+        session.external_id,
+        session.title.as_deref().unwrap_or("Untitled").bold()
+    )
+}
@@ -1,9 +1,16 @@
+pub mod config;
+pub mod delete;
 pub mod doctor;
 pub mod export;
+pub mod files;
 pub mod ingest;
 pub mod list;
+pub mod merge;
+pub mod prune;
 pub mod recompute;
 pub mod search;
 pub mod show;
 pub mod stats;
 pub mod support;
+pub mod tag;
+pub mod version;
@@ -0,0 +1,144 @@
+use super::delete::{backup_if, confirm, print_backup};
+use crate::util::truncate_ellipsis;
+use agent_v_core::SessionId;
+use agent_v_store::{Database, SessionPathRow};
+use anstream::println;
+use chrono::{DateTime, Utc};
+use owo_colors::OwoColorize;
+use std::fmt;
+use std::path::Path;
+
+/// Why a session is up for pruning
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PruneReason {
+    /// The session file recorded at ingest no longer exists
+    MissingFile(String),
+    /// The session was last updated before the `--older-than` cutoff
+    Stale(String),
+}
+
+impl fmt::Display for PruneReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PruneReason::MissingFile(path) => write!(f, "file missing: {}", truncate_ellipsis(path, 60)),
+            PruneReason::Stale(updated_at) => write!(f, "last updated {}", &updated_at[..10.min(updated_at.len())]),
+        }
+    }
+}
+
+/// Delete sessions whose source files are gone, plus those not updated since `older_than` when given.
+/// `dry_run` only lists them; otherwise the deletion is confirmed first unless `yes` is set, and
+/// the database is backed up first when `backup` is set.
+pub async fn run(
+    source: Option<String>, older_than: Option<DateTime<Utc>>, dry_run: bool, yes: bool, backup: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
+
+    let sessions = db.list_session_paths_by_source(source.as_deref()).await?;
+    let doomed = prune_candidates(sessions, older_than, |path| path.exists());
+
+    if doomed.is_empty() {
+        println!("{}", "Nothing to prune.".green());
+        return Ok(());
+    }
+
+    println!("{}", "Sessions to prune".bold().underline());
+    println!("{}", "-".repeat(80).dimmed());
+    for (session, reason) in &doomed {
+        println!(
+            "{:<36} {:<10} {}",
+            session.id.dimmed(),
+            session.source.cyan(),
+            reason.to_string().yellow()
+        );
+    }
+    println!();
+
+    if dry_run {
+        println!("Dry run: {} sessions would be deleted.", doomed.len());
+        return Ok(());
+    }
+    if !yes && !confirm(&format!("Delete these {} sessions?", doomed.len()))? {
+        println!("Nothing deleted.");
+        return Ok(());
+    }
+
+    let backup = backup_if(&db, backup).await?;
+    let mut deleted = 0;
+    for (session, _) in &doomed {
+        if db.delete_session(session.id.parse::<SessionId>()?).await? {
+            deleted += 1;
+        }
+    }
+
+    println!("{} {} sessions", "Pruned".green(), deleted);
+    print_backup(backup.as_deref());
+    Ok(())
+}
+
+/// Sessions to prune: those whose recorded file fails `exists`, then those last updated before
+/// `cutoff`. Sessions without a recorded file are only ever pruned by age.
+fn prune_candidates(
+    sessions: Vec<SessionPathRow>, cutoff: Option<DateTime<Utc>>, exists: impl Fn(&Path) -> bool,
+) -> Vec<(SessionPathRow, PruneReason)> {
+    sessions
+        .into_iter()
+        .filter_map(|session| {
+            if let Some(path) = &session.file_path
+                && !exists(Path::new(path))
+            {
+                let reason = PruneReason::MissingFile(path.clone());
+                return Some((session, reason));
+            }
+
+            let updated_at = DateTime::parse_from_rfc3339(&session.updated_at).ok()?;
+            if cutoff.is_some_and(|cutoff| updated_at < cutoff) {
+                let reason = PruneReason::Stale(session.updated_at.clone());
+                return Some((session, reason));
+            }
+            None
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, file_path: Option<&str>, updated_at: &str) -> SessionPathRow {
+        SessionPathRow {
+            id: id.to_string(),
+            source: "claude".to_string(),
+            external_id: id.to_string(),
+            file_path: file_path.map(str::to_string),
+            updated_at: updated_at.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_prunes_missing_files_and_sessions_past_the_cutoff() {
+        let sessions = vec![
+            session("present", Some("/sessions/present.jsonl"), "2025-06-01T00:00:00+00:00"),
+            session("missing", Some("/sessions/missing.jsonl"), "2025-06-01T00:00:00+00:00"),
+            session("old", Some("/sessions/old.jsonl"), "2025-01-01T00:00:00+00:00"),
+            session("old-no-file", None, "2025-01-01T00:00:00+00:00"),
+            session("new-no-file", None, "2025-06-01T00:00:00+00:00"),
+        ];
+        let exists = |path: &Path| path != Path::new("/sessions/missing.jsonl");
+        let ids =
+            |doomed: Vec<(SessionPathRow, PruneReason)>| doomed.into_iter().map(|(s, _)| s.id).collect::<Vec<_>>();
+
+        assert_eq!(ids(prune_candidates(sessions.clone(), None, exists)), ["missing"]);
+
+        let cutoff = DateTime::parse_from_rfc3339("2025-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let doomed = prune_candidates(sessions, Some(cutoff), exists);
+        assert_eq!(
+            doomed[0].1,
+            PruneReason::MissingFile("/sessions/missing.jsonl".to_string())
+        );
+        assert_eq!(ids(doomed), ["missing", "old", "old-no-file"]);
+    }
+}
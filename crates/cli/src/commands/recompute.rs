@@ -1,8 +1,9 @@
 use agent_v_store::Database;
+use anstream::{eprintln, print, println};
 use log::info;
 use owo_colors::OwoColorize;
 
-/// Recompute metrics for all sessions
+/// Fill in missing error signatures, then recompute metrics for all sessions
 pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let db = Database::open_default().await?;
     db.migrate().await?;
@@ -11,6 +12,11 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", "Recomputing Session Metrics".bold().underline());
     println!();
 
+    let signatures = db.backfill_error_signatures().await?;
+    if signatures > 0 {
+        println!("  {} Filled in {} error signatures", "✓".green(), signatures);
+    }
+
     let mut total_sessions = 0;
     let mut offset = 0;
     let batch_size = 100;
@@ -41,7 +47,11 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
         }
 
         for session in &sessions {
-            match db.compute_session_metrics(&session.id).await {
+            let result = match session.session_id() {
+                Ok(session_id) => db.compute_session_metrics(session_id).await.map_err(|e| e.to_string()),
+                Err(e) => Err(e),
+            };
+            match result {
                 Ok(_) => {
                     processed += 1;
                     if processed >= next_milestone {
@@ -1,17 +1,257 @@
-use agent_v_store::{Database, SearchFacets};
-use chrono::{DateTime, Duration, Utc};
+use super::export::find_session;
+use crate::color::event_style;
+use crate::error::AgentVizError;
+use crate::util::parse_window;
+use agent_v_store::{Database, RegexSearchError, SearchFacets, SearchResult, SessionRow};
+use anstream::println;
+use jsonpath_rust::JsonPath;
 use owo_colors::OwoColorize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::Path;
+
+/// Page size used when streaming results as JSON lines
+const STREAM_PAGE_SIZE: i64 = 500;
+
+/// Output format for search results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchFormat {
+    Text,
+    Jsonl,
+}
+
+impl SearchFormat {
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(SearchFormat::Text),
+            "jsonl" | "json-lines" => Ok(SearchFormat::Jsonl),
+            _ => Err(format!("Unknown format: {}. Use 'text' or 'jsonl'", s)),
+        }
+    }
+}
+
+/// Which full-text indexes a search runs against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchFields {
+    /// Event content (`events_fts`)
+    pub content: bool,
+    /// Session titles (`sessions_fts`)
+    pub title: bool,
+}
+
+impl SearchFields {
+    /// Parse a comma-separated field list such as `content,title`
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        let mut fields = SearchFields { content: false, title: false };
+        for field in s.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            match field.to_lowercase().as_str() {
+                "content" => fields.content = true,
+                "title" => fields.title = true,
+                _ => return Err(format!("Unknown field: {}. Use 'content' and/or 'title'", field)),
+            }
+        }
+        if !fields.content && !fields.title {
+            return Err("No search fields given. Use 'content' and/or 'title'".to_string());
+        }
+        Ok(fields)
+    }
+}
+
+/// Post-filter that keeps events whose raw payload has a JSONPath match
+#[derive(Debug, Clone)]
+pub struct PayloadFilter {
+    /// JSONPath expression evaluated against the stored `raw_payload`
+    pub expr: String,
+    /// Value a selected node must equal; any selected node matches when absent
+    pub equals: Option<String>,
+    /// `equals` parsed as JSON once, for comparing non-string nodes
+    equals_json: Option<serde_json::Value>,
+}
+
+impl PayloadFilter {
+    /// Validate the expression up front so typos fail before the search runs
+    pub fn new(expr: String, equals: Option<String>) -> Result<Self, String> {
+        jsonpath_rust::parser::parse_json_path(&expr).map_err(|e| format!("Invalid JSONPath '{}': {}", expr, e))?;
+        let equals_json = equals.as_deref().and_then(|e| serde_json::from_str(e).ok());
+        Ok(PayloadFilter { expr, equals, equals_json })
+    }
+
+    /// Whether the payload has a node selected by the expression (and equal to the expected value).
+    /// String nodes compare by their text, everything else by value against the expected JSON.
+    pub fn matches(&self, raw_payload: &str) -> bool {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(raw_payload) else {
+            return false;
+        };
+        let Ok(nodes) = payload.query(&self.expr) else {
+            return false;
+        };
+        match &self.equals {
+            None => !nodes.is_empty(),
+            Some(expected) => nodes.into_iter().any(|node| match node {
+                serde_json::Value::String(s) => s == expected,
+                other => self.equals_json.as_ref() == Some(other),
+            }),
+        }
+    }
+}
+
+/// Output and matching options for a search
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub format: SearchFormat,
+    /// Fixed snippet length in tokens
+    pub snippet_len: Option<i64>,
+    /// Collapse hits with identical content
+    pub dedupe_content: bool,
+    pub fields: SearchFields,
+    /// OR-join query terms instead of requiring all of them
+    pub any: bool,
+    /// Rank multiplier for events whose session title also matches the query
+    pub title_boost: Option<f64>,
+    /// Keep only events whose raw payload matches this JSONPath filter
+    pub payload_filter: Option<PayloadFilter>,
+    /// Search assistant reasoning text instead of event content
+    pub thinking: bool,
+    /// Only search sessions carrying this tag
+    pub tag: Option<String>,
+    /// Only search this session (internal or external ID)
+    pub session: Option<String>,
+    /// Only match events older than this window (e.g. `7d`), the far end of `--since`
+    pub until: Option<String>,
+    /// Treat the query as a regular expression matched against event content instead of FTS terms
+    pub regex: bool,
+}
+
+/// A batch query with its event matches
+type QueryGroup = (String, Vec<(SearchResult, Option<usize>)>);
+
+/// A match from either the event content or the session title index
+enum SearchHit {
+    Event(SearchResult, usize),
+    Title(SessionRow, f64),
+}
+
+impl SearchHit {
+    fn rank(&self) -> f64 {
+        match self {
+            SearchHit::Event(result, _) => result.rank,
+            SearchHit::Title(_, rank) => *rank,
+        }
+    }
+}
+
+/// Query every selected index and merge the hits by rank, best first.
+async fn collect_hits(
+    db: &Database, query: &str, facets: &SearchFacets, options: &SearchOptions, limit: i64,
+) -> Result<Vec<SearchHit>, Box<dyn std::error::Error>> {
+    let mut hits = Vec::new();
+
+    if options.fields.content {
+        let mut results = search_events_filtered(db, query, facets, options, limit).await?;
+        if let Some(boost) = options.title_boost {
+            let titled: HashSet<String> = db
+                .search_sessions(query, facets, STREAM_PAGE_SIZE, 0)
+                .await?
+                .into_iter()
+                .map(|(session, _)| session.id)
+                .collect();
+            boost_title_matches(&mut results, &titled, boost);
+        }
+        if options.dedupe_content {
+            hits.extend(
+                dedupe_by_content(results)
+                    .into_iter()
+                    .map(|(r, n)| SearchHit::Event(r, n)),
+            );
+        } else {
+            hits.extend(results.into_iter().map(|r| SearchHit::Event(r, 1)));
+        }
+    }
+    if options.fields.title {
+        let sessions = db.search_sessions(query, facets, limit, 0).await?;
+        hits.extend(sessions.into_iter().map(|(s, rank)| SearchHit::Title(s, rank)));
+    }
+
+    hits.sort_by(|a, b| a.rank().total_cmp(&b.rank()));
+    hits.truncate(limit as usize);
+    Ok(hits)
+}
+
+/// Fetch up to `limit` event matches. With a payload filter, keep paging until enough
+/// events survive it or the matches run out.
+async fn search_events_filtered(
+    db: &Database, query: &str, facets: &SearchFacets, options: &SearchOptions, limit: i64,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    if options.regex {
+        return regex_search(db, query, facets, limit).await;
+    }
+    let Some(filter) = options.payload_filter.as_ref() else {
+        return Ok(db.search_events(query, facets, limit, 0).await?);
+    };
+
+    let mut kept = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = db.search_events(query, facets, STREAM_PAGE_SIZE, offset).await?;
+        let page_len = page.len() as i64;
+        kept.extend(page.into_iter().filter(|r| filter.matches(&r.event.raw_payload)));
+        if kept.len() as i64 >= limit || page_len < STREAM_PAGE_SIZE {
+            break;
+        }
+        offset += STREAM_PAGE_SIZE;
+    }
+    kept.truncate(limit as usize);
+    Ok(kept)
+}
+
+/// Scan event content for `pattern`, reporting bad or oversized patterns as usage errors
+async fn regex_search(
+    db: &Database, pattern: &str, facets: &SearchFacets, limit: i64,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    db.search_events_regex(pattern, facets, limit)
+        .await
+        .map_err(|err| match err {
+            RegexSearchError::Database(err) => AgentVizError::from(err).into(),
+            other => AgentVizError::Usage(other.to_string()).into(),
+        })
+}
+
+/// Scale the rank of events from title-matching sessions. FTS5 ranks are negative
+/// bm25 scores where lower is better, so a boost above 1.0 moves them up.
+fn boost_title_matches(results: &mut [SearchResult], titled: &HashSet<String>, boost: f64) {
+    for result in results.iter_mut() {
+        if titled.contains(&result.event.session_id) {
+            result.rank *= boost;
+        }
+    }
+}
 
 /// Run the search command
 pub async fn run(
-    query: String, source: Option<String>, since: Option<String>, kind: Option<String>,
+    query: String, source: Option<String>, since: Option<String>, kind: Option<String>, category: Option<String>,
+    options: SearchOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let db = Database::open_default().await?;
     db.migrate().await?;
 
-    let since_dt = parse_since(&since)?;
+    let facets = build_facets(&db, source, &since, kind, category, &options).await?;
 
-    let facets = SearchFacets { source, project: None, kind, since: since_dt };
+    if options.format == SearchFormat::Jsonl {
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        if options.regex {
+            write_regex_jsonl(&db, &query, &facets, options.dedupe_content, &mut out).await?;
+        } else if options.fields.content {
+            let filter = options.payload_filter.as_ref();
+            stream_jsonl(&db, &query, &facets, options.dedupe_content, filter, &mut out).await?;
+        }
+        if options.fields.title {
+            stream_title_jsonl(&db, &query, &facets, &mut out).await?;
+        }
+        return Ok(());
+    }
 
     println!("{} {}", "Search:".bold().underline(), query.cyan());
 
@@ -21,12 +261,44 @@ pub async fn run(
     if let Some(ref k) = facets.kind {
         println!("  {} {}", "Kind:".dimmed(), k.cyan());
     }
+    if let Some(ref c) = facets.category {
+        println!("  {} {}", "Category:".dimmed(), c.cyan());
+    }
+    if let Some(ref id) = facets.session_id {
+        println!("  {} {}", "Session:".dimmed(), id.cyan());
+    }
     if let Some(ref s) = since {
         println!("  {} {}", "Since:".dimmed(), s.cyan());
     }
+    if let Some(ref u) = options.until {
+        println!("  {} {}", "Until:".dimmed(), u.cyan());
+    }
+    if options.any {
+        println!("  {} {}", "Match:".dimmed(), "any term".cyan());
+    }
+    if options.regex {
+        println!("  {} {}", "Match:".dimmed(), "regex".cyan());
+    }
+    if options.thinking {
+        println!("  {} {}", "Fields:".dimmed(), "thinking".cyan());
+    }
+    if let Some(boost) = options.title_boost {
+        println!("  {} {}", "Title boost:".dimmed(), format!("×{}", boost).cyan());
+    }
+    if let Some(ref filter) = options.payload_filter {
+        let label = match filter.equals {
+            Some(ref value) => format!("{} == {}", filter.expr, value),
+            None => filter.expr.clone(),
+        };
+        println!("  {} {}", "Payload:".dimmed(), label.cyan());
+    }
+    if options.fields.title {
+        let fields = if options.fields.content { "content, title" } else { "title" };
+        println!("  {} {}", "Fields:".dimmed(), fields.cyan());
+    }
     println!();
 
-    let results = db.search_events(&query, &facets, 50, 0).await?;
+    let results = collect_hits(&db, &query, &facets, &options, 50).await?;
 
     if results.is_empty() {
         println!("{}", "No results found.".yellow());
@@ -40,80 +312,708 @@ pub async fn run(
     );
     println!();
 
-    for result in results {
-        let event = &result.event;
-
-        let kind_label = match event.kind.as_str() {
-            "message" => "MSG".blue().to_string(),
-            "tool_call" => "TOOL".magenta().to_string(),
-            "tool_result" => "RES".green().to_string(),
-            "error" => "ERR".red().to_string(),
-            _ => event.kind.to_uppercase().dimmed().to_string(),
-        };
-
-        let role_label = event
-            .role
-            .as_ref()
-            .map(|r| match r.as_str() {
-                "user" => "user".blue().to_string(),
-                "assistant" => "asst".cyan().to_string(),
-                "system" => "sys".dimmed().to_string(),
-                _ => r.dimmed().to_string(),
-            })
-            .unwrap_or_else(|| "-".dimmed().to_string());
-
-        let content_preview = event
-            .content
-            .as_ref()
-            .map(|c| {
-                let preview = if c.len() > 80 { &c[..80] } else { c };
-                preview.replace('\n', " ")
-            })
-            .unwrap_or_else(|| "(no content)".dimmed().to_string());
+    for hit in results {
+        match hit {
+            SearchHit::Event(result, occurrences) => print_event_hit(&result, occurrences),
+            SearchHit::Title(session, rank) => print_title_hit(&session, rank),
+        }
+    }
 
-        let timestamp = &event.timestamp[..19.min(event.timestamp.len())];
+    Ok(())
+}
 
-        println!(
-            "  {} {} {} {} {}",
-            timestamp.dimmed(),
-            kind_label,
-            role_label,
-            "|".dimmed(),
-            content_preview
-        );
+/// Run every query from a queries file and print the event matches grouped by query
+pub async fn run_batch(
+    queries: Vec<String>, source: Option<String>, since: Option<String>, kind: Option<String>,
+    category: Option<String>, options: SearchOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
 
+    let facets = build_facets(&db, source, &since, kind, category, &options).await?;
+    let groups = batch_results(&db, &queries, &facets, &options, 50).await?;
+
+    if options.format == SearchFormat::Jsonl {
+        println!("{}", batch_json(&groups));
+        return Ok(());
+    }
+
+    for (idx, (query, results)) in groups.iter().enumerate() {
+        if idx > 0 {
+            println!("{}", "-".repeat(80).dimmed());
+        }
         println!(
-            "     {} {} {}",
-            "Session:".dimmed(),
-            event.session_id[..8].to_string().cyan(),
-            format!("(rank: {:.4})", result.rank).dimmed()
+            "{} {} {}",
+            "Search:".bold().underline(),
+            query.cyan(),
+            format!("({})", results.len()).dimmed()
         );
         println!();
+
+        if results.is_empty() {
+            println!("  {}", "No results found.".yellow());
+            println!();
+        }
+        for (result, occurrences) in results {
+            print_event_hit(result, occurrences.unwrap_or(1));
+        }
     }
 
     Ok(())
 }
 
-fn parse_since(since: &Option<String>) -> Result<Option<DateTime<Utc>>, Box<dyn std::error::Error>> {
-    let Some(s) = since else {
-        return Ok(None);
+/// Read one query per line, skipping blank lines and `#` comments
+pub fn read_queries(path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let queries: Vec<String> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect();
+
+    if queries.is_empty() {
+        return Err(AgentVizError::Usage(format!("No queries found in {}", path.display())).into());
+    }
+    Ok(queries)
+}
+
+/// Event matches for each query in order, paired with an occurrence count when deduplicating
+async fn batch_results(
+    db: &Database, queries: &[String], facets: &SearchFacets, options: &SearchOptions, limit: i64,
+) -> Result<Vec<QueryGroup>, Box<dyn std::error::Error>> {
+    let mut groups = Vec::with_capacity(queries.len());
+    for query in queries {
+        let results = search_events_filtered(db, query, facets, options, limit).await?;
+        let results = if options.dedupe_content {
+            dedupe_by_content(results)
+                .into_iter()
+                .map(|(r, n)| (r, Some(n)))
+                .collect()
+        } else {
+            results.into_iter().map(|r| (r, None)).collect()
+        };
+        groups.push((query.clone(), results));
+    }
+    Ok(groups)
+}
+
+/// Batch results as one JSON object keyed by query, each holding its hits in the JSON lines shape
+fn batch_json(groups: &[QueryGroup]) -> serde_json::Value {
+    let by_query: serde_json::Map<String, serde_json::Value> = groups
+        .iter()
+        .map(|(query, results)| {
+            let hits = results
+                .iter()
+                .map(|(result, occurrences)| result_json(result, *occurrences))
+                .collect();
+            (query.clone(), serde_json::Value::Array(hits))
+        })
+        .collect();
+    serde_json::Value::Object(by_query)
+}
+
+/// Search facets from the command line, resolving `--session` to the session's internal ID
+async fn build_facets(
+    db: &Database, source: Option<String>, since: &Option<String>, kind: Option<String>, category: Option<String>,
+    options: &SearchOptions,
+) -> Result<SearchFacets, Box<dyn std::error::Error>> {
+    let session_id = match options.session.as_deref() {
+        Some(id) => Some(find_session(db, id, None).await?.id),
+        None => None,
     };
+    let window = parse_window(since.as_deref(), options.until.as_deref()).map_err(AgentVizError::Usage)?;
+    Ok(SearchFacets {
+        source,
+        project: None,
+        kind,
+        since: window.since,
+        until: window.until,
+        snippet_len: options.snippet_len,
+        match_any: options.any,
+        category,
+        thinking: options.thinking,
+        tag: options.tag.clone(),
+        session_id,
+    })
+}
+
+fn print_event_hit(result: &SearchResult, occurrences: usize) {
+    let event = &result.event;
+
+    let style = event_style(&event.kind, event.role.as_deref());
+    let kind_label = match event.kind.as_str() {
+        "message" => "MSG".to_string(),
+        "tool_call" => "TOOL".to_string(),
+        "tool_result" => "RES".to_string(),
+        "error" => "ERR".to_string(),
+        other => other.to_uppercase(),
+    }
+    .style(style)
+    .to_string();
+
+    let role_label = event
+        .role
+        .as_ref()
+        .map(|r| match r.as_str() {
+            "user" => "user",
+            "assistant" => "asst",
+            "system" => "sys",
+            other => other,
+        })
+        .unwrap_or("-")
+        .style(style)
+        .to_string();
+
+    let content_preview = result
+        .snippet
+        .as_ref()
+        .or(event.content.as_ref())
+        .map(|c| c.replace('\n', " "))
+        .unwrap_or_else(|| "(no content)".dimmed().to_string());
+
+    let timestamp = &event.timestamp[..19.min(event.timestamp.len())];
+
+    println!(
+        "  {} {} {} {} {}",
+        timestamp.dimmed(),
+        kind_label,
+        role_label,
+        "|".dimmed(),
+        content_preview
+    );
+
+    let repeat_label = if occurrences > 1 { format!(" ×{}", occurrences) } else { String::new() };
+    println!(
+        "     {} {} {}{}",
+        "Session:".dimmed(),
+        event.session_id[..8].to_string().cyan(),
+        format!("(rank: {:.4})", result.rank).dimmed(),
+        repeat_label.yellow()
+    );
+    println!();
+}
+
+fn print_title_hit(session: &SessionRow, rank: f64) {
+    let timestamp = &session.updated_at[..19.min(session.updated_at.len())];
+    println!(
+        "  {} {} {} {} {}",
+        timestamp.dimmed(),
+        "TITLE".yellow(),
+        session.source.dimmed(),
+        "|".dimmed(),
+        session.title.as_deref().unwrap_or_default()
+    );
+    println!(
+        "     {} {} {}",
+        "Session:".dimmed(),
+        session.id[..8.min(session.id.len())].to_string().cyan(),
+        format!("(rank: {:.4})", rank).dimmed()
+    );
+    println!();
+}
+
+/// Stream session title matches as JSON lines, tagged with `"field": "title"`.
+async fn stream_title_jsonl<W: Write>(
+    db: &Database, query: &str, facets: &SearchFacets, out: &mut W,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut offset = 0;
+    let mut written = 0;
 
-    let duration = if s.ends_with('d') {
-        let days: i64 = s[..s.len() - 1].parse()?;
-        Duration::days(days)
-    } else if s.ends_with('h') {
-        let hours: i64 = s[..s.len() - 1].parse()?;
-        Duration::hours(hours)
-    } else if s.ends_with('w') {
-        let weeks: i64 = s[..s.len() - 1].parse()?;
-        Duration::weeks(weeks)
-    } else if s.ends_with('m') && !s.ends_with("min") {
-        let months: i64 = s[..s.len() - 1].parse()?;
-        Duration::days(months * 30)
+    loop {
+        let sessions = db.search_sessions(query, facets, STREAM_PAGE_SIZE, offset).await?;
+        if sessions.is_empty() {
+            break;
+        }
+        let page_len = sessions.len() as i64;
+
+        for (session, rank) in &sessions {
+            let line = serde_json::json!({
+                "field": "title",
+                "session_id": session.id,
+                "source": session.source,
+                "title": session.title,
+                "timestamp": session.updated_at,
+                "rank": rank,
+            });
+            serde_json::to_writer(&mut *out, &line)?;
+            out.write_all(b"\n")?;
+            written += 1;
+        }
+        out.flush()?;
+
+        if page_len < STREAM_PAGE_SIZE {
+            break;
+        }
+        offset += STREAM_PAGE_SIZE;
+    }
+
+    Ok(written)
+}
+
+/// Stream every match as one JSON object per line, paging through the store
+/// so large result sets are never held in memory at once.
+/// Deduplicating needs every match first, so it buffers and adds an `occurrences` field.
+async fn stream_jsonl<W: Write>(
+    db: &Database, query: &str, facets: &SearchFacets, dedupe_content: bool, filter: Option<&PayloadFilter>,
+    out: &mut W,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut offset = 0;
+    let mut written = 0;
+    let mut buffered = Vec::new();
+
+    loop {
+        let mut results = db.search_events(query, facets, STREAM_PAGE_SIZE, offset).await?;
+        if results.is_empty() {
+            break;
+        }
+        let page_len = results.len() as i64;
+        if let Some(filter) = filter {
+            results.retain(|r| filter.matches(&r.event.raw_payload));
+        }
+
+        if dedupe_content {
+            buffered.extend(results);
+        } else {
+            for result in &results {
+                write_jsonl_line(out, result, None)?;
+                written += 1;
+            }
+            out.flush()?;
+        }
+
+        if page_len < STREAM_PAGE_SIZE {
+            break;
+        }
+        offset += STREAM_PAGE_SIZE;
+    }
+
+    if dedupe_content {
+        for (result, occurrences) in dedupe_by_content(buffered) {
+            write_jsonl_line(out, &result, Some(occurrences))?;
+            written += 1;
+        }
+        out.flush()?;
+    }
+
+    Ok(written)
+}
+
+/// Write every regex match as JSON lines. The scan already returns matches without paging, so
+/// there is nothing to stream page by page.
+async fn write_regex_jsonl<W: Write>(
+    db: &Database, pattern: &str, facets: &SearchFacets, dedupe_content: bool, out: &mut W,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let results = regex_search(db, pattern, facets, i64::MAX).await?;
+    let lines: Vec<(SearchResult, Option<usize>)> = if dedupe_content {
+        dedupe_by_content(results)
+            .into_iter()
+            .map(|(r, n)| (r, Some(n)))
+            .collect()
     } else {
-        return Err(format!("Invalid duration format: {}. Use Nd, Nh, Nw, Nm", s).into());
+        results.into_iter().map(|r| (r, None)).collect()
     };
+    for (result, occurrences) in &lines {
+        write_jsonl_line(out, result, *occurrences)?;
+    }
+    out.flush()?;
+    Ok(lines.len())
+}
+
+fn write_jsonl_line<W: Write>(
+    out: &mut W, result: &SearchResult, occurrences: Option<usize>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    serde_json::to_writer(&mut *out, &result_json(result, occurrences))?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+fn result_json(result: &SearchResult, occurrences: Option<usize>) -> serde_json::Value {
+    let mut line = serde_json::json!({
+        "event_id": result.event.id,
+        "session_id": result.event.session_id,
+        "kind": result.event.kind,
+        "timestamp": result.event.timestamp,
+        "rank": result.rank,
+        "snippet": result.snippet,
+    });
+    if let Some(occurrences) = occurrences {
+        line["occurrences"] = serde_json::json!(occurrences);
+    }
+    line
+}
+
+/// Collapse results with identical content into their best-ranked hit, paired with how many
+/// results shared that content. Results without content are never merged.
+fn dedupe_by_content(results: Vec<SearchResult>) -> Vec<(SearchResult, usize)> {
+    let mut deduped: Vec<(SearchResult, usize)> = Vec::new();
+    let mut seen: HashMap<u64, usize> = HashMap::new();
+
+    for result in results {
+        let Some(content) = result.event.content.as_deref() else {
+            deduped.push((result, 1));
+            continue;
+        };
+
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        let hash = hasher.finish();
+        match seen.get(&hash) {
+            Some(&idx) => deduped[idx].1 += 1,
+            None => {
+                seen.insert(hash, deduped.len());
+                deduped.push((result, 1));
+            }
+        }
+    }
+
+    deduped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_v_core::{Event, EventKind, Role, Session, Source};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_stream_jsonl_lines_are_independent_json() {
+        let db = Database::open_in_memory().await.unwrap();
+
+        let session = Session {
+            id: Uuid::new_v4(),
+            source: Source::Claude,
+            external_id: "stream-1".to_string(),
+            project: None,
+            title: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_payload: serde_json::json!({}),
+        };
+        let events: Vec<Event> = (0..3)
+            .map(|i| Event {
+                id: Uuid::new_v4(),
+                session_id: session.id,
+                kind: EventKind::Message,
+                role: Some(Role::User),
+                content: Some(format!("needle number {}\nwith a second line", i)),
+                timestamp: Utc::now(),
+                raw_payload: serde_json::json!({}),
+                parent_id: None,
+            })
+            .collect();
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let mut out = Vec::new();
+        let written = stream_jsonl(&db, "needle", &SearchFacets::default(), false, None, &mut out)
+            .await
+            .unwrap();
+        assert_eq!(written, 3);
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for line in lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(value["session_id"], session.id.to_string());
+            assert_eq!(value["kind"], "message");
+            assert!(value["event_id"].is_string());
+            assert!(value["timestamp"].is_string());
+            assert!(value["rank"].is_number());
+        }
+    }
+
+    fn hit(id: &str, content: &str) -> SearchResult {
+        SearchResult {
+            event: agent_v_store::EventRow {
+                id: id.to_string(),
+                session_id: "s1".to_string(),
+                kind: "tool_result".to_string(),
+                role: None,
+                content: Some(content.to_string()),
+                timestamp: "2025-01-01T00:00:00Z".to_string(),
+                raw_payload: "{}".to_string(),
+                category: None,
+                parent_id: None,
+            },
+            rank: -1.0,
+            snippet: None,
+        }
+    }
+
+    #[test]
+    fn test_dedupe_by_content_collapses_identical_hits() {
+        let results = vec![
+            hit("e1", "Compiling crate v0.1.0"),
+            hit("e2", "unique output"),
+            hit("e3", "Compiling crate v0.1.0"),
+            hit("e4", "Compiling crate v0.1.0"),
+        ];
+
+        let deduped = dedupe_by_content(results);
+        assert_eq!(deduped.len(), 2);
+        assert_eq!(deduped[0].0.event.id, "e1");
+        assert_eq!(deduped[0].1, 3);
+        assert_eq!(deduped[1].0.event.id, "e2");
+        assert_eq!(deduped[1].1, 1);
+    }
+
+    #[tokio::test]
+    async fn test_title_field_matches_session_titles_only() {
+        let db = Database::open_in_memory().await.unwrap();
+
+        let session = Session {
+            id: Uuid::new_v4(),
+            source: Source::Claude,
+            external_id: "titled-1".to_string(),
+            project: None,
+            title: Some("Refactor the zeppelin loader".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_payload: serde_json::json!({}),
+        };
+        let events = vec![Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some("please clean up the loader".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        }];
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let options = |fields| SearchOptions {
+            format: SearchFormat::Text,
+            snippet_len: None,
+            dedupe_content: false,
+            fields,
+            any: false,
+            title_boost: None,
+            payload_filter: None,
+            thinking: false,
+            tag: None,
+            session: None,
+            until: None,
+            regex: false,
+        };
+        let facets = SearchFacets::default();
+
+        let content_only = options(SearchFields::from_str("content").unwrap());
+        let hits = collect_hits(&db, "zeppelin", &facets, &content_only, 50).await.unwrap();
+        assert!(hits.is_empty());
+
+        let title_only = options(SearchFields::from_str("title").unwrap());
+        let hits = collect_hits(&db, "zeppelin", &facets, &title_only, 50).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(matches!(&hits[0], SearchHit::Title(s, _) if s.id == session.id.to_string()));
+
+        let both = options(SearchFields::from_str("content,title").unwrap());
+        let hits = collect_hits(&db, "loader", &facets, &both, 50).await.unwrap();
+        assert_eq!(hits.len(), 2);
+
+        assert!(SearchFields::from_str("body").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_title_boost_ranks_title_matching_sessions_first() {
+        let db = Database::open_in_memory().await.unwrap();
+
+        let session_with = |external_id: &str, title: &str| Session {
+            id: Uuid::new_v4(),
+            source: Source::Claude,
+            external_id: external_id.to_string(),
+            project: None,
+            title: Some(title.to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_payload: serde_json::json!({}),
+        };
+        let event_in = |session: &Session| Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some("the flux capacitor is overheating".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        };
+        let plain = session_with("boost-1", "Unrelated chores");
+        let titled = session_with("boost-2", "Flux capacitor repairs");
+        db.insert_session_with_events(&plain, &[event_in(&plain)])
+            .await
+            .unwrap();
+        db.insert_session_with_events(&titled, &[event_in(&titled)])
+            .await
+            .unwrap();
 
-    Ok(Some(Utc::now() - duration))
+        let options = SearchOptions {
+            format: SearchFormat::Text,
+            snippet_len: None,
+            dedupe_content: false,
+            fields: SearchFields::from_str("content").unwrap(),
+            any: false,
+            title_boost: Some(2.0),
+            payload_filter: None,
+            thinking: false,
+            tag: None,
+            session: None,
+            until: None,
+            regex: false,
+        };
+        let hits = collect_hits(&db, "flux", &SearchFacets::default(), &options, 50)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(matches!(&hits[0], SearchHit::Event(r, _) if r.event.session_id == titled.id.to_string()));
+        assert!(hits[0].rank() < hits[1].rank());
+    }
+
+    #[tokio::test]
+    async fn test_payload_filter_keeps_events_on_matching_branch() {
+        let db = Database::open_in_memory().await.unwrap();
+
+        let session = Session {
+            id: Uuid::new_v4(),
+            source: Source::Claude,
+            external_id: "branches-1".to_string(),
+            project: None,
+            title: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_payload: serde_json::json!({}),
+        };
+        let event_on = |branch: &str| Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some("rebase the widget branch".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({ "gitBranch": branch }),
+            parent_id: None,
+        };
+        let feature = event_on("feature/widgets");
+        let main = event_on("main");
+        db.insert_session_with_events(&session, &[feature.clone(), main])
+            .await
+            .unwrap();
+
+        let options = SearchOptions {
+            format: SearchFormat::Text,
+            snippet_len: None,
+            dedupe_content: false,
+            fields: SearchFields::from_str("content").unwrap(),
+            any: false,
+            title_boost: None,
+            payload_filter: Some(
+                PayloadFilter::new("$.gitBranch".to_string(), Some("feature/widgets".to_string())).unwrap(),
+            ),
+            thinking: false,
+            tag: None,
+            session: None,
+            until: None,
+            regex: false,
+        };
+        let hits = collect_hits(&db, "widget", &SearchFacets::default(), &options, 50)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(matches!(&hits[0], SearchHit::Event(r, _) if r.event.id == feature.id.to_string()));
+
+        let mut out = Vec::new();
+        let written = stream_jsonl(
+            &db,
+            "widget",
+            &SearchFacets::default(),
+            false,
+            options.payload_filter.as_ref(),
+            &mut out,
+        )
+        .await
+        .unwrap();
+        assert_eq!(written, 1);
+
+        assert!(PayloadFilter::new("$.[".to_string(), None).is_err());
+    }
+
+    #[test]
+    fn test_payload_filter_compares_non_string_nodes_by_value() {
+        let filter = PayloadFilter::new("$.attempt".to_string(), Some("2".to_string())).unwrap();
+        assert!(filter.matches(r#"{"attempt": 2}"#));
+        assert!(!filter.matches(r#"{"attempt": 3}"#));
+        assert!(filter.matches(r#"{"attempt": "2"}"#));
+
+        let filter = PayloadFilter::new("$.cached".to_string(), Some("true".to_string())).unwrap();
+        assert!(filter.matches(r#"{"cached": true}"#));
+    }
+
+    #[tokio::test]
+    async fn test_queries_file_groups_results_under_each_query() {
+        let db = Database::open_in_memory().await.unwrap();
+
+        let session = Session {
+            id: Uuid::new_v4(),
+            source: Source::Claude,
+            external_id: "batch-1".to_string(),
+            project: None,
+            title: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_payload: serde_json::json!({}),
+        };
+        let event = |content: &str| Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some(content.to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        };
+        let events = vec![
+            event("the walrus ate the sandwich"),
+            event("another walrus sighting"),
+            event("a lone pelican"),
+        ];
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let path = std::env::temp_dir().join(format!("agent-viz-queries-{}.txt", Uuid::new_v4()));
+        std::fs::write(&path, "walrus\n\n# birds\npelican\n").unwrap();
+        let queries = read_queries(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(queries, vec!["walrus", "pelican"]);
+
+        let options = SearchOptions {
+            format: SearchFormat::Jsonl,
+            snippet_len: None,
+            dedupe_content: false,
+            fields: SearchFields::from_str("content").unwrap(),
+            any: false,
+            title_boost: None,
+            payload_filter: None,
+            thinking: false,
+            tag: None,
+            session: None,
+            until: None,
+            regex: false,
+        };
+        let groups = batch_results(&db, &queries, &SearchFacets::default(), &options, 50)
+            .await
+            .unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "walrus");
+        assert_eq!(groups[0].1.len(), 2);
+        assert_eq!(groups[1].0, "pelican");
+        assert_eq!(groups[1].1.len(), 1);
+
+        let json = batch_json(&groups);
+        assert_eq!(json["walrus"].as_array().unwrap().len(), 2);
+        assert_eq!(json["pelican"][0]["event_id"], events[2].id.to_string());
+        assert!(json["pelican"][0].get("occurrences").is_none());
+    }
 }
@@ -1,46 +1,82 @@
-use agent_v_store::Database;
+use crate::color::event_style;
+use crate::commands::export::{export_session_to_json, find_session};
+use crate::error::AgentVizError;
+use crate::util::{truncate_chars, truncate_ellipsis};
+use agent_v_core::Event;
+use agent_v_store::{Database, EventRow, SessionMetricsRow, SessionRow, TokenUsagePoint, ToolCallRow};
+use anstream::{print, println};
 use owo_colors::OwoColorize;
 
-pub async fn session(session_id: String) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn session(
+    session_id: String, source: Option<String>, raw: bool, json: bool, tools: bool, tokens: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     let db = Database::open_default().await?;
     db.migrate().await?;
 
-    let sessions = db.list_sessions(1000, 0).await?;
-
-    let session = sessions
-        .iter()
-        .find(|s| s.id == session_id || s.external_id == session_id)
-        .cloned();
-
-    let session = match session {
-        Some(s) => s,
-        None => {
-            println!("{} Session not found: {}", "✗".red(), session_id.cyan());
-            println!();
+    let session = match find_session(&db, &session_id, source.as_deref()).await {
+        Err(e) if !json && matches!(e.downcast_ref(), Some(AgentVizError::NotFound(_))) => {
             println!(
                 "{}",
                 "Run 'agent-viz list sessions' to see available sessions.".dimmed()
             );
-            return Ok(());
+            return Err(e);
         }
+        found => found?,
     };
 
+    let session_key = session.session_id()?;
+    let metrics = db.get_session_metrics(session_key).await?;
+    let events = db.get_session_events(session_key).await?;
+
+    if json {
+        println!("{}", export_session_to_json(&session, &events, metrics.as_ref()).await?);
+        return Ok(());
+    }
+
     println!("{}", "Session Details".bold().underline());
     println!();
-    println!("{} {}", "ID:".dimmed(), session.id);
-    println!("{} {}", "External ID:".dimmed(), session.external_id.cyan());
-    println!("{} {}", "Source:".dimmed(), session.source.cyan());
-    if let Some(ref project) = session.project {
-        println!("{} {}", "Project:".dimmed(), project.cyan());
-    }
-    if let Some(ref title) = session.title {
-        println!("{} {}", "Title:".dimmed(), title.bold());
+    for line in session_header(&session, metrics.as_ref()) {
+        println!("{}", line);
     }
-    println!("{} {}", "Created:".dimmed(), session.created_at);
-    println!("{} {}", "Updated:".dimmed(), session.updated_at);
     println!();
 
-    let events = db.get_session_events(session.id.clone()).await?;
+    if tokens {
+        let points = db.get_token_timeline(session_key).await?;
+        if points.is_empty() {
+            println!("{}", "No token counts recorded for this session.".yellow());
+            return Ok(());
+        }
+        println!(
+            "{} {} {}",
+            "Token Usage".bold().underline(),
+            "(".dimmed(),
+            format!("{} events", points.len()).dimmed()
+        );
+        println!();
+        for line in token_lines(&points) {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    if tools {
+        let calls = db.get_session_tool_calls(session_key).await?;
+        if calls.is_empty() {
+            println!("{}", "No tool calls found for this session.".yellow());
+            return Ok(());
+        }
+        println!(
+            "{} {} {}",
+            "Tool Calls".bold().underline(),
+            "(".dimmed(),
+            format!("{} calls", calls.len()).dimmed()
+        );
+        println!();
+        for line in tool_call_lines(&calls, &events) {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
 
     if events.is_empty() {
         println!("{}", "No events found for this session.".yellow());
@@ -56,38 +92,7 @@ pub async fn session(session_id: String) -> Result<(), Box<dyn std::error::Error
     println!();
 
     for (idx, event) in events.iter().enumerate() {
-        let role_label = event.role.as_deref().unwrap_or("-");
-        let role_colored = match role_label {
-            "user" => "USER".green().to_string(),
-            "assistant" => "ASSISTANT".blue().to_string(),
-            "system" => "SYSTEM".dimmed().to_string(),
-            _ => role_label.dimmed().to_string(),
-        };
-
-        let kind_label = match event.kind.as_str() {
-            "message" => "MSG",
-            "tool_call" => "TOOL",
-            "tool_result" => "RESULT",
-            "error" => "ERR",
-            "system" => "SYS",
-            _ => &event.kind.to_uppercase(),
-        };
-        let kind_colored = match event.kind.as_str() {
-            "message" => kind_label.to_string(),
-            "tool_call" => kind_label.yellow().to_string(),
-            "tool_result" => kind_label.yellow().to_string(),
-            "error" => kind_label.red().to_string(),
-            "system" => kind_label.dimmed().to_string(),
-            _ => kind_label.dimmed().to_string(),
-        };
-
-        print!(
-            "{:>3} {} {} {} ",
-            (idx + 1).to_string().dimmed(),
-            event.timestamp.split('T').next().unwrap_or("").dimmed(),
-            kind_colored,
-            role_colored
-        );
+        print!("{} ", event_header(idx + 1, event));
 
         if let Some(ref content) = event.content {
             let preview: String = content.lines().next().unwrap_or("").chars().take(60).collect();
@@ -100,6 +105,10 @@ pub async fn session(session_id: String) -> Result<(), Box<dyn std::error::Error
         }
 
         println!();
+
+        if raw {
+            print_raw_payload(&event.raw_payload);
+        }
     }
 
     println!();
@@ -110,3 +119,394 @@ pub async fn session(session_id: String) -> Result<(), Box<dyn std::error::Error
 
     Ok(())
 }
+
+/// Header lines describing a session, enriched with model, cost, and duration from its metrics.
+/// Fields without a value are left out.
+fn session_header(session: &SessionRow, metrics: Option<&SessionMetricsRow>) -> Vec<String> {
+    let mut lines = vec![
+        format!("{} {}", "ID:".dimmed(), session.id),
+        format!("{} {}", "External ID:".dimmed(), session.external_id.cyan()),
+        format!("{} {}", "Source:".dimmed(), session.source.cyan()),
+    ];
+    if let Some(ref project) = session.project {
+        lines.push(format!("{} {}", "Project:".dimmed(), project.cyan()));
+    }
+    if let Some(ref title) = session.title {
+        lines.push(format!("{} {}", "Title:".dimmed(), title.bold()));
+    }
+
+    if let Some(m) = metrics {
+        match (&m.model, &m.provider) {
+            (Some(model), Some(provider)) => lines.push(format!(
+                "{} {} {}",
+                "Model:".dimmed(),
+                model.cyan(),
+                format!("({})", provider).dimmed()
+            )),
+            (Some(model), None) => lines.push(format!("{} {}", "Model:".dimmed(), model.cyan())),
+            (None, Some(provider)) => lines.push(format!("{} {}", "Provider:".dimmed(), provider.cyan())),
+            (None, None) => {}
+        }
+        if let Some(cost) = m.estimated_cost {
+            lines.push(format!("{} ${:.4}", "Cost:".dimmed(), cost));
+        }
+        if let Some(duration) = m.duration_seconds {
+            lines.push(format!("{} {}", "Duration:".dimmed(), format_duration(duration)));
+        }
+    }
+
+    lines.push(format!("{} {}", "Created:".dimmed(), session.created_at));
+    lines.push(format!("{} {}", "Updated:".dimmed(), session.updated_at));
+    lines
+}
+
+/// Format seconds as e.g. `1h 02m`, `4m 05s`, or `42s`
+fn format_duration(seconds: i64) -> String {
+    let (hours, minutes, secs) = (seconds / 3600, (seconds % 3600) / 60, seconds % 60);
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Timeline header for an event: its position, date, and kind and role labels styled by event kind
+fn event_header(position: usize, event: &EventRow) -> String {
+    let kind_label = match event.kind.as_str() {
+        "message" => "MSG".to_string(),
+        "tool_call" => "TOOL".to_string(),
+        "tool_result" => "RESULT".to_string(),
+        "error" => "ERR".to_string(),
+        "system" => "SYS".to_string(),
+        other => other.to_uppercase(),
+    };
+    let role_label = event.role.as_deref().map_or_else(|| "-".to_string(), str::to_uppercase);
+    let style = event_style(&event.kind, event.role.as_deref());
+
+    format!(
+        "{:>3} {} {}",
+        position.to_string().dimmed(),
+        event.timestamp.split('T').next().unwrap_or("").dimmed(),
+        format!("{} {}", kind_label, role_label).style(style)
+    )
+}
+
+/// One line per tool call: start time, tool name, duration, outcome, and the calling event's
+/// arguments cut to their first line
+fn tool_call_lines(calls: &[ToolCallRow], events: &[EventRow]) -> Vec<String> {
+    calls
+        .iter()
+        .enumerate()
+        .map(|(idx, call)| {
+            let started = call
+                .started_at
+                .split('T')
+                .nth(1)
+                .map_or("", |time| truncate_chars(time, 8));
+            let duration = call.duration_ms.map_or_else(|| "-".to_string(), format_millis);
+            let outcome = match call.success {
+                Some(true) => "✓".green().to_string(),
+                Some(false) => "✗".red().to_string(),
+                None => "?".dimmed().to_string(),
+            };
+            let arguments = events
+                .iter()
+                .find(|e| e.id == call.event_id)
+                .and_then(|e| e.content.as_deref())
+                .and_then(|content| content.lines().next())
+                .map_or_else(String::new, |line| truncate_ellipsis(line, 80));
+
+            let mut line = format!(
+                "{:>3} {} {:<16} {:>8} {} {}",
+                (idx + 1).to_string().dimmed(),
+                started.dimmed(),
+                call.tool_name.cyan(),
+                duration,
+                outcome,
+                arguments
+            );
+            if let Some(ref error) = call.error_message {
+                line.push_str(&format!("\n    {}", error.red()));
+            }
+            line
+        })
+        .collect()
+}
+
+/// One line per event with token counts: time, input and output tokens, the running total, and a
+/// bar scaled to the largest event
+fn token_lines(points: &[TokenUsagePoint]) -> Vec<String> {
+    let largest = points
+        .iter()
+        .map(|p| p.input_tokens + p.output_tokens)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut running = 0;
+    points
+        .iter()
+        .enumerate()
+        .map(|(idx, point)| {
+            let time = point
+                .timestamp
+                .split('T')
+                .nth(1)
+                .map_or("", |time| truncate_chars(time, 8));
+            let total = point.input_tokens + point.output_tokens;
+            running += total;
+            let bar = "█".repeat((total * 20 / largest) as usize);
+            format!(
+                "{:>3} {} {:>8} in {:>7} out {:>10} total {}",
+                (idx + 1).to_string().dimmed(),
+                time.dimmed(),
+                point.input_tokens,
+                point.output_tokens,
+                running.to_string().cyan(),
+                bar.green()
+            )
+        })
+        .collect()
+}
+
+/// Format milliseconds as e.g. `850ms`, `1.5s`, or `4m 05s`
+fn format_millis(ms: i64) -> String {
+    if ms < 1_000 {
+        format!("{}ms", ms)
+    } else if ms < 60_000 {
+        format!("{:.1}s", ms as f64 / 1_000.0)
+    } else {
+        format_duration(ms / 1_000)
+    }
+}
+
+/// Print an event's stored raw payload, led by its source file line when recorded
+fn print_raw_payload(raw_payload: &str) {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(raw_payload) else {
+        println!("    {}", raw_payload.dimmed());
+        return;
+    };
+
+    if let Some(line) = Event::source_line(&value) {
+        println!("    {} {}", "Source line:".dimmed(), line.to_string().cyan());
+    }
+    let pretty = serde_json::to_string_pretty(&value).unwrap_or_else(|_| raw_payload.to_string());
+    for line in pretty.lines() {
+        println!("    {}", line.dimmed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn event(kind: &str, role: Option<&str>) -> EventRow {
+        EventRow {
+            id: "e1".to_string(),
+            session_id: "s1".to_string(),
+            kind: kind.to_string(),
+            role: role.map(str::to_string),
+            content: None,
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            raw_payload: "{}".to_string(),
+            category: None,
+            parent_id: None,
+        }
+    }
+
+    fn render(header: &str, choice: anstream::ColorChoice) -> String {
+        let mut out = anstream::AutoStream::new(Vec::new(), choice);
+        write!(out, "{}", header).unwrap();
+        String::from_utf8(out.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_session_header_includes_model_from_metrics() {
+        let session = SessionRow {
+            id: "s1".to_string(),
+            source: "claude".to_string(),
+            external_id: "ext1".to_string(),
+            project: None,
+            title: None,
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+            updated_at: "2025-01-01T00:01:30Z".to_string(),
+            raw_payload: "{}".to_string(),
+        };
+        let metrics = SessionMetricsRow {
+            session_id: "s1".to_string(),
+            total_events: 4,
+            message_count: 4,
+            tool_call_count: 0,
+            tool_result_count: 0,
+            error_count: 0,
+            user_messages: 2,
+            assistant_messages: 2,
+            duration_seconds: Some(90),
+            files_touched: 0,
+            lines_added: 0,
+            lines_removed: 0,
+            computed_at: "2025-01-01T00:02:00Z".to_string(),
+            model: Some("claude-sonnet-4".to_string()),
+            provider: None,
+            input_tokens: None,
+            output_tokens: None,
+            estimated_cost: None,
+            total_latency_ms: None,
+            avg_latency_ms: None,
+            p50_latency_ms: None,
+            p95_latency_ms: None,
+            reasoning_tokens: None,
+            reasoning_cost: None,
+            tokens_per_line: None,
+        };
+
+        let plain = |lines: Vec<String>| render(&lines.join("\n"), anstream::ColorChoice::Never);
+        let header = plain(session_header(&session, Some(&metrics)));
+        assert!(header.contains("Model: claude-sonnet-4\n"), "{}", header);
+        assert!(header.contains("Duration: 1m 30s"));
+        assert!(!header.contains("Project:"));
+        assert!(!header.contains("Cost:"));
+        assert!(!header.contains("Provider:"));
+
+        let without_metrics = plain(session_header(&session, None));
+        assert!(!without_metrics.contains("Model:"));
+        assert!(without_metrics.contains("Source: claude"));
+    }
+
+    /// A stored session with a prompt, one Bash call, and its result
+    async fn session_with_tool_call(db: &Database) -> SessionRow {
+        use agent_v_core::{EventKind, Role, Session, Source};
+
+        let started = chrono::Utc::now() - chrono::Duration::minutes(5);
+        let session = Session {
+            id: uuid::Uuid::new_v4(),
+            source: Source::Claude,
+            external_id: "show-tools".to_string(),
+            project: None,
+            title: None,
+            created_at: started,
+            updated_at: started,
+            raw_payload: serde_json::json!({}),
+        };
+        let event = |offset_ms: i64, kind: EventKind, role: Role, content: &str, blocks: serde_json::Value| Event {
+            id: uuid::Uuid::new_v4(),
+            session_id: session.id,
+            kind,
+            role: Some(role),
+            content: Some(content.to_string()),
+            timestamp: started + chrono::Duration::milliseconds(offset_ms),
+            raw_payload: serde_json::json!({"message": {"content": blocks}}),
+            parent_id: None,
+        };
+        let events = vec![
+            event(
+                0,
+                EventKind::Message,
+                Role::User,
+                "list the files",
+                serde_json::json!("list the files"),
+            ),
+            event(
+                100,
+                EventKind::ToolCall,
+                Role::Assistant,
+                "Bash: ls -la",
+                serde_json::json!([{"type": "tool_use", "id": "toolu_1", "name": "Bash", "input": {"command": "ls -la"}}]),
+            ),
+            event(
+                1600,
+                EventKind::ToolResult,
+                Role::User,
+                "main.rs",
+                serde_json::json!([{"type": "tool_result", "tool_use_id": "toolu_1", "content": "main.rs"}]),
+            ),
+        ];
+        db.insert_session_with_events(&session, &events).await.unwrap();
+        find_session(db, "show-tools", None).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_json_includes_every_event_with_its_raw_payload() {
+        let db = Database::open_in_memory().await.unwrap();
+        let session = session_with_tool_call(&db).await;
+        let events = db.get_session_events(session.session_id().unwrap()).await.unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&export_session_to_json(&session, &events, None).await.unwrap()).unwrap();
+        assert_eq!(json["external_id"], "show-tools");
+        let exported = json["events"].as_array().unwrap();
+        assert_eq!(exported.len(), 3);
+        assert_eq!(exported[1]["kind"], "tool_call");
+        assert_eq!(exported[1]["raw_payload"]["message"]["content"][0]["name"], "Bash");
+    }
+
+    #[tokio::test]
+    async fn test_tools_view_lists_only_tool_calls() {
+        let db = Database::open_in_memory().await.unwrap();
+        let session = session_with_tool_call(&db).await;
+        let session_key = session.session_id().unwrap();
+        let calls = db.get_session_tool_calls(session_key).await.unwrap();
+        let events = db.get_session_events(session_key).await.unwrap();
+
+        let lines: Vec<String> = tool_call_lines(&calls, &events)
+            .iter()
+            .map(|line| render(line, anstream::ColorChoice::Never))
+            .collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("Bash"), "{}", lines[0]);
+        assert!(lines[0].contains("1.5s ✓ Bash: ls -la"), "{}", lines[0]);
+        assert!(!lines[0].contains("list the files"));
+    }
+
+    #[test]
+    fn test_token_lines_keep_a_running_total() {
+        let point = |timestamp: &str, input_tokens, output_tokens| TokenUsagePoint {
+            event_id: "e".to_string(),
+            timestamp: timestamp.to_string(),
+            input_tokens,
+            output_tokens,
+        };
+        let lines: Vec<String> = token_lines(&[
+            point("2025-01-01T10:00:00+00:00", 1000, 200),
+            point("2025-01-01T10:00:30+00:00", 1500, 120),
+        ])
+        .iter()
+        .map(|line| render(line, anstream::ColorChoice::Never))
+        .collect();
+
+        assert!(
+            lines[0].contains("10:00:00     1000 in     200 out       1200 total"),
+            "{}",
+            lines[0]
+        );
+        assert!(lines[1].contains("2820 total"), "{}", lines[1]);
+        assert!(lines[1].ends_with(&"█".repeat(20)), "{}", lines[1]);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_session_exits_with_not_found_code() {
+        let db = Database::open_in_memory().await.unwrap();
+
+        let err = find_session(&db, "no-such-session", None).await.unwrap_err();
+        assert_eq!(err.to_string(), "Session not found: no-such-session");
+        assert_eq!(AgentVizError::from(err).exit_code(), crate::error::exit_code::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_error_event_header_is_red() {
+        let header = event_header(1, &event("error", None));
+        let colored = render(&header, anstream::ColorChoice::Always);
+        assert!(colored.contains("\x1b[31mERR -"), "{:?}", colored);
+
+        let assistant = render(
+            &event_header(2, &event("message", Some("assistant"))),
+            anstream::ColorChoice::Always,
+        );
+        assert!(assistant.contains("\x1b[36mMSG ASSISTANT"), "{:?}", assistant);
+
+        let plain = render(&header, anstream::ColorChoice::Never);
+        assert_eq!(plain, "  1 2025-01-01 ERR -");
+    }
+}
@@ -1,32 +1,273 @@
-use agent_v_store::Database;
-use chrono::{DateTime, Duration, Utc};
+use crate::error::AgentVizError;
+use crate::util::{parse_window, truncate_ellipsis};
+use agent_v_store::{CostStats, Database, FileGrouping, GroupedStats, StorageStats};
+use anstream::{print, println};
+use chrono::{DateTime, Utc};
 use owo_colors::OwoColorize;
+use std::fmt::Write;
+
+/// Output format for the stats command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsFormat {
+    Text,
+    /// `{"dimension": ..., "rows": [...]}` for the breakdown picked with `--by`
+    Json,
+    /// Prometheus text exposition format, for the node_exporter textfile collector
+    Prometheus,
+}
+
+impl StatsFormat {
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(StatsFormat::Text),
+            "json" => Ok(StatsFormat::Json),
+            "prometheus" | "prom" => Ok(StatsFormat::Prometheus),
+            _ => Err(format!("Unknown format: {}. Use 'text', 'json' or 'prometheus'", s)),
+        }
+    }
+}
+
+/// Filters applied to the cost and model breakdowns
+#[derive(Debug, Clone, Default)]
+pub struct CostFilter {
+    pub source: Option<String>,
+    pub model: Option<String>,
+    /// Leave reasoning-token cost out of cost totals
+    pub exclude_reasoning: bool,
+}
+
+/// Window applied when neither `--since` nor `--all` is given
+pub const DEFAULT_SINCE: &str = "30d";
+
+/// Environment variable overriding [`DEFAULT_SINCE`], e.g. `7d`, or `all` to scan all time by default
+pub const SINCE_ENV: &str = "AGENT_VIZ_STATS_SINCE";
+
+/// Pick the stats window: `--all` disables it, an explicit `--since` wins, and otherwise
+/// the configured default applies (falling back to [`DEFAULT_SINCE`] when unset).
+pub fn resolve_since(since: Option<String>, all: bool, configured: Option<String>) -> Option<String> {
+    if all {
+        return None;
+    }
+    if since.is_some() {
+        return since;
+    }
+    match configured.as_deref().map(str::trim) {
+        None | Some("") => Some(DEFAULT_SINCE.to_string()),
+        Some(window) if window.eq_ignore_ascii_case("all") => None,
+        Some(window) => Some(window.to_string()),
+    }
+}
 
 /// Run the stats command
-pub async fn run(by: Option<String>, since: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn run(
+    by: Option<String>, since: Option<String>, until: Option<String>, cost_filter: CostFilter, format: StatsFormat,
+    file_grouping: FileGrouping,
+) -> Result<(), Box<dyn std::error::Error>> {
     let db = Database::open_default().await?;
     db.migrate().await?;
 
-    let since_dt = parse_since(&since)?;
-    let until_dt = Some(Utc::now());
+    let window = parse_window(since.as_deref(), until.as_deref()).map_err(AgentVizError::Usage)?;
+    let (since_dt, until_dt) = (window.since, window.until.or_else(|| Some(Utc::now())));
+
+    if format == StatsFormat::Prometheus {
+        let metrics = PrometheusSnapshot {
+            sessions: db.get_stats_by_source().await?,
+            storage: db.get_storage_by_source().await?,
+            costs: db
+                .get_cost_stats_by_source(None, since_dt, until_dt, cost_filter.exclude_reasoning, None)
+                .await?,
+            errors: db
+                .get_errors_by_day(since_dt, until_dt)
+                .await?
+                .iter()
+                .map(|e| e.error_count)
+                .sum(),
+            tool_error_rate: db.get_efficiency_stats(None, since_dt, until_dt).await?.tool_error_rate,
+        };
+        print!("{}", metrics.render());
+        return Ok(());
+    }
+
+    let dimension = Dimension::from_by(by.as_deref());
+    if format == StatsFormat::Json {
+        let rows = json_rows(&db, dimension, since_dt, until_dt, &cost_filter, file_grouping).await?;
+        println!("{}", serde_json::to_string_pretty(&json_envelope(dimension, rows))?);
+        return Ok(());
+    }
 
-    match by.as_deref() {
-        Some("day") | Some("daily") => show_activity_by_day(&db, since_dt, until_dt).await?,
-        Some("source") => show_stats_by_source(&db).await?,
-        Some("project") => show_stats_by_project(&db, None).await?,
-        Some("tool") => show_stats_by_tool(&db, since_dt, until_dt).await?,
-        Some("error") | Some("errors") => show_error_stats(&db, since_dt, until_dt).await?,
-        Some("tools") | Some("tool-calls") => show_tool_call_frequency(&db, since_dt, until_dt).await?,
-        Some("files") => show_files_leaderboard(&db, since_dt, until_dt).await?,
-        Some("churn") => show_patch_churn(&db, since_dt, until_dt).await?,
-        Some("latency") | Some("slow") => show_long_running_tools(&db, since_dt, until_dt).await?,
-        Some("cost") => show_cost_stats(&db, since_dt, until_dt).await?,
-        _ => show_summary(&db).await?,
+    match dimension {
+        Dimension::Day => show_activity_by_day(&db, since_dt, until_dt).await?,
+        Dimension::Weekday => show_activity_by_weekday(&db, since_dt, until_dt).await?,
+        Dimension::Source => show_stats_by_source(&db).await?,
+        Dimension::Project => show_stats_by_project(&db, None).await?,
+        Dimension::Tool => show_stats_by_tool(&db, since_dt, until_dt).await?,
+        Dimension::Errors => show_error_stats(&db, since_dt, until_dt).await?,
+        Dimension::ToolCalls => show_tool_call_frequency(&db, since_dt, until_dt).await?,
+        Dimension::Files => show_files_leaderboard(&db, since_dt, until_dt, file_grouping).await?,
+        Dimension::Churn => show_patch_churn(&db, since_dt, until_dt).await?,
+        Dimension::Latency => show_long_running_tools(&db, since_dt, until_dt).await?,
+        Dimension::Cost => show_cost_stats(&db, since_dt, until_dt, &cost_filter).await?,
+        Dimension::Model => show_model_stats(&db, since_dt, until_dt, &cost_filter).await?,
+        Dimension::Efficiency => show_efficiency_stats(&db, since_dt, until_dt).await?,
+        Dimension::KindSource => show_kind_distribution(&db, since_dt, until_dt).await?,
+        Dimension::Storage => show_storage_stats(&db).await?,
+        Dimension::Summary => show_summary(&db).await?,
     }
 
     Ok(())
 }
 
+/// Breakdown picked with `--by`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Summary,
+    Day,
+    Weekday,
+    Source,
+    Project,
+    Tool,
+    Errors,
+    ToolCalls,
+    Files,
+    Churn,
+    Latency,
+    Cost,
+    Model,
+    Efficiency,
+    KindSource,
+    Storage,
+}
+
+impl Dimension {
+    /// The breakdown for a `--by` value; anything unrecognized shows the summary
+    fn from_by(by: Option<&str>) -> Self {
+        match by {
+            Some("day") | Some("daily") => Dimension::Day,
+            Some("weekday") => Dimension::Weekday,
+            Some("source") => Dimension::Source,
+            Some("project") => Dimension::Project,
+            Some("tool") => Dimension::Tool,
+            Some("error") | Some("errors") => Dimension::Errors,
+            Some("tools") | Some("tool-calls") => Dimension::ToolCalls,
+            Some("files") => Dimension::Files,
+            Some("churn") => Dimension::Churn,
+            Some("latency") | Some("slow") => Dimension::Latency,
+            Some("cost") => Dimension::Cost,
+            Some("model") | Some("models") => Dimension::Model,
+            Some("efficiency") => Dimension::Efficiency,
+            Some("kind-source") => Dimension::KindSource,
+            Some("storage") => Dimension::Storage,
+            _ => Dimension::Summary,
+        }
+    }
+
+    /// Name reported in JSON output, the first `--by` spelling that selects this breakdown
+    fn name(self) -> &'static str {
+        match self {
+            Dimension::Summary => "summary",
+            Dimension::Day => "day",
+            Dimension::Weekday => "weekday",
+            Dimension::Source => "source",
+            Dimension::Project => "project",
+            Dimension::Tool => "tool",
+            Dimension::Errors => "error",
+            Dimension::ToolCalls => "tools",
+            Dimension::Files => "files",
+            Dimension::Churn => "churn",
+            Dimension::Latency => "latency",
+            Dimension::Cost => "cost",
+            Dimension::Model => "model",
+            Dimension::Efficiency => "efficiency",
+            Dimension::KindSource => "kind-source",
+            Dimension::Storage => "storage",
+        }
+    }
+}
+
+/// The rows behind a breakdown's text output. Breakdowns that print several sections (summary,
+/// cost, efficiency, storage) tag each row with the section it belongs to under `group`.
+async fn json_rows(
+    db: &Database, dimension: Dimension, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+    filter: &CostFilter, file_grouping: FileGrouping,
+) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    let rows = match dimension {
+        Dimension::Summary => [
+            grouped_rows("source", db.get_stats_by_source().await?)?,
+            grouped_rows("project", db.get_stats_by_project(None).await?)?,
+            grouped_rows("kind", db.get_stats_by_tool(None, None).await?)?,
+        ]
+        .concat(),
+        Dimension::Day => json_array(db.get_activity_by_day(since, until, None).await?)?,
+        Dimension::Weekday => json_array(db.get_activity_by_weekday(since, until).await?)?,
+        Dimension::Source => json_array(db.get_stats_by_source().await?)?,
+        Dimension::Project => json_array(db.get_stats_by_project(None).await?)?,
+        Dimension::Tool => json_array(db.get_stats_by_tool(since, until).await?)?,
+        Dimension::Errors => json_array(db.get_errors_by_day(since, until).await?)?,
+        Dimension::ToolCalls => json_array(db.get_tool_call_frequency(since, until).await?)?,
+        Dimension::Files => json_array(
+            db.get_files_leaderboard_grouped(since, until, 20, file_grouping)
+                .await?,
+        )?,
+        Dimension::Churn => json_array(db.get_patch_churn_by_day(since, until).await?)?,
+        Dimension::Latency => json_array(db.get_long_running_tool_calls(since, until, 5000, 20).await?)?,
+        Dimension::Cost => {
+            let (source, model) = (filter.source.clone(), filter.model.clone());
+            [
+                grouped_rows(
+                    "source",
+                    db.get_cost_stats_by_source(source.clone(), since, until, filter.exclude_reasoning, model.clone())
+                        .await?,
+                )?,
+                grouped_rows(
+                    "project",
+                    db.get_cost_stats_by_project(source.clone(), since, until, filter.exclude_reasoning, model.clone())
+                        .await?,
+                )?,
+                grouped_rows("model", db.get_model_usage_stats(source, since, until, model).await?)?,
+            ]
+            .concat()
+        }
+        Dimension::Model => json_array(
+            db.get_model_usage_stats(filter.source.clone(), since, until, filter.model.clone())
+                .await?,
+        )?,
+        Dimension::Efficiency => [
+            grouped_rows("overall", vec![db.get_efficiency_stats(None, since, until).await?])?,
+            grouped_rows(
+                "session",
+                db.get_sessions_by_tokens_per_line(None, since, until, 20).await?,
+            )?,
+        ]
+        .concat(),
+        Dimension::KindSource => json_array(db.get_kind_distribution_by_source(since, until).await?)?,
+        Dimension::Storage => [
+            grouped_rows("source", db.get_storage_by_source().await?)?,
+            grouped_rows("session", db.get_storage_by_session(10).await?)?,
+        ]
+        .concat(),
+    };
+    Ok(rows)
+}
+
+fn json_array<T: serde::Serialize>(rows: Vec<T>) -> Result<Vec<serde_json::Value>, serde_json::Error> {
+    rows.into_iter().map(serde_json::to_value).collect()
+}
+
+/// Serialize rows with a `group` field naming the section they came from
+fn grouped_rows<T: serde::Serialize>(group: &str, rows: Vec<T>) -> Result<Vec<serde_json::Value>, serde_json::Error> {
+    let mut rows = json_array(rows)?;
+    for row in &mut rows {
+        if let Some(fields) = row.as_object_mut() {
+            fields.insert("group".to_string(), group.into());
+        }
+    }
+    Ok(rows)
+}
+
+fn json_envelope(dimension: Dimension, rows: Vec<serde_json::Value>) -> serde_json::Value {
+    serde_json::json!({ "dimension": dimension.name(), "rows": rows })
+}
+
 async fn show_summary(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", "Statistics Summary".bold().underline());
     println!();
@@ -96,11 +337,13 @@ async fn show_activity_by_day(
         let padding = " ".repeat(bar_width as usize - bar_len);
 
         println!(
-            "  {}  {:4} events  {}{}  ({} sessions)",
+            "  {}  {:4} events  {}{}  {} {}  ({} sessions)",
             stat.day.to_string().dimmed(),
             stat.event_count,
             bar.green(),
             padding,
+            "7d avg:".dimmed(),
+            format!("{:6.1}", stat.rolling_avg_7d).cyan(),
             stat.session_count
         );
     }
@@ -123,6 +366,40 @@ async fn show_activity_by_day(
     Ok(())
 }
 
+async fn show_activity_by_weekday(
+    db: &Database, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", "Activity by Weekday".bold().underline());
+    println!();
+
+    let stats = db.get_activity_by_weekday(since, until).await?;
+
+    let max_events: i64 = stats.iter().map(|s| s.event_count).max().unwrap_or(0);
+    if max_events == 0 {
+        println!("{}", "No activity found.".yellow());
+        return Ok(());
+    }
+
+    let bar_width = 40u64;
+
+    for stat in &stats {
+        let bar_len = ((stat.event_count as f64 / max_events as f64) * bar_width as f64) as usize;
+        let bar = "█".repeat(bar_len);
+        let padding = " ".repeat(bar_width as usize - bar_len);
+
+        println!(
+            "  {}  {:6} events  {}{}  ({} sessions)",
+            stat.weekday.to_string().dimmed(),
+            stat.event_count,
+            bar.green(),
+            padding,
+            stat.session_count
+        );
+    }
+
+    Ok(())
+}
+
 async fn show_stats_by_source(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", "Statistics by Source".bold().underline());
     println!();
@@ -181,6 +458,110 @@ async fn show_stats_by_tool(
     Ok(())
 }
 
+async fn show_kind_distribution(
+    db: &Database, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", "Event Kinds by Source".bold().underline());
+    println!();
+
+    let stats = db.get_kind_distribution_by_source(since, until).await?;
+
+    if stats.is_empty() {
+        println!("{}", "No events found.".yellow());
+        return Ok(());
+    }
+
+    let mut kinds: Vec<&str> = ["message", "tool_call", "tool_result", "error", "system"]
+        .into_iter()
+        .filter(|kind| stats.iter().any(|s| s.counts.contains_key(*kind)))
+        .collect();
+    for stat in &stats {
+        for kind in stat.counts.keys() {
+            if !kinds.contains(&kind.as_str()) {
+                kinds.push(kind);
+            }
+        }
+    }
+
+    let header: String = kinds.iter().map(|kind| format!("{:>12}", kind)).collect();
+    println!("  {:12}{}{:>10}", "source".bold(), header.bold(), "total".bold());
+
+    for stat in &stats {
+        let cells: String = kinds
+            .iter()
+            .map(|kind| format!("{:>12}", stat.counts.get(*kind).copied().unwrap_or(0)))
+            .collect();
+        println!("  {:12}{}{:>10}", stat.source.cyan(), cells, stat.total);
+    }
+
+    Ok(())
+}
+
+async fn show_storage_stats(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", "Storage by Source".bold().underline());
+    println!();
+
+    let by_source = db.get_storage_by_source().await?;
+    if by_source.is_empty() {
+        println!("{}", "No data stored.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "  {:12} {:>9} {:>9} {:>12} {:>12}",
+        "source".bold(),
+        "sessions".bold(),
+        "events".bold(),
+        "content".bold(),
+        "raw".bold()
+    );
+    for stat in &by_source {
+        println!(
+            "  {:12} {:>9} {:>9} {:>12} {:>12}",
+            stat.dimension.cyan(),
+            stat.session_count,
+            stat.event_count,
+            format_bytes(stat.content_bytes),
+            format_bytes(stat.raw_bytes).yellow()
+        );
+    }
+
+    let total_raw: i64 = by_source.iter().map(|s| s.raw_bytes).sum();
+    let total_content: i64 = by_source.iter().map(|s| s.content_bytes).sum();
+    println!();
+    println!(
+        "  {}: {} content, {} raw payload",
+        "Total".bold(),
+        format_bytes(total_content),
+        format_bytes(total_raw)
+    );
+
+    println!();
+    println!("{}", "Largest Sessions:".bold());
+    for stat in db.get_storage_by_session(10).await? {
+        println!(
+            "  {} {:10} {:>6} events  {:>12} raw",
+            stat.dimension[..8.min(stat.dimension.len())].to_string().cyan(),
+            stat.source.dimmed(),
+            stat.event_count,
+            format_bytes(stat.raw_bytes).yellow()
+        );
+    }
+
+    Ok(())
+}
+
+fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} {}", bytes, UNITS[0]) } else { format!("{:.1} {}", value, UNITS[unit]) }
+}
+
 async fn show_error_stats(
     db: &Database, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
 ) -> Result<(), Box<dyn std::error::Error>> {
@@ -214,7 +595,7 @@ async fn show_error_stats(
             .lines()
             .next()
             .unwrap_or("Unknown");
-        let preview = if signature.len() > 60 { format!("{}...", &signature[..60]) } else { signature.to_string() };
+        let preview = truncate_ellipsis(signature, 60);
 
         println!("    {:3} × {}", stat.error_count, preview.red());
         day_count += 1;
@@ -231,7 +612,7 @@ async fn show_error_stats(
     let top_errors = db.get_top_errors(since, until, 10).await?;
 
     for (idx, (signature, count)) in top_errors.iter().enumerate() {
-        let preview = if signature.len() > 60 { format!("{}...", &signature[..60]) } else { signature.clone() };
+        let preview = truncate_ellipsis(signature, 60);
         println!("  {}. {:4} × {}", idx + 1, count, preview.red());
     }
 
@@ -294,12 +675,17 @@ async fn show_tool_call_frequency(
 }
 
 async fn show_files_leaderboard(
-    db: &Database, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+    db: &Database, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, grouping: FileGrouping,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", "Files Touched Leaderboard".bold().underline());
+    let title = match grouping {
+        FileGrouping::Extension => "Files Touched Leaderboard by Extension",
+        FileGrouping::Directory => "Files Touched Leaderboard by Directory",
+        FileGrouping::None => "Files Touched Leaderboard",
+    };
+    println!("{}", title.bold().underline());
     println!();
 
-    let stats = db.get_files_leaderboard(since, until, 20).await?;
+    let stats = db.get_files_leaderboard_grouped(since, until, 20, grouping).await?;
 
     if stats.is_empty() {
         println!("{}", "No files touched found.".yellow());
@@ -419,12 +805,29 @@ async fn show_long_running_tools(
 }
 
 async fn show_cost_stats(
-    db: &Database, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+    db: &Database, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, filter: &CostFilter,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", "Cost & Latency Statistics".bold().underline());
+    if filter.exclude_reasoning {
+        println!("  {}", "(excluding reasoning-token cost)".dimmed());
+    }
+    if let Some(model) = &filter.model {
+        println!("  {}", format!("(model: {})", model).dimmed());
+    }
+    if let Some(source) = &filter.source {
+        println!("  {}", format!("(source: {})", source).dimmed());
+    }
     println!();
 
-    let by_source = db.get_cost_stats_by_source(None, since, until).await?;
+    let by_source = db
+        .get_cost_stats_by_source(
+            filter.source.clone(),
+            since,
+            until,
+            filter.exclude_reasoning,
+            filter.model.clone(),
+        )
+        .await?;
     println!("{}", "By Source:".bold());
     if by_source.is_empty() || by_source.iter().all(|s| s.total_cost.is_none()) {
         println!(
@@ -462,7 +865,15 @@ async fn show_cost_stats(
     }
     println!();
 
-    let by_project = db.get_cost_stats_by_project(None, since, until).await?;
+    let by_project = db
+        .get_cost_stats_by_project(
+            filter.source.clone(),
+            since,
+            until,
+            filter.exclude_reasoning,
+            filter.model.clone(),
+        )
+        .await?;
     println!("{}", "By Project:".bold());
     if by_project.is_empty() || by_project.iter().all(|s| s.total_cost.is_none()) {
         println!("  {}", "No cost data available.".yellow());
@@ -500,7 +911,7 @@ async fn show_cost_stats(
     }
     println!();
 
-    let latency_dist = db.get_latency_distribution(None, since, until).await?;
+    let latency_dist = db.get_latency_distribution(filter.source.clone(), since, until).await?;
     println!("{}", "Latency Distribution:".bold());
     if latency_dist.session_count > 0 {
         let avg = latency_dist
@@ -528,8 +939,31 @@ async fn show_cost_stats(
     }
     println!();
 
-    let model_stats = db.get_model_usage_stats(None, since, until).await?;
     println!("{}", "By Model/Provider:".bold());
+    print_model_usage(db, since, until, filter).await
+}
+
+async fn show_model_stats(
+    db: &Database, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, filter: &CostFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", "Model Usage".bold().underline());
+    if let Some(model) = &filter.model {
+        println!("  {}", format!("(model: {})", model).dimmed());
+    }
+    if let Some(source) = &filter.source {
+        println!("  {}", format!("(source: {})", source).dimmed());
+    }
+    println!();
+    print_model_usage(db, since, until, filter).await
+}
+
+/// One line per model with its sessions, tokens, total cost, and average latency
+async fn print_model_usage(
+    db: &Database, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, filter: &CostFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let model_stats = db
+        .get_model_usage_stats(filter.source.clone(), since, until, filter.model.clone())
+        .await?;
     if model_stats.is_empty() || model_stats.iter().all(|s| s.total_cost.is_none()) {
         println!("  {}", "No model usage data available.".yellow());
     } else {
@@ -562,26 +996,293 @@ async fn show_cost_stats(
     Ok(())
 }
 
-fn parse_since(since: &Option<String>) -> Result<Option<DateTime<Utc>>, Box<dyn std::error::Error>> {
-    let Some(s) = since else {
-        return Ok(None);
-    };
+async fn show_efficiency_stats(
+    db: &Database, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", "Efficiency Statistics".bold().underline());
+    println!();
 
-    let duration = if s.ends_with('d') {
-        let days: i64 = s[..s.len() - 1].parse()?;
-        Duration::days(days)
-    } else if s.ends_with('h') {
-        let hours: i64 = s[..s.len() - 1].parse()?;
-        Duration::hours(hours)
-    } else if s.ends_with('w') {
-        let weeks: i64 = s[..s.len() - 1].parse()?;
-        Duration::weeks(weeks)
-    } else if s.ends_with('m') && !s.ends_with("min") {
-        let months: i64 = s[..s.len() - 1].parse()?;
-        Duration::days(months * 30)
-    } else {
-        return Err(format!("Invalid duration format: {}. Use Nd, Nh, Nw, Nm", s).into());
-    };
+    let stats = db.get_efficiency_stats(None, since, until).await?;
+    let tokens_per_line = stats
+        .avg_tokens_per_line
+        .map(|t| format!("{:.1}", t))
+        .unwrap_or_else(|| "-".to_string());
+    println!("  {:22} {}", "Sessions:".bold(), stats.total_sessions);
+    println!(
+        "  {:22} {:.1}%",
+        "Tool error rate:".bold(),
+        stats.tool_error_rate * 100.0
+    );
+    println!("  {:22} {}", "Retry loops:".bold(), stats.retry_loops);
+    println!("  {:22} {}", "Avg tokens/line:".bold(), tokens_per_line.yellow());
+    println!();
+
+    let sessions = db.get_sessions_by_tokens_per_line(None, since, until, 20).await?;
+    println!("{}", "Most Economical Sessions (tokens per changed line):".bold());
+    if sessions.is_empty() {
+        println!("  {}", "No sessions with file changes found.".yellow());
+        return Ok(());
+    }
 
-    Ok(Some(Utc::now() - duration))
+    for session in sessions {
+        let label = session.project.as_deref().unwrap_or(&session.external_id);
+        println!(
+            "  {:10} {:40} {:>10.1}  {} tokens / {} lines",
+            session.source.cyan(),
+            label,
+            session.tokens_per_line.yellow(),
+            session.total_tokens,
+            session.lines_changed
+        );
+    }
+
+    Ok(())
+}
+
+/// Source, cost and error aggregates exported as Prometheus metrics
+struct PrometheusSnapshot {
+    sessions: Vec<GroupedStats>,
+    storage: Vec<StorageStats>,
+    costs: Vec<CostStats>,
+    errors: i64,
+    tool_error_rate: f64,
+}
+
+impl PrometheusSnapshot {
+    fn render(&self) -> String {
+        let mut out = String::new();
+
+        write_metric(
+            &mut out,
+            "agentviz_sessions_total",
+            "Sessions ingested per source",
+            "counter",
+            &self
+                .sessions
+                .iter()
+                .map(|s| (s.dimension.clone(), s.count as f64))
+                .collect::<Vec<_>>(),
+        );
+        write_metric(
+            &mut out,
+            "agentviz_events_total",
+            "Events ingested per source",
+            "counter",
+            &self
+                .storage
+                .iter()
+                .map(|s| (s.dimension.clone(), s.event_count as f64))
+                .collect::<Vec<_>>(),
+        );
+        write_metric(
+            &mut out,
+            "agentviz_stored_bytes",
+            "Raw payload bytes stored per source",
+            "gauge",
+            &self
+                .storage
+                .iter()
+                .map(|s| (s.dimension.clone(), s.raw_bytes as f64))
+                .collect::<Vec<_>>(),
+        );
+        write_metric(
+            &mut out,
+            "agentviz_cost_usd_total",
+            "Estimated model cost in USD per source",
+            "counter",
+            &self
+                .costs
+                .iter()
+                .filter_map(|c| c.total_cost.map(|cost| (c.dimension.clone(), cost)))
+                .collect::<Vec<_>>(),
+        );
+        write_metric(
+            &mut out,
+            "agentviz_input_tokens_total",
+            "Input tokens per source",
+            "counter",
+            &self
+                .costs
+                .iter()
+                .filter_map(|c| c.total_input_tokens.map(|t| (c.dimension.clone(), t as f64)))
+                .collect::<Vec<_>>(),
+        );
+        write_metric(
+            &mut out,
+            "agentviz_output_tokens_total",
+            "Output tokens per source",
+            "counter",
+            &self
+                .costs
+                .iter()
+                .filter_map(|c| c.total_output_tokens.map(|t| (c.dimension.clone(), t as f64)))
+                .collect::<Vec<_>>(),
+        );
+
+        let _ = writeln!(out, "# HELP agentviz_errors_total Error events across all sources");
+        let _ = writeln!(out, "# TYPE agentviz_errors_total counter");
+        let _ = writeln!(out, "agentviz_errors_total {}", self.errors);
+        let _ = writeln!(
+            out,
+            "# HELP agentviz_tool_error_ratio Share of recorded tool calls that failed"
+        );
+        let _ = writeln!(out, "# TYPE agentviz_tool_error_ratio gauge");
+        let _ = writeln!(out, "agentviz_tool_error_ratio {}", self.tool_error_rate);
+
+        out
+    }
+}
+
+/// Write one metric family with a `source` label per sample. Families without samples are omitted.
+fn write_metric(out: &mut String, name: &str, help: &str, kind: &str, samples: &[(String, f64)]) {
+    if samples.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for (source, value) in samples {
+        let _ = writeln!(out, "{}{{source=\"{}\"}} {}", name, escape_label(source), value);
+    }
+}
+
+/// Escape a label value per the Prometheus text format
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_window_defaults_to_configured_since() {
+        assert_eq!(resolve_since(None, false, None).as_deref(), Some(DEFAULT_SINCE));
+        assert_eq!(
+            resolve_since(None, false, Some("7d".to_string())).as_deref(),
+            Some("7d")
+        );
+        assert_eq!(
+            resolve_since(Some("90d".to_string()), false, Some("7d".to_string())).as_deref(),
+            Some("90d")
+        );
+
+        assert_eq!(resolve_since(None, true, Some("7d".to_string())), None);
+        assert_eq!(resolve_since(None, false, Some("all".to_string())), None);
+    }
+
+    #[test]
+    fn test_by_picks_the_breakdown() {
+        assert_eq!(Dimension::from_by(Some("model")), Dimension::Model);
+        assert_eq!(Dimension::from_by(Some("models")), Dimension::Model);
+        assert_eq!(Dimension::from_by(Some("cost")), Dimension::Cost);
+        assert_eq!(Dimension::from_by(Some("daily")), Dimension::Day);
+        assert_eq!(Dimension::from_by(Some("provider")), Dimension::Summary);
+        assert_eq!(Dimension::from_by(None), Dimension::Summary);
+    }
+
+    fn grouped(source: &str, count: i64) -> GroupedStats {
+        GroupedStats { dimension: source.to_string(), count, sessions: None, earliest: None, latest: None }
+    }
+
+    #[test]
+    fn test_prometheus_output_has_metric_line_per_source() {
+        let snapshot = PrometheusSnapshot {
+            sessions: vec![grouped("claude", 42), grouped("codex", 7)],
+            storage: Vec::new(),
+            costs: vec![CostStats {
+                dimension: "claude".to_string(),
+                session_count: 42,
+                total_cost: Some(1.25),
+                avg_cost_per_session: None,
+                total_input_tokens: Some(1000),
+                total_output_tokens: None,
+                avg_latency_ms: None,
+                p50_latency_ms: None,
+                p95_latency_ms: None,
+            }],
+            errors: 3,
+            tool_error_rate: 0.5,
+        };
+        let output = snapshot.render();
+
+        assert!(output.contains("agentviz_sessions_total{source=\"claude\"} 42\n"));
+        assert!(output.contains("agentviz_sessions_total{source=\"codex\"} 7\n"));
+        assert!(output.contains("agentviz_cost_usd_total{source=\"claude\"} 1.25\n"));
+        assert!(output.contains("agentviz_errors_total 3\n"));
+        assert!(!output.contains("agentviz_events_total"));
+
+        for line in output.lines().filter(|l| !l.starts_with('#')) {
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            assert!(value.parse::<f64>().is_ok(), "bad value in {:?}", line);
+            assert!(series.starts_with("agentviz_"), "bad name in {:?}", line);
+        }
+        assert_eq!(escape_label("a\"b"), "a\\\"b");
+    }
+
+    #[test]
+    fn test_json_by_source_is_a_parseable_envelope() {
+        assert_eq!(StatsFormat::from_str("JSON"), Ok(StatsFormat::Json));
+
+        let rows = json_array(vec![grouped("claude", 42), grouped("codex", 7)]).unwrap();
+        let output = serde_json::to_string_pretty(&json_envelope(Dimension::from_by(Some("source")), rows)).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(parsed["dimension"], "source");
+        assert_eq!(parsed["rows"].as_array().unwrap().len(), 2);
+        assert_eq!(parsed["rows"][0]["dimension"], "claude");
+        assert_eq!(parsed["rows"][1]["count"], 7);
+
+        let tagged = grouped_rows("kind", vec![grouped("message", 3)]).unwrap();
+        assert_eq!(tagged[0]["group"], "kind");
+    }
+
+    #[tokio::test]
+    async fn test_bounded_window_keeps_only_activity_between_since_and_until() {
+        use agent_v_core::{Event, EventKind, Session, Source};
+
+        let db = Database::open_in_memory().await.unwrap();
+        db.migrate().await.unwrap();
+        let now = Utc::now();
+        let session = Session {
+            id: uuid::Uuid::new_v4(),
+            source: Source::Claude,
+            external_id: "window".to_string(),
+            project: None,
+            title: None,
+            created_at: now - chrono::Duration::days(20),
+            updated_at: now - chrono::Duration::days(2),
+            raw_payload: serde_json::json!({}),
+        };
+        let events: Vec<Event> = [20, 10, 9, 2]
+            .into_iter()
+            .map(|days_ago| Event {
+                id: uuid::Uuid::new_v4(),
+                session_id: session.id,
+                kind: EventKind::Message,
+                role: None,
+                content: Some(format!("{} days ago", days_ago)),
+                timestamp: now - chrono::Duration::days(days_ago),
+                raw_payload: serde_json::json!({}),
+                parent_id: None,
+            })
+            .collect();
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let window = parse_window(Some("15d"), Some("7d")).unwrap();
+        let filter = CostFilter { source: None, model: None, exclude_reasoning: false };
+        let rows = json_rows(
+            &db,
+            Dimension::Day,
+            window.since,
+            window.until,
+            &filter,
+            FileGrouping::None,
+        )
+        .await
+        .unwrap();
+
+        let events_in_window: i64 = rows.iter().map(|row| row["event_count"].as_i64().unwrap()).sum();
+        assert_eq!(events_in_window, 2);
+        assert_eq!(rows.len(), 2);
+    }
 }
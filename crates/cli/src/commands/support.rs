@@ -1,3 +1,4 @@
+use anstream::println;
 use std::io::{self, Write};
 
 const SUPPORT_MESSAGE: &str = r#"
@@ -0,0 +1,110 @@
+use super::export::find_session;
+use crate::util::truncate_chars;
+use agent_v_store::Database;
+use anstream::println;
+use owo_colors::OwoColorize;
+
+/// Add `tags` to a session found by internal or external ID
+pub async fn add(
+    session_id: String, source: Option<String>, tags: Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
+
+    let session = find_session(&db, &session_id, source.as_deref()).await?;
+    let key = session.session_id()?;
+    for tag in &tags {
+        if db.add_tag(key, tag).await? {
+            println!("{} {} {}", "Tagged".green(), session.id.dimmed(), tag.cyan());
+        } else {
+            println!("{} already has {}", session.id.dimmed(), tag.cyan());
+        }
+    }
+    Ok(())
+}
+
+/// Remove `tags` from a session found by internal or external ID
+pub async fn remove(
+    session_id: String, source: Option<String>, tags: Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
+
+    let session = find_session(&db, &session_id, source.as_deref()).await?;
+    let key = session.session_id()?;
+    for tag in &tags {
+        if db.remove_tag(key, tag).await? {
+            println!("{} {} {}", "Untagged".green(), session.id.dimmed(), tag.cyan());
+        } else {
+            println!("{} has no tag {}", session.id.dimmed(), tag.cyan());
+        }
+    }
+    Ok(())
+}
+
+/// List a session's tags, the sessions carrying `tag`, or (with neither) every tag in use
+pub async fn list(
+    session_id: Option<String>, source: Option<String>, tag: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::open_default().await?;
+    db.migrate().await?;
+
+    if let Some(session_id) = session_id {
+        let session = find_session(&db, &session_id, source.as_deref()).await?;
+        let tags = db.get_tags(session.session_id()?).await?;
+        if tags.is_empty() {
+            println!("{}", "No tags.".yellow());
+        }
+        for tag in tags {
+            println!("{}", tag.cyan());
+        }
+    } else if let Some(tag) = tag {
+        let sessions = db.list_sessions_by_tag(&tag).await?;
+        if sessions.is_empty() {
+            println!("{}", format!("No sessions tagged {}.", tag).yellow());
+        }
+        for session in sessions {
+            println!(
+                "{:<36} {:<10} {:<20} {}",
+                session.id.dimmed(),
+                session.source.cyan(),
+                truncate_chars(session.project.as_deref().unwrap_or("-"), 20),
+                session.title.as_deref().unwrap_or("Untitled").bold()
+            );
+        }
+    } else {
+        let tags = db.list_tags().await?;
+        if tags.is_empty() {
+            println!("{}", "No tags yet.".yellow());
+            println!();
+            println!("To tag a session, run:");
+            println!("  {}", "agent-viz tag add <SESSION> <TAG>".cyan());
+        }
+        for (tag, sessions) in tags {
+            println!("{:<30} {}", tag.cyan(), format!("{} sessions", sessions).dimmed());
+        }
+    }
+    Ok(())
+}
+
+/// Trim tags, rejecting empty ones
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    tags.iter()
+        .map(|tag| match tag.trim() {
+            "" => Err("Tags can't be empty".to_string()),
+            trimmed => Ok(trimmed.to_string()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tags_trims_and_rejects_empty() {
+        let tags = vec![" bug-triage ".to_string(), "spike".to_string()];
+        assert_eq!(normalize_tags(&tags).unwrap(), ["bug-triage", "spike"]);
+        assert!(normalize_tags(&["  ".to_string()]).is_err());
+    }
+}
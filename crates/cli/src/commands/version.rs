@@ -0,0 +1,63 @@
+use agent_v_core::Source;
+use agent_v_store::migrations::MIGRATIONS;
+use anstream::println;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+
+use super::export::ExportFormat;
+
+/// Capabilities of this build, for tooling that integrates with agent-viz
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    pub version: String,
+    pub schema_version: usize,
+    pub supported_sources: Vec<String>,
+    pub supported_formats: Vec<String>,
+}
+
+/// Print version and build capabilities
+pub async fn run(json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let info = version_info();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    println!("{} {}", "agent-viz".bold(), info.version.cyan());
+    println!("  Schema version: {}", info.schema_version);
+    println!("  Sources: {}", info.supported_sources.join(", "));
+    println!("  Export formats: {}", info.supported_formats.join(", "));
+
+    Ok(())
+}
+
+fn version_info() -> VersionInfo {
+    VersionInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: MIGRATIONS.len(),
+        supported_sources: Source::all().iter().map(|s| s.to_string()).collect(),
+        supported_formats: ExportFormat::all().iter().map(|f| f.extension().to_string()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_json_lists_sources_and_formats() {
+        let value = serde_json::to_value(version_info()).unwrap();
+
+        assert_eq!(value["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(value["schema_version"], MIGRATIONS.len());
+        assert_eq!(
+            value["supported_sources"],
+            serde_json::json!(["claude", "codex", "opencode", "crush", "gemini"])
+        );
+        assert_eq!(
+            value["supported_formats"],
+            serde_json::json!(["md", "json", "jsonl", "csv"])
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! CLI errors and the process exit codes they map to.
+//!
+//! Commands return `Box<dyn Error>`; when a failure reaches `main` it is turned into an
+//! [`AgentVizError`]. Commands that know what went wrong return a specific variant, and
+//! well-known library errors (SQLite, JSON and timestamp parsing) are recognised by type.
+
+use std::error::Error;
+use thiserror::Error;
+
+/// Exit codes returned by `agent-viz`
+pub mod exit_code {
+    /// Any failure without a more specific code
+    pub const FAILURE: u8 = 1;
+    /// Bad arguments or option values (clap uses the same code for its own parse errors)
+    pub const USAGE: u8 = 2;
+    /// A session, file, or other requested item does not exist
+    pub const NOT_FOUND: u8 = 3;
+    /// The database could not be opened, migrated, or queried
+    pub const DATABASE: u8 = 4;
+    /// Input (session data, JSON, timestamps) could not be parsed
+    pub const PARSE: u8 = 5;
+}
+
+/// A command failure, classified by what the user can do about it
+#[derive(Debug, Error)]
+pub enum AgentVizError {
+    #[error("{0}")]
+    Usage(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("Database error: {0}")]
+    Database(Box<dyn Error>),
+    #[error("Parse error: {0}")]
+    Parse(Box<dyn Error>),
+    #[error("{0}")]
+    Other(Box<dyn Error>),
+}
+
+impl AgentVizError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            AgentVizError::Usage(_) => exit_code::USAGE,
+            AgentVizError::NotFound(_) => exit_code::NOT_FOUND,
+            AgentVizError::Database(_) => exit_code::DATABASE,
+            AgentVizError::Parse(_) => exit_code::PARSE,
+            AgentVizError::Other(_) => exit_code::FAILURE,
+        }
+    }
+}
+
+impl From<tokio_rusqlite::Error> for AgentVizError {
+    fn from(err: tokio_rusqlite::Error) -> Self {
+        AgentVizError::Database(Box::new(err))
+    }
+}
+
+impl From<Box<dyn Error>> for AgentVizError {
+    fn from(err: Box<dyn Error>) -> Self {
+        if err.is::<tokio_rusqlite::Error>() || err.is::<rusqlite::Error>() {
+            return AgentVizError::Database(err);
+        }
+        if err.is::<serde_json::Error>() || err.is::<chrono::ParseError>() {
+            return AgentVizError::Parse(err);
+        }
+        match err.downcast::<AgentVizError>() {
+            Ok(err) => *err,
+            Err(err) => AgentVizError::Other(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_reaching_main_map_to_their_exit_codes() {
+        let code = |err: Box<dyn Error>| AgentVizError::from(err).exit_code();
+
+        assert_eq!(
+            code(serde_json::from_str::<serde_json::Value>("{").unwrap_err().into()),
+            exit_code::PARSE
+        );
+        assert_eq!(
+            code(tokio_rusqlite::Error::ConnectionClosed.into()),
+            exit_code::DATABASE
+        );
+        assert_eq!(
+            code(AgentVizError::Usage("bad --format".to_string()).into()),
+            exit_code::USAGE
+        );
+        assert_eq!(code("something else".into()), exit_code::FAILURE);
+    }
+}
@@ -1,30 +1,86 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::process::ExitCode;
 
+mod color;
 mod commands;
+mod error;
+mod util;
 
-use commands::{doctor, export, ingest, list, recompute, search, show, stats, support};
+use commands::{
+    config, delete, doctor, export, files, ingest, list, merge, prune, recompute, search, show, stats, support, tag,
+    version,
+};
+use error::AgentVizError;
 
 #[derive(Parser)]
 #[command(name = "agent-viz")]
 #[command(about = "Agent session visualization and analysis tool")]
 #[command(version = env!("CARGO_PKG_VERSION"))]
 struct Cli {
+    /// When to colorize output: auto, always, never
+    #[arg(long, global = true, default_value = "auto", value_name = "WHEN")]
+    color: String,
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Check system health and configuration
-    Doctor,
+    /// Check system health and configuration; exits non-zero when the database or a source is unhealthy
+    Doctor {
+        /// Also parse a bundled fixture per source to catch session format drift
+        #[arg(long)]
+        parse_check: bool,
+        /// Print the results as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Ingest sessions from various sources
     Ingest {
-        /// Source to ingest from (claude, codex, opencode, crush)
+        /// Source to ingest from (claude, codex, opencode, crush, gemini)
         #[arg(short, long)]
         source: Option<String>,
         /// Watch for new sessions continuously
         #[arg(short, long)]
         watch: bool,
+        /// With --watch, run a single ingest pass over all sources and exit (for cron)
+        #[arg(long, requires = "watch")]
+        once: bool,
+        /// Record local image/file attachments (opencode, crush)
+        #[arg(long)]
+        attachments: bool,
+        /// Record each event's line number in its session file (claude)
+        #[arg(long)]
+        source_lines: bool,
+        /// Only read Crush databases whose path matches this glob (repeatable, e.g. "~/projects/**");
+        /// relative globs are matched under the Crush search root
+        #[arg(long)]
+        include: Vec<String>,
+        /// Skip Crush databases and directories whose path matches this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+        /// Abort on the first failed session instead of the default failure-rate guard
+        #[arg(long)]
+        fail_fast: bool,
+        /// Rewrite an event kind or source category after parsing, as FROM=TO (repeatable; TO may be "drop")
+        #[arg(long, value_name = "FROM=TO")]
+        remap_kind: Vec<String>,
+        /// Ingest a single session file instead of discovering sessions (claude)
+        #[arg(long, value_name = "FILE", conflicts_with = "watch")]
+        path: Option<PathBuf>,
+        /// Store event raw payloads zstd-compressed to shrink the database
+        #[arg(long)]
+        compress_raw: bool,
+        /// Re-ingest every session, including files unchanged since the last ingest
+        #[arg(long)]
+        force: bool,
+        /// Parse up to N sessions at once (defaults to the number of CPUs)
+        #[arg(long, value_name = "N")]
+        concurrency: Option<usize>,
+        /// Discover and parse sessions and report what would be imported, without writing to the database
+        #[arg(long, conflicts_with = "watch")]
+        dry_run: bool,
     },
     /// List sessions
     List {
@@ -36,29 +92,117 @@ enum Commands {
     Show {
         /// Session ID to show
         session_id: String,
+        /// Picks the session when its external ID exists in several sources
+        #[arg(short = 'S', long)]
+        source: Option<String>,
+        /// Print each event's raw payload and source line
+        #[arg(long)]
+        raw: bool,
+        /// Emit the session and all its events (with raw payloads) as one JSON document
+        #[arg(long, conflicts_with_all = ["raw", "tools", "tokens"])]
+        json: bool,
+        /// List only the session's tool calls, with their arguments and durations
+        #[arg(long, conflicts_with = "raw")]
+        tools: bool,
+        /// Show tokens used per event over the course of the session
+        #[arg(long, conflicts_with_all = ["raw", "tools"])]
+        tokens: bool,
     },
     /// Search across sessions
     Search {
         /// Search query
-        query: String,
+        #[arg(required_unless_present = "queries_file")]
+        query: Option<String>,
+        /// Run every query in this file (one per line) and group the results by query
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["query", "fields", "title_boost"])]
+        queries_file: Option<PathBuf>,
         /// Filter by source
         #[arg(short = 'S', long)]
         source: Option<String>,
         /// Filter by date range (e.g., "7d", "30d")
         #[arg(short = 's', long)]
         since: Option<String>,
+        /// Only match events older than this (e.g., "7d"); with --since, a bounded window
+        #[arg(long)]
+        until: Option<String>,
         /// Filter by event kind (message, tool_call, tool_result, error)
         #[arg(short = 'k', long)]
         kind: Option<String>,
+        /// Filter by source event category (e.g. progress, file-history-snapshot)
+        #[arg(long)]
+        category: Option<String>,
+        /// Output format (text, jsonl)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Stream results as JSON lines (same as --format jsonl)
+        #[arg(long, conflicts_with = "format")]
+        json_lines: bool,
+        /// Fixed snippet length in tokens (default scales with content length)
+        #[arg(long)]
+        snippet_len: Option<i64>,
+        /// Collapse results with identical content into one hit with an occurrence count
+        #[arg(long)]
+        dedupe_content: bool,
+        /// Indexes to search: content, title, or both (comma-separated)
+        #[arg(long, default_value = "content")]
+        fields: String,
+        /// List sessions whose title matches, by rank, instead of searching events (same as --fields title)
+        #[arg(long, conflicts_with_all = ["fields", "queries_file", "kind", "category", "title_boost", "jsonpath"])]
+        sessions: bool,
+        /// Match events containing any term (terms are AND-ed by default)
+        #[arg(long)]
+        any: bool,
+        /// Rank events higher by this factor when their session title also matches (text output)
+        #[arg(long, value_name = "FACTOR")]
+        title_boost: Option<f64>,
+        /// Keep only events whose raw payload has a node matching this JSONPath (e.g. "$.gitBranch")
+        #[arg(long, value_name = "EXPR")]
+        jsonpath: Option<String>,
+        /// Value the --jsonpath node must equal
+        #[arg(long, requires = "jsonpath")]
+        equals: Option<String>,
+        /// Search assistant thinking/reasoning text instead of event content
+        #[arg(long, conflicts_with_all = ["fields", "sessions"])]
+        thinking: bool,
+        /// Only search sessions carrying this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only search within this session (internal or external ID)
+        #[arg(long, value_name = "ID")]
+        session: Option<String>,
+        /// Treat the query as a regular expression over event content (scans instead of using FTS)
+        #[arg(long, conflicts_with_all = ["fields", "sessions", "any", "title_boost", "jsonpath", "thinking"])]
+        regex: bool,
     },
     /// Show statistics and analytics
     Stats {
-        /// Group by dimension (day, source, project, tool, error)
+        /// Group by dimension (day, weekday, source, project, tool, error, cost, model, efficiency, kind-source, storage)
         #[arg(short, long)]
         by: Option<String>,
-        /// Filter by date range (e.g., "7d", "30d")
+        /// Filter by date range (e.g., "7d", "90d"). Defaults to the last 30 days, or $AGENT_VIZ_STATS_SINCE
         #[arg(short, long)]
         since: Option<String>,
+        /// Include all time instead of the default window
+        #[arg(long, conflicts_with = "since")]
+        all: bool,
+        /// Only count activity older than this (e.g., "7d"); --since 30d --until 7d is a bounded window
+        #[arg(long)]
+        until: Option<String>,
+        /// Leave reasoning-token cost out of cost totals (with --by cost)
+        #[arg(long)]
+        exclude_reasoning_cost: bool,
+        /// Only count sessions from this source (with --by cost)
+        #[arg(short = 'S', long)]
+        source: Option<String>,
+        /// Only count sessions that used this model, matched by canonical name (with --by cost)
+        #[arg(long)]
+        model: Option<String>,
+        /// Output format (text, json, prometheus). Prometheus ignores --by and emits per-source metrics
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Group the files leaderboard by extension or top-level directory (ext, dir, none; with --by files)
+        #[arg(long, default_value = "none")]
+        group: String,
     },
     /// Export sessions or search results
     Export {
@@ -68,13 +212,24 @@ enum Commands {
         /// Export search results
         #[arg(long, group = "export_target")]
         search: Option<String>,
-        /// Output format (md, json, jsonl)
+        /// Export every session as one file per session (requires --output-dir)
+        #[arg(
+            long,
+            group = "export_target",
+            requires = "output_dir",
+            conflicts_with = "metrics_only"
+        )]
+        all: bool,
+        /// With --all, export only sessions updated after this session (internal or external ID)
+        #[arg(long, value_name = "ID", requires = "all")]
+        since_session: Option<String>,
+        /// Output format (md, json, jsonl, csv)
         #[arg(short, long, default_value = "md")]
         format: String,
         /// Output file (stdout if not specified)
         #[arg(short, long)]
         output: Option<String>,
-        /// Filter by source
+        /// Filter by source; also picks the session when a --session ID exists in several sources
         #[arg(short = 'S', long)]
         source: Option<String>,
         /// Filter by date range (e.g., "7d", "30d")
@@ -83,11 +238,98 @@ enum Commands {
         /// Filter by event kind (message, tool_call, tool_result, error)
         #[arg(short = 'k', long)]
         kind: Option<String>,
+        /// Write search results as one file per matching session
+        #[arg(long, requires = "search", requires = "output_dir")]
+        split_by_session: bool,
+        /// Directory for per-session files (used with --split-by-session and --all)
+        #[arg(long)]
+        output_dir: Option<String>,
+        /// Include the full session transcript instead of only matching events
+        #[arg(long, requires = "split_by_session")]
+        full_context: bool,
+        /// Export only session metrics, for one session or all sessions (json, jsonl)
+        #[arg(long, conflicts_with = "search")]
+        metrics_only: bool,
+        /// Export only the most recent N events of the session
+        #[arg(long, value_name = "N", requires = "session", conflicts_with = "metrics_only")]
+        last_n_events: Option<usize>,
+        /// Merge consecutive messages from the same role into one block (tool calls still split turns)
+        #[arg(long, requires = "session", conflicts_with = "metrics_only")]
+        merge_turns: bool,
+    },
+    /// Delete a session and its events from the database
+    Delete {
+        /// Session to delete (internal or external ID)
+        session_id: String,
+        /// Picks the session when its external ID exists in several sources
+        #[arg(short = 'S', long)]
+        source: Option<String>,
+        /// Delete without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+        /// Skip backing up the database first
+        #[arg(long)]
+        no_backup: bool,
+    },
+    /// Merge one session into another, e.g. the same work recorded by two agents
+    Merge {
+        /// Session to keep (internal or external ID)
+        primary: String,
+        /// Session whose events move into the primary; it is deleted afterwards
+        secondary: String,
+        /// Merge without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+        /// Skip backing up the database first
+        #[arg(long)]
+        no_backup: bool,
+    },
+    /// Delete sessions whose source files are gone, or that have not been updated in a while
+    Prune {
+        /// Only prune sessions from this source
+        #[arg(short = 'S', long)]
+        source: Option<String>,
+        /// Also prune sessions not updated within this window (e.g., "90d", "6m")
+        #[arg(long, value_name = "DURATION")]
+        older_than: Option<String>,
+        /// List the sessions that would be deleted without deleting them
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete without asking for confirmation
+        #[arg(short, long, conflicts_with = "dry_run")]
+        yes: bool,
+        /// Skip backing up the database first
+        #[arg(long)]
+        no_backup: bool,
+    },
+    /// Label sessions with free-form tags
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+    /// Show or change settings in ~/.config/agent-viz/config.toml
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Find sessions that edited files matching a pattern (glob or substring, e.g. "main.rs", "src/**/*.rs")
+    Files {
+        /// Path glob or substring
+        pattern: String,
+        /// Maximum number of sessions to show
+        #[arg(short, long, default_value = "50")]
+        limit: i64,
     },
     /// Show support information and funding links
     Support,
-    /// Recompute session metrics (useful after schema updates)
+    /// Recompute session metrics and fill in missing error signatures (useful after schema updates)
     Recompute,
+    /// Show version and supported capabilities
+    Version {
+        /// Emit machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -97,54 +339,314 @@ enum ListWhat {
         /// Filter by source
         #[arg(short, long)]
         source: Option<String>,
+        /// Show a sparkline of each session's event-kind mix
+        #[arg(long)]
+        sparkline: bool,
+        /// Maximum number of sessions to show
+        #[arg(short, long, default_value = "50")]
+        limit: i64,
+        /// Number of sessions to skip, for paging through long lists
+        #[arg(long, default_value = "0")]
+        offset: i64,
+        /// Print sessions as a JSON array instead of a table
+        #[arg(long, conflicts_with = "sparkline")]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print every setting and where the config file lives
+    Show,
+    /// Set a setting: db_path or sources.<source> (claude, codex, opencode, crush, gemini)
+    Set {
+        /// Setting to change, e.g. sources.claude
+        key: String,
+        /// New path; an empty string restores the default
+        value: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagAction {
+    /// Add tags to a session
+    Add {
+        /// Session to tag (internal or external ID)
+        session_id: String,
+        /// Tags to add
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Picks the session when its external ID exists in several sources
+        #[arg(short = 'S', long)]
+        source: Option<String>,
+    },
+    /// Remove tags from a session
+    Remove {
+        /// Session to untag (internal or external ID)
+        session_id: String,
+        /// Tags to remove
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Picks the session when its external ID exists in several sources
+        #[arg(short = 'S', long)]
+        source: Option<String>,
+    },
+    /// List a session's tags, the sessions with a tag, or every tag in use
+    List {
+        /// Show this session's tags
+        session_id: Option<String>,
+        /// Show the sessions carrying this tag
+        #[arg(long, conflicts_with = "session_id")]
+        tag: Option<String>,
+        /// Picks the session when its external ID exists in several sources
+        #[arg(short = 'S', long)]
+        source: Option<String>,
     },
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+async fn main() -> ExitCode {
+    let _ = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
         .format_timestamp(None)
         .format_target(false)
-        .try_init()?;
+        .try_init();
 
-    let cli = Cli::parse();
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            let err = AgentVizError::from(err);
+            anstream::eprintln!("Error: {}", err);
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    color::ColorMode::from_str(&cli.color)
+        .map_err(AgentVizError::Usage)?
+        .apply();
+    // Bad price overrides only skew cost estimates, so they shouldn't stop every command
+    if let Some(path) = agent_v_core::pricing::default_pricing_path()
+        && let Err(e) = agent_v_core::pricing::load_overrides(&path)
+    {
+        log::warn!("Ignoring pricing overrides: {}", e);
+    }
+    // `config` reads the file itself, so a broken file can still be shown and fixed
+    if !matches!(cli.command, Commands::Config { .. })
+        && let Some(path) = agent_v_core::config::default_config_path()
+    {
+        agent_v_core::config::load_config(&path).map_err(AgentVizError::Usage)?;
+    }
 
     match cli.command {
-        Commands::Doctor => {
+        Commands::Doctor { parse_check, json } => {
             log::info!("Running doctor command");
-            doctor::run().await?;
+            doctor::run(parse_check, json).await?;
         }
-        Commands::Ingest { source, watch } => {
+        Commands::Ingest {
+            source,
+            watch,
+            once,
+            attachments,
+            source_lines,
+            include,
+            exclude,
+            fail_fast,
+            remap_kind,
+            path,
+            compress_raw,
+            force,
+            concurrency,
+            dry_run,
+        } => {
             log::info!("Running ingest command");
-            ingest::run(source, watch).await?;
+            if concurrency == Some(0) {
+                return Err(AgentVizError::Usage("--concurrency must be at least 1".to_string()).into());
+            }
+            let options = ingest::IngestOptions {
+                attachments,
+                source_lines,
+                include,
+                exclude,
+                fail_fast,
+                kind_remap: ingest::parse_kind_remap(&remap_kind).map_err(AgentVizError::Usage)?,
+                path,
+                compress_raw,
+                force,
+                concurrency: concurrency.unwrap_or_else(ingest::default_concurrency),
+                dry_run,
+            };
+            ingest::run(source, watch, once, options).await?;
         }
         Commands::List { what } => match what {
-            ListWhat::Sessions { source } => {
+            ListWhat::Sessions { source, sparkline, limit, offset, json } => {
                 log::info!("Running list sessions command");
-                list::sessions(source).await?;
+                list::sessions(source, sparkline, limit, offset, json).await?;
             }
         },
-        Commands::Show { session_id } => {
+        Commands::Show { session_id, source, raw, json, tools, tokens } => {
             log::info!("Showing session: {}", session_id);
-            show::session(session_id).await?;
+            show::session(session_id, source, raw, json, tools, tokens).await?;
         }
-        Commands::Search { query, source, since, kind } => {
-            log::info!("Searching for: {}", query);
-            search::run(query, source, since, kind).await?;
+        Commands::Search {
+            query,
+            queries_file,
+            source,
+            since,
+            until,
+            kind,
+            category,
+            format,
+            json_lines,
+            snippet_len,
+            dedupe_content,
+            fields,
+            sessions,
+            any,
+            title_boost,
+            jsonpath,
+            equals,
+            thinking,
+            tag,
+            session,
+            regex,
+        } => {
+            let search_format = if json_lines {
+                search::SearchFormat::Jsonl
+            } else {
+                search::SearchFormat::from_str(&format).map_err(AgentVizError::Usage)?
+            };
+            if title_boost.is_some_and(|boost| boost < 1.0) {
+                return Err(AgentVizError::Usage("--title-boost must be at least 1.0".to_string()).into());
+            }
+            let options = search::SearchOptions {
+                format: search_format,
+                snippet_len,
+                dedupe_content,
+                fields: if sessions {
+                    search::SearchFields { content: false, title: true }
+                } else {
+                    search::SearchFields::from_str(&fields).map_err(AgentVizError::Usage)?
+                },
+                any,
+                title_boost,
+                payload_filter: jsonpath
+                    .map(|expr| search::PayloadFilter::new(expr, equals))
+                    .transpose()
+                    .map_err(AgentVizError::Usage)?,
+                thinking,
+                tag,
+                session,
+                until,
+                regex,
+            };
+            if let Some(path) = queries_file {
+                let queries = search::read_queries(&path)?;
+                log::info!("Running {} queries from {}", queries.len(), path.display());
+                search::run_batch(queries, source, since, kind, category, options).await?;
+            } else if let Some(query) = query {
+                log::info!("Searching for: {}", query);
+                search::run(query, source, since, kind, category, options).await?;
+            }
         }
-        Commands::Stats { by, since } => {
+        Commands::Stats { by, since, all, until, exclude_reasoning_cost, source, model, format, group } => {
             log::info!("Running stats command");
-            stats::run(by, since).await?;
+            let since = stats::resolve_since(since, all, std::env::var(stats::SINCE_ENV).ok());
+            stats::run(
+                by,
+                since,
+                until,
+                stats::CostFilter { source, model, exclude_reasoning: exclude_reasoning_cost },
+                stats::StatsFormat::from_str(&format).map_err(AgentVizError::Usage)?,
+                group.parse().map_err(AgentVizError::Usage)?,
+            )
+            .await?;
         }
-        Commands::Export { session, search, format, output, source, since, kind } => {
+        Commands::Export {
+            session,
+            search,
+            all,
+            since_session,
+            format,
+            output,
+            source,
+            since,
+            kind,
+            split_by_session,
+            output_dir,
+            full_context,
+            metrics_only,
+            last_n_events,
+            merge_turns,
+        } => {
             log::info!("Running export command");
-            let export_format = export::ExportFormat::from_str(&format)?;
-            if let Some(session_id) = session {
-                export::export_session(session_id, export_format, output).await?;
+            let export_format = export::ExportFormat::from_str(&format).map_err(AgentVizError::Usage)?;
+            if metrics_only {
+                export::export_metrics(session, source, export_format, output).await?;
+            } else if let Some(session_id) = session {
+                export::export_session(session_id, source, export_format, output, last_n_events, merge_turns).await?;
+            } else if all && let Some(dir) = output_dir {
+                export::export_all(source, since_session, export_format, dir).await?;
             } else if let Some(query) = search {
-                export::export_search(query, source, since, kind, export_format, output).await?;
+                if split_by_session && let Some(dir) = output_dir {
+                    export::export_search_split(query, source, since, kind, export_format, dir, full_context).await?;
+                } else {
+                    export::export_search(query, source, since, kind, export_format, output).await?;
+                }
             }
         }
+        Commands::Delete { session_id, source, yes, no_backup } => {
+            log::info!("Deleting session: {}", session_id);
+            delete::run(session_id, source, yes, !no_backup).await?;
+        }
+        Commands::Merge { primary, secondary, yes, no_backup } => {
+            log::info!("Merging session {} into {}", secondary, primary);
+            merge::run(primary, secondary, yes, !no_backup).await?;
+        }
+        Commands::Prune { source, older_than, dry_run, yes, no_backup } => {
+            log::info!("Running prune command");
+            let cutoff = older_than
+                .as_deref()
+                .map(util::parse_since)
+                .transpose()
+                .map_err(AgentVizError::Usage)?;
+            prune::run(source, cutoff, dry_run, yes, !no_backup).await?;
+        }
+        Commands::Tag { action } => match action {
+            TagAction::Add { session_id, tags, source } => {
+                log::info!("Tagging session: {}", session_id);
+                tag::add(
+                    session_id,
+                    source,
+                    tag::normalize_tags(&tags).map_err(AgentVizError::Usage)?,
+                )
+                .await?;
+            }
+            TagAction::Remove { session_id, tags, source } => {
+                log::info!("Untagging session: {}", session_id);
+                tag::remove(
+                    session_id,
+                    source,
+                    tag::normalize_tags(&tags).map_err(AgentVizError::Usage)?,
+                )
+                .await?;
+            }
+            TagAction::List { session_id, tag, source } => {
+                log::info!("Listing tags");
+                tag::list(session_id, source, tag).await?;
+            }
+        },
+        Commands::Config { action } => match action {
+            ConfigAction::Show => config::show().await?,
+            ConfigAction::Set { key, value } => {
+                log::info!("Setting config key: {}", key);
+                config::set(key, value).await?;
+            }
+        },
+        Commands::Files { pattern, limit } => {
+            log::info!("Finding sessions touching: {}", pattern);
+            files::run(pattern, limit).await?;
+        }
         Commands::Support => {
             log::info!("Running support command");
             support::run().await?;
@@ -153,7 +655,57 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             log::info!("Running recompute command");
             recompute::run().await?;
         }
+        Commands::Version { json } => {
+            version::run(json).await?;
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_sessions_paging_args() {
+        let parse = |args: &[&str]| match Cli::try_parse_from(args).map(|cli| cli.command) {
+            Ok(Commands::List { what: ListWhat::Sessions { limit, offset, json, .. } }) => Ok((limit, offset, json)),
+            Ok(_) => panic!("parsed into another command"),
+            Err(e) => Err(e.kind()),
+        };
+
+        assert_eq!(parse(&["agent-viz", "list", "sessions"]), Ok((50, 0, false)));
+        assert_eq!(
+            parse(&[
+                "agent-viz",
+                "list",
+                "sessions",
+                "--limit",
+                "10",
+                "--offset",
+                "20",
+                "--json"
+            ]),
+            Ok((10, 20, true))
+        );
+        assert_eq!(
+            parse(&["agent-viz", "list", "sessions", "--json", "--sparkline"]),
+            Err(clap::error::ErrorKind::ArgumentConflict)
+        );
+    }
+    #[test]
+    fn test_delete_takes_yes_flag() {
+        let parse = |args: &[&str]| match Cli::try_parse_from(args).map(|cli| cli.command) {
+            Ok(Commands::Delete { session_id, yes, .. }) => (session_id, yes),
+            _ => panic!("expected a delete command"),
+        };
+
+        assert_eq!(parse(&["agent-viz", "delete", "abc"]), ("abc".to_string(), false));
+        assert_eq!(
+            parse(&["agent-viz", "delete", "abc", "--yes"]),
+            ("abc".to_string(), true)
+        );
+        assert_eq!(parse(&["agent-viz", "delete", "-y", "abc"]), ("abc".to_string(), true));
+    }
+}
@@ -0,0 +1,92 @@
+//! Helpers shared by the commands.
+//!
+//! Session content, error signatures, and titles are arbitrary UTF-8, so slicing them by byte
+//! index can land inside a multibyte character and panic. The truncation helpers cut on char
+//! boundaries.
+
+use chrono::{DateTime, Utc};
+
+/// The first `max_chars` characters of `s`
+pub fn truncate_chars(s: &str, max_chars: usize) -> &str {
+    match s.char_indices().nth(max_chars) {
+        Some((end, _)) => &s[..end],
+        None => s,
+    }
+}
+
+/// `s` cut to `max_chars` characters, with `...` appended when anything was dropped
+pub fn truncate_ellipsis(s: &str, max_chars: usize) -> String {
+    let truncated = truncate_chars(s, max_chars);
+    if truncated.len() < s.len() { format!("{}...", truncated) } else { s.to_string() }
+}
+
+/// The start of a `--since`-style window (e.g. `7d`, `90min`, `1w3d`) ending now
+pub fn parse_since(since: &str) -> Result<DateTime<Utc>, String> {
+    agent_v_core::parse_duration(since)
+        .map(|window| Utc::now() - window)
+        .ok_or_else(|| {
+            format!(
+                "Invalid duration format: {}. Use e.g. 90min, 12h, 7d, 2w, 3m, or 1w3d",
+                since
+            )
+        })
+}
+
+/// A `--since`/`--until` window; either end is open when unset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Parse a `--since`/`--until` pair into a window; `--until` is measured back from now like `--since`,
+/// so `--since 30d --until 7d` covers the three weeks before the last seven days
+pub fn parse_window(since: Option<&str>, until: Option<&str>) -> Result<TimeWindow, String> {
+    let since = since.map(parse_since).transpose()?;
+    let until = until.map(parse_since).transpose()?;
+    if let (Some(start), Some(end)) = (since, until)
+        && start >= end
+    {
+        return Err("--until must be more recent than --since (e.g. --since 30d --until 7d)".to_string());
+    }
+    Ok(TimeWindow { since, until })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncating_multibyte_text_respects_char_boundaries() {
+        let signature = "🔥 build failed: ünïcödé everywhere";
+        assert_eq!(truncate_chars(signature, 1), "🔥");
+        assert_eq!(truncate_chars(signature, 9), "🔥 build f");
+        assert_eq!(truncate_ellipsis(signature, 3), "🔥 b...");
+        assert_eq!(truncate_ellipsis("ünï", 3), "ünï");
+
+        for n in 0..=signature.chars().count() + 1 {
+            assert_eq!(
+                truncate_chars(signature, n).chars().count(),
+                n.min(signature.chars().count())
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_since_accepts_compound_windows() {
+        let since = parse_since("1d12h").unwrap();
+        let expected = Utc::now() - chrono::Duration::hours(36);
+        assert!((expected - since).num_seconds().abs() < 5);
+        assert!(parse_since("12").unwrap_err().contains("Invalid duration format"));
+    }
+
+    #[test]
+    fn test_parse_window_rejects_an_until_before_since() {
+        let window = parse_window(Some("30d"), Some("7d")).unwrap();
+        assert!(window.since.unwrap() < window.until.unwrap());
+        assert_eq!(parse_window(None, None).unwrap(), TimeWindow::default());
+        assert!(parse_window(None, Some("1w")).unwrap().until.is_some());
+        assert!(parse_window(Some("7d"), Some("30d")).unwrap_err().contains("--until"));
+        assert!(parse_window(Some("7d"), Some("soon")).is_err());
+    }
+}
@@ -0,0 +1,212 @@
+//! User settings for non-standard install locations.
+//!
+//! A `config.toml` in `~/.config/agent-viz/` (next to `pricing.toml`) can point each adapter at a
+//! different root and move the database. Every key is optional; a missing one keeps the built-in
+//! default, and `~/` expands to the home directory:
+//!
+//! ```toml
+//! db_path = "~/data/agent-viz.db"
+//!
+//! [sources]
+//! claude = "/mnt/work/.claude/projects"
+//! codex = "~/alt/.codex/sessions"
+//! opencode = "~/.local/share/opencode-dev"
+//! crush = "~/projects"
+//! gemini = "~/.gemini/tmp"
+//! ```
+//!
+//! `crush` is the directory searched for `.crush/crush.db` files, `opencode` the base directory
+//! holding `storage/` and `auth.json`, and the rest are the directories the adapters scan.
+use crate::Source;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Settings installed once at startup by [`load_config`]
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Contents of `config.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Database file used instead of the platform data directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db_path: Option<PathBuf>,
+    #[serde(default)]
+    pub sources: SourcePaths,
+}
+
+/// Per-source root overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SourcePaths {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codex: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opencode: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crush: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gemini: Option<PathBuf>,
+}
+
+impl SourcePaths {
+    fn slot(&mut self, source: Source) -> &mut Option<PathBuf> {
+        match source {
+            Source::Claude => &mut self.claude,
+            Source::Codex => &mut self.codex,
+            Source::OpenCode => &mut self.opencode,
+            Source::Crush => &mut self.crush,
+            Source::Gemini => &mut self.gemini,
+        }
+    }
+
+    fn get(&self, source: Source) -> Option<&PathBuf> {
+        match source {
+            Source::Claude => self.claude.as_ref(),
+            Source::Codex => self.codex.as_ref(),
+            Source::OpenCode => self.opencode.as_ref(),
+            Source::Crush => self.crush.as_ref(),
+            Source::Gemini => self.gemini.as_ref(),
+        }
+    }
+}
+
+impl Config {
+    /// Parse the contents of a config file
+    pub fn parse(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Render the config as it would be saved
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string(self).map_err(|e| e.to_string())
+    }
+
+    /// Root override for `source`, with `~/` expanded
+    pub fn source_path(&self, source: Source) -> Option<PathBuf> {
+        self.sources.get(source).map(|path| expand_home(path))
+    }
+
+    /// Database file override, with `~/` expanded
+    pub fn db_path(&self) -> Option<PathBuf> {
+        self.db_path.as_deref().map(expand_home)
+    }
+
+    /// Every settable key with its current value, e.g. `("sources.claude", None)`
+    pub fn entries(&self) -> Vec<(String, Option<&PathBuf>)> {
+        let mut entries = vec![("db_path".to_string(), self.db_path.as_ref())];
+        for &source in Source::all() {
+            entries.push((format!("sources.{}", source), self.sources.get(source)));
+        }
+        entries
+    }
+
+    /// Set `key` (`db_path` or `sources.<source>`) to `value`; an empty value removes the override
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let slot = match key.split_once('.') {
+            None if key == "db_path" => &mut self.db_path,
+            Some(("sources", source)) => self.sources.slot(source.parse()?),
+            _ => return Err(format!("Unknown config key: {}. Use db_path or sources.<source>", key)),
+        };
+        *slot = Some(value.trim()).filter(|v| !v.is_empty()).map(PathBuf::from);
+        Ok(())
+    }
+}
+
+/// Where the settings live: `~/.config/agent-viz/config.toml`
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config").join("agent-viz").join("config.toml"))
+}
+
+/// Read the config at `path`; a missing file is an empty config
+pub fn read_config(path: &Path) -> Result<Config, String> {
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Config::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Write `config` to `path`, creating its directory
+pub fn save_config(path: &Path, config: &Config) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    std::fs::write(path, config.to_toml()?).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Install the config in `path` for [`current`] to return.
+///
+/// Like the pricing overrides, settings can only be installed once per process; later calls leave
+/// the first set in place.
+pub fn load_config(path: &Path) -> Result<(), String> {
+    let config = read_config(path)?;
+    let _ = CONFIG.set(config);
+    Ok(())
+}
+
+/// The installed settings, or an empty config when none were loaded
+pub fn current() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Replace a leading `~/` with the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_expand_overrides() {
+        let config = Config::parse(
+            r#"
+            db_path = "~/data/agent-viz.db"
+
+            [sources]
+            claude = "/mnt/work/projects"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.source_path(Source::Claude),
+            Some(PathBuf::from("/mnt/work/projects"))
+        );
+        assert_eq!(config.source_path(Source::Codex), None);
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(config.db_path(), Some(home.join("data").join("agent-viz.db")));
+        }
+
+        assert!(Config::parse("[sources]\nclaud = \"/tmp\"").is_err());
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_set_round_trips_through_toml() {
+        let mut config = Config::default();
+        config.set("sources.opencode", "/opt/opencode").unwrap();
+        config.set("db_path", "/var/lib/agent-viz.db").unwrap();
+        assert!(config.set("sources.cursor", "/tmp").is_err());
+        assert!(config.set("theme", "dark").is_err());
+
+        let saved = Config::parse(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(saved, config);
+        assert_eq!(
+            saved.source_path(Source::OpenCode),
+            Some(PathBuf::from("/opt/opencode"))
+        );
+
+        config.set("db_path", "").unwrap();
+        assert_eq!(config.db_path, None);
+        assert_eq!(config.entries().len(), 1 + Source::all().len());
+    }
+}
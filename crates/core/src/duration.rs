@@ -0,0 +1,83 @@
+//! Relative time windows such as `--since 7d`.
+//!
+//! A window is one or more `<count><unit>` components, e.g. `90min`, `12h`, or `1w3d`. Units are
+//! `s`, `min`, `h`, `d`, `w`, and `m` for a 30-day month, so `m` alone never means minutes.
+use chrono::Duration;
+
+/// Seconds per unit suffix
+const UNITS: &[(&str, i64)] = &[
+    ("s", 1),
+    ("min", 60),
+    ("h", 60 * 60),
+    ("d", 24 * 60 * 60),
+    ("w", 7 * 24 * 60 * 60),
+    ("m", 30 * 24 * 60 * 60),
+];
+
+/// Parse a window like `30min`, `7d`, or `1d12h` into a duration.
+///
+/// Returns `None` for an empty string, a component without a count or unit, an unknown unit,
+/// or a total too large to represent.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut total: i64 = 0;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let letters = rest[digits..]
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .map_or(rest.len(), |end| digits + end);
+        let count: i64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..letters].to_ascii_lowercase();
+        let (_, seconds) = UNITS.iter().find(|(suffix, _)| *suffix == unit)?;
+
+        total = total.checked_add(count.checked_mul(*seconds)?)?;
+        rest = &rest[letters..];
+    }
+
+    Duration::try_seconds(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_unit() {
+        assert_eq!(parse_duration("45s"), Some(Duration::seconds(45)));
+        assert_eq!(parse_duration("90min"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("12h"), Some(Duration::hours(12)));
+        assert_eq!(parse_duration("7d"), Some(Duration::days(7)));
+        assert_eq!(parse_duration("2w"), Some(Duration::weeks(2)));
+        assert_eq!(parse_duration("3m"), Some(Duration::days(90)));
+    }
+
+    #[test]
+    fn test_m_is_months_and_min_is_minutes() {
+        assert_eq!(parse_duration("1m"), Some(Duration::days(30)));
+        assert_eq!(parse_duration("1min"), Some(Duration::minutes(1)));
+        assert_eq!(
+            parse_duration("1m30min"),
+            Some(Duration::days(30) + Duration::minutes(30))
+        );
+        assert_eq!(parse_duration("1mi"), None);
+    }
+
+    #[test]
+    fn test_compound_windows() {
+        assert_eq!(parse_duration("1w3d"), Some(Duration::days(10)));
+        assert_eq!(parse_duration("1d12h"), Some(Duration::hours(36)));
+        assert_eq!(parse_duration("2h30min15s"), Some(Duration::seconds(9015)));
+        assert_eq!(parse_duration(" 1D "), Some(Duration::days(1)));
+    }
+
+    #[test]
+    fn test_rejects_malformed_windows() {
+        for bad in ["", "7", "d", "7x", "1d h", "-1d", "1.5h", "99999999999999999999d"] {
+            assert_eq!(parse_duration(bad), None, "{:?}", bad);
+        }
+    }
+}
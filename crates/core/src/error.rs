@@ -0,0 +1,26 @@
+//! Errors raised by source adapters while reading a session.
+use thiserror::Error;
+
+/// Why an adapter couldn't read a session
+#[derive(Debug, Error)]
+pub enum AdapterError {
+    /// The session file or database couldn't be read
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Session data isn't valid JSON; `line` is 1-based
+    #[error("parse error on line {line}: {source}")]
+    Parse { line: usize, source: serde_json::Error },
+    /// A source database doesn't have the tables or columns the adapter expects
+    #[error("schema error: {0}")]
+    Schema(String),
+    /// The session has nothing to import
+    #[error("session is empty")]
+    Empty,
+}
+
+/// A JSON error from parsing a whole document, located by the line serde_json stopped on
+impl From<serde_json::Error> for AdapterError {
+    fn from(source: serde_json::Error) -> Self {
+        AdapterError::Parse { line: source.line(), source }
+    }
+}
@@ -0,0 +1,139 @@
+//! Typed identifiers for sessions and events.
+//!
+//! Internal IDs are UUIDs assigned at ingest, while external IDs are whatever the source
+//! uses (and are only unique per source). Keeping them as distinct types stops an external
+//! ID from being passed where the store expects an internal one:
+//!
+//! ```compile_fail
+//! use agent_v_core::{ExternalId, SessionId};
+//!
+//! fn load(_id: SessionId) {}
+//!
+//! load(ExternalId::from("rollout-2025-01-01"));
+//! ```
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Internal session ID, as stored in `sessions.id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SessionId(pub Uuid);
+
+/// Internal event ID, as stored in `events.id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EventId(pub Uuid);
+
+/// A session's ID in its source (a Claude file stem, a Codex rollout ID, ...)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ExternalId(String);
+
+impl SessionId {
+    pub fn as_uuid(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+impl EventId {
+    pub fn as_uuid(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+impl ExternalId {
+    pub fn new(id: impl Into<String>) -> Self {
+        ExternalId(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<Uuid> for SessionId {
+    fn from(id: Uuid) -> Self {
+        SessionId(id)
+    }
+}
+
+impl From<Uuid> for EventId {
+    fn from(id: Uuid) -> Self {
+        EventId(id)
+    }
+}
+
+impl From<String> for ExternalId {
+    fn from(id: String) -> Self {
+        ExternalId(id)
+    }
+}
+
+impl From<&str> for ExternalId {
+    fn from(id: &str) -> Self {
+        ExternalId(id.to_string())
+    }
+}
+
+impl FromStr for SessionId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s.trim())
+            .map(SessionId)
+            .map_err(|_| format!("Invalid session ID: {} (expected a UUID)", s))
+    }
+}
+
+impl FromStr for EventId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s.trim())
+            .map(EventId)
+            .map_err(|_| format!("Invalid event ID: {} (expected a UUID)", s))
+    }
+}
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for ExternalId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_id_round_trips_through_its_stored_form() {
+        let uuid = Uuid::new_v4();
+        let id = SessionId::from(uuid);
+        assert_eq!(id.to_string(), uuid.to_string());
+        assert_eq!(id.to_string().parse::<SessionId>().unwrap(), id);
+    }
+
+    #[test]
+    fn test_external_ids_are_rejected_as_internal_ids() {
+        let err = "rollout-2025-01-01T10-00-00".parse::<SessionId>().unwrap_err();
+        assert!(err.contains("Invalid session ID"));
+        assert!("".parse::<EventId>().is_err());
+
+        let external = ExternalId::from("rollout-2025-01-01T10-00-00");
+        assert_eq!(external.as_str(), "rollout-2025-01-01T10-00-00");
+    }
+}
@@ -1,3 +1,13 @@
+pub mod config;
+pub mod duration;
+pub mod error;
+pub mod ids;
 pub mod models;
+pub mod pricing;
+pub mod signature;
 
+pub use duration::parse_duration;
+pub use error::AdapterError;
+pub use ids::*;
 pub use models::*;
+pub use signature::error_signature;
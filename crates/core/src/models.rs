@@ -1,9 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::OnceLock;
 use uuid::Uuid;
 
-/// Source of the agent session (e.g., claude, codex, opencode, crush)
+/// Source of the agent session (e.g., claude, codex, opencode, crush, gemini)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Source {
@@ -11,6 +12,20 @@ pub enum Source {
     Codex,
     OpenCode,
     Crush,
+    Gemini,
+}
+
+impl Source {
+    /// Every source, in the order a full ingest visits them
+    pub fn all() -> &'static [Source] {
+        &[
+            Source::Claude,
+            Source::Codex,
+            Source::OpenCode,
+            Source::Crush,
+            Source::Gemini,
+        ]
+    }
 }
 
 impl std::fmt::Display for Source {
@@ -20,6 +35,7 @@ impl std::fmt::Display for Source {
             Source::Codex => write!(f, "codex"),
             Source::OpenCode => write!(f, "opencode"),
             Source::Crush => write!(f, "crush"),
+            Source::Gemini => write!(f, "gemini"),
         }
     }
 }
@@ -33,6 +49,7 @@ impl std::str::FromStr for Source {
             "codex" => Ok(Source::Codex),
             "opencode" => Ok(Source::OpenCode),
             "crush" => Ok(Source::Crush),
+            "gemini" => Ok(Source::Gemini),
             _ => Err(format!("Unknown source: {}", s)),
         }
     }
@@ -61,6 +78,21 @@ impl std::fmt::Display for EventKind {
     }
 }
 
+impl std::str::FromStr for EventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "message" => Ok(EventKind::Message),
+            "tool_call" => Ok(EventKind::ToolCall),
+            "tool_result" => Ok(EventKind::ToolResult),
+            "error" => Ok(EventKind::Error),
+            "system" => Ok(EventKind::System),
+            _ => Err(format!("Unknown event kind: {}", s)),
+        }
+    }
+}
+
 /// Role of a message sender
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -68,6 +100,8 @@ pub enum Role {
     User,
     Assistant,
     System,
+    /// Output returned by a tool, as opposed to the assistant's own prose or its tool calls
+    Tool,
 }
 
 impl std::fmt::Display for Role {
@@ -76,6 +110,21 @@ impl std::fmt::Display for Role {
             Role::User => write!(f, "user"),
             Role::Assistant => write!(f, "assistant"),
             Role::System => write!(f, "system"),
+            Role::Tool => write!(f, "tool"),
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "user" => Ok(Role::User),
+            "assistant" => Ok(Role::Assistant),
+            "system" => Ok(Role::System),
+            "tool" => Ok(Role::Tool),
+            _ => Err(format!("Unknown role: {}", s)),
         }
     }
 }
@@ -103,6 +152,223 @@ pub struct Event {
     pub content: Option<String>,
     pub timestamp: DateTime<Utc>,
     pub raw_payload: serde_json::Value,
+    /// Event this one follows from, resolved from the source's native parent reference (Claude
+    /// `parentUuid`, OpenCode `parentID`, ...). Only full parses set it, since an incremental
+    /// parse can't see the events before it.
+    #[serde(default)]
+    pub parent_id: Option<Uuid>,
+}
+
+impl Event {
+    /// Key under which adapters record the zero-based source file line in an event's raw payload
+    pub const SOURCE_LINE_KEY: &'static str = "source_line";
+
+    /// Read the source file line recorded in the raw payload, if any
+    pub fn source_line(payload: &serde_json::Value) -> Option<u64> {
+        payload.get(Self::SOURCE_LINE_KEY).and_then(|v| v.as_u64())
+    }
+
+    /// Key under which adapters record the source's own event sub-type (e.g. `progress`) in the raw payload
+    pub const CATEGORY_KEY: &'static str = "category";
+
+    /// Read the source sub-type recorded in the raw payload, if any
+    pub fn category(payload: &serde_json::Value) -> Option<&str> {
+        payload.get(Self::CATEGORY_KEY).and_then(|v| v.as_str())
+    }
+
+    /// Record the source sub-type in a raw payload; non-object payloads are left untouched
+    pub fn set_category(payload: &mut serde_json::Value, category: &str) {
+        if let Some(obj) = payload.as_object_mut() {
+            obj.insert(Self::CATEGORY_KEY.to_string(), serde_json::json!(category));
+        }
+    }
+
+    /// Read the assistant's reasoning text from a raw payload: Claude `thinking` content blocks
+    /// (joined by blank lines) or the message of a Codex `agent_reasoning` event
+    pub fn thinking(payload: &serde_json::Value) -> Option<String> {
+        if Self::category(payload) == Some("agent_reasoning") {
+            return payload
+                .pointer("/payload/message")
+                .and_then(|m| m.as_str())
+                .map(str::to_string);
+        }
+
+        let blocks: Vec<&str> = payload
+            .pointer("/message/content")
+            .and_then(|c| c.as_array())?
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("thinking"))
+            .filter_map(|block| block.get("thinking").and_then(|t| t.as_str()))
+            .filter(|t| !t.trim().is_empty())
+            .collect();
+        if blocks.is_empty() { None } else { Some(blocks.join("\n\n")) }
+    }
+}
+
+/// Rewrites event kinds after parsing, e.g. counting `tool_result` as `system` or dropping `reasoning`.
+///
+/// Rule keys match an event's source category (see [`Event::category`]) first, then its kind.
+/// Targets are an [`EventKind`] name or [`KindRemap::DROP`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KindRemap {
+    rules: HashMap<String, Option<EventKind>>,
+}
+
+impl KindRemap {
+    /// Target that removes matching events instead of remapping them
+    pub const DROP: &'static str = "drop";
+
+    /// Build a remap from `from -> to` rules, rejecting unknown target kinds
+    pub fn new(rules: &HashMap<String, String>) -> Result<Self, String> {
+        let rules = rules
+            .iter()
+            .map(|(from, to)| {
+                let target = if to.eq_ignore_ascii_case(Self::DROP) {
+                    None
+                } else {
+                    Some(
+                        to.parse::<EventKind>()
+                            .map_err(|e| format!("Invalid remap {}={}: {}", from, to, e))?,
+                    )
+                };
+                Ok((from.to_lowercase(), target))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The rules as sorted `from=to` pairs joined by commas, stable across runs
+    pub fn fingerprint(&self) -> String {
+        let mut rules: Vec<String> = self
+            .rules
+            .iter()
+            .map(|(from, to)| match to {
+                Some(kind) => format!("{}={}", from, kind),
+                None => format!("{}={}", from, Self::DROP),
+            })
+            .collect();
+        rules.sort();
+        rules.join(",")
+    }
+
+    /// Apply the rules, dropping events whose rule targets [`KindRemap::DROP`]
+    pub fn apply(&self, events: Vec<Event>) -> Vec<Event> {
+        if self.is_empty() {
+            return events;
+        }
+        events
+            .into_iter()
+            .filter_map(|mut event| {
+                let rule = Event::category(&event.raw_payload)
+                    .and_then(|c| self.rules.get(&c.to_lowercase()))
+                    .or_else(|| self.rules.get(&event.kind.to_string()));
+                match rule {
+                    Some(Some(kind)) => {
+                        event.kind = *kind;
+                        Some(event)
+                    }
+                    Some(None) => None,
+                    None => Some(event),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Local file referenced by an event, such as an image or file part
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    pub path: String,
+    pub mime_type: Option<String>,
+}
+
+impl Attachment {
+    /// Key under which adapters record attachments in an event's raw payload
+    pub const PAYLOAD_KEY: &'static str = "attachments";
+
+    /// Build an attachment from a local path or `file://` URL.
+    /// Remote URLs and inline data are not resolvable and yield `None`.
+    pub fn from_local(location: &str, mime_type: Option<String>) -> Option<Self> {
+        let path = location.strip_prefix("file://").unwrap_or(location);
+        if !std::path::Path::new(path).is_absolute() {
+            return None;
+        }
+
+        Some(Self { path: path.to_string(), mime_type })
+    }
+
+    /// Read the attachments recorded in an event's raw payload
+    pub fn from_payload(payload: &serde_json::Value) -> Vec<Self> {
+        payload
+            .get(Self::PAYLOAD_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Structured outcome of a file edit made by a tool call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEdit {
+    pub path: String,
+    /// Kind of change, e.g. `create` or `edit`
+    pub operation: String,
+    pub lines_added: i64,
+    pub lines_removed: i64,
+}
+
+impl FileEdit {
+    /// Key under which adapters record file edits in an event's raw payload
+    pub const PAYLOAD_KEY: &'static str = "file_edits";
+
+    /// Read the file edits recorded in an event's raw payload
+    pub fn from_payload(payload: &serde_json::Value) -> Vec<Self> {
+        payload
+            .get(Self::PAYLOAD_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Outcome of a tool call as reported by its result event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolOutcome {
+    /// Name of the tool that produced the result, when the adapter can resolve it
+    #[serde(default)]
+    pub tool_name: Option<String>,
+    pub success: bool,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub duration_ms: Option<i64>,
+    #[serde(default)]
+    pub error_message: Option<String>,
+}
+
+impl ToolOutcome {
+    /// Key under which adapters record a tool outcome in an event's raw payload
+    pub const PAYLOAD_KEY: &'static str = "tool_outcome";
+
+    /// Build an outcome from a process exit code, where only zero counts as success
+    pub fn from_exit_code(exit_code: i32, duration_ms: Option<i64>) -> Self {
+        Self {
+            tool_name: None,
+            success: exit_code == 0,
+            exit_code: Some(exit_code),
+            duration_ms,
+            error_message: (exit_code != 0).then(|| format!("exit code {}", exit_code)),
+        }
+    }
+
+    /// Read the tool outcome recorded in an event's raw payload
+    pub fn from_payload(payload: &serde_json::Value) -> Option<Self> {
+        payload
+            .get(Self::PAYLOAD_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
 }
 
 /// Health status of an adapter
@@ -181,11 +447,18 @@ impl ModelMetadata {
             })
             .filter(|m| {
                 let registry_normalized = Self::normalize_model_name(&m.model_id);
-                Self::calculate_match_score(&normalized, &registry_normalized) > 0
+                Self::shares_component(&normalized, &registry_normalized)
             })
             .cloned()
     }
 
+    /// Registry ID a model name resolves to, or the lowercased name when it matches no registry entry
+    pub fn canonical_name(model_name: &str) -> String {
+        Self::lookup(model_name)
+            .map(|meta| meta.model_id)
+            .unwrap_or_else(|| model_name.to_lowercase())
+    }
+
     /// Normalize model name for fuzzy matching
     /// - Remove dates (8 consecutive digits like 20251001)
     /// - Normalize dashes to dots for version numbers
@@ -229,6 +502,12 @@ impl ModelMetadata {
         score
     }
 
+    /// Whether two normalized model names have a component in common, so a name that only
+    /// happens to be about as long as a registry ID doesn't match it
+    fn shares_component(query: &str, candidate: &str) -> bool {
+        query.split('.').any(|q| candidate.split('.').any(|c| c == q))
+    }
+
     /// Estimate token count from text using characters-per-token heuristic
     pub fn estimate_tokens(text: &str) -> usize {
         text.len().div_ceil(Self::CHARS_PER_TOKEN)
@@ -259,6 +538,69 @@ impl ModelMetadata {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_all_sources_lists_every_variant_once() {
+        // Exhaustive, so a new variant fails to compile here until `Source::all` is updated too
+        let position = |source: &Source| match source {
+            Source::Claude => 0,
+            Source::Codex => 1,
+            Source::OpenCode => 2,
+            Source::Crush => 3,
+            Source::Gemini => 4,
+        };
+        let positions: Vec<usize> = Source::all().iter().map(position).collect();
+        assert_eq!(positions, [0, 1, 2, 3, 4]);
+        for source in Source::all() {
+            assert_eq!(source.to_string().parse::<Source>(), Ok(*source));
+        }
+    }
+
+    #[test]
+    fn test_roles_round_trip_through_their_names() {
+        for role in [Role::User, Role::Assistant, Role::System, Role::Tool] {
+            assert_eq!(role.to_string().parse::<Role>(), Ok(role));
+            assert_eq!(serde_json::to_value(role).unwrap(), role.to_string());
+        }
+        assert_eq!("TOOL".parse::<Role>(), Ok(Role::Tool));
+        assert!("function".parse::<Role>().is_err());
+    }
+
+    #[test]
+    fn test_kind_remap_validates_targets_and_drops() {
+        let rules = HashMap::from([("reasoning".to_string(), "drop".to_string())]);
+        let remap = KindRemap::new(&rules).unwrap();
+
+        let mut reasoning = serde_json::json!({});
+        Event::set_category(&mut reasoning, "reasoning");
+        let event = |kind, raw_payload| Event {
+            id: Uuid::new_v4(),
+            session_id: Uuid::nil(),
+            kind,
+            role: None,
+            content: None,
+            timestamp: Utc::now(),
+            raw_payload,
+            parent_id: None,
+        };
+        let kept = remap.apply(vec![
+            event(EventKind::System, reasoning),
+            event(EventKind::System, serde_json::json!({})),
+        ]);
+        assert_eq!(kept.len(), 1);
+
+        let bad = HashMap::from([("tool_result".to_string(), "thought".to_string())]);
+        assert!(KindRemap::new(&bad).unwrap_err().contains("thought"));
+
+        let rules = HashMap::from([
+            ("tool_result".to_string(), "System".to_string()),
+            ("reasoning".to_string(), "DROP".to_string()),
+        ]);
+        assert_eq!(
+            KindRemap::new(&rules).unwrap().fingerprint(),
+            "reasoning=drop,tool_result=system"
+        );
+    }
+
     #[test]
     fn test_token_estimation() {
         assert_eq!(ModelMetadata::estimate_tokens(""), 0);
@@ -275,6 +617,8 @@ mod tests {
         let fuzzy = ModelMetadata::lookup("gpt-5.3");
         assert!(fuzzy.is_some());
         assert_eq!(fuzzy.unwrap().provider, "openai");
+
+        assert!(ModelMetadata::lookup("mystery-model").is_none());
     }
 
     #[test]
@@ -0,0 +1,131 @@
+//! Cost estimates for token usage.
+//!
+//! Built-in prices come from the [`ModelMetadata`] registry. A `pricing.toml` in
+//! `~/.config/agent-viz/` can override them per model, e.g. for negotiated rates or a model the
+//! registry doesn't know yet:
+//!
+//! ```toml
+//! [models."claude-4.5-sonnet"]
+//! input_per_1k = 0.003
+//! output_per_1k = 0.015
+//! ```
+use crate::ModelMetadata;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// User price overrides, installed once at startup by [`load_overrides`]
+static OVERRIDES: OnceLock<HashMap<String, ModelPrice>> = OnceLock::new();
+
+/// USD per thousand tokens
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ModelPrice {
+    pub input_per_1k: f64,
+    pub output_per_1k: f64,
+}
+
+impl ModelPrice {
+    fn cost(&self, input_tokens: usize, output_tokens: usize) -> f64 {
+        (input_tokens as f64 / 1_000.0) * self.input_per_1k + (output_tokens as f64 / 1_000.0) * self.output_per_1k
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PricingFile {
+    #[serde(default)]
+    models: HashMap<String, ModelPrice>,
+}
+
+/// Where the pricing overrides live: `~/.config/agent-viz/pricing.toml`
+pub fn default_pricing_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config").join("agent-viz").join("pricing.toml"))
+}
+
+/// Parse a pricing file into per-model prices, keyed by lowercased model name
+pub fn parse_overrides(text: &str) -> Result<HashMap<String, ModelPrice>, String> {
+    let file: PricingFile = toml::from_str(text).map_err(|e| e.to_string())?;
+    Ok(file
+        .models
+        .into_iter()
+        .map(|(model, price)| (model.to_lowercase(), price))
+        .collect())
+}
+
+/// Install the overrides in `path`, returning how many models they price.
+///
+/// A missing file is not an error and installs nothing. Overrides can only be installed once per
+/// process; later calls leave the first set in place.
+pub fn load_overrides(path: &Path) -> Result<usize, String> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let overrides = parse_overrides(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let count = overrides.len();
+    let _ = OVERRIDES.set(overrides);
+    Ok(count)
+}
+
+/// Estimated USD cost of a model call, or `None` for a model with no known price.
+///
+/// An override matches the model name as given or the registry ID it resolves to, and wins over
+/// the registry's built-in price.
+pub fn estimate_cost(model: &str, input_tokens: usize, output_tokens: usize) -> Option<f64> {
+    estimate_cost_with(OVERRIDES.get(), model, input_tokens, output_tokens)
+}
+
+fn estimate_cost_with(
+    overrides: Option<&HashMap<String, ModelPrice>>, model: &str, input_tokens: usize, output_tokens: usize,
+) -> Option<f64> {
+    let meta = ModelMetadata::lookup(model);
+    if let Some(overrides) = overrides {
+        let name = model.to_lowercase();
+        let price = overrides.get(&name).or_else(|| overrides.get(&meta.as_ref()?.model_id));
+        if let Some(price) = price {
+            return Some(price.cost(input_tokens, output_tokens));
+        }
+    }
+    meta.map(|meta| meta.calculate_cost(input_tokens, output_tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_model_uses_registry_price() {
+        // claude-4.5-sonnet: $3 in, $15 out per million
+        let cost = estimate_cost_with(None, "claude-sonnet-4-5-20250929", 100_000, 10_000).unwrap();
+        assert!((cost - 0.45).abs() < 1e-9, "{}", cost);
+    }
+
+    #[test]
+    fn test_unknown_model_has_no_cost() {
+        assert_eq!(estimate_cost_with(None, "mystery-model", 1_000, 1_000), None);
+    }
+
+    #[test]
+    fn test_overrides_win_over_the_registry() {
+        let overrides = parse_overrides(
+            r#"
+            [models."claude-4.5-sonnet"]
+            input_per_1k = 0.001
+            output_per_1k = 0.002
+
+            [models.Local-Llama]
+            input_per_1k = 0.0
+            output_per_1k = 0.0
+            "#,
+        )
+        .unwrap();
+
+        let cost = estimate_cost_with(Some(&overrides), "claude-sonnet-4-5-20250929", 10_000, 1_000).unwrap();
+        assert!((cost - 0.012).abs() < 1e-9, "{}", cost);
+        assert_eq!(
+            estimate_cost_with(Some(&overrides), "local-llama", 5_000, 5_000),
+            Some(0.0)
+        );
+        assert!(parse_overrides("[models.bad]\ninput_per_1k = 1.0").is_err());
+    }
+}
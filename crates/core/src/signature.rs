@@ -0,0 +1,146 @@
+//! Error signatures for grouping errors that differ only in incidental detail.
+//!
+//! Two failures of the same kind rarely have identical messages: one mentions
+//! `/home/alice/app/src/main.rs:12:5`, the next `/home/bob/app/src/lib.rs:40:9`. A signature keeps
+//! the wording and replaces the parts that vary between occurrences with placeholders:
+//! - absolute paths become `<path>`
+//! - UUIDs become `<uuid>`
+//! - hex addresses such as `0x7ffd5e8c` become `<addr>`
+//! - line and column numbers (`file.rs:12:5`, `line 12`) become `<n>`
+
+/// Punctuation trimmed from either end of a word before it is classified
+const WRAPPERS: &[char] = &['"', '\'', '`', '(', ')', '[', ']', '{', '}', '<', '>', ',', ';'];
+
+/// Words after which a bare number is a position rather than part of the message
+const POSITION_WORDS: &[&str] = &["line", "lines", "column", "col", "ln"];
+
+/// Normalize an error message into a signature shared by every occurrence of the same error
+pub fn error_signature(message: &str) -> String {
+    let mut words = Vec::new();
+    let mut previous = String::new();
+    for word in message.split_whitespace() {
+        let start = word.len() - word.trim_start_matches(WRAPPERS).len();
+        let end = start + word[start..].trim_end_matches(WRAPPERS).len();
+        let (prefix, core, suffix) = (&word[..start], &word[start..end], &word[end..]);
+
+        let normalized = if is_absolute_path(core) {
+            "<path>".to_string()
+        } else if POSITION_WORDS.contains(&previous.as_str()) && is_number(core.trim_end_matches([':', '.'])) {
+            let rest = core.trim_start_matches(|c: char| c.is_ascii_digit());
+            format!("<n>{}", rest)
+        } else {
+            strip_position(&replace_ids(core))
+        };
+
+        previous = core.to_ascii_lowercase();
+        words.push(format!("{}{}{}", prefix, normalized, suffix));
+    }
+    words.join(" ")
+}
+
+/// `/usr/lib`, `~/src`, `C:\Users` or `C:/Users`
+fn is_absolute_path(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    let drive =
+        bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'\\' | b'/');
+    (word.starts_with('/') && word.len() > 1) || word.starts_with("~/") || drive
+}
+
+fn is_number(word: &str) -> bool {
+    !word.is_empty() && word.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Replace `:12` and `:12:5` at the end of a word (a file position) with `:<n>`
+fn strip_position(word: &str) -> String {
+    let mut rest = word.trim_end_matches(':');
+    let trailing = &word[rest.len()..];
+    let mut positions = 0;
+    while positions < 2 {
+        match rest.rsplit_once(':') {
+            Some((head, tail)) if is_number(tail) && !head.is_empty() => {
+                rest = head;
+                positions += 1;
+            }
+            _ => break,
+        }
+    }
+    format!("{}{}{}", rest, ":<n>".repeat(positions), trailing)
+}
+
+/// Replace UUIDs and `0x` hex addresses anywhere in a word
+fn replace_ids(word: &str) -> String {
+    let mut out = String::with_capacity(word.len());
+    let mut i = 0;
+    while i < word.len() {
+        let rest = &word[i..];
+        if let Some(len) = uuid_len(rest) {
+            out.push_str("<uuid>");
+            i += len;
+        } else if let Some(len) = hex_address_len(rest) {
+            out.push_str("<addr>");
+            i += len;
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            out.push(c);
+            i += c.len_utf8();
+        }
+    }
+    out
+}
+
+/// Length of a UUID (`8-4-4-4-12` hex digits) at the start of `s`
+fn uuid_len(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    if bytes.len() < 36 {
+        return None;
+    }
+    let valid = bytes[..36].iter().enumerate().all(|(i, b)| match i {
+        8 | 13 | 18 | 23 => *b == b'-',
+        _ => b.is_ascii_hexdigit(),
+    });
+    let bounded = bytes.get(36).is_none_or(|b| !b.is_ascii_hexdigit());
+    (valid && bounded).then_some(36)
+}
+
+/// Length of a `0x`-prefixed hex number at the start of `s`
+fn hex_address_len(s: &str) -> Option<usize> {
+    let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))?;
+    let len = digits.bytes().take_while(u8::is_ascii_hexdigit).count();
+    (len > 0).then_some(2 + len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_differing_in_paths_and_positions_share_a_signature() {
+        let a = error_signature("panicked at /home/alice/app/src/main.rs:12:5: index out of bounds");
+        let b = error_signature("panicked at /Users/bob/work/app/src/lib.rs:40:9: index out of bounds");
+        assert_eq!(a, b);
+        assert_eq!(a, "panicked at <path> index out of bounds");
+
+        assert_eq!(
+            error_signature("error[E0308]: mismatched types --> src/main.rs:3:14"),
+            error_signature("error[E0308]: mismatched types --> src/main.rs:88:2"),
+        );
+        assert_eq!(
+            error_signature("SyntaxError on line 7, column 12"),
+            "SyntaxError on line <n>, column <n>"
+        );
+    }
+
+    #[test]
+    fn test_ids_and_addresses_are_replaced() {
+        assert_eq!(
+            error_signature("session 3f2c9a1e-8b4d-4c6f-9e1a-2b3c4d5e6f70 not found"),
+            "session <uuid> not found"
+        );
+        assert_eq!(
+            error_signature("segfault at 0x7ffd5e8c (pc=0xDEADBEEF)"),
+            error_signature("segfault at 0x10 (pc=0x1)"),
+        );
+        assert_eq!(error_signature("exit code 127"), "exit code 127");
+        assert_eq!(error_signature("  spaced   out  "), "spaced out");
+    }
+}
@@ -0,0 +1,106 @@
+use agent_v_core::{Event, Session};
+use agent_v_store::Database;
+use std::collections::HashSet;
+use std::future::Future;
+use tokio_util::sync::CancellationToken;
+
+/// Counts from importing a batch of discovered sessions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchOutcome {
+    pub imported: usize,
+    pub failed: usize,
+    /// The batch stopped early because its token was cancelled
+    pub cancelled: bool,
+}
+
+/// Parse and insert discovered sessions one at a time, recording imported external IDs in `known`.
+///
+/// `cancel` is checked before each session so a caller can abort a long import; sessions already
+/// inserted are kept and counted in the partial outcome.
+pub async fn import_sessions<T, F, Fut, E>(
+    db: &Database, items: Vec<T>, parse: F, known: &mut HashSet<String>, cancel: &CancellationToken,
+) -> BatchOutcome
+where
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = Result<(Session, Vec<Event>), E>>,
+    E: std::fmt::Display,
+{
+    let mut outcome = BatchOutcome::default();
+
+    for item in items {
+        if cancel.is_cancelled() {
+            outcome.cancelled = true;
+            break;
+        }
+
+        match parse(item).await {
+            Ok((session, events)) => match db.insert_session_with_events(&session, &events).await {
+                Ok(_) => {
+                    known.insert(session.external_id);
+                    outcome.imported += 1;
+                }
+                Err(e) => {
+                    log::warn!("Failed to insert session {}: {}", session.external_id, e);
+                    outcome.failed += 1;
+                }
+            },
+            Err(e) => {
+                log::warn!("Failed to parse session: {}", e);
+                outcome.failed += 1;
+            }
+        }
+    }
+
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_v_core::Source;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn session(external_id: &str) -> Session {
+        Session {
+            id: Uuid::new_v4(),
+            source: Source::Codex,
+            external_id: external_id.to_string(),
+            project: None,
+            title: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_payload: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_mid_import_returns_partial_count() {
+        let db = Database::open_in_memory().await.unwrap();
+
+        let cancel = CancellationToken::new();
+        let ids: Vec<String> = (0..5).map(|i| format!("batch-{}", i)).collect();
+        let mut known = HashSet::new();
+
+        let outcome = import_sessions(
+            &db,
+            ids,
+            |id: String| {
+                let cancel = cancel.clone();
+                async move {
+                    if id == "batch-1" {
+                        cancel.cancel();
+                    }
+                    Ok::<_, String>((session(&id), Vec::new()))
+                }
+            },
+            &mut known,
+            &cancel,
+        )
+        .await;
+
+        assert_eq!(outcome, BatchOutcome { imported: 2, failed: 0, cancelled: true });
+        assert_eq!(known.len(), 2);
+        assert_eq!(db.get_external_ids("codex").await.unwrap(), known);
+    }
+}
@@ -1,3 +1,8 @@
+pub mod batch;
+pub mod sources;
 pub mod watcher;
 
+pub use batch::{BatchOutcome, import_sessions};
+pub use sources::{discover_external_ids, ingest_one};
+pub use tokio_util::sync::CancellationToken;
 pub use watcher::{EventCallback, IngestProgress, ProgressCallback, StreamingEvent, Watcher, WatcherConfig};
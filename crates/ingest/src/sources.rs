@@ -0,0 +1,121 @@
+//! Per-source dispatch shared by the CLI, the desktop app, and the API.
+//!
+//! Each source has its own adapter type, so callers that want "every source" end up matching on
+//! [`Source`]. Keeping those matches here means a new adapter is wired in once rather than at
+//! every call site.
+
+use crate::batch::{BatchOutcome, import_sessions};
+use agent_v_adapters::{ClaudeAdapter, CodexAdapter, CrushAdapter, GeminiAdapter, OpenCodeAdapter};
+use agent_v_core::Source;
+use agent_v_store::Database;
+use std::collections::HashSet;
+use tokio_util::sync::CancellationToken;
+
+/// Discover and import every session of `source`, recording imported external IDs in `known`
+pub async fn ingest_one(
+    source: Source, db: &Database, known: &mut HashSet<String>, cancel: &CancellationToken,
+) -> BatchOutcome {
+    match source {
+        Source::Claude => {
+            let adapter = ClaudeAdapter::new();
+            let sessions = adapter.discover_sessions().await;
+            let adapter = &adapter;
+            import_sessions(
+                db,
+                sessions,
+                |f| async move { adapter.parse_session(&f).await },
+                known,
+                cancel,
+            )
+            .await
+        }
+        Source::Codex => {
+            let adapter = CodexAdapter::new();
+            let sessions = adapter.discover_sessions().await;
+            let adapter = &adapter;
+            import_sessions(
+                db,
+                sessions,
+                |f| async move { adapter.parse_session(&f).await },
+                known,
+                cancel,
+            )
+            .await
+        }
+        Source::OpenCode => {
+            let adapter = OpenCodeAdapter::new();
+            let sessions = adapter.discover_sessions().await;
+            let adapter = &adapter;
+            import_sessions(
+                db,
+                sessions,
+                |s| async move { adapter.parse_session(&s).await },
+                known,
+                cancel,
+            )
+            .await
+        }
+        Source::Crush => {
+            let adapter = CrushAdapter::new();
+            let sessions = adapter.discover_sessions().await;
+            let adapter = &adapter;
+            import_sessions(
+                db,
+                sessions,
+                |f| async move { adapter.parse_session(&f).await },
+                known,
+                cancel,
+            )
+            .await
+        }
+        Source::Gemini => {
+            let adapter = GeminiAdapter::new();
+            let sessions = adapter.discover_sessions().await;
+            let adapter = &adapter;
+            import_sessions(
+                db,
+                sessions,
+                |f| async move { adapter.parse_session(&f).await },
+                known,
+                cancel,
+            )
+            .await
+        }
+    }
+}
+
+/// External IDs of the sessions `source` would import, without parsing them
+pub async fn discover_external_ids(source: Source) -> Vec<String> {
+    match source {
+        Source::Claude => ClaudeAdapter::new()
+            .discover_sessions()
+            .await
+            .into_iter()
+            .map(|f| f.session_id)
+            .collect(),
+        Source::Codex => CodexAdapter::new()
+            .discover_sessions()
+            .await
+            .into_iter()
+            .map(|f| f.session_id)
+            .collect(),
+        Source::OpenCode => OpenCodeAdapter::new()
+            .discover_sessions()
+            .await
+            .into_iter()
+            .map(|s| s.id)
+            .collect(),
+        Source::Crush => CrushAdapter::new()
+            .discover_sessions()
+            .await
+            .into_iter()
+            .map(|f| f.session_id)
+            .collect(),
+        Source::Gemini => GeminiAdapter::new()
+            .discover_sessions()
+            .await
+            .into_iter()
+            .map(|f| f.session_id)
+            .collect(),
+    }
+}
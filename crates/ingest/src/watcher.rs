@@ -1,5 +1,6 @@
-use agent_v_adapters::{ClaudeAdapter, CodexAdapter, CrushAdapter, OpenCodeAdapter};
-use agent_v_core::Source;
+use crate::batch::BatchOutcome;
+use agent_v_adapters::{ClaudeAdapter, CodexAdapter, CrushAdapter, GeminiAdapter, OpenCodeAdapter};
+use agent_v_core::{ExternalId, SessionId, Source};
 use agent_v_store::Database;
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher as NotifyWatcher};
 use serde::Serialize;
@@ -15,13 +16,61 @@ use tokio::time::interval;
 pub struct WatcherConfig {
     /// Polling interval for Crush (SQLite files)
     pub crush_poll_interval: Duration,
+    /// Longest Crush polling interval reached by backing off while nothing changes
+    pub crush_max_poll_interval: Duration,
+    /// Consecutive no-change Crush polls before the interval starts doubling
+    pub crush_idle_polls_before_backoff: u32,
     /// Debounce duration for file system events
     pub debounce_duration: Duration,
 }
 
 impl Default for WatcherConfig {
     fn default() -> Self {
-        Self { crush_poll_interval: Duration::from_secs(30), debounce_duration: Duration::from_secs(2) }
+        Self {
+            crush_poll_interval: Duration::from_secs(30),
+            crush_max_poll_interval: Duration::from_secs(300),
+            crush_idle_polls_before_backoff: 3,
+            debounce_duration: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Adaptive polling interval: doubles after a run of idle polls, up to a cap,
+/// and drops back to the base interval as soon as a poll sees a change
+#[derive(Debug, Clone)]
+struct PollBackoff {
+    base: Duration,
+    cap: Duration,
+    idle_threshold: u32,
+    idle_polls: u32,
+    current: Duration,
+}
+
+impl PollBackoff {
+    fn new(base: Duration, cap: Duration, idle_threshold: u32) -> Self {
+        Self { base, cap: cap.max(base), idle_threshold, idle_polls: 0, current: base }
+    }
+
+    fn for_crush(config: &WatcherConfig) -> Self {
+        Self::new(
+            config.crush_poll_interval,
+            config.crush_max_poll_interval,
+            config.crush_idle_polls_before_backoff,
+        )
+    }
+
+    /// Record the outcome of a poll and return how long to wait before the next one
+    fn next_interval(&mut self, changed: bool) -> Duration {
+        if changed {
+            self.idle_polls = 0;
+            self.current = self.base;
+        } else {
+            self.idle_polls = self.idle_polls.saturating_add(1);
+            if self.idle_polls >= self.idle_threshold {
+                self.current = (self.current * 2).min(self.cap);
+            }
+        }
+        self.current
     }
 }
 
@@ -68,6 +117,8 @@ enum SessionCursor {
     KnownFiles(HashSet<String>),
     /// Last message created_at timestamp (Crush)
     LastCreatedAt(i64),
+    /// Number of events already seen (Gemini, which rewrites the whole chat file)
+    EventCount(usize),
 }
 
 /// Watcher for file system changes and database updates
@@ -76,7 +127,7 @@ pub struct Watcher {
     stats: Arc<Mutex<Vec<IngestStats>>>,
     cursors: Arc<Mutex<HashMap<String, SessionCursor>>>,
     file_mtimes: Arc<Mutex<HashMap<PathBuf, SystemTime>>>,
-    dirty_sessions: Arc<Mutex<HashSet<String>>>,
+    dirty_sessions: Arc<Mutex<HashSet<SessionId>>>,
     event_callback: Option<EventCallback>,
     progress_callback: Option<ProgressCallback>,
 }
@@ -135,12 +186,14 @@ impl Watcher {
         let claude_paths = self.get_claude_watch_paths().await;
         let codex_paths = self.get_codex_watch_paths().await;
         let opencode_paths = self.get_opencode_watch_paths().await;
+        let gemini_paths = self.get_gemini_watch_paths().await;
         let source_roots: Vec<(String, PathBuf)> = claude_paths
             .iter()
             .cloned()
             .map(|p| ("claude".to_string(), p))
             .chain(codex_paths.iter().cloned().map(|p| ("codex".to_string(), p)))
             .chain(opencode_paths.iter().cloned().map(|p| ("opencode".to_string(), p)))
+            .chain(gemini_paths.iter().cloned().map(|p| ("gemini".to_string(), p)))
             .collect();
 
         let tx_clone = tx.clone();
@@ -192,7 +245,7 @@ impl Watcher {
             }
         }
 
-        let crush_poll_interval = self.config.crush_poll_interval;
+        let crush_backoff = PollBackoff::for_crush(&self.config);
         let crush_stats = self.stats.clone();
         let crush_cursors = self.cursors.clone();
         let crush_callback = self.event_callback.clone();
@@ -200,7 +253,7 @@ impl Watcher {
         let crush_mtimes = self.file_mtimes.clone();
         let crush_dirty = self.dirty_sessions.clone();
         let crush_handle = tokio::spawn(Self::watch_crush_streaming(
-            crush_poll_interval,
+            crush_backoff,
             crush_stats,
             crush_cursors,
             crush_callback,
@@ -240,15 +293,7 @@ impl Watcher {
                             pending_events.remove(&source_str);
                             log::info!("Ingesting from {} due to file change", source_str);
 
-                            let source = match source_str.as_str() {
-                                "claude" => Some(Source::Claude),
-                                "codex" => Some(Source::Codex),
-                                "opencode" => Some(Source::OpenCode),
-                                "crush" => Some(Source::Crush),
-                                _ => None,
-                            };
-
-                            if let Some(src) = source {
+                            if let Ok(src) = source_str.parse::<Source>() {
                                 let result = Self::ingest_source_streaming(
                                     src,
                                     cursors.clone(),
@@ -259,12 +304,12 @@ impl Watcher {
                                 ).await;
 
                                 match result {
-                                    Ok(_) => {
+                                    Ok(outcome) => {
                                         let mut s = stats.lock().await;
                                         s.push(IngestStats {
                                             source: source_str.clone(),
-                                            imported: 1,
-                                            failed: 0,
+                                            imported: outcome.imported,
+                                            failed: outcome.failed,
                                             timestamp: SystemTime::now(),
                                         });
                                     }
@@ -289,11 +334,12 @@ impl Watcher {
     /// Watch a specific source
     pub async fn watch_source(self, source: Source) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         match source {
-            Source::Claude | Source::Codex | Source::OpenCode => {
+            Source::Claude | Source::Codex | Source::OpenCode | Source::Gemini => {
                 let paths = match source {
                     Source::Claude => self.get_claude_watch_paths().await,
                     Source::Codex => self.get_codex_watch_paths().await,
                     Source::OpenCode => self.get_opencode_watch_paths().await,
+                    Source::Gemini => self.get_gemini_watch_paths().await,
                     _ => unreachable!(),
                 };
 
@@ -326,13 +372,9 @@ impl Watcher {
                 let source_str = source.to_string();
 
                 while let Some(_ts) = rx.recv().await {
-                    tokio::time::sleep(debounce_duration).await;
-
-                    while rx.try_recv().is_ok() {
-                        log::debug!("Draining pending events");
-                    }
+                    let changes = Self::coalesce_burst(&mut rx, debounce_duration).await;
 
-                    log::info!("Ingesting from {:?} due to file change", source);
+                    log::info!("Ingesting from {:?} after {} file change(s)", source, changes);
                     match Self::ingest_source_streaming(
                         source,
                         cursors.clone(),
@@ -343,12 +385,12 @@ impl Watcher {
                     )
                     .await
                     {
-                        Ok(_) => {
+                        Ok(outcome) => {
                             let mut s = stats.lock().await;
                             s.push(IngestStats {
                                 source: source_str.clone(),
-                                imported: 1,
-                                failed: 0,
+                                imported: outcome.imported,
+                                failed: outcome.failed,
                                 timestamp: SystemTime::now(),
                             });
                         }
@@ -363,7 +405,7 @@ impl Watcher {
                 let _metrics_handle = tokio::spawn(Self::background_metrics_worker(metrics_dirty));
 
                 Self::watch_crush_streaming(
-                    self.config.crush_poll_interval,
+                    PollBackoff::for_crush(&self.config),
                     self.stats.clone(),
                     self.cursors.clone(),
                     self.event_callback.clone(),
@@ -378,18 +420,19 @@ impl Watcher {
         Ok(())
     }
 
-    /// Background worker that computes metrics for dirty sessions on a debounced interval
-    async fn background_metrics_worker(dirty_sessions: Arc<Mutex<HashSet<String>>>) {
+    /// Background worker that rebuilds tool calls, files touched, and metrics for sessions that had
+    /// events appended, on a debounced interval. Full inserts rebuild inline and are never marked.
+    async fn background_metrics_worker(dirty_sessions: Arc<Mutex<HashSet<SessionId>>>) {
         let mut tick = interval(Duration::from_secs(5));
         loop {
             tick.tick().await;
 
-            let sessions_to_compute: Vec<String> = {
+            let sessions_to_compute: Vec<SessionId> = {
                 let mut dirty = dirty_sessions.lock().await;
                 if dirty.is_empty() {
                     continue;
                 }
-                let batch: Vec<String> = dirty.drain().collect();
+                let batch: Vec<SessionId> = dirty.drain().collect();
                 batch
             };
 
@@ -408,7 +451,7 @@ impl Watcher {
             match db_ok {
                 Some(db) => {
                     for session_id in &sessions_to_compute {
-                        if let Err(e) = db.compute_session_metrics(session_id).await {
+                        if let Err(e) = db.rebuild_session_derived(*session_id).await {
                             log::warn!("Failed to compute metrics for {}: {}", session_id, e);
                         }
                     }
@@ -423,20 +466,17 @@ impl Watcher {
         }
     }
 
-    /// Watch Crush database for changes with streaming support
+    /// Watch Crush database for changes with streaming support, polling less often while it stays idle
     async fn watch_crush_streaming(
-        poll_interval: Duration, stats: Arc<Mutex<Vec<IngestStats>>>,
+        mut backoff: PollBackoff, stats: Arc<Mutex<Vec<IngestStats>>>,
         cursors: Arc<Mutex<HashMap<String, SessionCursor>>>, callback: Option<EventCallback>,
         progress: Option<ProgressCallback>, _mtimes: Arc<Mutex<HashMap<PathBuf, SystemTime>>>,
-        dirty_sessions: Arc<Mutex<HashSet<String>>>,
+        dirty_sessions: Arc<Mutex<HashSet<SessionId>>>,
     ) {
         let adapter = CrushAdapter::new();
         let mut last_check = SystemTime::now();
-        let mut check_interval = interval(poll_interval);
 
         loop {
-            check_interval.tick().await;
-
             let sessions = adapter.discover_sessions().await;
             let mut needs_ingest = false;
 
@@ -452,20 +492,22 @@ impl Watcher {
 
             if needs_ingest {
                 log::info!("Crush database modified, re-ingesting");
-                match Self::ingest_crush_streaming(
-                    cursors.clone(),
-                    callback.clone(),
-                    progress.clone(),
-                    dirty_sessions.clone(),
-                )
-                .await
-                {
-                    Ok(_) => {
+                match Self::open_db().await {
+                    Ok(db) => {
+                        let outcome = Self::ingest_crush_streaming(
+                            &db,
+                            &adapter,
+                            cursors.clone(),
+                            callback.clone(),
+                            progress.clone(),
+                            dirty_sessions.clone(),
+                        )
+                        .await;
                         let mut s = stats.lock().await;
                         s.push(IngestStats {
                             source: "crush".to_string(),
-                            imported: 1,
-                            failed: 0,
+                            imported: outcome.imported,
+                            failed: outcome.failed,
                             timestamp: SystemTime::now(),
                         });
                     }
@@ -474,7 +516,20 @@ impl Watcher {
             }
 
             last_check = SystemTime::now();
+            let wait = backoff.next_interval(needs_ingest);
+            log::trace!("Next Crush poll in {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Absorb the rest of a burst of change notifications, the first of which was just received:
+    /// keep taking them until none arrives for `quiet`, and return how many the burst had in all
+    async fn coalesce_burst<T>(rx: &mut mpsc::Receiver<T>, quiet: Duration) -> usize {
+        let mut changes = 1;
+        while let Ok(Some(_)) = tokio::time::timeout(quiet, rx.recv()).await {
+            changes += 1;
         }
+        changes
     }
 
     /// Get paths to watch for Claude
@@ -491,8 +546,26 @@ impl Watcher {
 
     /// Get paths to watch for OpenCode
     async fn get_opencode_watch_paths(&self) -> Vec<PathBuf> {
-        let adapter = OpenCodeAdapter::new();
-        vec![adapter.log_path().clone()]
+        Self::opencode_watch_paths(&OpenCodeAdapter::new())
+    }
+
+    /// OpenCode's per-file session, message, and part storage, plus its log directory, which
+    /// changes while a version that keeps sessions in `opencode.db` is running. Either way the
+    /// change re-ingests through the adapter.
+    fn opencode_watch_paths(adapter: &OpenCodeAdapter) -> Vec<PathBuf> {
+        let storage = adapter.storage_path();
+        vec![
+            storage.join("session"),
+            storage.join("message"),
+            storage.join("part"),
+            adapter.log_path().clone(),
+        ]
+    }
+
+    /// Get paths to watch for Gemini CLI
+    async fn get_gemini_watch_paths(&self) -> Vec<PathBuf> {
+        let adapter = GeminiAdapter::new();
+        vec![adapter.tmp_dir().clone()]
     }
 
     /// Check if a file has been modified since last processing
@@ -526,20 +599,38 @@ impl Watcher {
         }
     }
 
-    /// Ingest from a specific source with streaming callback support
+    /// Ingest from a specific source with streaming callback support. `imported` counts sessions
+    /// stored or extended with new events, `failed` those that couldn't be parsed or stored.
     async fn ingest_source_streaming(
         source: Source, cursors: Arc<Mutex<HashMap<String, SessionCursor>>>, callback: Option<EventCallback>,
         progress: Option<ProgressCallback>, mtimes: Arc<Mutex<HashMap<PathBuf, SystemTime>>>,
-        dirty_sessions: Arc<Mutex<HashSet<String>>>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        match source {
-            Source::Claude => Self::ingest_claude_streaming(cursors, callback, progress, mtimes, dirty_sessions).await,
-            Source::Codex => Self::ingest_codex_streaming(cursors, callback, progress, mtimes, dirty_sessions).await,
+        dirty_sessions: Arc<Mutex<HashSet<SessionId>>>,
+    ) -> Result<BatchOutcome, Box<dyn std::error::Error + Send + Sync>> {
+        let db = Self::open_db().await?;
+        let outcome = match source {
+            Source::Claude => {
+                let adapter = ClaudeAdapter::new();
+                Self::ingest_claude_streaming(&db, &adapter, cursors, callback, progress, mtimes, dirty_sessions).await
+            }
+            Source::Codex => {
+                let adapter = CodexAdapter::new();
+                Self::ingest_codex_streaming(&db, &adapter, cursors, callback, progress, mtimes, dirty_sessions).await
+            }
             Source::OpenCode => {
-                Self::ingest_opencode_streaming(cursors, callback, progress, mtimes, dirty_sessions).await
+                let adapter = OpenCodeAdapter::new();
+                Self::ingest_opencode_streaming(&db, &adapter, cursors, callback, progress, mtimes, dirty_sessions)
+                    .await
             }
-            Source::Crush => Self::ingest_crush_streaming(cursors, callback, progress, dirty_sessions).await,
-        }
+            Source::Crush => {
+                let adapter = CrushAdapter::new();
+                Self::ingest_crush_streaming(&db, &adapter, cursors, callback, progress, dirty_sessions).await
+            }
+            Source::Gemini => {
+                let adapter = GeminiAdapter::new();
+                Self::ingest_gemini_streaming(&db, &adapter, cursors, callback, progress, mtimes).await
+            }
+        };
+        Ok(outcome)
     }
 
     /// Helper function to open database with proper error conversion
@@ -550,22 +641,50 @@ impl Watcher {
         }
     }
 
-    /// Mark a session as needing metric recomputation
-    async fn mark_dirty(dirty_sessions: &Arc<Mutex<HashSet<String>>>, session_id: &str) {
+    /// Mark a session whose events were appended as needing its derived rows rebuilt
+    async fn mark_dirty(dirty_sessions: &Arc<Mutex<HashSet<SessionId>>>, session_id: SessionId) {
         let mut dirty = dirty_sessions.lock().await;
-        dirty.insert(session_id.to_string());
+        dirty.insert(session_id);
+    }
+
+    /// Append incrementally parsed events to the stored session with `external_id`, marking it dirty.
+    /// Returns `false`, leaving the session untouched, when it can't be found or the append fails.
+    async fn append_to_stored_session(
+        db: &Database, source: &str, external_id: &str, events: &[agent_v_core::Event],
+        dirty_sessions: &Arc<Mutex<HashSet<SessionId>>>,
+    ) -> bool {
+        let session_id = match db
+            .get_session_id_by_external(source, &ExternalId::from(external_id))
+            .await
+        {
+            Ok(Some(session_id)) => session_id,
+            Ok(None) => {
+                log::warn!("No stored {} session {} to append events to", source, external_id);
+                return false;
+            }
+            Err(e) => {
+                log::warn!("Failed to look up {} session {}: {}", source, external_id, e);
+                return false;
+            }
+        };
+        if let Err(e) = db.append_events(session_id, events).await {
+            log::warn!("Failed to append events to {} session {}: {}", source, external_id, e);
+            return false;
+        }
+        let _ = db.update_session_timestamp(session_id, &chrono::Utc::now()).await;
+        Self::mark_dirty(dirty_sessions, session_id).await;
+        true
     }
 
     /// Ingest Claude sessions with incremental parsing and callback
     async fn ingest_claude_streaming(
-        cursors: Arc<Mutex<HashMap<String, SessionCursor>>>, callback: Option<EventCallback>,
-        progress: Option<ProgressCallback>, mtimes: Arc<Mutex<HashMap<PathBuf, SystemTime>>>,
-        dirty_sessions: Arc<Mutex<HashSet<String>>>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let db = Self::open_db().await?;
-        let adapter = ClaudeAdapter::new();
+        db: &Database, adapter: &ClaudeAdapter, cursors: Arc<Mutex<HashMap<String, SessionCursor>>>,
+        callback: Option<EventCallback>, progress: Option<ProgressCallback>,
+        mtimes: Arc<Mutex<HashMap<PathBuf, SystemTime>>>, dirty_sessions: Arc<Mutex<HashSet<SessionId>>>,
+    ) -> BatchOutcome {
         let session_files = adapter.discover_sessions().await;
         let total = session_files.len();
+        let mut outcome = BatchOutcome::default();
 
         for (idx, session_file) in session_files.into_iter().enumerate() {
             if let Some(ref pcb) = progress {
@@ -596,9 +715,13 @@ impl Watcher {
 
             if is_new_session {
                 if let Ok((session, events)) = adapter.parse_session(&session_file).await {
-                    let session_id = session.id.to_string();
-                    let _ = db.insert_session_with_events(&session, &events).await;
-                    Self::mark_dirty(&dirty_sessions, &session_id).await;
+                    match db.insert_session_with_events(&session, &events).await {
+                        Ok(_) => outcome.imported += 1,
+                        Err(e) => {
+                            log::warn!("Failed to store session {}: {}", session.external_id, e);
+                            outcome.failed += 1;
+                        }
+                    }
 
                     let file_len = tokio::fs::metadata(&session_file.path)
                         .await
@@ -621,18 +744,26 @@ impl Watcher {
                             is_new_session: true,
                         });
                     }
+                } else {
+                    outcome.failed += 1;
                 }
             } else {
                 match adapter.parse_session_incremental(&session_file, byte_offset).await {
                     Ok((new_events, new_offset)) => {
                         if !new_events.is_empty() {
-                            if let Ok(Some(session_id)) =
-                                db.get_session_id_by_external("claude", &session_file.session_id).await
+                            if !Self::append_to_stored_session(
+                                db,
+                                "claude",
+                                &session_file.session_id,
+                                &new_events,
+                                &dirty_sessions,
+                            )
+                            .await
                             {
-                                let _ = db.append_events(&session_id, &new_events).await;
-                                let _ = db.update_session_timestamp(&session_id, &chrono::Utc::now()).await;
-                                Self::mark_dirty(&dirty_sessions, &session_id).await;
+                                outcome.failed += 1;
+                                continue;
                             }
+                            outcome.imported += 1;
 
                             if let Some(ref cb) = callback {
                                 cb(StreamingEvent {
@@ -650,7 +781,10 @@ impl Watcher {
                             c.insert(cursor_key, SessionCursor::ByteOffset(new_offset));
                         }
                     }
-                    Err(e) => log::warn!("Incremental parse failed for {}: {}", session_file.session_id, e),
+                    Err(e) => {
+                        log::warn!("Incremental parse failed for {}: {}", session_file.session_id, e);
+                        outcome.failed += 1;
+                    }
                 }
             }
 
@@ -661,19 +795,18 @@ impl Watcher {
             pcb(IngestProgress { source: "claude".to_string(), phase: "Complete".to_string(), current: total, total });
         }
 
-        Ok(())
+        outcome
     }
 
     /// Ingest Codex sessions with incremental parsing and callback
     async fn ingest_codex_streaming(
-        cursors: Arc<Mutex<HashMap<String, SessionCursor>>>, callback: Option<EventCallback>,
-        progress: Option<ProgressCallback>, mtimes: Arc<Mutex<HashMap<PathBuf, SystemTime>>>,
-        dirty_sessions: Arc<Mutex<HashSet<String>>>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let db = Self::open_db().await?;
-        let adapter = CodexAdapter::new();
+        db: &Database, adapter: &CodexAdapter, cursors: Arc<Mutex<HashMap<String, SessionCursor>>>,
+        callback: Option<EventCallback>, progress: Option<ProgressCallback>,
+        mtimes: Arc<Mutex<HashMap<PathBuf, SystemTime>>>, dirty_sessions: Arc<Mutex<HashSet<SessionId>>>,
+    ) -> BatchOutcome {
         let session_files = adapter.discover_sessions().await;
         let total = session_files.len();
+        let mut outcome = BatchOutcome::default();
 
         for (idx, session_file) in session_files.into_iter().enumerate() {
             if let Some(ref pcb) = progress {
@@ -704,9 +837,13 @@ impl Watcher {
 
             if is_new_session {
                 if let Ok((session, events)) = adapter.parse_session(&session_file).await {
-                    let session_id = session.id.to_string();
-                    let _ = db.insert_session_with_events(&session, &events).await;
-                    Self::mark_dirty(&dirty_sessions, &session_id).await;
+                    match db.insert_session_with_events(&session, &events).await {
+                        Ok(_) => outcome.imported += 1,
+                        Err(e) => {
+                            log::warn!("Failed to store session {}: {}", session.external_id, e);
+                            outcome.failed += 1;
+                        }
+                    }
 
                     let file_len = tokio::fs::metadata(&session_file.path)
                         .await
@@ -729,18 +866,26 @@ impl Watcher {
                             is_new_session: true,
                         });
                     }
+                } else {
+                    outcome.failed += 1;
                 }
             } else {
                 match adapter.parse_session_incremental(&session_file, byte_offset).await {
                     Ok((new_events, new_offset)) => {
                         if !new_events.is_empty() {
-                            if let Ok(Some(session_id)) =
-                                db.get_session_id_by_external("codex", &session_file.session_id).await
+                            if !Self::append_to_stored_session(
+                                db,
+                                "codex",
+                                &session_file.session_id,
+                                &new_events,
+                                &dirty_sessions,
+                            )
+                            .await
                             {
-                                let _ = db.append_events(&session_id, &new_events).await;
-                                let _ = db.update_session_timestamp(&session_id, &chrono::Utc::now()).await;
-                                Self::mark_dirty(&dirty_sessions, &session_id).await;
+                                outcome.failed += 1;
+                                continue;
                             }
+                            outcome.imported += 1;
 
                             if let Some(ref cb) = callback {
                                 cb(StreamingEvent {
@@ -758,7 +903,10 @@ impl Watcher {
                             c.insert(cursor_key, SessionCursor::ByteOffset(new_offset));
                         }
                     }
-                    Err(e) => log::warn!("Incremental parse failed for {}: {}", session_file.session_id, e),
+                    Err(e) => {
+                        log::warn!("Incremental parse failed for {}: {}", session_file.session_id, e);
+                        outcome.failed += 1;
+                    }
                 }
             }
 
@@ -769,19 +917,91 @@ impl Watcher {
             pcb(IngestProgress { source: "codex".to_string(), phase: "Complete".to_string(), current: total, total });
         }
 
-        Ok(())
+        outcome
+    }
+
+    /// Ingest Gemini CLI chats, re-parsing each changed chat file and streaming the events past
+    /// the ones already seen, since Gemini rewrites the whole file on every turn
+    async fn ingest_gemini_streaming(
+        db: &Database, adapter: &GeminiAdapter, cursors: Arc<Mutex<HashMap<String, SessionCursor>>>,
+        callback: Option<EventCallback>, progress: Option<ProgressCallback>,
+        mtimes: Arc<Mutex<HashMap<PathBuf, SystemTime>>>,
+    ) -> BatchOutcome {
+        let session_files = adapter.discover_sessions().await;
+        let total = session_files.len();
+        let mut outcome = BatchOutcome::default();
+
+        for (idx, session_file) in session_files.into_iter().enumerate() {
+            if let Some(ref pcb) = progress {
+                pcb(IngestProgress {
+                    source: "gemini".to_string(),
+                    phase: "Ingesting sessions".to_string(),
+                    current: idx,
+                    total,
+                });
+            }
+
+            if !Self::file_changed(&session_file.path, &mtimes).await {
+                log::debug!("Skipping unchanged file: {:?}", session_file.path);
+                continue;
+            }
+
+            let cursor_key = format!("gemini:{}", session_file.session_id);
+            let seen = {
+                let c = cursors.lock().await;
+                match c.get(&cursor_key) {
+                    Some(SessionCursor::EventCount(count)) => Some(*count),
+                    _ => None,
+                }
+            };
+
+            let (session, events) = match adapter.parse_session(&session_file).await {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    log::warn!("Failed to parse Gemini chat {:?}: {}", session_file.path, e);
+                    outcome.failed += 1;
+                    continue;
+                }
+            };
+            if seen.is_some_and(|count| count >= events.len()) {
+                continue;
+            }
+
+            if let Err(e) = db.insert_session_with_events(&session, &events).await {
+                log::warn!("Failed to store Gemini session {}: {}", session.external_id, e);
+                outcome.failed += 1;
+                continue;
+            }
+            outcome.imported += 1;
+
+            {
+                let mut c = cursors.lock().await;
+                c.insert(cursor_key, SessionCursor::EventCount(events.len()));
+            }
+
+            if let Some(ref cb) = callback {
+                cb(StreamingEvent {
+                    session_external_id: session_file.session_id.clone(),
+                    source: "gemini".to_string(),
+                    project: None,
+                    new_events: events.into_iter().skip(seen.unwrap_or(0)).collect(),
+                    is_new_session: seen.is_none(),
+                });
+            }
+        }
+
+        outcome
     }
 
     /// Ingest OpenCode sessions with incremental parsing and callback
     async fn ingest_opencode_streaming(
-        cursors: Arc<Mutex<HashMap<String, SessionCursor>>>, callback: Option<EventCallback>,
-        progress: Option<ProgressCallback>, _mtimes: Arc<Mutex<HashMap<PathBuf, SystemTime>>>,
-        dirty_sessions: Arc<Mutex<HashSet<String>>>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let db = Self::open_db().await?;
-        let adapter = OpenCodeAdapter::new();
+        db: &Database, adapter: &OpenCodeAdapter, cursors: Arc<Mutex<HashMap<String, SessionCursor>>>,
+        callback: Option<EventCallback>, progress: Option<ProgressCallback>,
+        _mtimes: Arc<Mutex<HashMap<PathBuf, SystemTime>>>, dirty_sessions: Arc<Mutex<HashSet<SessionId>>>,
+    ) -> BatchOutcome {
         let sessions_list = adapter.discover_sessions().await;
         let total = sessions_list.len();
+        let mut outcome = BatchOutcome::default();
 
         for (idx, session) in sessions_list.into_iter().enumerate() {
             if let Some(ref pcb) = progress {
@@ -807,9 +1027,13 @@ impl Watcher {
 
             if is_new_session {
                 if let Ok((session_obj, events)) = adapter.parse_session(&session).await {
-                    let session_id = session_obj.id.to_string();
-                    let _ = db.insert_session_with_events(&session_obj, &events).await;
-                    Self::mark_dirty(&dirty_sessions, &session_id).await;
+                    match db.insert_session_with_events(&session_obj, &events).await {
+                        Ok(_) => outcome.imported += 1,
+                        Err(e) => {
+                            log::warn!("Failed to store session {}: {}", session_obj.external_id, e);
+                            outcome.failed += 1;
+                        }
+                    }
 
                     let files = adapter.collect_incremental_known_files(&session.id).await;
 
@@ -829,16 +1053,26 @@ impl Watcher {
                             is_new_session: true,
                         });
                     }
+                } else {
+                    outcome.failed += 1;
                 }
             } else {
                 match adapter.parse_session_incremental(&session, &known_files).await {
                     Ok((new_events, new_known)) => {
                         if !new_events.is_empty() {
-                            if let Ok(Some(session_id)) = db.get_session_id_by_external("opencode", &session.id).await {
-                                let _ = db.append_events(&session_id, &new_events).await;
-                                let _ = db.update_session_timestamp(&session_id, &chrono::Utc::now()).await;
-                                Self::mark_dirty(&dirty_sessions, &session_id).await;
+                            if !Self::append_to_stored_session(
+                                db,
+                                "opencode",
+                                &session.id,
+                                &new_events,
+                                &dirty_sessions,
+                            )
+                            .await
+                            {
+                                outcome.failed += 1;
+                                continue;
                             }
+                            outcome.imported += 1;
 
                             if let Some(ref cb) = callback {
                                 cb(StreamingEvent {
@@ -856,7 +1090,10 @@ impl Watcher {
                             c.insert(cursor_key, SessionCursor::KnownFiles(new_known));
                         }
                     }
-                    Err(e) => log::warn!("Incremental parse failed for {}: {}", session.id, e),
+                    Err(e) => {
+                        log::warn!("Incremental parse failed for {}: {}", session.id, e);
+                        outcome.failed += 1;
+                    }
                 }
             }
         }
@@ -870,18 +1107,18 @@ impl Watcher {
             });
         }
 
-        Ok(())
+        outcome
     }
 
     /// Ingest Crush sessions with incremental parsing and callback
     async fn ingest_crush_streaming(
-        cursors: Arc<Mutex<HashMap<String, SessionCursor>>>, callback: Option<EventCallback>,
-        progress: Option<ProgressCallback>, dirty_sessions: Arc<Mutex<HashSet<String>>>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let db = Self::open_db().await?;
-        let adapter = CrushAdapter::new();
+        db: &Database, adapter: &CrushAdapter, cursors: Arc<Mutex<HashMap<String, SessionCursor>>>,
+        callback: Option<EventCallback>, progress: Option<ProgressCallback>,
+        dirty_sessions: Arc<Mutex<HashSet<SessionId>>>,
+    ) -> BatchOutcome {
         let session_files = adapter.discover_sessions().await;
         let total = session_files.len();
+        let mut outcome = BatchOutcome::default();
 
         for (idx, session_file) in session_files.into_iter().enumerate() {
             if let Some(ref pcb) = progress {
@@ -907,9 +1144,13 @@ impl Watcher {
 
             if is_new_session {
                 if let Ok((session, events)) = adapter.parse_session(&session_file).await {
-                    let session_id = session.id.to_string();
-                    let _ = db.insert_session_with_events(&session, &events).await;
-                    Self::mark_dirty(&dirty_sessions, &session_id).await;
+                    match db.insert_session_with_events(&session, &events).await {
+                        Ok(_) => outcome.imported += 1,
+                        Err(e) => {
+                            log::warn!("Failed to store session {}: {}", session.external_id, e);
+                            outcome.failed += 1;
+                        }
+                    }
 
                     let max_ts = events
                         .iter()
@@ -939,18 +1180,26 @@ impl Watcher {
                             is_new_session: true,
                         });
                     }
+                } else {
+                    outcome.failed += 1;
                 }
             } else {
                 match adapter.parse_session_incremental(&session_file, last_created_at).await {
                     Ok((new_events, new_last)) => {
                         if !new_events.is_empty() {
-                            if let Ok(Some(session_id)) =
-                                db.get_session_id_by_external("crush", &session_file.session_id).await
+                            if !Self::append_to_stored_session(
+                                db,
+                                "crush",
+                                &session_file.session_id,
+                                &new_events,
+                                &dirty_sessions,
+                            )
+                            .await
                             {
-                                let _ = db.append_events(&session_id, &new_events).await;
-                                let _ = db.update_session_timestamp(&session_id, &chrono::Utc::now()).await;
-                                Self::mark_dirty(&dirty_sessions, &session_id).await;
+                                outcome.failed += 1;
+                                continue;
                             }
+                            outcome.imported += 1;
 
                             if let Some(ref cb) = callback {
                                 cb(StreamingEvent {
@@ -974,7 +1223,10 @@ impl Watcher {
                             c.insert(cursor_key, SessionCursor::LastCreatedAt(new_last));
                         }
                     }
-                    Err(e) => log::warn!("Incremental parse failed for {}: {}", session_file.session_id, e),
+                    Err(e) => {
+                        log::warn!("Incremental parse failed for {}: {}", session_file.session_id, e);
+                        outcome.failed += 1;
+                    }
                 }
             }
         }
@@ -983,7 +1235,7 @@ impl Watcher {
             pcb(IngestProgress { source: "crush".to_string(), phase: "Complete".to_string(), current: total, total });
         }
 
-        Ok(())
+        outcome
     }
 
     /// Get recent ingestion statistics
@@ -1010,6 +1262,100 @@ mod tests {
         assert_eq!(config.debounce_duration, Duration::from_secs(2));
     }
 
+    #[test]
+    fn test_crush_backoff_grows_while_idle_and_resets_on_change() {
+        let config = WatcherConfig {
+            crush_poll_interval: Duration::from_secs(10),
+            crush_max_poll_interval: Duration::from_secs(60),
+            crush_idle_polls_before_backoff: 2,
+            ..WatcherConfig::default()
+        };
+        let mut backoff = PollBackoff::for_crush(&config);
+
+        let idle: Vec<u64> = (0..6).map(|_| backoff.next_interval(false).as_secs()).collect();
+        assert_eq!(idle, vec![10, 20, 40, 60, 60, 60]);
+
+        assert_eq!(backoff.next_interval(true), Duration::from_secs(10));
+        assert_eq!(backoff.next_interval(false), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_opencode_watch_paths_are_inside_its_storage() {
+        let base = PathBuf::from("/home/dev/.local/share/opencode");
+        let adapter = OpenCodeAdapter::with_paths(base.join("storage"), base.join("auth.json"));
+
+        let paths = Watcher::opencode_watch_paths(&adapter);
+        let storage: Vec<&PathBuf> = paths.iter().filter(|p| p.starts_with(adapter.storage_path())).collect();
+        assert_eq!(
+            storage,
+            [
+                &base.join("storage/session"),
+                &base.join("storage/message"),
+                &base.join("storage/part")
+            ]
+        );
+        assert!(paths.contains(&base.join("log")));
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_burst_counts_changes_until_quiet() {
+        let (tx, mut rx) = mpsc::channel(100);
+        for _ in 0..5 {
+            tx.send(SystemTime::now()).await.unwrap();
+        }
+        let late = tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            late.send(SystemTime::now()).await.unwrap();
+        });
+
+        rx.recv().await.unwrap();
+        assert_eq!(Watcher::coalesce_burst(&mut rx, Duration::from_millis(200)).await, 6);
+
+        tx.send(SystemTime::now()).await.unwrap();
+        rx.recv().await.unwrap();
+        assert_eq!(Watcher::coalesce_burst(&mut rx, Duration::from_millis(20)).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_codex_ingest_reports_imported_and_failed_sessions() {
+        let fixture = agent_v_adapters::parse_check::fixtures()
+            .into_iter()
+            .find(|f| f.source == Source::Codex)
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let day = dir.path().join("2025/01/01");
+        std::fs::create_dir_all(&day).unwrap();
+        for name in ["a", "b", "c"] {
+            std::fs::write(day.join(format!("rollout-{}.jsonl", name)), fixture.contents).unwrap();
+        }
+        std::fs::write(day.join("rollout-broken.jsonl"), [0xff, 0xfe, 0xfd]).unwrap();
+
+        let db = Database::open_in_memory().await.unwrap();
+        let adapter = CodexAdapter::with_sessions_dir(dir.path().to_path_buf());
+        let cursors = Arc::new(Mutex::new(HashMap::new()));
+        let mtimes = Arc::new(Mutex::new(HashMap::new()));
+        let dirty = Arc::new(Mutex::new(HashSet::new()));
+        let ingest = || {
+            Watcher::ingest_codex_streaming(
+                &db,
+                &adapter,
+                cursors.clone(),
+                None,
+                None,
+                mtimes.clone(),
+                dirty.clone(),
+            )
+        };
+
+        let outcome = ingest().await;
+        assert_eq!((outcome.imported, outcome.failed), (3, 1));
+        assert_eq!(db.list_sessions(10, 0).await.unwrap().len(), 3);
+
+        let outcome = ingest().await;
+        assert_eq!((outcome.imported, outcome.failed), (0, 0));
+    }
+
     #[tokio::test]
     async fn test_watcher_new() {
         let watcher = Watcher::new();
@@ -0,0 +1,160 @@
+//! Conversation trees rebuilt from stored parent links.
+//!
+//! Adapters record each event's parent in `events.parent_id` where the source has one, so a
+//! session with edited prompts or retried turns forms a tree rather than a single timeline.
+//! Like [`crate::tool_calls`], this module is pure and leaves reading events to
+//! [`crate::Database`].
+
+use crate::models::EventRow;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A session's events arranged by parent link.
+///
+/// Nodes live in one flat list and refer to each other by index, so a linear session thousands
+/// of events deep is built, walked, serialized and dropped without recursion.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConversationTree {
+    /// Every event, in the order they were given
+    pub nodes: Vec<ConversationNode>,
+    /// Indexes into `nodes` of the events that start a conversation
+    pub roots: Vec<usize>,
+}
+
+/// An event and the indexes of the events that follow from it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationNode {
+    pub event: EventRow,
+    pub children: Vec<usize>,
+}
+
+impl ConversationTree {
+    /// The root nodes, oldest first
+    pub fn root_nodes(&self) -> impl Iterator<Item = &ConversationNode> {
+        self.roots.iter().map(|&i| &self.nodes[i])
+    }
+
+    /// The nodes that follow from `node`, oldest first
+    pub fn children<'a>(&'a self, node: &'a ConversationNode) -> impl Iterator<Item = &'a ConversationNode> {
+        node.children.iter().map(|&i| &self.nodes[i])
+    }
+}
+
+/// Arrange a session's events (in timestamp order) into trees.
+///
+/// Events without a parent, or whose parent isn't in the session, become roots. Roots and
+/// siblings keep their input order, so branches appear oldest first. Events caught in a parent
+/// cycle can't hang off any root, so the earliest of them is promoted to one.
+pub fn build_conversation_tree(events: Vec<EventRow>) -> ConversationTree {
+    let index: HashMap<&str, usize> = events.iter().enumerate().map(|(i, e)| (e.id.as_str(), i)).collect();
+    let parents: Vec<Option<usize>> = events
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            e.parent_id
+                .as_deref()
+                .and_then(|p| index.get(p))
+                .copied()
+                .filter(|&p| p != i)
+        })
+        .collect();
+
+    let mut nodes: Vec<ConversationNode> = events
+        .into_iter()
+        .map(|event| ConversationNode { event, children: Vec::new() })
+        .collect();
+    let mut roots = Vec::new();
+    for (i, parent) in parents.iter().enumerate() {
+        match parent {
+            Some(p) => nodes[*p].children.push(i),
+            None => roots.push(i),
+        }
+    }
+
+    let mut reached = vec![false; nodes.len()];
+    mark_reached(&nodes, &roots, &mut reached);
+    while let Some(stranded) = earliest_unreached(&nodes, &reached) {
+        if let Some(parent) = parents[stranded] {
+            nodes[parent].children.retain(|&c| c != stranded);
+        }
+        roots.push(stranded);
+        mark_reached(&nodes, &[stranded], &mut reached);
+    }
+
+    ConversationTree { nodes, roots }
+}
+
+/// Mark every node reachable from `start`, walking with an explicit stack
+fn mark_reached(nodes: &[ConversationNode], start: &[usize], reached: &mut [bool]) {
+    let mut stack = start.to_vec();
+    while let Some(i) = stack.pop() {
+        if !reached[i] {
+            reached[i] = true;
+            stack.extend(&nodes[i].children);
+        }
+    }
+}
+
+/// The earliest event no root leads to, which only happens inside a parent cycle
+fn earliest_unreached(nodes: &[ConversationNode], reached: &[bool]) -> Option<usize> {
+    (0..nodes.len()).filter(|&i| !reached[i]).min_by(|&a, &b| {
+        let (a, b) = (&nodes[a].event, &nodes[b].event);
+        (&a.timestamp, &a.id).cmp(&(&b.timestamp, &b.id))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, parent: Option<&str>) -> EventRow {
+        EventRow {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            kind: "message".to_string(),
+            role: None,
+            content: None,
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            raw_payload: "{}".to_string(),
+            category: None,
+            parent_id: parent.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_unknown_and_cyclic_parents_do_not_lose_events() {
+        let tree = build_conversation_tree(vec![event("a", Some("missing")), event("b", Some("b"))]);
+        let roots: Vec<&str> = tree.root_nodes().map(|n| n.event.id.as_str()).collect();
+        assert_eq!(roots, ["a", "b"]);
+
+        let cycle = build_conversation_tree(vec![event("x", Some("y")), event("y", Some("x"))]);
+        let roots: Vec<&ConversationNode> = cycle.root_nodes().collect();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].event.id, "x");
+        let y: Vec<&ConversationNode> = cycle.children(roots[0]).collect();
+        assert_eq!(y[0].event.id, "y");
+        assert!(y[0].children.is_empty());
+    }
+
+    #[test]
+    fn test_a_ten_thousand_event_chain_builds_and_drops_without_recursion() {
+        let ids: Vec<String> = (0..10_000).map(|i| format!("e{}", i)).collect();
+        let events = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| event(id, i.checked_sub(1).map(|p| ids[p].as_str())))
+            .collect();
+        let tree = build_conversation_tree(events);
+
+        assert_eq!(tree.roots, [0]);
+        let mut depth = 0;
+        let mut node = tree.root_nodes().next();
+        while let Some(current) = node {
+            depth += 1;
+            node = tree.children(current).next();
+        }
+        assert_eq!(depth, 10_000);
+        assert!(serde_json::to_string(&tree).is_ok());
+        drop(tree);
+    }
+}
@@ -1,24 +1,105 @@
-use agent_v_core::{Event, EventKind, HealthStatus, ModelMetadata, Session, Source, SourceHealth};
-use chrono::{DateTime, NaiveDate, Utc};
+use agent_v_core::config;
+use agent_v_core::pricing::estimate_cost;
+use agent_v_core::{
+    Attachment, Event, EventKind, ExternalId, HealthStatus, ModelMetadata, Session, SessionId, Source, SourceHealth,
+    ToolOutcome, error_signature,
+};
+use chrono::{DateTime, NaiveDate, Utc, Weekday};
 use log::{error, info};
-use rusqlite::OptionalExtension;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use rusqlite::{InterruptHandle, OptionalExtension};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio_rusqlite::Connection;
 
+use crate::conversation_tree::{ConversationTree, build_conversation_tree};
+use crate::file_touches::{FileTouch, extract_file_touches};
 use crate::migrations::MIGRATIONS;
-use crate::models::{EventRow, SessionMetricsRow, SessionRow};
+use crate::models::{
+    AttachmentRow, EventRow, FileTouchedRow, SessionMetricsRow, SessionPathRow, SessionRow, ToolCallRow,
+};
 use crate::queries;
 use crate::session_merge::{MergeEvent, MergeSession, build_merge_plan};
+use crate::tool_calls::{ToolCallRecord, extract_tool_calls};
 
 /// Search result with highlighted snippet
 #[derive(Debug, Clone)]
 pub struct SearchResult {
     pub event: EventRow,
     pub rank: f64,
+    /// Excerpt around the match with each matched term wrapped in `[` `]`
     pub snippet: Option<String>,
 }
 
+/// Why [`Database::search_events_regex`] could not run
+#[derive(Debug, thiserror::Error)]
+pub enum RegexSearchError {
+    #[error("Invalid regex: {0}")]
+    InvalidPattern(String),
+    #[error(
+        "Regex is too complex to search with (compiled size over {} bytes)",
+        REGEX_SIZE_LIMIT
+    )]
+    TooComplex,
+    #[error(transparent)]
+    Database(#[from] tokio_rusqlite::Error),
+}
+
+/// Why [`Database::backup_before_destructive`] could not back up the database
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    #[error("Failed to back up the database: {0}")]
+    Database(#[from] tokio_rusqlite::Error),
+    #[error("Failed to prune old database backups: {0}")]
+    Prune(#[from] std::io::Error),
+}
+
+/// Compiled size allowed for a search regex; patterns like `(\w{100}){100}` blow past it
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+/// Deepest group nesting allowed in a search regex
+const REGEX_NEST_LIMIT: u32 = 32;
+/// Characters of context kept on each side of a regex match in its snippet
+const REGEX_SNIPPET_CONTEXT: usize = 60;
+
+/// Monotonic query counter shared by every search issued from one search box
+///
+/// Cloning shares the counter, so all clones see the same latest epoch.
+#[derive(Debug, Clone, Default)]
+pub struct SearchGeneration(Arc<AtomicU64>);
+
+impl SearchGeneration {
+    /// Start a new query, superseding every earlier one, and return its epoch
+    pub fn begin(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Epoch of the most recently started query, 0 before any
+    pub fn current(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Whether `epoch` is still the newest query
+    pub fn is_current(&self, epoch: u64) -> bool {
+        self.current() == epoch
+    }
+}
+
+/// Search results tagged with the epoch of the query that produced them
+#[derive(Debug, Clone)]
+pub struct TaggedSearchResults {
+    pub epoch: u64,
+    pub results: Vec<SearchResult>,
+}
+
+impl TaggedSearchResults {
+    /// Whether a newer query has started since these results were requested
+    pub fn is_stale(&self, generation: &SearchGeneration) -> bool {
+        !generation.is_current(self.epoch)
+    }
+}
+
 /// Facets for filtering search results
 #[derive(Debug, Clone, Default)]
 pub struct SearchFacets {
@@ -26,18 +107,66 @@ pub struct SearchFacets {
     pub project: Option<String>,
     pub kind: Option<String>,
     pub since: Option<DateTime<Utc>>,
+    /// Only match events before this time (sessions created before it, for session search)
+    pub until: Option<DateTime<Utc>>,
+    /// Fixed snippet length in tokens, overriding the length-based budget
+    pub snippet_len: Option<i64>,
+    /// Match any query term instead of requiring all of them
+    pub match_any: bool,
+    /// Source sub-type of the event, e.g. `progress`
+    pub category: Option<String>,
+    /// Search assistant reasoning text instead of event content
+    pub thinking: bool,
+    /// Only match sessions carrying this tag
+    pub tag: Option<String>,
+    /// Only match within this session (internal ID)
+    pub session_id: Option<String>,
 }
 
+/// Smallest snippet budget, in tokens, used for short events
+pub const SNIPPET_MIN_TOKENS: i64 = 16;
+/// Largest snippet budget FTS5 allows, in tokens
+pub const SNIPPET_MAX_TOKENS: i64 = 64;
+/// Content characters per additional snippet token
+const SNIPPET_CHARS_PER_TOKEN: i64 = 40;
+
 /// Activity stats for a day
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ActivityStats {
     pub day: NaiveDate,
     pub event_count: i64,
     pub session_count: i64,
+    /// Mean daily events over the 7 calendar days ending on `day`, fewer at the start of the range
+    pub rolling_avg_7d: f64,
+}
+
+/// Activity for one source within a timeline day
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceActivity {
+    pub source: String,
+    pub event_count: i64,
+    pub session_count: i64,
+}
+
+/// Activity for a day across all sources, with a per-source breakdown
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineDay {
+    pub day: NaiveDate,
+    pub event_count: i64,
+    pub session_count: i64,
+    pub sources: Vec<SourceActivity>,
+}
+
+/// Activity stats for a day of the week
+#[derive(Debug, Clone, Serialize)]
+pub struct WeekdayActivity {
+    pub weekday: Weekday,
+    pub event_count: i64,
+    pub session_count: i64,
 }
 
 /// Error stats for a day
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ErrorStats {
     pub day: NaiveDate,
     pub error_count: i64,
@@ -45,7 +174,7 @@ pub struct ErrorStats {
 }
 
 /// Stats grouped by a dimension
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct GroupedStats {
     pub dimension: String,
     pub count: i64,
@@ -55,10 +184,102 @@ pub struct GroupedStats {
 }
 
 /// Database connection wrapper with async support
-#[derive(Debug)]
 pub struct Database {
     conn: Connection,
+    /// Aborts whatever statement `conn` is running, since dropping a query's future doesn't
+    interrupt: Arc<InterruptHandle>,
     path: PathBuf,
+    compress_raw: bool,
+}
+
+impl std::fmt::Debug for Database {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Database")
+            .field("path", &self.path)
+            .field("compress_raw", &self.compress_raw)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Number of pre-operation backups kept next to the database file
+pub const BACKUP_KEEP: usize = 5;
+
+/// Remove the oldest `<db>.bak-<timestamp>` files beyond `keep`, returning how many were removed.
+pub fn prune_backups(db_path: &Path, keep: usize) -> std::io::Result<usize> {
+    let (Some(dir), Some(name)) = (db_path.parent(), db_path.file_name()) else {
+        return Ok(0);
+    };
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let prefix = format!("{}.bak-", name.to_string_lossy());
+
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+        .map(|entry| entry.path())
+        .collect();
+    // Timestamps are zero-padded, so name order is age order
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(keep);
+    for path in &backups[..excess] {
+        std::fs::remove_file(path)?;
+    }
+    Ok(excess)
+}
+
+/// Token usage summed over a Claude session's API messages, with each message's model
+///
+/// Claude Code writes one line per content block of an API response, each repeating the
+/// response's `message.id`, `model`, and `usage`, so a message is only counted the first time
+/// its ID is seen.
+#[derive(Debug, Default)]
+struct ClaudeUsage {
+    input_tokens: usize,
+    output_tokens: usize,
+    messages: usize,
+    seen: HashSet<String>,
+    /// Messages per model, in first-seen order
+    models: Vec<(String, usize)>,
+}
+
+impl ClaudeUsage {
+    fn record(&mut self, message: &serde_json::Value, usage: &serde_json::Value) {
+        if let Some(id) = message.get("id").and_then(|v| v.as_str())
+            && !self.seen.insert(id.to_string())
+        {
+            return;
+        }
+
+        let tokens = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        self.input_tokens += tokens("input_tokens");
+        self.output_tokens += tokens("output_tokens");
+        self.messages += 1;
+
+        // Claude Code labels locally generated messages (e.g. API error notices) `<synthetic>`
+        if let Some(model) = message.get("model").and_then(|v| v.as_str())
+            && model != "<synthetic>"
+        {
+            match self.models.iter_mut().find(|(name, _)| name == model) {
+                Some((_, count)) => *count += 1,
+                None => self.models.push((model.to_string(), 1)),
+            }
+        }
+    }
+
+    fn has_usage(&self) -> bool {
+        self.messages > 0
+    }
+
+    /// The model behind the most messages, the earliest one on a tie
+    fn main_model(&self) -> Option<String> {
+        self.models
+            .iter()
+            .fold(None, |best: Option<&(String, usize)>, entry| match best {
+                Some(best) if best.1 >= entry.1 => Some(best),
+                _ => Some(entry),
+            })
+            .map(|(name, _)| name.clone())
+    }
 }
 
 impl Database {
@@ -68,21 +289,43 @@ impl Database {
         let path_clone = path.clone();
 
         let conn = Connection::open(path_clone).await?;
+        let interrupt = Arc::new(conn.call(|conn| Ok(conn.get_interrupt_handle())).await?);
 
         info!("Database opened at: {:?}", path);
 
-        Ok(Self { conn, path })
+        Ok(Self { conn, interrupt, path, compress_raw: false })
     }
 
-    /// Open the default database in the user's data directory
-    pub async fn open_default() -> Result<Self, Box<dyn std::error::Error>> {
-        let data_dir = dirs::data_dir()
-            .ok_or("Could not determine data directory")?
-            .join("agent-viz");
+    /// Open a migrated database that lives only in memory and disappears when dropped, for tests
+    /// and throwaway analysis
+    pub async fn open_in_memory() -> Result<Self, tokio_rusqlite::Error> {
+        let conn = Connection::open_in_memory().await?;
+        let interrupt = Arc::new(conn.call(|conn| Ok(conn.get_interrupt_handle())).await?);
+        let db = Self { conn, interrupt, path: PathBuf::from(":memory:"), compress_raw: false };
+        db.migrate().await?;
+        Ok(db)
+    }
+
+    /// Store new event payloads zstd-compressed in `raw_payload_z` instead of as JSON text.
+    /// Reads decompress transparently, so this only changes how new events are written.
+    pub fn with_compressed_payloads(mut self, enabled: bool) -> Self {
+        self.compress_raw = enabled;
+        self
+    }
 
-        std::fs::create_dir_all(&data_dir)?;
+    /// Open the configured `db_path`, or the default database in the user's data directory
+    pub async fn open_default() -> Result<Self, Box<dyn std::error::Error>> {
+        let db_path = match config::current().db_path() {
+            Some(path) => path,
+            None => dirs::data_dir()
+                .ok_or("Could not determine data directory")?
+                .join("agent-viz")
+                .join("agent-viz.db"),
+        };
 
-        let db_path = data_dir.join("agent-viz.db");
+        if let Some(dir) = db_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
 
         Ok(Self::open(db_path).await?)
     }
@@ -136,11 +379,84 @@ impl Database {
         Ok(())
     }
 
+    /// Migrations recorded as applied, in the order they ran, as `(name, applied_at)`.
+    /// A database that has never been migrated has none.
+    pub async fn applied_migrations(&self) -> Result<Vec<(String, String)>, tokio_rusqlite::Error> {
+        self.conn
+            .call(|conn| {
+                let tracked: bool = conn
+                    .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_migrations'")?
+                    .exists([])?;
+                if !tracked {
+                    return Ok(Vec::new());
+                }
+                let mut stmt = conn.prepare("SELECT name, applied_at FROM _migrations ORDER BY id")?;
+                let rows = stmt
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
+    /// Bundled migrations not yet applied, in the order [`Database::migrate`] would run them
+    pub async fn pending_migrations(&self) -> Result<Vec<&'static str>, tokio_rusqlite::Error> {
+        let applied: HashSet<String> = self
+            .applied_migrations()
+            .await?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        Ok(MIGRATIONS
+            .iter()
+            .map(|m| m.name)
+            .filter(|name| !applied.contains(*name))
+            .collect())
+    }
+
+    /// Abort the statement the connection is running, which then fails with `SQLITE_INTERRUPT`.
+    ///
+    /// Cancelling a query by dropping its future only stops waiting for it, leaving the statement
+    /// running and every later query queued behind it, so callers that give up on a query call
+    /// this too. It's a no-op when nothing is running. The connection is shared, so any other
+    /// statement in flight at that moment is aborted as well.
+    pub fn interrupt(&self) {
+        self.interrupt.interrupt();
+    }
+
     /// Get the database file path
     pub fn path(&self) -> &PathBuf {
         &self.path
     }
 
+    /// Write a consistent copy of the database to `path`, which must not already exist
+    pub async fn backup_to(&self, path: impl AsRef<Path>) -> Result<(), tokio_rusqlite::Error> {
+        let target = path.as_ref().to_string_lossy().into_owned();
+        self.conn
+            .call(move |conn| {
+                conn.execute("VACUUM INTO ?1", [target])?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// Back up to `<db>.bak-<timestamp>` ahead of a destructive operation, keeping the newest `keep` backups
+    pub async fn backup_before_destructive(&self, keep: usize) -> Result<PathBuf, BackupError> {
+        let stamp = Utc::now().format("%Y%m%dT%H%M%S%3fZ");
+        let mut backup = self.path.clone().into_os_string();
+        backup.push(format!(".bak-{}", stamp));
+        let backup = PathBuf::from(backup);
+
+        self.backup_to(&backup).await?;
+        info!("Backed up database to {:?}", backup);
+
+        let pruned = prune_backups(&self.path, keep)?;
+        if pruned > 0 {
+            info!("Pruned {} old database backups", pruned);
+        }
+        Ok(backup)
+    }
+
     /// Check database health
     pub async fn health_check(&self) -> HealthStatus {
         match self
@@ -184,9 +500,12 @@ impl Database {
     }
 
     /// Prune duplicate session rows by `(source, external_id)` and keep the richest record.
+    ///
+    /// The pruned rows' ingest stamps are cleared, so their files are read again on the next ingest.
     pub async fn prune_duplicate_sessions(&self) -> Result<usize, tokio_rusqlite::Error> {
+        let compress = self.compress_raw;
         self.conn
-            .call(|conn| {
+            .call(move |conn| {
                 let tx = conn.transaction()?;
                 let mut pruned_sessions = 0usize;
 
@@ -242,7 +561,7 @@ impl Database {
                         let events = {
                             let mut stmt = tx.prepare(
                                 r#"
-                                SELECT id, kind, role, content, timestamp, raw_payload
+                                SELECT id, kind, role, content, timestamp, raw_payload, raw_payload_z, parent_id
                                 FROM events
                                 WHERE session_id = ?1
                                 ORDER BY timestamp ASC, id ASC
@@ -256,7 +575,8 @@ impl Database {
                                     role: row.get(2)?,
                                     content: row.get(3)?,
                                     timestamp: row.get(4)?,
-                                    raw_payload: row.get(5)?,
+                                    raw_payload: decode_payload(row, 5, 6)?,
+                                    parent_id: row.get(7)?,
                                 })
                             })?
                             .collect::<Result<Vec<_>, _>>()?
@@ -281,12 +601,16 @@ impl Database {
                         ],
                     )?;
 
+                    let mut token_messages = recorded_token_messages(&tx, &plan.keep_session_id)?;
                     for event in plan.events_to_insert {
+                        let payload = serde_json::from_str::<serde_json::Value>(&event.raw_payload).ok();
+                        let signature = signature_column(&event.kind, event.content.as_deref());
+                        let (input_tokens, output_tokens) = payload
+                            .as_ref()
+                            .map_or((None, None), |p| token_columns(p, &mut token_messages));
+                        let (raw_payload, raw_payload_z) = encode_payload(event.raw_payload, compress)?;
                         tx.execute(
-                            r#"
-                            INSERT INTO events (id, session_id, kind, role, content, timestamp, raw_payload)
-                            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
-                            "#,
+                            queries::INSERT_EVENT,
                             rusqlite::params![
                                 event.id,
                                 &plan.keep_session_id,
@@ -294,12 +618,22 @@ impl Database {
                                 event.role.unwrap_or_default(),
                                 event.content.unwrap_or_default(),
                                 event.timestamp,
-                                event.raw_payload,
+                                raw_payload,
+                                payload.as_ref().and_then(|p| Event::category(p).map(str::to_string)),
+                                payload.as_ref().and_then(Event::thinking),
+                                raw_payload_z,
+                                event.parent_id,
+                                signature,
+                                input_tokens,
+                                output_tokens,
+                                None::<String>,
                             ],
                         )?;
                     }
 
                     for donor_id in &plan.donor_session_ids {
+                        tx.execute(queries::REDIRECT_SESSION_MERGES, [&plan.keep_session_id, donor_id])?;
+                        tx.execute(queries::DELETE_INGESTED_FILE_BY_SESSION_ID, [donor_id])?;
                         tx.execute("DELETE FROM sessions WHERE id = ?1", [donor_id])?;
                     }
 
@@ -312,8 +646,163 @@ impl Database {
             .await
     }
 
+    /// Delete a session with its events, metrics, tool calls, touched files, attachments, and tags.
+    ///
+    /// Foreign key enforcement is off, so child rows are removed explicitly rather than by cascade;
+    /// the delete triggers drop the matching content, thinking, and title FTS entries. The ingest
+    /// stamp of the session's file goes too, so the next ingest reads it again instead of skipping
+    /// it as unchanged. Returns whether the session existed.
+    pub async fn delete_session(&self, id: SessionId) -> Result<bool, tokio_rusqlite::Error> {
+        let id = id.to_string();
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                tx.execute(queries::DELETE_ATTACHMENTS_BY_SESSION_ID, [&id])?;
+                tx.execute(queries::DELETE_FILES_TOUCHED_BY_SESSION_ID, [&id])?;
+                tx.execute(queries::DELETE_TOOL_CALLS_BY_SESSION_ID, [&id])?;
+                tx.execute(queries::DELETE_SESSION_METRICS_BY_SESSION_ID, [&id])?;
+                tx.execute(queries::DELETE_TAGS_BY_SESSION_ID, [&id])?;
+                tx.execute(queries::DELETE_SESSION_MERGES_BY_PRIMARY, [&id])?;
+                tx.execute(queries::DELETE_INGESTED_FILE_BY_SESSION_ID, [&id])?;
+                tx.execute(queries::DELETE_EVENTS_BY_SESSION_ID, [&id])?;
+                let deleted = tx.execute(queries::DELETE_SESSION, [&id])?;
+                tx.commit()?;
+                Ok(deleted > 0)
+            })
+            .await
+    }
+
+    /// Merge `secondary` into `primary`: its events, attachments, and tags move over, the primary's
+    /// time span widens to cover both, and the secondary session is deleted. Tool calls, touched
+    /// files, and metrics are then rebuilt from the combined events, which read back in timestamp
+    /// order like any session's.
+    ///
+    /// The merge is recorded under the secondary's source and external ID, so re-ingesting its file
+    /// replaces its events inside the primary instead of recreating it. Both sessions' ingest stamps
+    /// are cleared so the next ingest reads their files through that mapping.
+    ///
+    /// Returns the number of events moved, or `None` (changing nothing) when either session
+    /// doesn't exist or both IDs name the same session.
+    pub async fn merge_sessions(
+        &self, primary: SessionId, secondary: SessionId,
+    ) -> Result<Option<usize>, tokio_rusqlite::Error> {
+        if primary == secondary {
+            return Ok(None);
+        }
+        let (primary_id, secondary_id) = (primary.to_string(), secondary.to_string());
+
+        let moved = self
+            .conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                let found: i64 = tx.query_row(
+                    "SELECT COUNT(*) FROM sessions WHERE id IN (?1, ?2)",
+                    [&primary_id, &secondary_id],
+                    |row| row.get(0),
+                )?;
+                if found < 2 {
+                    return Ok(None);
+                }
+
+                tx.execute(
+                    queries::RECORD_SESSION_MERGE,
+                    [&primary_id, &secondary_id, &Utc::now().to_rfc3339()],
+                )?;
+                tx.execute(queries::REDIRECT_SESSION_MERGES, [&primary_id, &secondary_id])?;
+                tx.execute(queries::DELETE_INGESTED_FILE_BY_SESSION_ID, [&primary_id])?;
+                tx.execute(queries::DELETE_INGESTED_FILE_BY_SESSION_ID, [&secondary_id])?;
+
+                let moved = tx.execute(queries::MOVE_EVENTS_TO_SESSION, [&primary_id, &secondary_id])?;
+                tx.execute(queries::MOVE_ATTACHMENTS_TO_SESSION, [&primary_id, &secondary_id])?;
+                tx.execute(queries::COPY_TAGS_TO_SESSION, [&primary_id, &secondary_id])?;
+                tx.execute(queries::WIDEN_SESSION_SPAN, [&primary_id, &secondary_id])?;
+                tx.execute(queries::DELETE_TAGS_BY_SESSION_ID, [&secondary_id])?;
+                tx.execute(queries::DELETE_FILES_TOUCHED_BY_SESSION_ID, [&secondary_id])?;
+                tx.execute(queries::DELETE_TOOL_CALLS_BY_SESSION_ID, [&secondary_id])?;
+                tx.execute(queries::DELETE_SESSION_METRICS_BY_SESSION_ID, [&secondary_id])?;
+                tx.execute(queries::DELETE_SESSION, [&secondary_id])?;
+                tx.commit()?;
+                Ok(Some(moved))
+            })
+            .await?;
+
+        if moved.is_some() {
+            self.rebuild_session_derived(primary).await?;
+        }
+        Ok(moved)
+    }
+
+    /// Tag a session, returning `false` when it already had the tag
+    pub async fn add_tag(&self, session_id: SessionId, tag: &str) -> Result<bool, tokio_rusqlite::Error> {
+        let (session_id, tag) = (session_id.to_string(), tag.to_string());
+        self.conn
+            .call(move |conn| Ok(conn.execute(queries::INSERT_TAG, [&session_id, &tag])? > 0))
+            .await
+    }
+
+    /// Remove a tag from a session, returning `false` when it didn't have the tag
+    pub async fn remove_tag(&self, session_id: SessionId, tag: &str) -> Result<bool, tokio_rusqlite::Error> {
+        let (session_id, tag) = (session_id.to_string(), tag.to_string());
+        self.conn
+            .call(move |conn| Ok(conn.execute(queries::DELETE_TAG, [&session_id, &tag])? > 0))
+            .await
+    }
+
+    /// Get a session's tags in alphabetical order
+    pub async fn get_tags(&self, session_id: SessionId) -> Result<Vec<String>, tokio_rusqlite::Error> {
+        let session_id = session_id.to_string();
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::GET_SESSION_TAGS)?;
+                let tags = stmt
+                    .query_map([session_id], |row| row.get(0))?
+                    .collect::<Result<Vec<String>, _>>()?;
+                Ok(tags)
+            })
+            .await
+    }
+
+    /// List every tag in use with its session count, in alphabetical order
+    pub async fn list_tags(&self) -> Result<Vec<(String, i64)>, tokio_rusqlite::Error> {
+        self.conn
+            .call(|conn| {
+                let mut stmt = conn.prepare(queries::LIST_TAGS)?;
+                let tags = stmt
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(tags)
+            })
+            .await
+    }
+
+    /// List the sessions carrying a tag, most recently updated first
+    pub async fn list_sessions_by_tag(&self, tag: &str) -> Result<Vec<SessionRow>, tokio_rusqlite::Error> {
+        let tag = tag.to_string();
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::LIST_SESSIONS_BY_TAG)?;
+                let rows = stmt
+                    .query_map([tag], |row| {
+                        Ok(SessionRow {
+                            id: row.get(0)?,
+                            source: row.get(1)?,
+                            external_id: row.get(2)?,
+                            project: row.get(3)?,
+                            title: row.get(4)?,
+                            created_at: row.get(5)?,
+                            updated_at: row.get(6)?,
+                            raw_payload: row.get(7)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
     /// Get a session by ID
-    pub async fn get_session(&self, id: String) -> Result<Option<SessionRow>, tokio_rusqlite::Error> {
+    pub async fn get_session(&self, id: SessionId) -> Result<Option<SessionRow>, tokio_rusqlite::Error> {
+        let id = id.to_string();
         self.conn
             .call(move |conn| {
                 let mut stmt = conn.prepare("SELECT id, source, external_id, project, title, created_at, updated_at, raw_payload FROM sessions WHERE id = ?1")?;
@@ -371,8 +860,35 @@ impl Database {
             .await
     }
 
+    /// List every session (of `source`, when given) with the source file recorded at ingest, so
+    /// sessions whose files were deleted can be found. Sources that don't read one file per
+    /// session (OpenCode, Crush) have no path.
+    pub async fn list_session_paths_by_source(
+        &self, source: Option<&str>,
+    ) -> Result<Vec<SessionPathRow>, tokio_rusqlite::Error> {
+        let source = source.unwrap_or("").to_string();
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::LIST_SESSION_PATHS)?;
+                let rows = stmt
+                    .query_map([&source], |row| {
+                        Ok(SessionPathRow {
+                            id: row.get(0)?,
+                            source: row.get(1)?,
+                            external_id: row.get(2)?,
+                            file_path: row.get(3)?,
+                            updated_at: row.get(4)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
     /// Get events for a session
-    pub async fn get_session_events(&self, session_id: String) -> Result<Vec<EventRow>, tokio_rusqlite::Error> {
+    pub async fn get_session_events(&self, session_id: SessionId) -> Result<Vec<EventRow>, tokio_rusqlite::Error> {
+        let session_id = session_id.to_string();
         self.conn
             .call(move |conn| {
                 let mut stmt = conn.prepare(queries::GET_SESSION_EVENTS)?;
@@ -385,7 +901,9 @@ impl Database {
                             role: row.get(3)?,
                             content: row.get(4)?,
                             timestamp: row.get(5)?,
-                            raw_payload: row.get(6)?,
+                            raw_payload: decode_payload(row, 6, 8)?,
+                            category: row.get(7)?,
+                            parent_id: row.get(9)?,
                         })
                     })?
                     .collect::<Result<Vec<_>, _>>()?;
@@ -394,6 +912,38 @@ impl Database {
             .await
     }
 
+    /// Get a session's events arranged by their parent links, for rendering branched or edited
+    /// conversations; sessions without parent links come back as one root per event
+    pub async fn get_conversation_tree(
+        &self, session_id: SessionId,
+    ) -> Result<ConversationTree, tokio_rusqlite::Error> {
+        Ok(build_conversation_tree(self.get_session_events(session_id).await?))
+    }
+
+    /// Get the tokens each event of a session reported, oldest first; events without token
+    /// counts are left out
+    pub async fn get_token_timeline(
+        &self, session_id: SessionId,
+    ) -> Result<Vec<TokenUsagePoint>, tokio_rusqlite::Error> {
+        let session_id = session_id.to_string();
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::GET_TOKEN_TIMELINE)?;
+                let points = stmt
+                    .query_map([session_id], |row| {
+                        Ok(TokenUsagePoint {
+                            event_id: row.get(0)?,
+                            timestamp: row.get(1)?,
+                            input_tokens: row.get(2)?,
+                            output_tokens: row.get(3)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(points)
+            })
+            .await
+    }
+
     /// Insert or update a session
     pub async fn insert_session(&self, session: &Session) -> Result<(), tokio_rusqlite::Error> {
         let id = session.id.to_string();
@@ -434,37 +984,87 @@ impl Database {
         let content = event.content.clone().unwrap_or_default();
         let timestamp = event.timestamp.to_rfc3339();
         let raw_payload = serde_json::to_string(&event.raw_payload).unwrap_or_default();
+        let category = Event::category(&event.raw_payload).map(str::to_string);
+        let thinking = Event::thinking(&event.raw_payload);
+        let parent_id = event.parent_id.map(|p| p.to_string());
+        let signature = signature_column(&kind, event.content.as_deref());
+        let payload = event.raw_payload.clone();
+        let attachments = Attachment::from_payload(&event.raw_payload);
+        let compress = self.compress_raw;
 
         self.conn
             .call(move |conn| {
+                let (raw_payload, raw_payload_z) = encode_payload(raw_payload, compress)?;
+                let (input_tokens, output_tokens) =
+                    token_columns(&payload, &mut recorded_token_messages(conn, &session_id)?);
                 conn.execute(
                     queries::INSERT_EVENT,
-                    [id, session_id, kind, role, content, timestamp, raw_payload],
+                    rusqlite::params![
+                        id,
+                        session_id,
+                        kind,
+                        role,
+                        content,
+                        timestamp,
+                        raw_payload,
+                        category,
+                        thinking,
+                        raw_payload_z,
+                        parent_id,
+                        signature,
+                        input_tokens,
+                        output_tokens,
+                        None::<String>,
+                    ],
                 )?;
+                insert_attachments(conn, &session_id, &id, &attachments)?;
                 Ok(())
             })
             .await
     }
 
-    /// Insert a session with all its events atomically in a transaction
+    /// Insert a session with all its events atomically in a transaction, then refresh its metrics
+    ///
+    /// Adapters mint fresh event IDs on every parse, so re-ingesting a session (matched by source
+    /// and external ID) deletes its stored events, attachments, and touched files before inserting
+    /// the new parse. The events FTS delete trigger drops the old search entries with them, so a
+    /// re-ingest replaces the session's events rather than adding to them.
+    ///
+    /// A session that was merged into another (see [`Database::merge_sessions`]) isn't recreated:
+    /// its events replace the ones it contributed to the session it joined, leaving that session's
+    /// own events alone.
+    ///
+    /// Returns the ID the events were stored under, which is the existing (or merged-into)
+    /// session's rather than `session.id` on a re-ingest.
     pub async fn insert_session_with_events(
         &self, session: &Session, events: &[Event],
-    ) -> Result<(), tokio_rusqlite::Error> {
+    ) -> Result<SessionId, tokio_rusqlite::Error> {
         let session = session.clone();
         let events: Vec<Event> = events.to_vec();
         let source = session.source.to_string();
         let external_id = session.external_id.clone();
         let external_id_for_log = external_id.clone();
         let event_count = events.len();
+        let compress = self.compress_raw;
 
-        self.conn
+        let stored_id = self
+            .conn
             .call(move |conn| {
                 let tx = conn.transaction()?;
 
-                let existing_id: Option<String> = {
-                    let mut stmt = tx.prepare(queries::GET_SESSION_ID_BY_SOURCE_AND_EXTERNAL_ID)?;
-                    stmt.query_row([&source, &external_id], |row| row.get(0)).ok()
+                let merge: Option<(String, String)> = tx
+                    .query_row(queries::GET_SESSION_MERGE, [&source, &external_id], |row| {
+                        Ok((row.get(0)?, row.get(1)?))
+                    })
+                    .optional()?;
+                let existing_id: Option<String> = match &merge {
+                    Some((_, primary_id)) => Some(primary_id.clone()),
+                    None => {
+                        let mut stmt = tx.prepare(queries::GET_SESSION_ID_BY_SOURCE_AND_EXTERNAL_ID)?;
+                        stmt.query_row([&source, &external_id], |row| row.get(0)).ok()
+                    }
                 };
+                let merged_from = merge.map(|(secondary_id, _)| secondary_id);
 
                 let (session_id_to_use, is_update) = match existing_id {
                     Some(id) => (id, true),
@@ -472,7 +1072,10 @@ impl Database {
                 };
 
                 if is_update {
-                    tx.execute(queries::DELETE_EVENTS_BY_SESSION_ID, [&session_id_to_use])?;
+                    let origin = rusqlite::params![&session_id_to_use, &merged_from];
+                    tx.execute(queries::DELETE_ATTACHMENTS_BY_ORIGIN, origin)?;
+                    tx.execute(queries::DELETE_FILES_TOUCHED_BY_SESSION_ID, [&session_id_to_use])?;
+                    tx.execute(queries::DELETE_EVENTS_BY_ORIGIN, origin)?;
                 }
 
                 let id = session_id_to_use.clone();
@@ -484,87 +1087,383 @@ impl Database {
                 let updated_at = session.updated_at.to_rfc3339();
                 let raw_payload = serde_json::to_string(&session.raw_payload).unwrap_or_default();
 
-                tx.execute(
-                    queries::INSERT_SESSION,
-                    [
-                        id,
-                        source,
-                        external_id,
-                        project.unwrap_or_default(),
-                        title.unwrap_or_default(),
-                        created_at,
-                        updated_at,
-                        raw_payload,
-                    ],
-                )?;
+                if merged_from.is_some() {
+                    tx.execute(queries::WIDEN_SESSION_SPAN_TO, [id, created_at, updated_at])?;
+                } else {
+                    tx.execute(
+                        queries::INSERT_SESSION,
+                        [
+                            id,
+                            source,
+                            external_id,
+                            project.unwrap_or_default(),
+                            title.unwrap_or_default(),
+                            created_at,
+                            updated_at,
+                            raw_payload,
+                        ],
+                    )?;
+                }
 
+                // One prepared statement for every event; any failure drops `tx` before the commit,
+                // rolling back the session and all of its events together.
+                let parsed_session_id = uuid::Uuid::parse_str(&session_id_to_use).map(|uuid| uuid.to_string());
+                let mut token_messages = HashSet::new();
+                let mut insert_event = tx.prepare(queries::INSERT_EVENT)?;
                 for event in &events {
                     let id = event.id.to_string();
-                    let session_id = uuid::Uuid::parse_str(&session_id_to_use)
-                        .map(|uuid| uuid.to_string())
+                    let session_id = parsed_session_id
+                        .clone()
                         .unwrap_or_else(|_| event.session_id.to_string());
                     let kind = event.kind.to_string();
                     let role = event.role.map(|r| r.to_string()).unwrap_or_default();
                     let content = event.content.clone().unwrap_or_default();
                     let timestamp = event.timestamp.to_rfc3339();
-                    let raw_payload = serde_json::to_string(&event.raw_payload).unwrap_or_default();
+                    let (raw_payload, raw_payload_z) =
+                        encode_payload(serde_json::to_string(&event.raw_payload).unwrap_or_default(), compress)?;
+                    let (input_tokens, output_tokens) = token_columns(&event.raw_payload, &mut token_messages);
 
-                    tx.execute(
-                        queries::INSERT_EVENT,
-                        [id, session_id, kind, role, content, timestamp, raw_payload],
-                    )?;
+                    insert_event.execute(rusqlite::params![
+                        id,
+                        session_id,
+                        kind,
+                        role,
+                        content,
+                        timestamp,
+                        raw_payload,
+                        Event::category(&event.raw_payload),
+                        Event::thinking(&event.raw_payload),
+                        raw_payload_z,
+                        event.parent_id.map(|p| p.to_string()),
+                        signature_column(&kind, event.content.as_deref()),
+                        input_tokens,
+                        output_tokens,
+                        merged_from,
+                    ])?;
+                    insert_attachments(&tx, &session_id, &id, &Attachment::from_payload(&event.raw_payload))?;
                 }
+                drop(insert_event);
 
                 tx.commit()?;
-                Ok(())
+                Ok(session_id_to_use)
             })
             .await?;
 
         info!("Inserted session {} with {} events", external_id_for_log, event_count);
 
-        Ok(())
+        let session_id: SessionId = stored_id
+            .parse()
+            .map_err(|e: String| tokio_rusqlite::Error::Other(e.into()))?;
+        self.rebuild_session_derived(session_id).await?;
+        Ok(session_id)
     }
 
-    /// Look up internal session ID by source and external_id
-    pub async fn get_session_id_by_external(
-        &self, source: &str, external_id: &str,
-    ) -> Result<Option<String>, tokio_rusqlite::Error> {
-        let source = source.to_string();
-        let external_id = external_id.to_string();
-        self.conn
-            .call(move |conn| {
-                let mut stmt = conn.prepare(queries::GET_SESSION_ID_BY_SOURCE_AND_EXTERNAL_ID)?;
-                let id: Option<String> = stmt.query_row([&source, &external_id], |row| row.get(0)).ok();
-                Ok(id)
-            })
-            .await
+    /// Rebuild everything derived from a session's events — its `tool_calls` and `files_touched`
+    /// rows and its metrics — loading the events once
+    pub async fn rebuild_session_derived(&self, session_id: SessionId) -> Result<(), tokio_rusqlite::Error> {
+        let events = self.get_session_events(session_id).await?;
+        self.replace_tool_calls(session_id, extract_tool_calls(&events)).await?;
+        self.insert_files_touched(session_id, extract_file_touches(&events))
+            .await?;
+        self.store_session_metrics(session_id, &events).await
     }
 
-    /// Append new events to an existing session without deleting existing events
-    pub async fn append_events(&self, session_id: &str, events: &[Event]) -> Result<(), tokio_rusqlite::Error> {
-        let session_id_owned = session_id.to_string();
-        let events: Vec<Event> = events.to_vec();
-        let event_count = events.len();
-        let session_id_for_log = session_id_owned.clone();
+    /// Rebuild a session's `tool_calls` rows from its events, pairing each call with its result.
+    /// Returns the number of rows written.
+    pub async fn insert_tool_calls_for_session(&self, session_id: SessionId) -> Result<usize, tokio_rusqlite::Error> {
+        let records = extract_tool_calls(&self.get_session_events(session_id).await?);
+        self.replace_tool_calls(session_id, records).await
+    }
+
+    /// Replace a session's `tool_calls` rows with `records`. Returns the number of rows written.
+    async fn replace_tool_calls(
+        &self, session_id: SessionId, records: Vec<ToolCallRecord>,
+    ) -> Result<usize, tokio_rusqlite::Error> {
+        let session_id = session_id.to_string();
 
         self.conn
             .call(move |conn| {
                 let tx = conn.transaction()?;
+                tx.execute(queries::DELETE_TOOL_CALLS_BY_SESSION_ID, [&session_id])?;
+                for record in &records {
+                    tx.execute(
+                        queries::INSERT_TOOL_CALL,
+                        rusqlite::params![
+                            record.id,
+                            session_id,
+                            record.event_id,
+                            record.tool_name,
+                            record.started_at,
+                            record.completed_at,
+                            record.duration_ms,
+                            record.success,
+                            record.error_message,
+                        ],
+                    )?;
+                }
+                tx.commit()?;
+                Ok(records.len())
+            })
+            .await
+    }
+
+    /// Get a session's tool calls, oldest first
+    pub async fn get_session_tool_calls(
+        &self, session_id: SessionId,
+    ) -> Result<Vec<ToolCallRow>, tokio_rusqlite::Error> {
+        let session_id = session_id.to_string();
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::GET_SESSION_TOOL_CALLS)?;
+                let rows = stmt
+                    .query_map([session_id], |row| {
+                        Ok(ToolCallRow {
+                            id: row.get(0)?,
+                            session_id: row.get(1)?,
+                            event_id: row.get(2)?,
+                            tool_name: row.get(3)?,
+                            started_at: row.get(4)?,
+                            completed_at: row.get(5)?,
+                            duration_ms: row.get(6)?,
+                            success: row.get(7)?,
+                            error_message: row.get(8)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
+    /// Replace a session's `files_touched` rows with `touches`. Returns the number of rows written.
+    pub async fn insert_files_touched(
+        &self, session_id: SessionId, touches: Vec<FileTouch>,
+    ) -> Result<usize, tokio_rusqlite::Error> {
+        let session_id = session_id.to_string();
 
-                for event in &events {
-                    let id = event.id.to_string();
-                    let sid = session_id_owned.clone();
-                    let kind = event.kind.to_string();
-                    let role = event.role.map(|r| r.to_string()).unwrap_or_default();
-                    let content = event.content.clone().unwrap_or_default();
-                    let timestamp = event.timestamp.to_rfc3339();
-                    let raw_payload = serde_json::to_string(&event.raw_payload).unwrap_or_default();
-
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                tx.execute(queries::DELETE_FILES_TOUCHED_BY_SESSION_ID, [&session_id])?;
+                for touch in &touches {
                     tx.execute(
-                        queries::APPEND_EVENTS,
-                        [id, sid, kind, role, content, timestamp, raw_payload],
+                        queries::INSERT_FILE_TOUCHED,
+                        rusqlite::params![
+                            uuid::Uuid::new_v4().to_string(),
+                            session_id,
+                            touch.path,
+                            touch.operation,
+                            touch.lines_added,
+                            touch.lines_removed,
+                            touch.touched_at,
+                        ],
                     )?;
                 }
+                tx.commit()?;
+                Ok(touches.len())
+            })
+            .await
+    }
+
+    /// Whether a session file needs parsing again: true unless it was ingested before with the
+    /// same modification time (ms since the epoch), size, and ingest `options` fingerprint
+    pub async fn should_reingest(
+        &self, source: &str, path: &str, mtime: i64, size: i64, options: &str,
+    ) -> Result<bool, tokio_rusqlite::Error> {
+        let source = source.to_string();
+        let path = path.to_string();
+        let options = options.to_string();
+        self.conn
+            .call(move |conn| {
+                let recorded: Option<(i64, i64, String)> = conn
+                    .query_row(queries::GET_INGESTED_FILE, [&source, &path], |row| {
+                        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                    })
+                    .optional()?;
+                Ok(recorded != Some((mtime, size, options)))
+            })
+            .await
+    }
+
+    /// Record a session file's modification time, size, and ingest `options` fingerprint once it
+    /// has been ingested
+    pub async fn upsert_ingested_file(
+        &self, source: &str, path: &str, mtime: i64, size: i64, options: &str,
+    ) -> Result<(), tokio_rusqlite::Error> {
+        let source = source.to_string();
+        let path = path.to_string();
+        let options = options.to_string();
+        let ingested_at = Utc::now().to_rfc3339();
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    queries::UPSERT_INGESTED_FILE,
+                    rusqlite::params![source, path, mtime, size, ingested_at, options],
+                )?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// Get the external IDs already stored for a source, used to tell new sessions from re-ingests
+    pub async fn get_external_ids(&self, source: &str) -> Result<HashSet<String>, tokio_rusqlite::Error> {
+        let source = source.to_string();
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::GET_EXTERNAL_IDS_BY_SOURCE)?;
+                let ids = stmt
+                    .query_map([source], |row| row.get(0))?
+                    .collect::<Result<HashSet<String>, _>>()?;
+                Ok(ids)
+            })
+            .await
+    }
+
+    /// Look up internal session ID by source and external_id
+    pub async fn get_session_id_by_external(
+        &self, source: &str, external_id: &ExternalId,
+    ) -> Result<Option<SessionId>, tokio_rusqlite::Error> {
+        let source = source.to_string();
+        let external_id = external_id.to_string();
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::GET_SESSION_ID_BY_SOURCE_AND_EXTERNAL_ID)?;
+                let id: Option<String> = stmt.query_row([&source, &external_id], |row| row.get(0)).ok();
+                Ok(id.and_then(|id| id.parse().ok()))
+            })
+            .await
+    }
+
+    /// Find sessions with the given external ID, restricted to `source` when given
+    ///
+    /// External IDs are only unique within a source, so more than one row can come back without a source.
+    pub async fn find_sessions_by_external_id(
+        &self, external_id: &ExternalId, source: Option<&str>,
+    ) -> Result<Vec<SessionRow>, tokio_rusqlite::Error> {
+        let id = external_id.to_string();
+        let source = source.unwrap_or_default().to_string();
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::FIND_SESSIONS_BY_EXTERNAL_ID)?;
+                let rows = stmt
+                    .query_map([&id, &source], |row| {
+                        Ok(SessionRow {
+                            id: row.get(0)?,
+                            source: row.get(1)?,
+                            external_id: row.get(2)?,
+                            project: row.get(3)?,
+                            title: row.get(4)?,
+                            created_at: row.get(5)?,
+                            updated_at: row.get(6)?,
+                            raw_payload: row.get(7)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
+    /// Find the sessions an internal or external ID refers to, restricted to `source` when given
+    ///
+    /// An internal ID match is returned alone. Otherwise every session with that external ID comes
+    /// back, ordered by source; external IDs are only unique per source, so more than one match
+    /// means the ID is ambiguous without a source.
+    pub async fn find_sessions(
+        &self, id_or_external: &str, source: Option<&str>,
+    ) -> Result<Vec<SessionRow>, tokio_rusqlite::Error> {
+        let id = id_or_external.to_string();
+        let source = source.unwrap_or_default().to_string();
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::FIND_SESSIONS_BY_ID_OR_EXTERNAL_ID)?;
+                let mut rows = stmt
+                    .query_map([&id, &source], |row| {
+                        Ok(SessionRow {
+                            id: row.get(0)?,
+                            source: row.get(1)?,
+                            external_id: row.get(2)?,
+                            project: row.get(3)?,
+                            title: row.get(4)?,
+                            created_at: row.get(5)?,
+                            updated_at: row.get(6)?,
+                            raw_payload: row.get(7)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                if rows.first().is_some_and(|row| row.id == id) {
+                    rows.truncate(1);
+                }
+                Ok(rows)
+            })
+            .await
+    }
+
+    /// Get external IDs that are stored under more than one source
+    pub async fn get_external_id_collisions(&self) -> Result<Vec<ExternalIdCollision>, tokio_rusqlite::Error> {
+        self.conn
+            .call(|conn| {
+                let mut stmt = conn.prepare(queries::EXTERNAL_ID_COLLISIONS)?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        let sources: String = row.get(1)?;
+                        Ok(ExternalIdCollision {
+                            external_id: row.get(0)?,
+                            sources: sources.split(',').map(str::to_string).collect(),
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
+    /// Append new events to an existing session without deleting existing events
+    pub async fn append_events(&self, session_id: SessionId, events: &[Event]) -> Result<(), tokio_rusqlite::Error> {
+        let session_id_owned = session_id.to_string();
+        let events: Vec<Event> = events.to_vec();
+        let event_count = events.len();
+        let session_id_for_log = session_id_owned.clone();
+        let compress = self.compress_raw;
+
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                let mut token_messages = recorded_token_messages(&tx, &session_id_owned)?;
+                let mut append_event = tx.prepare(queries::APPEND_EVENTS)?;
+
+                for event in &events {
+                    let id = event.id.to_string();
+                    let sid = session_id_owned.clone();
+                    let kind = event.kind.to_string();
+                    let role = event.role.map(|r| r.to_string()).unwrap_or_default();
+                    let content = event.content.clone().unwrap_or_default();
+                    let timestamp = event.timestamp.to_rfc3339();
+                    let (raw_payload, raw_payload_z) =
+                        encode_payload(serde_json::to_string(&event.raw_payload).unwrap_or_default(), compress)?;
+                    let (input_tokens, output_tokens) = token_columns(&event.raw_payload, &mut token_messages);
+
+                    let inserted = append_event.execute(rusqlite::params![
+                        id,
+                        sid,
+                        kind,
+                        role,
+                        content,
+                        timestamp,
+                        raw_payload,
+                        Event::category(&event.raw_payload),
+                        Event::thinking(&event.raw_payload),
+                        raw_payload_z,
+                        event.parent_id.map(|p| p.to_string()),
+                        signature_column(&kind, event.content.as_deref()),
+                        input_tokens,
+                        output_tokens,
+                    ])?;
+                    if inserted > 0 {
+                        insert_attachments(&tx, &sid, &id, &Attachment::from_payload(&event.raw_payload))?;
+                    }
+                }
+                drop(append_event);
 
                 tx.commit()?;
                 Ok(())
@@ -575,9 +1474,60 @@ impl Database {
         Ok(())
     }
 
+    /// Get files touched during a session
+    pub async fn get_session_files_touched(
+        &self, session_id: &str,
+    ) -> Result<Vec<FileTouchedRow>, tokio_rusqlite::Error> {
+        let session_id = session_id.to_string();
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::GET_SESSION_FILES_TOUCHED)?;
+                let rows = stmt
+                    .query_map([session_id], |row| {
+                        Ok(FileTouchedRow {
+                            id: row.get(0)?,
+                            session_id: row.get(1)?,
+                            file_path: row.get(2)?,
+                            operation: row.get(3)?,
+                            lines_added: row.get(4)?,
+                            lines_removed: row.get(5)?,
+                            touched_at: row.get(6)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
+    /// Get attachments recorded for a session's events
+    pub async fn get_session_attachments(
+        &self, session_id: SessionId,
+    ) -> Result<Vec<AttachmentRow>, tokio_rusqlite::Error> {
+        let session_id = session_id.to_string();
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::GET_SESSION_ATTACHMENTS)?;
+                let rows = stmt
+                    .query_map([session_id], |row| {
+                        Ok(AttachmentRow {
+                            id: row.get(0)?,
+                            session_id: row.get(1)?,
+                            event_id: row.get(2)?,
+                            path: row.get(3)?,
+                            mime_type: row.get(4)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
     /// Update a session's updated_at timestamp
     pub async fn update_session_timestamp(
-        &self, session_id: &str, updated_at: &chrono::DateTime<Utc>,
+        &self, session_id: SessionId, updated_at: &chrono::DateTime<Utc>,
     ) -> Result<(), tokio_rusqlite::Error> {
         let id = session_id.to_string();
         let ts = updated_at.to_rfc3339();
@@ -593,25 +1543,42 @@ impl Database {
     pub async fn search_events(
         &self, query: &str, facets: &SearchFacets, limit: i64, offset: i64,
     ) -> Result<Vec<SearchResult>, tokio_rusqlite::Error> {
-        let query = query.to_string();
+        let query = if facets.match_any { sanitize_fts_query_any(query) } else { sanitize_fts_query(query) };
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
         let source = facets.source.clone();
         let project = facets.project.clone();
         let kind = facets.kind.clone();
+        let category = facets.category.clone();
+        let tag = facets.tag.clone();
+        let session_id = facets.session_id.clone();
         let since = facets.since.map(|dt| dt.to_rfc3339());
+        let until = facets.until.map(|dt| dt.to_rfc3339());
+        let snippet_len = facets.snippet_len.map_or(0, |n| n.clamp(1, SNIPPET_MAX_TOKENS));
+        let sql = if facets.thinking { queries::SEARCH_EVENTS_THINKING } else { queries::SEARCH_EVENTS_FILTERED };
 
         self.conn
             .call(move |conn| {
-                let mut stmt = conn.prepare(queries::SEARCH_EVENTS_FILTERED)?;
+                let mut stmt = conn.prepare(sql)?;
                 let rows = stmt
                     .query_map(
-                        [
+                        rusqlite::params![
                             query,
                             source.unwrap_or_default(),
                             project.unwrap_or_default(),
                             kind.unwrap_or_default(),
                             since.unwrap_or_default(),
-                            limit.to_string(),
-                            offset.to_string(),
+                            limit,
+                            offset,
+                            snippet_len,
+                            SNIPPET_MIN_TOKENS,
+                            SNIPPET_MAX_TOKENS,
+                            SNIPPET_CHARS_PER_TOKEN,
+                            category.unwrap_or_default(),
+                            tag.unwrap_or_default(),
+                            session_id.unwrap_or_default(),
+                            until.unwrap_or_default(),
                         ],
                         |row| {
                             Ok(SearchResult {
@@ -622,10 +1589,12 @@ impl Database {
                                     role: row.get(3)?,
                                     content: row.get(4)?,
                                     timestamp: row.get(5)?,
-                                    raw_payload: row.get(6)?,
+                                    raw_payload: decode_payload(row, 6, 10)?,
+                                    category: row.get(9)?,
+                                    parent_id: row.get(11)?,
                                 },
                                 rank: row.get(7)?,
-                                snippet: None,
+                                snippet: row.get(8)?,
                             })
                         },
                     )?
@@ -635,14 +1604,140 @@ impl Database {
             .await
     }
 
+    /// Find up to `limit` events, newest first, whose content matches a regular expression.
+    ///
+    /// FTS can't match patterns such as `error code \d+`, so this scans the content of every
+    /// event that passes the source, project, kind, since, until, category, tag, and session facets.
+    /// Snippets wrap the first match in `[` `]`; ranks are all zero. Patterns that don't parse or
+    /// compile past [`REGEX_SIZE_LIMIT`] are rejected before any row is read.
+    pub async fn search_events_regex(
+        &self, pattern: &str, facets: &SearchFacets, limit: i64,
+    ) -> Result<Vec<SearchResult>, RegexSearchError> {
+        let regex = regex::RegexBuilder::new(pattern)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .nest_limit(REGEX_NEST_LIMIT)
+            .build()
+            .map_err(|err| match err {
+                regex::Error::CompiledTooBig(_) => RegexSearchError::TooComplex,
+                other => RegexSearchError::InvalidPattern(other.to_string()),
+            })?;
+        let params = [
+            facets.source.clone().unwrap_or_default(),
+            facets.project.clone().unwrap_or_default(),
+            facets.kind.clone().unwrap_or_default(),
+            facets.since.map(|dt| dt.to_rfc3339()).unwrap_or_default(),
+            facets.category.clone().unwrap_or_default(),
+            facets.tag.clone().unwrap_or_default(),
+            facets.session_id.clone().unwrap_or_default(),
+            facets.until.map(|dt| dt.to_rfc3339()).unwrap_or_default(),
+        ];
+        let limit = limit.max(0) as usize;
+
+        let results = self
+            .conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::SCAN_EVENTS_FILTERED)?;
+                let mut rows = stmt.query(params)?;
+                let mut results = Vec::new();
+                while results.len() < limit
+                    && let Some(row) = rows.next()?
+                {
+                    let content: String = row.get(4)?;
+                    let Some(found) = regex.find(&content) else {
+                        continue;
+                    };
+                    let snippet = regex_snippet(&content, found.start(), found.end());
+                    results.push(SearchResult {
+                        event: EventRow {
+                            id: row.get(0)?,
+                            session_id: row.get(1)?,
+                            kind: row.get(2)?,
+                            role: row.get(3)?,
+                            content: Some(content),
+                            timestamp: row.get(5)?,
+                            raw_payload: decode_payload(row, 6, 8)?,
+                            category: row.get(7)?,
+                            parent_id: row.get(9)?,
+                        },
+                        rank: 0.0,
+                        snippet: Some(snippet),
+                    });
+                }
+                Ok(results)
+            })
+            .await?;
+        Ok(results)
+    }
+
+    /// Number of events matching a search, regardless of paging
+    pub async fn count_search_events(&self, query: &str, facets: &SearchFacets) -> Result<i64, tokio_rusqlite::Error> {
+        let query = if facets.match_any { sanitize_fts_query_any(query) } else { sanitize_fts_query(query) };
+        if query.is_empty() {
+            return Ok(0);
+        }
+        let params = [
+            query,
+            facets.source.clone().unwrap_or_default(),
+            facets.project.clone().unwrap_or_default(),
+            facets.kind.clone().unwrap_or_default(),
+            facets.since.map(|dt| dt.to_rfc3339()).unwrap_or_default(),
+            facets.category.clone().unwrap_or_default(),
+            facets.tag.clone().unwrap_or_default(),
+            facets.session_id.clone().unwrap_or_default(),
+            facets.until.map(|dt| dt.to_rfc3339()).unwrap_or_default(),
+        ];
+        let sql = if facets.thinking {
+            queries::COUNT_SEARCH_EVENTS_THINKING
+        } else {
+            queries::COUNT_SEARCH_EVENTS_FILTERED
+        };
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(sql)?;
+                let total: i64 = stmt.query_row(params, |row| row.get(0))?;
+                Ok(total)
+            })
+            .await
+    }
+
+    /// One page of [`Database::search_events`] along with the total number of matches, for
+    /// "showing 20 of 340" style paging
+    pub async fn search_events_paged(
+        &self, query: &str, facets: &SearchFacets, limit: i64, offset: i64,
+    ) -> Result<(Vec<SearchResult>, i64), tokio_rusqlite::Error> {
+        let results = self.search_events(query, facets, limit, offset).await?;
+        let total = self.count_search_events(query, facets).await?;
+        Ok((results, total))
+    }
+
+    /// Run [`Database::search_events`] as the newest query of `generation`, tagging the results with its epoch.
+    ///
+    /// Meant for search-as-you-type: debounce keystrokes on the caller side (~150ms), call this once per settled
+    /// query, and drop any response for which [`TaggedSearchResults::is_stale`] is true, since a later keystroke
+    /// has already started a newer query whose results should win even if they arrive first.
+    pub async fn search_events_tagged(
+        &self, query: &str, facets: &SearchFacets, limit: i64, offset: i64, generation: &SearchGeneration,
+    ) -> Result<TaggedSearchResults, tokio_rusqlite::Error> {
+        let epoch = generation.begin();
+        let results = self.search_events(query, facets, limit, offset).await?;
+        Ok(TaggedSearchResults { epoch, results })
+    }
+
     /// Search sessions with FTS5 and faceted filtering
     pub async fn search_sessions(
         &self, query: &str, facets: &SearchFacets, limit: i64, offset: i64,
     ) -> Result<Vec<(SessionRow, f64)>, tokio_rusqlite::Error> {
-        let query = query.to_string();
+        let query = if facets.match_any { sanitize_fts_query_any(query) } else { sanitize_fts_query(query) };
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
         let source = facets.source.clone();
         let project = facets.project.clone();
         let since = facets.since.map(|dt| dt.to_rfc3339());
+        let until = facets.until.map(|dt| dt.to_rfc3339());
+        let tag = facets.tag.clone();
+        let session_id = facets.session_id.clone();
 
         self.conn
             .call(move |conn| {
@@ -656,6 +1751,9 @@ impl Database {
                             since.unwrap_or_default(),
                             limit.to_string(),
                             offset.to_string(),
+                            tag.unwrap_or_default(),
+                            session_id.unwrap_or_default(),
+                            until.unwrap_or_default(),
                         ],
                         |row| {
                             let session = SessionRow {
@@ -700,53 +1798,147 @@ impl Database {
                             let day_str: String = row.get(0)?;
                             let day = NaiveDate::parse_from_str(&day_str, "%Y-%m-%d")
                                 .unwrap_or_else(|_| Utc::now().date_naive());
-                            Ok(ActivityStats { day, event_count: row.get(1)?, session_count: row.get(2)? })
+                            Ok(ActivityStats {
+                                day,
+                                event_count: row.get(1)?,
+                                session_count: row.get(2)?,
+                                rolling_avg_7d: 0.0,
+                            })
                         },
                     )?
                     .collect::<Result<Vec<_>, _>>()?;
                 Ok(rows)
             })
             .await
+            .map(|mut rows| {
+                apply_rolling_average(&mut rows);
+                rows
+            })
     }
 
-    /// Get error stats by day
-    pub async fn get_errors_by_day(
+    /// Get a unified timeline of active days, newest first, with each day's per-source breakdown
+    pub async fn get_session_timeline(
         &self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
-    ) -> Result<Vec<ErrorStats>, tokio_rusqlite::Error> {
+    ) -> Result<Vec<TimelineDay>, tokio_rusqlite::Error> {
+        let days = self.get_activity_by_day(since, until, None).await?;
+
         let since_str = since.map(|dt| dt.to_rfc3339());
         let until_str = until.map(|dt| dt.to_rfc3339());
-
-        self.conn
+        let mut by_day: HashMap<NaiveDate, Vec<SourceActivity>> = self
+            .conn
             .call(move |conn| {
-                let mut stmt = conn.prepare(queries::ERRORS_BY_DAY)?;
+                let mut stmt = conn.prepare(queries::ACTIVITY_BY_DAY_AND_SOURCE)?;
                 let rows = stmt
                     .query_map([since_str.unwrap_or_default(), until_str.unwrap_or_default()], |row| {
                         let day_str: String = row.get(0)?;
                         let day =
                             NaiveDate::parse_from_str(&day_str, "%Y-%m-%d").unwrap_or_else(|_| Utc::now().date_naive());
-                        Ok(ErrorStats { day, error_count: row.get(1)?, signature: row.get(2)? })
+                        let activity = SourceActivity {
+                            source: row.get(1)?,
+                            event_count: row.get(2)?,
+                            session_count: row.get(3)?,
+                        };
+                        Ok((day, activity))
                     })?
                     .collect::<Result<Vec<_>, _>>()?;
-                Ok(rows)
+
+                let mut by_day: HashMap<NaiveDate, Vec<SourceActivity>> = HashMap::new();
+                for (day, activity) in rows {
+                    by_day.entry(day).or_default().push(activity);
+                }
+                Ok(by_day)
             })
-            .await
+            .await?;
+
+        Ok(days
+            .into_iter()
+            .map(|d| TimelineDay {
+                day: d.day,
+                event_count: d.event_count,
+                session_count: d.session_count,
+                sources: by_day.remove(&d.day).unwrap_or_default(),
+            })
+            .collect())
     }
 
-    /// Get top error signatures
-    pub async fn get_top_errors(
-        &self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, limit: i64,
-    ) -> Result<Vec<(String, i64)>, tokio_rusqlite::Error> {
+    /// Get activity stats by day of week, always returning seven rows from Monday to Sunday
+    pub async fn get_activity_by_weekday(
+        &self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<WeekdayActivity>, tokio_rusqlite::Error> {
         let since_str = since.map(|dt| dt.to_rfc3339());
         let until_str = until.map(|dt| dt.to_rfc3339());
 
         self.conn
             .call(move |conn| {
-                let mut stmt = conn.prepare(queries::TOP_ERROR_SIGNATURES)?;
+                let mut stmt = conn.prepare(queries::ACTIVITY_BY_WEEKDAY)?;
                 let rows = stmt
-                    .query_map(
-                        [
-                            since_str.unwrap_or_default(),
-                            until_str.unwrap_or_default(),
+                    .query_map([since_str.unwrap_or_default(), until_str.unwrap_or_default()], |row| {
+                        Ok((
+                            row.get::<_, Option<u32>>(0)?,
+                            row.get::<_, i64>(1)?,
+                            row.get::<_, i64>(2)?,
+                        ))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let mut weekdays: Vec<WeekdayActivity> = (0..7)
+                    .map(|i| WeekdayActivity {
+                        weekday: Weekday::try_from(i as u8).unwrap_or(Weekday::Mon),
+                        event_count: 0,
+                        session_count: 0,
+                    })
+                    .collect();
+
+                for (sunday_based, event_count, session_count) in rows {
+                    let Some(sunday_based) = sunday_based else { continue };
+                    let slot = &mut weekdays[((sunday_based + 6) % 7) as usize];
+                    slot.event_count = event_count;
+                    slot.session_count = session_count;
+                }
+
+                Ok(weekdays)
+            })
+            .await
+    }
+
+    /// Get error stats by day
+    pub async fn get_errors_by_day(
+        &self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ErrorStats>, tokio_rusqlite::Error> {
+        let since_str = since.map(|dt| dt.to_rfc3339());
+        let until_str = until.map(|dt| dt.to_rfc3339());
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::ERRORS_BY_DAY)?;
+                let rows = stmt
+                    .query_map([since_str.unwrap_or_default(), until_str.unwrap_or_default()], |row| {
+                        let day_str: String = row.get(0)?;
+                        let day =
+                            NaiveDate::parse_from_str(&day_str, "%Y-%m-%d").unwrap_or_else(|_| Utc::now().date_naive());
+                        Ok(ErrorStats { day, error_count: row.get(1)?, signature: row.get(2)? })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
+    /// Get top error signatures
+    pub async fn get_top_errors(
+        &self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, limit: i64,
+    ) -> Result<Vec<(String, i64)>, tokio_rusqlite::Error> {
+        let since_str = since.map(|dt| dt.to_rfc3339());
+        let until_str = until.map(|dt| dt.to_rfc3339());
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::TOP_ERROR_SIGNATURES)?;
+                let rows = stmt
+                    .query_map(
+                        [
+                            since_str.unwrap_or_default(),
+                            until_str.unwrap_or_default(),
                             limit.to_string(),
                         ],
                         |row| Ok((row.get(0)?, row.get(1)?)),
@@ -827,6 +2019,109 @@ impl Database {
             .await
     }
 
+    /// Get stored content and raw-payload bytes per source, largest first
+    pub async fn get_storage_by_source(&self) -> Result<Vec<StorageStats>, tokio_rusqlite::Error> {
+        self.conn
+            .call(|conn| {
+                let mut stmt = conn.prepare(queries::STORAGE_BY_SOURCE)?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        let source: String = row.get(0)?;
+                        Ok(StorageStats {
+                            dimension: source.clone(),
+                            source,
+                            session_count: row.get(1)?,
+                            event_count: row.get(2)?,
+                            content_bytes: row.get(3)?,
+                            raw_bytes: row.get(4)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
+    /// Get the `limit` sessions with the most stored raw-payload bytes
+    pub async fn get_storage_by_session(&self, limit: i64) -> Result<Vec<StorageStats>, tokio_rusqlite::Error> {
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::STORAGE_BY_SESSION)?;
+                let rows = stmt
+                    .query_map([limit], |row| {
+                        Ok(StorageStats {
+                            dimension: row.get(0)?,
+                            source: row.get(1)?,
+                            session_count: 1,
+                            event_count: row.get(2)?,
+                            content_bytes: row.get(3)?,
+                            raw_bytes: row.get(4)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
+    /// Get the count of each event kind per source
+    pub async fn get_kind_distribution_by_source(
+        &self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<KindDistribution>, tokio_rusqlite::Error> {
+        let since_str = since.map(|dt| dt.to_rfc3339());
+        let until_str = until.map(|dt| dt.to_rfc3339());
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::KIND_DISTRIBUTION_BY_SOURCE)?;
+                let rows = stmt
+                    .query_map([since_str.unwrap_or_default(), until_str.unwrap_or_default()], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, i64>(2)?,
+                        ))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let mut distributions: Vec<KindDistribution> = Vec::new();
+                for (source, kind, count) in rows {
+                    match distributions.last_mut() {
+                        Some(last) if last.source == source => {
+                            last.total += count;
+                            last.counts.insert(kind, count);
+                        }
+                        _ => distributions.push(KindDistribution {
+                            source,
+                            counts: HashMap::from([(kind, count)]),
+                            total: count,
+                        }),
+                    }
+                }
+
+                Ok(distributions)
+            })
+            .await
+    }
+
+    /// Count a session's events by kind
+    pub async fn get_session_kind_counts(
+        &self, session_id: SessionId,
+    ) -> Result<HashMap<String, i64>, tokio_rusqlite::Error> {
+        let session_id = session_id.to_string();
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::SESSION_KIND_COUNTS)?;
+                let counts = stmt
+                    .query_map([session_id], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+                    })?
+                    .collect::<Result<HashMap<_, _>, _>>()?;
+                Ok(counts)
+            })
+            .await
+    }
+
     /// Get available sources for faceting
     pub async fn get_sources(&self) -> Result<Vec<String>, tokio_rusqlite::Error> {
         self.conn
@@ -884,6 +2179,9 @@ impl Database {
         let avg_latency_ms = metrics.avg_latency_ms;
         let p50_latency_ms = metrics.p50_latency_ms;
         let p95_latency_ms = metrics.p95_latency_ms;
+        let reasoning_tokens = metrics.reasoning_tokens;
+        let reasoning_cost = metrics.reasoning_cost;
+        let tokens_per_line = metrics.tokens_per_line;
 
         self.conn
             .call(move |conn| {
@@ -912,6 +2210,9 @@ impl Database {
                         avg_latency_ms,
                         p50_latency_ms,
                         p95_latency_ms,
+                        reasoning_tokens,
+                        reasoning_cost,
+                        tokens_per_line,
                     ],
                 )?;
                 Ok(())
@@ -919,11 +2220,44 @@ impl Database {
             .await
     }
 
+    /// Fill in the signature of error events stored before signatures were recorded, returning
+    /// how many were filled
+    pub async fn backfill_error_signatures(&self) -> Result<usize, tokio_rusqlite::Error> {
+        self.conn
+            .call(|conn| {
+                let tx = conn.transaction()?;
+                let errors = {
+                    let mut stmt = tx.prepare(queries::ERRORS_WITHOUT_SIGNATURE)?;
+                    stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                        .collect::<Result<Vec<_>, _>>()?
+                };
+                let mut filled = 0;
+                {
+                    let mut update = tx.prepare(queries::UPDATE_EVENT_SIGNATURE)?;
+                    for (id, content) in &errors {
+                        if let Some(signature) = signature_column("error", Some(content)) {
+                            filled += update.execute([id, &signature])?;
+                        }
+                    }
+                }
+                tx.commit()?;
+                Ok(filled)
+            })
+            .await
+    }
+
     /// Compute and store metrics for a session
-    pub async fn compute_session_metrics(&self, session_id: &str) -> Result<(), tokio_rusqlite::Error> {
+    pub async fn compute_session_metrics(&self, session_id: SessionId) -> Result<(), tokio_rusqlite::Error> {
+        let events = self.get_session_events(session_id).await?;
+        self.store_session_metrics(session_id, &events).await
+    }
+
+    /// Compute and store metrics for a session from its already-loaded `events`
+    async fn store_session_metrics(
+        &self, session_id: SessionId, events: &[EventRow],
+    ) -> Result<(), tokio_rusqlite::Error> {
         let session_id_str = session_id.to_string();
-        let session = self.get_session(session_id_str.clone()).await?;
-        let events = self.get_session_events(session_id_str.clone()).await?;
+        let session = self.get_session(session_id).await?;
 
         if session.is_none() {
             return Ok(());
@@ -977,7 +2311,7 @@ impl Database {
             error_count: 0,
             user_messages: 0,
             assistant_messages: 0,
-            duration_seconds: None,
+            duration_seconds: event_span_seconds(events),
             files_touched: file_stats.0,
             lines_added: file_stats.1,
             lines_removed: file_stats.2,
@@ -991,11 +2325,17 @@ impl Database {
             avg_latency_ms: latency_stats.1,
             p50_latency_ms: None,
             p95_latency_ms: None,
+            reasoning_tokens: None,
+            reasoning_cost: None,
+            tokens_per_line: None,
         };
 
         let mut input_tokens = 0;
         let mut output_tokens = 0;
+        let mut reasoning_tokens = 0;
         let mut model_name: Option<String> = None;
+        let mut claude_usage = ClaudeUsage::default();
+        let mut opencode_usage: Option<(usize, usize)> = None;
 
         if let Ok(payload) = serde_json::from_str::<serde_json::Value>(&session.raw_payload) {
             if let Some(m) = payload.get("model").and_then(|v| v.as_str()) {
@@ -1032,6 +2372,9 @@ impl Database {
             }
 
             if let Ok(payload) = serde_json::from_str::<serde_json::Value>(&event.raw_payload) {
+                if ToolOutcome::from_payload(&payload).is_some_and(|outcome| !outcome.success) {
+                    metrics.error_count += 1;
+                }
                 if model_name.is_none() {
                     if let Some(m) = payload.get("model").and_then(|v| v.as_str()) {
                         model_name = Some(m.to_string());
@@ -1050,13 +2393,10 @@ impl Database {
                     }
                 }
 
-                if let Some(usage) = payload.get("message").and_then(|msg| msg.get("usage")) {
-                    if let Some(it) = usage.get("input_tokens").and_then(|v| v.as_i64()) {
-                        input_tokens = it as usize;
-                    }
-                    if let Some(ot) = usage.get("output_tokens").and_then(|v| v.as_i64()) {
-                        output_tokens = ot as usize;
-                    }
+                if let Some(message) = payload.get("message")
+                    && let Some(usage) = message.get("usage")
+                {
+                    claude_usage.record(message, usage);
                 }
 
                 if let Some(usage) = payload.get("usage") {
@@ -1066,6 +2406,21 @@ impl Database {
                     if let Some(ot) = usage.get("completion_tokens").and_then(|v| v.as_i64()) {
                         output_tokens = ot as usize;
                     }
+                    if let Some(rt) = usage
+                        .get("completion_tokens_details")
+                        .and_then(|d| d.get("reasoning_tokens"))
+                        .and_then(|v| v.as_i64())
+                    {
+                        reasoning_tokens = rt as usize;
+                    }
+                }
+
+                if let Some(tokens) = opencode_tokens(&payload) {
+                    let count = |key: &str| tokens.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+                    let (input, output) = opencode_usage.get_or_insert((0, 0));
+                    *input += count("input");
+                    *output += count("output");
+                    reasoning_tokens += count("reasoning");
                 }
 
                 if let Some(info) = payload.get("info")
@@ -1077,19 +2432,39 @@ impl Database {
                     if let Some(ot) = token_usage.get("output_tokens").and_then(|v| v.as_i64()) {
                         output_tokens = ot as usize;
                     }
+                    if let Some(rt) = token_usage.get("reasoning_output_tokens").and_then(|v| v.as_i64()) {
+                        reasoning_tokens = rt as usize;
+                    }
                 }
             }
         }
 
+        if claude_usage.has_usage() {
+            input_tokens = claude_usage.input_tokens;
+            output_tokens = claude_usage.output_tokens;
+            if let Some(model) = claude_usage.main_model() {
+                model_name = Some(model);
+            }
+        } else if let Some((input, output)) = opencode_usage {
+            input_tokens = input;
+            output_tokens = output;
+        }
+
         metrics.model = model_name.clone();
         metrics.input_tokens = Some(input_tokens as i64);
         metrics.output_tokens = Some(output_tokens as i64);
-
-        if let Some(m) = model_name
-            && let Some(meta) = ModelMetadata::lookup(&m)
-        {
-            metrics.provider = Some(meta.provider.clone());
-            metrics.estimated_cost = Some(meta.calculate_cost(input_tokens, output_tokens));
+        metrics.reasoning_tokens = Some(reasoning_tokens as i64);
+        metrics.tokens_per_line = tokens_per_line(
+            (input_tokens + output_tokens) as i64,
+            metrics.lines_added + metrics.lines_removed,
+        );
+
+        if let Some(m) = model_name {
+            metrics.provider = ModelMetadata::lookup(&m).map(|meta| meta.provider);
+            metrics.estimated_cost = estimate_cost(&m, input_tokens, output_tokens);
+            metrics.reasoning_cost = estimate_cost(&m, input_tokens, reasoning_tokens)
+                .zip(estimate_cost(&m, input_tokens, 0))
+                .map(|(with_reasoning, without)| with_reasoning - without);
         }
 
         self.upsert_session_metrics(&metrics).await?;
@@ -1123,6 +2498,48 @@ impl Database {
             .await
     }
 
+    /// Get sessions that touched files matching `path_pattern`, most recent first.
+    /// Patterns containing `*`, `?` or `[` are globs (relative ones match anywhere in the path);
+    /// anything else matches as a case-insensitive substring.
+    pub async fn get_sessions_by_file(
+        &self, path_pattern: &str, limit: i64,
+    ) -> Result<Vec<FileSessionMatch>, tokio_rusqlite::Error> {
+        let is_glob = path_pattern.contains(['*', '?', '[']);
+        let pattern = if !is_glob {
+            let escaped = path_pattern
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{}%", escaped)
+        } else if path_pattern.starts_with(['/', '*']) {
+            path_pattern.to_string()
+        } else {
+            format!("*{}", path_pattern)
+        };
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::SESSIONS_BY_FILE)?;
+                let rows = stmt
+                    .query_map(rusqlite::params![pattern, is_glob, limit], |row| {
+                        Ok(FileSessionMatch {
+                            session_id: row.get(0)?,
+                            source: row.get(1)?,
+                            project: row.get(2)?,
+                            title: row.get(3)?,
+                            updated_at: row.get(4)?,
+                            match_count: row.get(5)?,
+                            file_count: row.get(6)?,
+                            lines_added: row.get(7)?,
+                            lines_removed: row.get(8)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+
     /// Get files touched leaderboard
     pub async fn get_files_leaderboard(
         &self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, limit: i64,
@@ -1156,6 +2573,35 @@ impl Database {
             .await
     }
 
+    /// Get files touched leaderboard with paths bucketed by `grouping`; each entry's `file_path` is the bucket
+    pub async fn get_files_leaderboard_grouped(
+        &self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, limit: i64, grouping: FileGrouping,
+    ) -> Result<Vec<FileLeaderboardEntry>, tokio_rusqlite::Error> {
+        if grouping == FileGrouping::None {
+            return self.get_files_leaderboard(since, until, limit).await;
+        }
+
+        let since_str = since.map(|dt| dt.to_rfc3339());
+        let until_str = until.map(|dt| dt.to_rfc3339());
+
+        let touches: Vec<(String, String, i64, i64)> = self
+            .conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::FILES_TOUCHED_IN_RANGE)?;
+                let rows = stmt
+                    .query_map([since_str.unwrap_or_default(), until_str.unwrap_or_default()], |row| {
+                        Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await?;
+
+        let mut entries = group_file_touches(&touches, grouping);
+        entries.truncate(limit.max(0) as usize);
+        Ok(entries)
+    }
+
     /// Get patch churn stats by day
     pub async fn get_patch_churn_by_day(
         &self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
@@ -1222,7 +2668,7 @@ impl Database {
 
     /// Get session metrics
     pub async fn get_session_metrics(
-        &self, session_id: &str,
+        &self, session_id: SessionId,
     ) -> Result<Option<SessionMetricsRow>, tokio_rusqlite::Error> {
         let session_id = session_id.to_string();
 
@@ -1254,6 +2700,9 @@ impl Database {
                             avg_latency_ms: row.get(19)?,
                             p50_latency_ms: row.get(20)?,
                             p95_latency_ms: row.get(21)?,
+                            reasoning_tokens: row.get(22)?,
+                            reasoning_cost: row.get(23)?,
+                            tokens_per_line: row.get(24)?,
                         })
                     })
                     .optional()?;
@@ -1305,6 +2754,9 @@ impl Database {
                                 avg_latency_ms: row.get(26)?,
                                 p50_latency_ms: row.get(27)?,
                                 p95_latency_ms: row.get(28)?,
+                                reasoning_tokens: row.get(29)?,
+                                reasoning_cost: row.get(30)?,
+                                tokens_per_line: row.get(31)?,
                             })
                         } else {
                             None
@@ -1318,30 +2770,36 @@ impl Database {
     }
 
     /// Get cost stats by source
+    /// When `exclude_reasoning` is set, reasoning-token cost is subtracted from the totals.
     pub async fn get_cost_stats_by_source(
         &self, source_filter: Option<String>, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+        exclude_reasoning: bool, model_filter: Option<String>,
     ) -> Result<Vec<CostStats>, tokio_rusqlite::Error> {
         let source = source_filter.unwrap_or_default();
         let since_str = since.map(|dt| dt.to_rfc3339()).unwrap_or_default();
         let until_str = until.map(|dt| dt.to_rfc3339()).unwrap_or_default();
+        let models = self.resolve_model_filter(model_filter).await?;
 
         self.conn
             .call(move |conn| {
                 let mut stmt = conn.prepare(queries::COST_STATS_BY_SOURCE)?;
                 let rows = stmt
-                    .query_map([source, since_str, until_str], |row| {
-                        Ok(CostStats {
-                            dimension: row.get(0)?,
-                            session_count: row.get(1)?,
-                            total_cost: row.get(2)?,
-                            avg_cost_per_session: row.get(3)?,
-                            total_input_tokens: row.get(4)?,
-                            total_output_tokens: row.get(5)?,
-                            avg_latency_ms: row.get(6)?,
-                            p50_latency_ms: row.get(7)?,
-                            p95_latency_ms: row.get(8)?,
-                        })
-                    })?
+                    .query_map(
+                        rusqlite::params![source, since_str, until_str, exclude_reasoning, models],
+                        |row| {
+                            Ok(CostStats {
+                                dimension: row.get(0)?,
+                                session_count: row.get(1)?,
+                                total_cost: row.get(2)?,
+                                avg_cost_per_session: row.get(3)?,
+                                total_input_tokens: row.get(4)?,
+                                total_output_tokens: row.get(5)?,
+                                avg_latency_ms: row.get(6)?,
+                                p50_latency_ms: row.get(7)?,
+                                p95_latency_ms: row.get(8)?,
+                            })
+                        },
+                    )?
                     .collect::<Result<Vec<_>, _>>()?;
                 Ok(rows)
             })
@@ -1349,30 +2807,36 @@ impl Database {
     }
 
     /// Get cost stats by project
+    /// When `exclude_reasoning` is set, reasoning-token cost is subtracted from the totals.
     pub async fn get_cost_stats_by_project(
         &self, source_filter: Option<String>, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+        exclude_reasoning: bool, model_filter: Option<String>,
     ) -> Result<Vec<CostStats>, tokio_rusqlite::Error> {
         let source = source_filter.unwrap_or_default();
         let since_str = since.map(|dt| dt.to_rfc3339()).unwrap_or_default();
         let until_str = until.map(|dt| dt.to_rfc3339()).unwrap_or_default();
+        let models = self.resolve_model_filter(model_filter).await?;
 
         self.conn
             .call(move |conn| {
                 let mut stmt = conn.prepare(queries::COST_STATS_BY_PROJECT)?;
                 let rows = stmt
-                    .query_map([source, since_str, until_str], |row| {
-                        Ok(CostStats {
-                            dimension: row.get(0)?,
-                            session_count: row.get(1)?,
-                            total_cost: row.get(2)?,
-                            avg_cost_per_session: row.get(3)?,
-                            total_input_tokens: row.get(4)?,
-                            total_output_tokens: row.get(5)?,
-                            avg_latency_ms: row.get(6)?,
-                            p50_latency_ms: row.get(7)?,
-                            p95_latency_ms: row.get(8)?,
-                        })
-                    })?
+                    .query_map(
+                        rusqlite::params![source, since_str, until_str, exclude_reasoning, models],
+                        |row| {
+                            Ok(CostStats {
+                                dimension: row.get(0)?,
+                                session_count: row.get(1)?,
+                                total_cost: row.get(2)?,
+                                avg_cost_per_session: row.get(3)?,
+                                total_input_tokens: row.get(4)?,
+                                total_output_tokens: row.get(5)?,
+                                avg_latency_ms: row.get(6)?,
+                                p50_latency_ms: row.get(7)?,
+                                p95_latency_ms: row.get(8)?,
+                            })
+                        },
+                    )?
                     .collect::<Result<Vec<_>, _>>()?;
                 Ok(rows)
             })
@@ -1445,16 +2909,18 @@ impl Database {
     /// Get model usage stats
     pub async fn get_model_usage_stats(
         &self, source_filter: Option<String>, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
+        model_filter: Option<String>,
     ) -> Result<Vec<ModelUsageStats>, tokio_rusqlite::Error> {
         let source = source_filter.unwrap_or_default();
         let since_str = since.map(|dt| dt.to_rfc3339()).unwrap_or_default();
         let until_str = until.map(|dt| dt.to_rfc3339()).unwrap_or_default();
+        let models = self.resolve_model_filter(model_filter).await?;
 
         self.conn
             .call(move |conn| {
                 let mut stmt = conn.prepare(queries::MODEL_USAGE_STATS)?;
                 let rows = stmt
-                    .query_map([source, since_str, until_str], |row| {
+                    .query_map([source, since_str, until_str, models], |row| {
                         Ok(ModelUsageStats {
                             model: row.get(0)?,
                             provider: row.get(1)?,
@@ -1471,6 +2937,31 @@ impl Database {
             .await
     }
 
+    /// Resolve a model filter to the stored model names it covers, as a JSON array for `json_each`.
+    /// Names match case-insensitively or by canonical registry ID, so `claude-sonnet-4-5` also covers dated
+    /// variants. Returns an empty string when there is no filter.
+    async fn resolve_model_filter(&self, model_filter: Option<String>) -> Result<String, tokio_rusqlite::Error> {
+        let Some(filter) = model_filter else {
+            return Ok(String::new());
+        };
+        let canonical = ModelMetadata::canonical_name(&filter);
+
+        let stored: Vec<String> = self
+            .conn
+            .call(|conn| {
+                let mut stmt = conn.prepare(queries::DISTINCT_METRIC_MODELS)?;
+                let rows = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await?;
+
+        let matching: Vec<String> = stored
+            .into_iter()
+            .filter(|model| model.eq_ignore_ascii_case(&filter) || ModelMetadata::canonical_name(model) == canonical)
+            .collect();
+        Ok(serde_json::Value::from(matching).to_string())
+    }
+
     /// Get aggregate efficiency stats
     pub async fn get_efficiency_stats(
         &self, source_filter: Option<String>, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>,
@@ -1491,16 +2982,66 @@ impl Database {
                         retry_loops: row.get(4)?,
                         p50_latency_ms: row.get(5)?,
                         p95_latency_ms: row.get(6)?,
+                        avg_tokens_per_line: row.get(7)?,
                     })
                 })?;
                 Ok(row)
             })
             .await
     }
+
+    /// Get sessions ordered by tokens per changed line, skipping sessions that changed no lines
+    pub async fn get_sessions_by_tokens_per_line(
+        &self, source_filter: Option<String>, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, limit: i64,
+    ) -> Result<Vec<SessionEfficiency>, tokio_rusqlite::Error> {
+        let source = source_filter.unwrap_or_default();
+        let since_str = since.map(|dt| dt.to_rfc3339()).unwrap_or_default();
+        let until_str = until.map(|dt| dt.to_rfc3339()).unwrap_or_default();
+
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(queries::SESSIONS_BY_TOKENS_PER_LINE)?;
+                let rows = stmt
+                    .query_map([source, since_str, until_str, limit.to_string()], |row| {
+                        Ok(SessionEfficiency {
+                            session_id: row.get(0)?,
+                            external_id: row.get(1)?,
+                            project: row.get(2)?,
+                            source: row.get(3)?,
+                            total_tokens: row.get(4)?,
+                            lines_changed: row.get(5)?,
+                            tokens_per_line: row.get(6)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+    }
+}
+
+/// Event kind counts for a single source
+#[derive(Debug, Clone, Serialize)]
+pub struct KindDistribution {
+    pub source: String,
+    pub counts: HashMap<String, i64>,
+    pub total: i64,
+}
+
+/// Stored event bytes for a source or a single session
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageStats {
+    /// Source name, or session ID for per-session rows
+    pub dimension: String,
+    pub source: String,
+    pub session_count: i64,
+    pub event_count: i64,
+    pub content_bytes: i64,
+    pub raw_bytes: i64,
 }
 
 /// Stats for tool call frequency
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ToolFrequencyStats {
     pub tool_name: String,
     pub call_count: i64,
@@ -1510,7 +3051,7 @@ pub struct ToolFrequencyStats {
 }
 
 /// Entry in the files touched leaderboard
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FileLeaderboardEntry {
     pub file_path: String,
     pub touch_count: i64,
@@ -1519,8 +3060,56 @@ pub struct FileLeaderboardEntry {
     pub total_lines_removed: i64,
 }
 
-/// Patch churn stats for a day
+/// How the files leaderboard buckets paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileGrouping {
+    /// File extension, e.g. `.rs`
+    Extension,
+    /// Top-level directory below the directory shared by every touched path
+    Directory,
+    /// Individual file paths
+    None,
+}
+
+impl std::str::FromStr for FileGrouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ext" | "extension" => Ok(FileGrouping::Extension),
+            "dir" | "directory" => Ok(FileGrouping::Directory),
+            "none" => Ok(FileGrouping::None),
+            _ => Err(format!("Unknown grouping: {}. Use 'ext', 'dir' or 'none'", s)),
+        }
+    }
+}
+
+/// An external ID shared by sessions from several sources
+#[derive(Debug, Clone)]
+pub struct ExternalIdCollision {
+    pub external_id: String,
+    /// Sources holding a session with this ID, sorted by name
+    pub sources: Vec<String>,
+}
+
+/// A session that touched files matching a path pattern
 #[derive(Debug, Clone)]
+pub struct FileSessionMatch {
+    pub session_id: String,
+    pub source: String,
+    pub project: Option<String>,
+    pub title: Option<String>,
+    pub updated_at: String,
+    /// Number of matching file touches in the session
+    pub match_count: i64,
+    /// Number of distinct matching paths
+    pub file_count: i64,
+    pub lines_added: i64,
+    pub lines_removed: i64,
+}
+
+/// Patch churn stats for a day
+#[derive(Debug, Clone, Serialize)]
 pub struct PatchChurnStats {
     pub day: NaiveDate,
     pub lines_added: i64,
@@ -1530,7 +3119,7 @@ pub struct PatchChurnStats {
 }
 
 /// Long-running tool call entry
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct LongRunningToolCall {
     pub tool_name: String,
     pub duration_ms: i64,
@@ -1541,7 +3130,7 @@ pub struct LongRunningToolCall {
 }
 
 /// Cost and latency stats by source or project
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CostStats {
     pub dimension: String,
     pub session_count: i64,
@@ -1555,7 +3144,7 @@ pub struct CostStats {
 }
 
 /// Cost stats for a single session
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SessionCostStats {
     pub session_id: String,
     pub external_id: String,
@@ -1572,7 +3161,7 @@ pub struct SessionCostStats {
 }
 
 /// Latency distribution summary
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct LatencyDistribution {
     pub avg_latency: Option<f64>,
     pub p50_latency: Option<f64>,
@@ -1582,7 +3171,7 @@ pub struct LatencyDistribution {
 }
 
 /// Model/provider usage stats
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ModelUsageStats {
     pub model: String,
     pub provider: String,
@@ -1594,7 +3183,7 @@ pub struct ModelUsageStats {
 }
 
 /// Aggregate efficiency stats
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EfficiencyStats {
     pub total_sessions: i64,
     pub total_cost: f64,
@@ -1603,6 +3192,323 @@ pub struct EfficiencyStats {
     pub retry_loops: i64,
     pub p50_latency_ms: f64,
     pub p95_latency_ms: f64,
+    /// Mean tokens per changed line over sessions that changed any lines
+    pub avg_tokens_per_line: Option<f64>,
+}
+
+/// Token economy of a single session
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEfficiency {
+    pub session_id: String,
+    pub external_id: String,
+    pub project: Option<String>,
+    pub source: String,
+    pub total_tokens: i64,
+    /// Lines added plus lines removed
+    pub lines_changed: i64,
+    pub tokens_per_line: f64,
+}
+
+/// Tokens reported by one event of a session
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenUsagePoint {
+    pub event_id: String,
+    pub timestamp: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+}
+
+/// Seconds between a session's earliest and latest event, or `None` without a parseable timestamp
+fn event_span_seconds(events: &[EventRow]) -> Option<i64> {
+    let timestamps: Vec<DateTime<Utc>> = events
+        .iter()
+        .filter_map(|e| DateTime::parse_from_rfc3339(&e.timestamp).ok())
+        .map(|t| t.with_timezone(&Utc))
+        .collect();
+    let first = timestamps.iter().min()?;
+    let last = timestamps.iter().max()?;
+    Some((*last - *first).num_seconds())
+}
+
+/// Tokens spent per changed line, or `None` when the session changed no lines
+fn tokens_per_line(tokens: i64, lines_changed: i64) -> Option<f64> {
+    if lines_changed > 0 { Some(tokens as f64 / lines_changed as f64) } else { None }
+}
+
+/// Fill in `rolling_avg_7d` for each day. Days missing from `stats` count as zero activity,
+/// and days within a week of the earliest row average over only the days available so far.
+fn apply_rolling_average(stats: &mut [ActivityStats]) {
+    let Some(first_day) = stats.iter().map(|s| s.day).min() else {
+        return;
+    };
+    let counts: HashMap<NaiveDate, i64> = stats.iter().map(|s| (s.day, s.event_count)).collect();
+
+    for stat in stats.iter_mut() {
+        let window_start = (stat.day - chrono::Duration::days(6)).max(first_day);
+        let total: i64 = window_start
+            .iter_days()
+            .take_while(|d| *d <= stat.day)
+            .filter_map(|d| counts.get(&d))
+            .sum();
+        let days = (stat.day - window_start).num_days() + 1;
+        stat.rolling_avg_7d = total as f64 / days as f64;
+    }
+}
+
+/// Turn free-form user input into a safe FTS5 query.
+/// Each whitespace-separated term becomes a quoted string so characters like `"`, `*`, `:` and `-`
+/// are matched literally instead of parsed as syntax; a trailing `*` is kept as a prefix match.
+/// Terms are space-joined, which FTS5 treats as AND: every term must match.
+pub fn sanitize_fts_query(query: &str) -> String {
+    fts_terms(query).join(" ")
+}
+
+/// Like [`sanitize_fts_query`], but OR-joins the terms so a row matching any one of them is a hit.
+pub fn sanitize_fts_query_any(query: &str) -> String {
+    fts_terms(query).join(" OR ")
+}
+
+fn fts_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .filter_map(|term| {
+            let (term, prefix) = match term.strip_suffix('*') {
+                Some(stem) => (stem.trim_end_matches('*'), true),
+                None => (term, false),
+            };
+            if term.is_empty() {
+                return None;
+            }
+            let quoted = format!("\"{}\"", term.replace('"', "\"\""));
+            Some(if prefix { quoted + "*" } else { quoted })
+        })
+        .collect()
+}
+
+/// zstd level for compressed event payloads; low levels keep ingest fast and still shrink JSON well
+const PAYLOAD_ZSTD_LEVEL: i32 = 3;
+
+/// Split an event's JSON payload into its `raw_payload` and `raw_payload_z` column values.
+/// A compressed payload leaves the text column empty so the JSON is only stored once.
+fn encode_payload(json: String, compress: bool) -> rusqlite::Result<(String, Option<Vec<u8>>)> {
+    if !compress {
+        return Ok((json, None));
+    }
+    let bytes = zstd::encode_all(json.as_bytes(), PAYLOAD_ZSTD_LEVEL)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    Ok((String::new(), Some(bytes)))
+}
+
+/// Read an event's JSON payload from the `raw_payload` text column, or from the zstd
+/// bytes in `raw_payload_z` when that column is set
+fn decode_payload(row: &rusqlite::Row, text_idx: usize, compressed_idx: usize) -> rusqlite::Result<String> {
+    let Some(bytes) = row.get::<_, Option<Vec<u8>>>(compressed_idx)? else {
+        return row.get(text_idx);
+    };
+    let conversion_error = |e: Box<dyn std::error::Error + Send + Sync>| {
+        rusqlite::Error::FromSqlConversionFailure(compressed_idx, rusqlite::types::Type::Blob, e)
+    };
+    let json = zstd::decode_all(bytes.as_slice()).map_err(|e| conversion_error(Box::new(e)))?;
+    String::from_utf8(json).map_err(|e| conversion_error(Box::new(e)))
+}
+
+/// Excerpt of `content` around the match at `start..end`, with the match wrapped in `[` `]` and
+/// `…` marking trimmed context, like FTS snippets
+fn regex_snippet(content: &str, start: usize, end: usize) -> String {
+    let before = &content[..start];
+    let context_start = before
+        .char_indices()
+        .rev()
+        .nth(REGEX_SNIPPET_CONTEXT - 1)
+        .map_or(0, |(i, _)| i);
+    let after = &content[end..];
+    let context_end = after
+        .char_indices()
+        .nth(REGEX_SNIPPET_CONTEXT)
+        .map_or(content.len(), |(i, _)| end + i);
+
+    format!(
+        "{}{}[{}]{}{}",
+        if context_start > 0 { "…" } else { "" },
+        &content[context_start..start],
+        &content[start..end],
+        &content[end..context_end],
+        if context_end < content.len() { "…" } else { "" },
+    )
+}
+
+/// Values for an event's `input_tokens` and `output_tokens` columns.
+///
+/// Claude repeats a response's `message.usage` on every line of the response, so only the first
+/// event seen for a `message.id` (tracked in `seen`) gets the counts, as in [`ClaudeUsage`].
+/// OpenCode assistant messages carry their own `tokens`.
+fn token_columns(payload: &serde_json::Value, seen: &mut HashSet<String>) -> (Option<i64>, Option<i64>) {
+    let count = |tokens: &serde_json::Value, key: &str| Some(tokens.get(key).and_then(|v| v.as_i64()).unwrap_or(0));
+    if let Some(message) = payload.get("message")
+        && let Some(usage) = message.get("usage")
+    {
+        if let Some(id) = message.get("id").and_then(|v| v.as_str())
+            && !seen.insert(id.to_string())
+        {
+            return (None, None);
+        }
+        return (count(usage, "input_tokens"), count(usage, "output_tokens"));
+    }
+    match opencode_tokens(payload) {
+        Some(tokens) => (count(tokens, "input"), count(tokens, "output")),
+        None => (None, None),
+    }
+}
+
+/// The `tokens` object of an OpenCode message payload
+fn opencode_tokens(payload: &serde_json::Value) -> Option<&serde_json::Value> {
+    payload
+        .get("tokens")
+        .filter(|tokens| tokens.get("input").is_some() || tokens.get("output").is_some())
+}
+
+/// Claude message IDs whose tokens are already recorded on one of the session's events
+fn recorded_token_messages(conn: &rusqlite::Connection, session_id: &str) -> rusqlite::Result<HashSet<String>> {
+    let mut stmt = conn.prepare(queries::TOKEN_EVENT_PAYLOADS)?;
+    let payloads = stmt.query_map([session_id], |row| decode_payload(row, 0, 1))?;
+    let mut seen = HashSet::new();
+    for payload in payloads {
+        let payload: serde_json::Value = serde_json::from_str(&payload?).unwrap_or_default();
+        if let Some(id) = payload.pointer("/message/id").and_then(|v| v.as_str()) {
+            seen.insert(id.to_string());
+        }
+    }
+    Ok(seen)
+}
+
+/// Value for an event's `signature` column: the normalized message of an error event, or `None`
+/// for other events and errors without a message
+fn signature_column(kind: &str, content: Option<&str>) -> Option<String> {
+    (kind == "error")
+        .then_some(content)
+        .flatten()
+        .map(error_signature)
+        .filter(|signature| !signature.is_empty())
+}
+
+/// Record attachment references for an inserted event
+fn insert_attachments(
+    conn: &rusqlite::Connection, session_id: &str, event_id: &str, attachments: &[Attachment],
+) -> rusqlite::Result<()> {
+    for attachment in attachments {
+        conn.prepare_cached(queries::INSERT_ATTACHMENT)?
+            .execute(rusqlite::params![
+                uuid::Uuid::new_v4().to_string(),
+                session_id,
+                event_id,
+                attachment.path,
+                attachment.mime_type,
+            ])?;
+    }
+    Ok(())
+}
+
+/// Sum `(file_path, session_id, lines_added, lines_removed)` touches into leaderboard rows, most touched first
+fn group_file_touches(touches: &[(String, String, i64, i64)], grouping: FileGrouping) -> Vec<FileLeaderboardEntry> {
+    let common_dir = match grouping {
+        FileGrouping::Directory => common_parent_len(touches.iter().map(|t| Path::new(&t.0))),
+        _ => 0,
+    };
+
+    let mut groups: HashMap<String, (FileLeaderboardEntry, HashSet<&str>)> = HashMap::new();
+    for (path, session_id, added, removed) in touches {
+        let key = match grouping {
+            FileGrouping::Extension => Path::new(path)
+                .extension()
+                .map(|ext| format!(".{}", ext.to_string_lossy()))
+                .unwrap_or_else(|| "(no extension)".to_string()),
+            FileGrouping::Directory => {
+                let components: Vec<_> = Path::new(path).components().collect();
+                if components.len() > common_dir + 1 {
+                    components[common_dir].as_os_str().to_string_lossy().into_owned()
+                } else {
+                    ".".to_string()
+                }
+            }
+            FileGrouping::None => path.clone(),
+        };
+
+        let (entry, sessions) = groups.entry(key.clone()).or_insert_with(|| {
+            (
+                FileLeaderboardEntry {
+                    file_path: key,
+                    touch_count: 0,
+                    sessions: 0,
+                    total_lines_added: 0,
+                    total_lines_removed: 0,
+                },
+                HashSet::new(),
+            )
+        });
+        entry.touch_count += 1;
+        entry.total_lines_added += added;
+        entry.total_lines_removed += removed;
+        sessions.insert(session_id.as_str());
+    }
+
+    let mut entries: Vec<FileLeaderboardEntry> = groups
+        .into_values()
+        .map(|(mut entry, sessions)| {
+            entry.sessions = sessions.len() as i64;
+            entry
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.touch_count
+            .cmp(&a.touch_count)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    entries
+}
+
+/// Number of leading components shared by the parent directories of all `paths`
+fn common_parent_len<'a>(paths: impl Iterator<Item = &'a Path>) -> usize {
+    let mut common: Option<Vec<std::path::Component<'a>>> = None;
+    for path in paths {
+        let parent: Vec<_> = path.parent().map(|p| p.components().collect()).unwrap_or_default();
+        common = Some(match common {
+            None => parent,
+            Some(prefix) => prefix
+                .into_iter()
+                .zip(parent)
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+    common.map(|c| c.len()).unwrap_or(0)
+}
+
+/// Claude Code data directory, relative to home or a project
+const CLAUDE_DIR: &str = ".claude";
+/// Codex data directory, relative to home or a project
+const CODEX_DIR: &str = ".codex";
+/// Crush data directory, relative to home or a project
+const CRUSH_DIR: &str = ".crush";
+/// Crush database file inside `CRUSH_DIR`
+const CRUSH_DB: &str = "crush.db";
+/// Gemini CLI data directory, relative to home or a project
+const GEMINI_DIR: &str = ".gemini";
+
+/// Detect which agent has been used in a directory from its on-disk markers.
+/// Crush is checked first since its marker is the most specific.
+pub fn detect_source_in_dir(dir: &std::path::Path) -> Option<Source> {
+    if dir.join(CRUSH_DIR).join(CRUSH_DB).is_file() {
+        Some(Source::Crush)
+    } else if dir.join(CLAUDE_DIR).is_dir() {
+        Some(Source::Claude)
+    } else if dir.join(CODEX_DIR).is_dir() {
+        Some(Source::Codex)
+    } else if dir.join(GEMINI_DIR).is_dir() {
+        Some(Source::Gemini)
+    } else {
+        None
+    }
 }
 
 /// Check health of all configured data sources
@@ -1612,11 +3518,12 @@ pub async fn check_sources_health() -> Vec<SourceHealth> {
     results.push(check_codex_health().await);
     results.push(check_opencode_health().await);
     results.push(check_crush_health().await);
+    results.push(check_gemini_health().await);
     results
 }
 
 async fn check_claude_health() -> SourceHealth {
-    let claude_dir = dirs::home_dir().map(|h| h.join(".claude").join("projects"));
+    let claude_dir = dirs::home_dir().map(|h| h.join(CLAUDE_DIR).join("projects"));
 
     match claude_dir {
         Some(path) if path.exists() => SourceHealth {
@@ -1644,7 +3551,7 @@ async fn check_codex_health() -> SourceHealth {
     let codex_home: Option<PathBuf> = std::env::var("CODEX_HOME")
         .ok()
         .map(PathBuf::from)
-        .or_else(|| dirs::home_dir().map(|h| h.join(".codex")));
+        .or_else(|| dirs::home_dir().map(|h| h.join(CODEX_DIR)));
 
     match codex_home {
         Some(path) if path.exists() => SourceHealth {
@@ -1726,7 +3633,7 @@ fn opencode_candidate_paths() -> Vec<PathBuf> {
 }
 
 async fn check_crush_health() -> SourceHealth {
-    let crush_global = dirs::home_dir().map(|h| h.join(".crush"));
+    let crush_global = dirs::home_dir().map(|h| h.join(CRUSH_DIR));
 
     match crush_global {
         Some(path) if path.exists() => SourceHealth {
@@ -1750,6 +3657,31 @@ async fn check_crush_health() -> SourceHealth {
     }
 }
 
+async fn check_gemini_health() -> SourceHealth {
+    let gemini_tmp = dirs::home_dir().map(|h| h.join(GEMINI_DIR).join("tmp"));
+
+    match gemini_tmp {
+        Some(path) if path.exists() => SourceHealth {
+            source: Source::Gemini,
+            status: HealthStatus::Healthy,
+            path: Some(path.to_string_lossy().to_string()),
+            message: Some(format!("Found {} projects", count_projects(&path).await)),
+        },
+        Some(path) => SourceHealth {
+            source: Source::Gemini,
+            status: HealthStatus::Unknown,
+            path: Some(path.to_string_lossy().to_string()),
+            message: Some("Gemini CLI chat directory not found".to_string()),
+        },
+        None => SourceHealth {
+            source: Source::Gemini,
+            status: HealthStatus::Unknown,
+            path: None,
+            message: Some("Could not determine home directory".to_string()),
+        },
+    }
+}
+
 async fn count_projects(path: &std::path::Path) -> usize {
     match tokio::fs::read_dir(path).await {
         Ok(mut entries) => {
@@ -1766,131 +3698,1205 @@ async fn count_projects(path: &std::path::Path) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use agent_v_core::{Event, EventKind, Role, Session, Source};
+    use crate::conversation_tree::ConversationNode;
+    use agent_v_core::{Event, EventKind, FileEdit, Role, Session, Source};
     use uuid::Uuid;
 
     async fn setup_test_db() -> Database {
-        let db = Database::open(":memory:").await.unwrap();
-        db.migrate().await.unwrap();
-        db
+        Database::open_in_memory().await.unwrap()
+    }
+
+    fn session(source: Source, external_id: &str) -> Session {
+        Session {
+            id: Uuid::new_v4(),
+            source,
+            external_id: external_id.to_string(),
+            project: None,
+            title: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            raw_payload: serde_json::json!({}),
+        }
     }
 
     #[tokio::test]
-    async fn test_compute_session_metrics() {
+    async fn test_search_total_ignores_paging() {
         let db = setup_test_db().await;
-        let session_id = Uuid::new_v4();
+        let session = session(Source::Claude, "paged-search-1");
+        let events: Vec<Event> = (0..7)
+            .map(|i| Event {
+                id: Uuid::new_v4(),
+                session_id: session.id,
+                kind: if i < 5 { EventKind::Message } else { EventKind::ToolCall },
+                role: None,
+                content: Some(format!("rerun the flaky migration test, attempt {}", i)),
+                timestamp: Utc::now(),
+                raw_payload: serde_json::json!({}),
+                parent_id: None,
+            })
+            .collect();
+        db.insert_session_with_events(&session, &events).await.unwrap();
 
-        let session = Session {
-            id: session_id,
-            source: Source::Claude,
-            external_id: "ext-1".to_string(),
-            project: Some("test-project".to_string()),
-            title: Some("Test Session".to_string()),
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-            raw_payload: serde_json::json!({"model": "claude-4.5-sonnet"}),
+        let facets = SearchFacets::default();
+        let (page, total) = db.search_events_paged("flaky", &facets, 3, 0).await.unwrap();
+        assert_eq!((page.len(), total), (3, 7));
+        let (page, total) = db.search_events_paged("flaky", &facets, 3, 6).await.unwrap();
+        assert_eq!((page.len(), total), (1, 7));
+
+        let messages = SearchFacets { kind: Some("message".to_string()), ..Default::default() };
+        assert_eq!(db.count_search_events("flaky", &messages).await.unwrap(), 5);
+        assert_eq!(db.count_search_events("zebra", &facets).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_database_is_migrated_and_searchable() {
+        let db = Database::open_in_memory().await.unwrap();
+        assert_eq!(db.path(), Path::new(":memory:"));
+
+        let session = session(Source::Codex, "in-memory-1");
+        let event = Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some("profile the tokenizer".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
         };
+        db.insert_session_with_events(&session, &[event]).await.unwrap();
+
+        let results = db
+            .search_events("tokenizer", &SearchFacets::default(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].event.session_id, session.id.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_leaves_no_pending_migrations() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open(dir.path().join("agent-viz.db")).await.unwrap();
+
+        assert!(db.applied_migrations().await.unwrap().is_empty());
+        assert_eq!(db.pending_migrations().await.unwrap().len(), MIGRATIONS.len());
+
+        db.migrate().await.unwrap();
+        let applied: Vec<String> = db
+            .applied_migrations()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        let bundled: Vec<&str> = MIGRATIONS.iter().map(|m| m.name).collect();
+        assert_eq!(applied, bundled);
+        assert!(db.pending_migrations().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backup_before_destructive_keeps_pre_delete_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("agent-viz.db");
+        let db = Database::open(&db_path).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let session = session(Source::Claude, "backup-1");
+        db.insert_session(&session).await.unwrap();
+
+        let backup = db.backup_before_destructive(BACKUP_KEEP).await.unwrap();
+        assert!(
+            backup
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("agent-viz.db.bak-")
+        );
+
+        let sid = session.id.to_string();
+        db.conn
+            .call(move |conn| {
+                conn.execute("DELETE FROM sessions WHERE id = ?1", [sid])?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+        assert!(db.get_session(session.id.into()).await.unwrap().is_none());
+
+        let restored = Database::open(&backup).await.unwrap();
+        assert!(restored.get_session(session.id.into()).await.unwrap().is_some());
+
+        for _ in 0..BACKUP_KEEP + 2 {
+            db.backup_before_destructive(BACKUP_KEEP).await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        }
+        let backups = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|e| e.as_ref().unwrap().file_name().to_string_lossy().contains(".bak-"))
+            .count();
+        assert_eq!(backups, BACKUP_KEEP);
+    }
 
+    #[tokio::test]
+    async fn test_event_category_is_stored_and_filterable() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "category-1");
+        let mut progress_payload = serde_json::json!({"type": "progress"});
+        Event::set_category(&mut progress_payload, "progress");
         let events = vec![
             Event {
                 id: Uuid::new_v4(),
-                session_id,
-                kind: EventKind::Message,
-                role: Some(Role::User),
-                content: Some("Hello".to_string()),
+                session_id: session.id,
+                kind: EventKind::System,
+                role: None,
+                content: Some("build step running".to_string()),
                 timestamp: Utc::now(),
-                raw_payload: serde_json::json!({}),
+                raw_payload: progress_payload,
+                parent_id: None,
             },
             Event {
                 id: Uuid::new_v4(),
-                session_id,
+                session_id: session.id,
                 kind: EventKind::Message,
-                role: Some(Role::Assistant),
-                content: Some("Hi there! I am a 2026 model.".to_string()),
+                role: Some(Role::User),
+                content: Some("start the build step".to_string()),
                 timestamp: Utc::now(),
-                raw_payload: serde_json::json!({"usage": {"prompt_tokens": 10, "completion_tokens": 20}}),
+                raw_payload: serde_json::json!({}),
+                parent_id: None,
             },
         ];
-
         db.insert_session_with_events(&session, &events).await.unwrap();
 
-        let s = db.get_session(session_id.to_string()).await.unwrap();
-        assert!(s.is_some(), "Session should be in DB");
+        let stored = db.get_session_events(session.id.into()).await.unwrap();
+        let progress = stored.iter().find(|e| e.kind == "system").unwrap();
+        assert_eq!(progress.category.as_deref(), Some("progress"));
+        assert!(stored.iter().any(|e| e.kind == "message" && e.category.is_none()));
 
-        let evs = db.get_session_events(session_id.to_string()).await.unwrap();
-        assert_eq!(evs.len(), 2, "Should have 2 events in DB");
+        let facets = SearchFacets { category: Some("progress".to_string()), ..Default::default() };
+        let results = db.search_events("build", &facets, 10, 0).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].event.category.as_deref(), Some("progress"));
 
-        db.compute_session_metrics(&session_id.to_string()).await.unwrap();
+        let facets = SearchFacets { category: Some("command".to_string()), ..Default::default() };
+        assert!(db.search_events("build", &facets, 10, 0).await.unwrap().is_empty());
+    }
 
-        let metrics_opt = db.get_session_metrics(&session_id.to_string()).await.unwrap();
-        if metrics_opt.is_none() {
-            let exists: bool = db
-                .conn
-                .call({
-                    let sid = session_id.to_string();
-                    move |conn| {
-                        let mut stmt = conn.prepare("SELECT COUNT(*) FROM session_metrics WHERE session_id = ?1")?;
-                        let count: i64 = stmt.query_row([sid], |row| row.get(0))?;
-                        Ok(count > 0)
-                    }
-                })
-                .await
-                .unwrap();
-            panic!("Metrics not found for {}. Row exists: {}", session_id, exists);
-        }
-        let metrics = metrics_opt.unwrap();
+    #[tokio::test]
+    async fn test_conversation_tree_follows_parent_links() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "branched-1");
+        let base = Utc::now();
+        let event = |seconds: i64, content: &str, parent: Option<&Event>| Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some(content.to_string()),
+            timestamp: base + chrono::Duration::seconds(seconds),
+            raw_payload: serde_json::json!({}),
+            parent_id: parent.map(|p| p.id),
+        };
+        let question = event(0, "question", None);
+        let answer = event(1, "answer", Some(&question));
+        let first_try = event(2, "first try", Some(&answer));
+        let first_reply = event(3, "first reply", Some(&first_try));
+        let edited = event(4, "edited", Some(&answer));
+        let edited_reply = event(5, "edited reply", Some(&edited));
+        let events = vec![question, answer, first_try, first_reply, edited, edited_reply];
+        db.insert_session_with_events(&session, &events).await.unwrap();
 
-        assert_eq!(metrics.total_events, 2);
-        assert_eq!(metrics.message_count, 2);
-        assert_eq!(metrics.user_messages, 1);
-        assert_eq!(metrics.assistant_messages, 1);
-        assert_eq!(metrics.model, Some("claude-4.5-sonnet".to_string()));
-        assert_eq!(metrics.provider, Some("anthropic".to_string()));
+        let stored = db.get_session_events(session.id.into()).await.unwrap();
+        assert_eq!(stored[1].parent_id, Some(events[0].id.to_string()));
+
+        let tree = db.get_conversation_tree(session.id.into()).await.unwrap();
+        let roots: Vec<&ConversationNode> = tree.root_nodes().collect();
+        assert_eq!(roots.len(), 1);
+        let content = |node: &ConversationNode| node.event.content.clone().unwrap();
+        assert_eq!(content(roots[0]), "question");
+        let answer = tree.children(roots[0]).next().unwrap();
+        assert_eq!(content(answer), "answer");
+        let branches: Vec<&ConversationNode> = tree.children(answer).collect();
+        assert_eq!(
+            branches.iter().map(|n| content(n)).collect::<Vec<_>>(),
+            ["first try", "edited"]
+        );
+        let replies: Vec<String> = branches
+            .iter()
+            .map(|branch| content(tree.children(branch).next().unwrap()))
+            .collect();
+        assert_eq!(replies, ["first reply", "edited reply"]);
+        assert!(tree.children(branches[1]).next().unwrap().children.is_empty());
+    }
 
-        assert_eq!(metrics.input_tokens, Some(10));
-        assert_eq!(metrics.output_tokens, Some(20));
+    #[tokio::test]
+    async fn test_merge_sessions_moves_events_and_drops_the_secondary() {
+        let db = setup_test_db().await;
+        let base = Utc::now() - chrono::Duration::hours(1);
+        let session = |source: Source, external_id: &str, start: i64| Session {
+            created_at: base + chrono::Duration::minutes(start),
+            updated_at: base + chrono::Duration::minutes(start + 10),
+            ..session(source, external_id)
+        };
+        let event = |session: &Session, minute: i64, content: &str| Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some(content.to_string()),
+            timestamp: base + chrono::Duration::minutes(minute),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        };
+        let claude = session(Source::Claude, "merge-claude", 0);
+        let crush = session(Source::Crush, "merge-crush", 5);
+        let claude_events = [event(&claude, 0, "one"), event(&claude, 10, "four")];
+        let crush_events = [event(&crush, 5, "two"), event(&crush, 7, "three")];
+        db.insert_session_with_events(&claude, &claude_events).await.unwrap();
+        db.insert_session_with_events(&crush, &crush_events).await.unwrap();
+        db.add_tag(crush.id.into(), "spike").await.unwrap();
 
-        assert!(metrics.estimated_cost.unwrap() > 0.0);
+        let moved = db.merge_sessions(claude.id.into(), crush.id.into()).await.unwrap();
+        assert_eq!(moved, Some(2));
+
+        let sessions = db.list_sessions(10, 0).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, claude.id.to_string());
+        assert_eq!(sessions[0].updated_at, crush.updated_at.to_rfc3339());
+
+        let events = db.get_session_events(claude.id.into()).await.unwrap();
+        let contents: Vec<&str> = events.iter().filter_map(|e| e.content.as_deref()).collect();
+        assert_eq!(contents, ["one", "two", "three", "four"]);
+        assert_eq!(db.get_tags(claude.id.into()).await.unwrap(), ["spike"]);
+        let metrics = db.get_session_metrics(claude.id.into()).await.unwrap().unwrap();
+        assert_eq!(metrics.total_events, 4);
+
+        assert_eq!(
+            db.merge_sessions(claude.id.into(), crush.id.into()).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            db.merge_sessions(claude.id.into(), claude.id.into()).await.unwrap(),
+            None
+        );
     }
 
     #[tokio::test]
-    async fn test_prune_duplicate_sessions_merges_unique_events_and_dedupes_overlaps() {
-        let db = Database::open(":memory:").await.unwrap();
+    async fn test_reingesting_a_merged_session_updates_the_session_it_joined() {
+        let db = setup_test_db().await;
+        let base = Utc::now() - chrono::Duration::hours(1);
+        let session = |external_id: &str, start: i64| Session {
+            created_at: base + chrono::Duration::minutes(start),
+            updated_at: base + chrono::Duration::minutes(start + 10),
+            raw_payload: serde_json::json!({"file_path": format!("/sessions/{external_id}.jsonl")}),
+            ..session(Source::Claude, external_id)
+        };
+        let event = |session: &Session, minute: i64, content: &str| Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some(content.to_string()),
+            timestamp: base + chrono::Duration::minutes(minute),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        };
+        let primary = session("reingest-primary", 0);
+        let secondary = session("reingest-secondary", 5);
+        db.insert_session_with_events(&primary, &[event(&primary, 0, "one"), event(&primary, 10, "four")])
+            .await
+            .unwrap();
+        db.insert_session_with_events(&secondary, &[event(&secondary, 5, "two")])
+            .await
+            .unwrap();
+        for external_id in ["reingest-primary", "reingest-secondary"] {
+            db.upsert_ingested_file("claude", &format!("/sessions/{external_id}.jsonl"), 1, 1, "")
+                .await
+                .unwrap();
+        }
 
-        db.conn
-            .call(|conn| {
-                conn.execute_batch(
-                    r#"
-                    CREATE TABLE sessions (
-                        id TEXT PRIMARY KEY,
-                        source TEXT NOT NULL,
-                        external_id TEXT NOT NULL,
-                        project TEXT,
-                        title TEXT,
-                        created_at TIMESTAMP NOT NULL,
-                        updated_at TIMESTAMP NOT NULL,
-                        raw_payload TEXT NOT NULL
-                    );
+        db.merge_sessions(primary.id.into(), secondary.id.into()).await.unwrap();
+        for external_id in ["reingest-primary", "reingest-secondary"] {
+            let path = format!("/sessions/{external_id}.jsonl");
+            assert!(db.should_reingest("claude", &path, 1, 1, "").await.unwrap());
+        }
 
-                    CREATE TABLE events (
-                        id TEXT PRIMARY KEY,
-                        session_id TEXT NOT NULL,
-                        kind TEXT NOT NULL,
-                        role TEXT,
-                        content TEXT,
-                        timestamp TIMESTAMP NOT NULL,
-                        raw_payload TEXT NOT NULL
-                    );
-                    "#,
-                )?;
-                Ok(())
-            })
+        // Both files are parsed again with fresh session and event IDs; the secondary has grown.
+        let secondary = Session { id: Uuid::new_v4(), ..secondary };
+        db.insert_session_with_events(
+            &secondary,
+            &[event(&secondary, 5, "two"), event(&secondary, 7, "three")],
+        )
+        .await
+        .unwrap();
+        let reparsed = Session { id: Uuid::new_v4(), ..primary.clone() };
+        db.insert_session_with_events(&reparsed, &[event(&reparsed, 0, "one"), event(&reparsed, 10, "four")])
             .await
             .unwrap();
 
-        db.migrate().await.unwrap();
+        let sessions = db.list_sessions(10, 0).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, primary.id.to_string());
 
-        let keep_id = Uuid::new_v4().to_string();
+        let events = db.get_session_events(primary.id.into()).await.unwrap();
+        let contents: Vec<&str> = events.iter().filter_map(|e| e.content.as_deref()).collect();
+        assert_eq!(contents, ["one", "two", "three", "four"]);
+        let metrics = db.get_session_metrics(primary.id.into()).await.unwrap().unwrap();
+        assert_eq!(metrics.total_events, 4);
+    }
+
+    #[tokio::test]
+    async fn test_errors_group_by_signature() {
+        let db = setup_test_db().await;
+        let session = session(Source::Codex, "errors-1");
+        let error = |content: &str| Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Error,
+            role: None,
+            content: Some(content.to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        };
+        let events = [
+            error("No such file or directory: /home/alice/app/config.toml"),
+            error("No such file or directory: /tmp/build-7/config.toml"),
+            error("permission denied"),
+        ];
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let top = db.get_top_errors(None, None, 10).await.unwrap();
+        assert_eq!(
+            top,
+            [
+                ("No such file or directory: <path>".to_string(), 2),
+                ("permission denied".to_string(), 1)
+            ]
+        );
+        let by_day = db.get_errors_by_day(None, None).await.unwrap();
+        assert_eq!(by_day.len(), 2);
+        assert_eq!(by_day[0].error_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_error_signatures_are_null_without_content_and_backfilled_with_it() {
+        let db = setup_test_db().await;
+        let session = session(Source::Codex, "errors-2");
+        let error = |content: Option<&str>| Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Error,
+            role: None,
+            content: content.map(str::to_string),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        };
+        let events = [error(None), error(Some("timed out after 30s in /tmp/run-4"))];
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let signatures = || {
+            db.conn.call(|conn| {
+                let mut stmt = conn.prepare("SELECT content, signature FROM events ORDER BY content")?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+        };
+        let stored = signatures().await.unwrap();
+        assert_eq!(stored[0], (String::new(), None));
+        let signature = stored[1].1.clone().unwrap();
+
+        // As stored before signatures were recorded
+        db.conn
+            .call(|conn| Ok(conn.execute("UPDATE events SET signature = NULL", [])?))
+            .await
+            .unwrap();
+        assert_eq!(db.backfill_error_signatures().await.unwrap(), 1);
+        let stored = signatures().await.unwrap();
+        assert_eq!(stored[0].1, None);
+        assert_eq!(stored[1].1.as_deref(), Some(signature.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_thinking_terms_are_only_found_in_thinking_search() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "thinking-1");
+        let events = vec![Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::Assistant),
+            content: Some("Renamed the config loader.".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({
+                "type": "assistant",
+                "message": {"content": [
+                    {"type": "thinking", "thinking": "The quokka module shadows the loader name."},
+                    {"type": "text", "text": "Renamed the config loader."}
+                ]}
+            }),
+            parent_id: None,
+        }];
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let content = SearchFacets::default();
+        assert!(db.search_events("quokka", &content, 10, 0).await.unwrap().is_empty());
+        assert_eq!(db.search_events("loader", &content, 10, 0).await.unwrap().len(), 1);
+
+        let thinking = SearchFacets { thinking: true, ..Default::default() };
+        let results = db.search_events("quokka", &thinking, 10, 0).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].snippet.as_deref().unwrap().contains("[quokka]"));
+        assert!(db.search_events("renamed", &thinking, 10, 0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_compressed_payload_round_trips_to_original_json() {
+        let db = setup_test_db().await.with_compressed_payloads(true);
+        let session = session(Source::Claude, "compressed-1");
+        let payload = serde_json::json!({
+            "type": "assistant",
+            "message": {"model": "claude-sonnet-4", "content": [{"type": "text", "text": "needle ".repeat(200)}]}
+        });
+        let event = Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::Assistant),
+            content: Some("needle in the haystack".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: payload.clone(),
+            parent_id: None,
+        };
+        db.insert_session_with_events(&session, std::slice::from_ref(&event))
+            .await
+            .unwrap();
+
+        let (text, compressed): (String, Vec<u8>) = db
+            .conn
+            .call(|conn| {
+                Ok(
+                    conn.query_row("SELECT raw_payload, raw_payload_z FROM events", [], |row| {
+                        Ok((row.get(0)?, row.get(1)?))
+                    })?,
+                )
+            })
+            .await
+            .unwrap();
+        assert!(text.is_empty());
+        assert!(compressed.len() < serde_json::to_string(&payload).unwrap().len());
+
+        let events = db.get_session_events(SessionId(session.id)).await.unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&events[0].raw_payload).unwrap(),
+            payload
+        );
+
+        let results = db
+            .search_events("needle", &SearchFacets::default(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&results[0].event.raw_payload).unwrap(),
+            payload
+        );
+    }
+
+    #[tokio::test]
+    async fn test_external_id_collisions_across_sources() {
+        let db = setup_test_db().await;
+        for source in [Source::OpenCode, Source::Claude] {
+            let session = session(source, "shared-1");
+            db.insert_session(&session).await.unwrap();
+        }
+        let unique = session(Source::Claude, "unique-1");
+        db.insert_session(&unique).await.unwrap();
+
+        let collisions = db.get_external_id_collisions().await.unwrap();
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].external_id, "shared-1");
+        assert_eq!(
+            collisions[0].sources,
+            vec!["claude".to_string(), "opencode".to_string()]
+        );
+
+        let shared = ExternalId::from("shared-1");
+        assert_eq!(db.find_sessions_by_external_id(&shared, None).await.unwrap().len(), 2);
+        let scoped = db
+            .find_sessions_by_external_id(&shared, Some("opencode"))
+            .await
+            .unwrap();
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(scoped[0].source, "opencode");
+        let by_internal = ExternalId::from(unique.id.to_string());
+        assert!(
+            db.find_sessions_by_external_id(&by_internal, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let by_id = db.get_session(unique.id.into()).await.unwrap().unwrap();
+        assert_eq!(by_id.external_id, "unique-1");
+
+        let ambiguous = db.find_sessions("shared-1", None).await.unwrap();
+        let sources: Vec<&str> = ambiguous.iter().map(|s| s.source.as_str()).collect();
+        assert_eq!(sources, vec!["claude", "opencode"]);
+        let chosen = db.find_sessions("shared-1", Some("claude")).await.unwrap();
+        assert_eq!(chosen.len(), 1);
+        assert_eq!(chosen[0].source, "claude");
+    }
+
+    #[tokio::test]
+    async fn test_find_session_uses_an_index_instead_of_scanning() {
+        let db = setup_test_db().await;
+        let mut last = None;
+        for i in 0..300 {
+            let session = session(Source::Claude, &format!("ext-{}", i));
+            db.insert_session(&session).await.unwrap();
+            last = Some(session);
+        }
+        let last = last.unwrap();
+
+        let by_external = db.find_sessions("ext-299", None).await.unwrap();
+        assert_eq!(by_external.len(), 1);
+        assert_eq!(by_external[0].id, last.id.to_string());
+        let by_id = db.find_sessions(&last.id.to_string(), None).await.unwrap();
+        assert_eq!(by_id.len(), 1);
+        assert_eq!(by_id[0].external_id, "ext-299");
+        assert!(db.find_sessions("ext-300", None).await.unwrap().is_empty());
+
+        let plan: Vec<String> = db
+            .conn
+            .call(|conn| {
+                let mut stmt = conn.prepare(&format!(
+                    "EXPLAIN QUERY PLAN {}",
+                    queries::FIND_SESSIONS_BY_ID_OR_EXTERNAL_ID
+                ))?;
+                let details = stmt
+                    .query_map(["ext-299", ""], |row| row.get::<_, String>(3))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(details)
+            })
+            .await
+            .unwrap();
+        assert!(plan.iter().all(|step| !step.starts_with("SCAN sessions")), "{:?}", plan);
+    }
+
+    #[tokio::test]
+    async fn test_activity_by_day_rolling_average() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "rolling-1");
+        // Day n of March 2025 has n events
+        let events: Vec<Event> = (1..=7)
+            .flat_map(|day| {
+                (0..day).map(move |i| Event {
+                    id: Uuid::new_v4(),
+                    session_id: session.id,
+                    kind: EventKind::Message,
+                    role: Some(Role::User),
+                    content: Some(format!("day {} event {}", day, i)),
+                    timestamp: chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 3, day, 12, 0, 0).unwrap(),
+                    raw_payload: serde_json::json!({}),
+                    parent_id: None,
+                })
+            })
+            .collect();
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let stats = db.get_activity_by_day(None, None, None).await.unwrap();
+        assert_eq!(stats.len(), 7);
+        let avg_on = |day: u32| {
+            stats
+                .iter()
+                .find(|s| s.day == NaiveDate::from_ymd_opt(2025, 3, day).unwrap())
+                .unwrap()
+                .rolling_avg_7d
+        };
+        assert_eq!(avg_on(1), 1.0);
+        assert_eq!(avg_on(2), 1.5);
+        assert_eq!(avg_on(4), 2.5);
+        assert_eq!(avg_on(7), 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_storage_stats_sum_payload_bytes() {
+        let db = setup_test_db().await;
+        let session = session(Source::Codex, "storage-1");
+        let events: Vec<Event> = ["short", "naïve ünïcode", "a much longer piece of content"]
+            .iter()
+            .map(|content| Event {
+                id: Uuid::new_v4(),
+                session_id: session.id,
+                kind: EventKind::Message,
+                role: Some(Role::Assistant),
+                content: Some(content.to_string()),
+                timestamp: Utc::now(),
+                raw_payload: serde_json::json!({"text": content, "padding": "x".repeat(content.len())}),
+                parent_id: None,
+            })
+            .collect();
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let expected_raw: usize = events
+            .iter()
+            .map(|e| serde_json::to_string(&e.raw_payload).unwrap().len())
+            .sum();
+        let expected_content: usize = events.iter().map(|e| e.content.as_ref().unwrap().len()).sum();
+
+        let by_source = db.get_storage_by_source().await.unwrap();
+        assert_eq!(by_source.len(), 1);
+        assert_eq!(by_source[0].source, "codex");
+        assert_eq!(by_source[0].event_count, 3);
+        assert_eq!(by_source[0].raw_bytes, expected_raw as i64);
+        assert_eq!(by_source[0].content_bytes, expected_content as i64);
+
+        let by_session = db.get_storage_by_session(10).await.unwrap();
+        assert_eq!(by_session[0].dimension, session.id.to_string());
+        assert_eq!(by_session[0].raw_bytes, expected_raw as i64);
+    }
+
+    #[tokio::test]
+    async fn test_get_sessions_by_file_matches_substring_and_glob() {
+        let db = setup_test_db().await;
+        let edit = |path: &str, added: i64| FileEdit {
+            path: path.to_string(),
+            operation: "edit".to_string(),
+            lines_added: added,
+            lines_removed: 1,
+        };
+        let session_with = |external_id: &str, edits: Vec<FileEdit>| {
+            let session = session(Source::Claude, external_id);
+            let event = Event {
+                id: Uuid::new_v4(),
+                session_id: session.id,
+                kind: EventKind::ToolResult,
+                role: None,
+                content: None,
+                timestamp: Utc::now(),
+                raw_payload: serde_json::json!({ FileEdit::PAYLOAD_KEY: edits }),
+                parent_id: None,
+            };
+            (session, vec![event])
+        };
+
+        let (main_session, main_events) = session_with(
+            "files-1",
+            vec![edit("/repo/src/main.rs", 5), edit("/repo/src/main.rs", 2)],
+        );
+        let (other_session, other_events) = session_with("files-2", vec![edit("/repo/src/lib_utils.rs", 3)]);
+        db.insert_session_with_events(&main_session, &main_events)
+            .await
+            .unwrap();
+        db.insert_session_with_events(&other_session, &other_events)
+            .await
+            .unwrap();
+
+        let matches = db.get_sessions_by_file("main.rs", 10).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].session_id, main_session.id.to_string());
+        assert_eq!(matches[0].match_count, 2);
+        assert_eq!(matches[0].file_count, 1);
+        assert_eq!(matches[0].lines_added, 7);
+
+        assert_eq!(db.get_sessions_by_file("src/*.rs", 10).await.unwrap().len(), 2);
+        assert!(db.get_sessions_by_file("lib%", 10).await.unwrap().is_empty());
+        assert_eq!(db.get_sessions_by_file("lib_utils", 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_tool_outcome_records_unsuccessful_tool_call() {
+        let db = setup_test_db().await;
+        let session = session(Source::Codex, "tool-outcome-1");
+        let mut outcome = ToolOutcome::from_exit_code(2, Some(1200));
+        outcome.tool_name = Some("shell".to_string());
+        let event = Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::ToolResult,
+            role: None,
+            content: Some("No such file or directory".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({ ToolOutcome::PAYLOAD_KEY: outcome }),
+            parent_id: None,
+        };
+        db.insert_session_with_events(&session, &[event]).await.unwrap();
+
+        let slow = db.get_long_running_tool_calls(None, None, 1000, 10).await.unwrap();
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow[0].tool_name, "shell");
+        assert_eq!(slow[0].error_message.as_deref(), Some("exit code 2"));
+
+        db.compute_session_metrics(session.id.into()).await.unwrap();
+        let metrics = db.get_session_metrics(session.id.into()).await.unwrap().unwrap();
+        assert_eq!(metrics.error_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_insert_pairs_tool_calls_with_results() {
+        let db = setup_test_db().await;
+        let session_id = Uuid::new_v4();
+        let started = Utc::now() - chrono::Duration::minutes(5);
+        let session = Session {
+            id: session_id,
+            created_at: started,
+            updated_at: started,
+            ..session(Source::Claude, "tool-pairing-1")
+        };
+        let event = |offset_ms: i64, role: Role, content: serde_json::Value| Event {
+            id: Uuid::new_v4(),
+            session_id,
+            kind: EventKind::Message,
+            role: Some(role),
+            content: None,
+            timestamp: started + chrono::Duration::milliseconds(offset_ms),
+            raw_payload: serde_json::json!({"message": {"content": content}}),
+            parent_id: None,
+        };
+        let events = vec![
+            event(
+                0,
+                Role::Assistant,
+                serde_json::json!([{"type": "tool_use", "id": "toolu_1", "name": "Bash", "input": {}}]),
+            ),
+            event(
+                1500,
+                Role::User,
+                serde_json::json!([{"type": "tool_result", "tool_use_id": "toolu_1", "content": "ok"}]),
+            ),
+        ];
+
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let tools = db.get_tool_call_frequency(None, None).await.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].tool_name, "Bash");
+        assert_eq!(tools[0].max_duration_ms, Some(1500));
+
+        let calls = db.get_session_tool_calls(session_id.into()).await.unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!((calls[0].duration_ms, calls[0].success), (Some(1500), Some(true)));
+
+        assert_eq!(db.insert_tool_calls_for_session(session_id.into()).await.unwrap(), 1);
+        let tools = db.get_tool_call_frequency(None, None).await.unwrap();
+        assert_eq!(tools[0].call_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_insert_records_churn_of_edit_tool_calls() {
+        let db = setup_test_db().await;
+        let session_id = Uuid::new_v4();
+        let started = Utc::now() - chrono::Duration::minutes(5);
+        let session = Session {
+            id: session_id,
+            created_at: started,
+            updated_at: started,
+            ..session(Source::Claude, "edit-churn-1")
+        };
+        let events = vec![Event {
+            id: Uuid::new_v4(),
+            session_id,
+            kind: EventKind::Message,
+            role: Some(Role::Assistant),
+            content: None,
+            timestamp: started,
+            raw_payload: serde_json::json!({"message": {"content": [
+                {"type": "tool_use", "id": "toolu_1", "name": "Edit", "input": {
+                    "file_path": "/repo/src/lib.rs", "old_string": "a\nb", "new_string": "a\nb\nc\nd"
+                }}
+            ]}}),
+            parent_id: None,
+        }];
+
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let files = db.get_session_files_touched(&session_id.to_string()).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].file_path, "/repo/src/lib.rs");
+        assert_eq!((files[0].lines_added, files[0].lines_removed), (4, 2));
+
+        let metrics = db.get_session_metrics(session_id.into()).await.unwrap().unwrap();
+        assert_eq!(metrics.files_touched, 1);
+        assert_eq!((metrics.lines_added, metrics.lines_removed), (4, 2));
+
+        db.insert_session_with_events(&session, &events).await.unwrap();
+        assert_eq!(
+            db.get_session_files_touched(&session_id.to_string())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interrupt_stops_an_abandoned_query_so_the_next_one_runs() {
+        let db = setup_test_db().await;
+        let endless = db.conn.call(|conn| {
+            let count = conn.query_row(
+                "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n) SELECT COUNT(*) FROM n",
+                [],
+                |row| row.get::<_, i64>(0),
+            )?;
+            Ok(count)
+        });
+
+        let finished = tokio::select! {
+            _ = endless => true,
+            _ = tokio::time::sleep(std::time::Duration::from_millis(50)) => false,
+        };
+        assert!(!finished);
+        db.interrupt();
+
+        let sessions = tokio::time::timeout(std::time::Duration::from_secs(5), db.list_sessions(10, 0))
+            .await
+            .expect("the abandoned query should have been interrupted")
+            .unwrap();
+        assert!(sessions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_should_reingest_only_changed_files() {
+        let db = setup_test_db().await;
+        let path = "/home/me/.codex/sessions/2025/01/01/rollout-a.jsonl";
+
+        assert!(db.should_reingest("codex", path, 1_000, 512, "").await.unwrap());
+        db.upsert_ingested_file("codex", path, 1_000, 512, "").await.unwrap();
+        assert!(!db.should_reingest("codex", path, 1_000, 512, "").await.unwrap());
+        assert!(db.should_reingest("codex", path, 2_000, 512, "").await.unwrap());
+        assert!(db.should_reingest("codex", path, 1_000, 640, "").await.unwrap());
+        assert!(db.should_reingest("claude", path, 1_000, 512, "").await.unwrap());
+        assert!(
+            db.should_reingest("codex", path, 1_000, 512, "compress-raw")
+                .await
+                .unwrap()
+        );
+
+        db.upsert_ingested_file("codex", path, 2_000, 640, "compress-raw")
+            .await
+            .unwrap();
+        assert!(
+            !db.should_reingest("codex", path, 2_000, 640, "compress-raw")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deleting_a_session_forgets_its_ingested_file() {
+        let db = setup_test_db().await;
+        let path = "/home/me/.codex/sessions/2025/01/01/rollout-gone.jsonl";
+        let session =
+            Session { raw_payload: serde_json::json!({ "file_path": path }), ..session(Source::Codex, "rollout-gone") };
+        db.insert_session_with_events(&session, &[]).await.unwrap();
+        db.upsert_ingested_file("codex", path, 1_000, 512, "").await.unwrap();
+
+        assert!(db.delete_session(session.id.into()).await.unwrap());
+        assert!(db.should_reingest("codex", path, 1_000, 512, "").await.unwrap());
+
+        let crush = Session {
+            id: Uuid::new_v4(),
+            source: Source::Crush,
+            external_id: "crush-gone".to_string(),
+            raw_payload: serde_json::json!({ "db_path": "/repo/.crush/crush.db", "session_id": "abc" }),
+            ..session
+        };
+        db.insert_session_with_events(&crush, &[]).await.unwrap();
+        db.upsert_ingested_file("crush", "/repo/.crush/crush.db#abc", 1_000, 0, "")
+            .await
+            .unwrap();
+
+        assert!(db.delete_session(crush.id.into()).await.unwrap());
+        assert!(
+            db.should_reingest("crush", "/repo/.crush/crush.db#abc", 1_000, 0, "")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert_stores_session_metrics() {
+        let db = setup_test_db().await;
+        let session_id = Uuid::new_v4();
+        let started = Utc::now() - chrono::Duration::minutes(10);
+
+        let session = Session {
+            id: session_id,
+            created_at: started,
+            updated_at: started,
+            ..session(Source::Claude, "metrics-on-insert")
+        };
+        let event = |offset_secs: i64, kind: EventKind, role: Option<Role>, raw_payload: serde_json::Value| Event {
+            id: Uuid::new_v4(),
+            session_id,
+            kind,
+            role,
+            content: Some("step".to_string()),
+            timestamp: started + chrono::Duration::seconds(offset_secs),
+            raw_payload,
+            parent_id: None,
+        };
+        let events = vec![
+            event(0, EventKind::Message, Some(Role::User), serde_json::json!({})),
+            event(
+                5,
+                EventKind::Message,
+                Some(Role::Assistant),
+                serde_json::json!({"message": {"usage": {"input_tokens": 1200, "output_tokens": 340}}}),
+            ),
+            event(10, EventKind::ToolCall, None, serde_json::json!({})),
+            event(20, EventKind::ToolResult, None, serde_json::json!({})),
+            event(90, EventKind::Error, None, serde_json::json!({})),
+        ];
+
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let metrics = db.get_session_metrics(session_id.into()).await.unwrap().unwrap();
+        assert_eq!(metrics.total_events, 5);
+        assert_eq!(metrics.message_count, 2);
+        assert_eq!(metrics.user_messages, 1);
+        assert_eq!(metrics.assistant_messages, 1);
+        assert_eq!(metrics.tool_call_count, 1);
+        assert_eq!(metrics.tool_result_count, 1);
+        assert_eq!(metrics.error_count, 1);
+        assert_eq!(metrics.duration_seconds, Some(90));
+        assert_eq!(metrics.input_tokens, Some(1200));
+        assert_eq!(metrics.output_tokens, Some(340));
+
+        db.insert_session_with_events(&session, &events[..2]).await.unwrap();
+        let metrics = db.get_session_metrics(session_id.into()).await.unwrap().unwrap();
+        assert_eq!(metrics.total_events, 2);
+        assert_eq!(metrics.duration_seconds, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_compute_session_metrics() {
+        let db = setup_test_db().await;
+        let session_id = Uuid::new_v4();
+
+        let session = Session {
+            id: session_id,
+            project: Some("test-project".to_string()),
+            title: Some("Test Session".to_string()),
+            raw_payload: serde_json::json!({"model": "claude-4.5-sonnet"}),
+            ..session(Source::Claude, "ext-1")
+        };
+
+        let events = vec![
+            Event {
+                id: Uuid::new_v4(),
+                session_id,
+                kind: EventKind::Message,
+                role: Some(Role::User),
+                content: Some("Hello".to_string()),
+                timestamp: Utc::now(),
+                raw_payload: serde_json::json!({}),
+                parent_id: None,
+            },
+            Event {
+                id: Uuid::new_v4(),
+                session_id,
+                kind: EventKind::Message,
+                role: Some(Role::Assistant),
+                content: Some("Hi there! I am a 2026 model.".to_string()),
+                timestamp: Utc::now(),
+                raw_payload: serde_json::json!({"usage": {"prompt_tokens": 10, "completion_tokens": 20}}),
+                parent_id: None,
+            },
+        ];
+
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let s = db.get_session(session_id.into()).await.unwrap();
+        assert!(s.is_some(), "Session should be in DB");
+
+        let evs = db.get_session_events(session_id.into()).await.unwrap();
+        assert_eq!(evs.len(), 2, "Should have 2 events in DB");
+
+        db.compute_session_metrics(session_id.into()).await.unwrap();
+
+        let metrics_opt = db.get_session_metrics(session_id.into()).await.unwrap();
+        if metrics_opt.is_none() {
+            let exists: bool = db
+                .conn
+                .call({
+                    let sid = session_id.to_string();
+                    move |conn| {
+                        let mut stmt = conn.prepare("SELECT COUNT(*) FROM session_metrics WHERE session_id = ?1")?;
+                        let count: i64 = stmt.query_row([sid], |row| row.get(0))?;
+                        Ok(count > 0)
+                    }
+                })
+                .await
+                .unwrap();
+            panic!("Metrics not found for {}. Row exists: {}", session_id, exists);
+        }
+        let metrics = metrics_opt.unwrap();
+
+        assert_eq!(metrics.total_events, 2);
+        assert_eq!(metrics.message_count, 2);
+        assert_eq!(metrics.user_messages, 1);
+        assert_eq!(metrics.assistant_messages, 1);
+        assert_eq!(metrics.model, Some("claude-4.5-sonnet".to_string()));
+        assert_eq!(metrics.provider, Some("anthropic".to_string()));
+
+        assert_eq!(metrics.input_tokens, Some(10));
+        assert_eq!(metrics.output_tokens, Some(20));
+
+        assert!(metrics.estimated_cost.unwrap() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_cost_stats_can_exclude_reasoning_cost() {
+        let db = setup_test_db().await;
+        let session_id = Uuid::new_v4();
+
+        let session = Session {
+            id: session_id,
+            project: Some("test-project".to_string()),
+            raw_payload: serde_json::json!({"model": "claude-4.5-sonnet"}),
+            ..session(Source::Codex, "reasoning-1")
+        };
+        let events = vec![Event {
+            id: Uuid::new_v4(),
+            session_id,
+            kind: EventKind::Message,
+            role: Some(Role::Assistant),
+            content: Some("Done.".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({
+                "usage": {
+                    "prompt_tokens": 1000,
+                    "completion_tokens": 5000,
+                    "completion_tokens_details": {"reasoning_tokens": 4000}
+                }
+            }),
+            parent_id: None,
+        }];
+        db.insert_session_with_events(&session, &events).await.unwrap();
+        db.compute_session_metrics(session_id.into()).await.unwrap();
+
+        let metrics = db.get_session_metrics(session_id.into()).await.unwrap().unwrap();
+        assert_eq!(metrics.reasoning_tokens, Some(4000));
+        let reasoning_cost = metrics.reasoning_cost.unwrap();
+        assert!(reasoning_cost > 0.0);
+
+        let with = db
+            .get_cost_stats_by_source(None, None, None, false, None)
+            .await
+            .unwrap();
+        let without = db.get_cost_stats_by_source(None, None, None, true, None).await.unwrap();
+        let with_total = with[0].total_cost.unwrap();
+        let without_total = without[0].total_cost.unwrap();
+        assert!(without_total < with_total);
+        assert!((with_total - without_total - reasoning_cost).abs() < 1e-9);
+
+        let by_project = db
+            .get_cost_stats_by_project(None, None, None, true, None)
+            .await
+            .unwrap();
+        assert!((by_project[0].total_cost.unwrap() - without_total).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_cost_stats_model_filter_excludes_other_models() {
+        let db = setup_test_db().await;
+        for (source, external_id, model) in [
+            (Source::Claude, "model-a", "claude-4.5-sonnet"),
+            (Source::Codex, "model-b", "gpt-5.2-codex"),
+        ] {
+            let session = Session {
+                project: Some(external_id.to_string()),
+                raw_payload: serde_json::json!({"model": model}),
+                ..session(source, external_id)
+            };
+            let event = Event {
+                id: Uuid::new_v4(),
+                session_id: session.id,
+                kind: EventKind::Message,
+                role: Some(Role::Assistant),
+                content: Some("Done.".to_string()),
+                timestamp: Utc::now(),
+                raw_payload: serde_json::json!({"usage": {"prompt_tokens": 1000, "completion_tokens": 500}}),
+                parent_id: None,
+            };
+            db.insert_session_with_events(&session, std::slice::from_ref(&event))
+                .await
+                .unwrap();
+            db.compute_session_metrics(session.id.into()).await.unwrap();
+        }
+
+        let model = Some("Claude-4.5-Sonnet".to_string());
+        let by_source = db
+            .get_cost_stats_by_source(None, None, None, false, model.clone())
+            .await
+            .unwrap();
+        assert_eq!(by_source.len(), 1);
+        assert_eq!(by_source[0].dimension, "claude");
+
+        let by_project = db
+            .get_cost_stats_by_project(None, None, None, false, model.clone())
+            .await
+            .unwrap();
+        assert_eq!(by_project.len(), 1);
+        assert_eq!(by_project[0].dimension, "model-a");
+
+        let by_model = db.get_model_usage_stats(None, None, None, model.clone()).await.unwrap();
+        assert_eq!(by_model.len(), 1);
+        assert_eq!(by_model[0].model, "claude-4.5-sonnet");
+
+        let other_source = db
+            .get_cost_stats_by_source(Some("codex".to_string()), None, None, false, model)
+            .await
+            .unwrap();
+        assert!(other_source.is_empty());
+
+        let unfiltered = db.get_model_usage_stats(None, None, None, None).await.unwrap();
+        assert_eq!(unfiltered.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_prune_duplicate_sessions_merges_unique_events_and_dedupes_overlaps() {
+        let db = Database::open(":memory:").await.unwrap();
+
+        db.conn
+            .call(|conn| {
+                conn.execute_batch(
+                    r#"
+                    CREATE TABLE sessions (
+                        id TEXT PRIMARY KEY,
+                        source TEXT NOT NULL,
+                        external_id TEXT NOT NULL,
+                        project TEXT,
+                        title TEXT,
+                        created_at TIMESTAMP NOT NULL,
+                        updated_at TIMESTAMP NOT NULL,
+                        raw_payload TEXT NOT NULL
+                    );
+
+                    CREATE TABLE events (
+                        id TEXT PRIMARY KEY,
+                        session_id TEXT NOT NULL,
+                        kind TEXT NOT NULL,
+                        role TEXT,
+                        content TEXT,
+                        timestamp TIMESTAMP NOT NULL,
+                        raw_payload TEXT NOT NULL
+                    );
+                    "#,
+                )?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        db.migrate().await.unwrap();
+
+        let keep_id = Uuid::new_v4().to_string();
         let duplicate_id = Uuid::new_v4().to_string();
         let source = "claude".to_string();
         let external_id = "dup-ext-id".to_string();
@@ -1954,82 +4960,1005 @@ mod tests {
                         rusqlite::params![Uuid::new_v4().to_string(), &keep_id],
                     )?;
 
-                    Ok(())
-                }
+                    Ok(())
+                }
+            })
+            .await
+            .unwrap();
+
+        let pruned = db.prune_duplicate_sessions().await.unwrap();
+        assert_eq!(pruned, 1);
+
+        let sessions = db.list_sessions(10, 0).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, keep_id);
+        assert_eq!(sessions[0].created_at, "2026-03-01T00:00:00Z");
+        assert_eq!(sessions[0].updated_at, "2026-03-04T00:00:00Z");
+
+        let events = db.get_session_events(keep_id.parse().unwrap()).await.unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().any(|e| e.content.as_deref() == Some("donor-unique")));
+    }
+
+    #[tokio::test]
+    async fn test_prune_duplicate_sessions_does_not_merge_across_sources() {
+        let db = setup_test_db().await;
+        let shared_external_id = "shared-id";
+
+        let claude_session = Session {
+            project: Some("p1".to_string()),
+            title: Some("Claude".to_string()),
+            ..session(Source::Claude, shared_external_id)
+        };
+        let codex_session = Session {
+            project: Some("p2".to_string()),
+            title: Some("Codex".to_string()),
+            ..session(Source::Codex, shared_external_id)
+        };
+
+        let claude_events = vec![Event {
+            id: Uuid::new_v4(),
+            session_id: claude_session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some("claude".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        }];
+        let codex_events = vec![Event {
+            id: Uuid::new_v4(),
+            session_id: codex_session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some("codex".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        }];
+
+        db.insert_session_with_events(&claude_session, &claude_events)
+            .await
+            .unwrap();
+        db.insert_session_with_events(&codex_session, &codex_events)
+            .await
+            .unwrap();
+
+        let pruned = db.prune_duplicate_sessions().await.unwrap();
+        assert_eq!(pruned, 0);
+
+        let sessions = db.list_sessions(10, 0).await.unwrap();
+        assert_eq!(sessions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_image_part_with_path_produces_attachment_row() {
+        let db = setup_test_db().await;
+        let session = Session { title: Some("Screenshots".to_string()), ..session(Source::OpenCode, "attach-1") };
+        let event = Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some("[Image]".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({
+                "attachments": [{"path": "/tmp/screenshot.png", "mime_type": "image/png"}],
+            }),
+            parent_id: None,
+        };
+
+        db.insert_session_with_events(&session, std::slice::from_ref(&event))
+            .await
+            .unwrap();
+        db.insert_session_with_events(&session, std::slice::from_ref(&event))
+            .await
+            .unwrap();
+
+        let attachments = db.get_session_attachments(session.id.into()).await.unwrap();
+        assert_eq!(attachments.len(), 1, "re-ingest should replace attachments");
+        assert_eq!(attachments[0].event_id, event.id.to_string());
+        assert_eq!(attachments[0].path, "/tmp/screenshot.png");
+        assert_eq!(attachments[0].mime_type.as_deref(), Some("image/png"));
+    }
+
+    #[tokio::test]
+    async fn test_kind_distribution_by_source() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "kinds-1");
+        let event = |kind: EventKind, role: Option<Role>| Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind,
+            role,
+            content: Some("content".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        };
+        let events = vec![
+            event(EventKind::Message, Some(Role::User)),
+            event(EventKind::Message, Some(Role::Assistant)),
+            event(EventKind::ToolCall, Some(Role::Assistant)),
+        ];
+
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let distribution = db.get_kind_distribution_by_source(None, None).await.unwrap();
+        assert_eq!(distribution.len(), 1);
+
+        let claude = &distribution[0];
+        assert_eq!(claude.source, "claude");
+        assert_eq!(claude.counts.get("message"), Some(&2));
+        assert_eq!(claude.counts.get("tool_call"), Some(&1));
+        assert_eq!(claude.total, 3);
+    }
+
+    #[test]
+    fn test_detect_source_in_dir_selects_crush() {
+        let dir = std::env::temp_dir().join(format!("agent-viz-detect-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join(".crush")).unwrap();
+        assert_eq!(detect_source_in_dir(&dir), None);
+
+        std::fs::write(dir.join(".crush").join("crush.db"), b"").unwrap();
+        std::fs::create_dir_all(dir.join(".claude")).unwrap();
+        assert_eq!(detect_source_in_dir(&dir), Some(Source::Crush));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_activity_by_weekday_groups_mondays() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "weekday-1");
+        let event = |timestamp: &str| Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some("content".to_string()),
+            timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        };
+        let events = vec![
+            event("2024-06-03T09:00:00Z"),
+            event("2024-06-10T15:30:00Z"),
+            event("2024-06-05T12:00:00Z"),
+        ];
+
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let weekdays = db.get_activity_by_weekday(None, None).await.unwrap();
+        assert_eq!(weekdays.len(), 7);
+        assert_eq!(weekdays[0].weekday, Weekday::Mon);
+        assert_eq!(weekdays[0].event_count, 2);
+        assert_eq!(weekdays[0].session_count, 1);
+        assert_eq!(weekdays[2].weekday, Weekday::Wed);
+        assert_eq!(weekdays[2].event_count, 1);
+        assert_eq!(weekdays[6].weekday, Weekday::Sun);
+        assert_eq!(weekdays[6].event_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_snippet_scales_with_content_length() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "snippet-1");
+        let short = "the needle is here".to_string();
+        let long = format!("{} needle {}", "filler ".repeat(400), "padding ".repeat(400));
+        let event = |content: &str| Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some(content.to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        };
+
+        db.insert_session_with_events(&session, &[event(&short), event(&long)])
+            .await
+            .unwrap();
+
+        let results = db
+            .search_events("needle", &SearchFacets::default(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+
+        let snippet_for = |content: &str| {
+            results
+                .iter()
+                .find(|r| r.event.content.as_deref() == Some(content))
+                .and_then(|r| r.snippet.clone())
+                .unwrap()
+        };
+
+        assert_eq!(snippet_for(&short), "the [needle] is here");
+
+        let long_snippet = snippet_for(&long);
+        assert!(long_snippet.contains("[needle]"));
+        assert!(long_snippet.split_whitespace().count() <= SNIPPET_MAX_TOKENS as usize + 2);
+
+        let facets = SearchFacets { snippet_len: Some(4), ..Default::default() };
+        let results = db.search_events("needle", &facets, 10, 0).await.unwrap();
+        for result in results {
+            assert!(result.snippet.unwrap().split_whitespace().count() <= 6);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_snippet_brackets_the_matched_term() {
+        let db = setup_test_db().await;
+        let session = session(Source::Codex, "snippet-2");
+        let event = Event {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            kind: EventKind::ToolResult,
+            role: None,
+            content: Some("deploy failed because the build cache was stale".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({}),
+            parent_id: None,
+        };
+        db.insert_session_with_events(&session, &[event]).await.unwrap();
+
+        let results = db
+            .search_events("cache", &SearchFacets::default(), 10, 0)
+            .await
+            .unwrap();
+        let snippet = results[0].snippet.as_deref().unwrap();
+        assert!(snippet.contains("build [cache] was"), "{}", snippet);
+        assert_eq!(snippet.matches('[').count(), 1);
+    }
+
+    #[test]
+    fn test_sanitize_fts_query() {
+        assert_eq!(sanitize_fts_query("hello world"), "\"hello\" \"world\"");
+        assert_eq!(sanitize_fts_query("say \"hi"), "\"say\" \"\"\"hi\"");
+        assert_eq!(sanitize_fts_query("refac*"), "\"refac\"*");
+        assert_eq!(sanitize_fts_query("  * ** "), "");
+        assert_eq!(sanitize_fts_query_any("hello wor*"), "\"hello\" OR \"wor\"*");
+        assert_eq!(sanitize_fts_query_any("or"), "\"or\"");
+    }
+
+    #[tokio::test]
+    async fn test_search_terms_are_and_by_default_and_or_with_match_any() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "any-1");
+        let events: Vec<Event> = ["alpha only", "beta only", "alpha with beta", "neither"]
+            .iter()
+            .map(|content| Event {
+                id: Uuid::new_v4(),
+                session_id: session.id,
+                kind: EventKind::Message,
+                role: Some(Role::User),
+                content: Some(content.to_string()),
+                timestamp: Utc::now(),
+                raw_payload: serde_json::json!({}),
+                parent_id: None,
             })
+            .collect();
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let all = db
+            .search_events("alpha beta", &SearchFacets::default(), 10, 0)
             .await
             .unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].event.content.as_deref(), Some("alpha with beta"));
 
-        let pruned = db.prune_duplicate_sessions().await.unwrap();
-        assert_eq!(pruned, 1);
+        let facets = SearchFacets { match_any: true, ..Default::default() };
+        let any = db.search_events("alpha beta", &facets, 10, 0).await.unwrap();
+        assert_eq!(any.len(), 3);
+        assert!(any.iter().all(|r| r.event.content.as_deref() != Some("neither")));
+    }
 
-        let sessions = db.list_sessions(10, 0).await.unwrap();
-        assert_eq!(sessions.len(), 1);
-        assert_eq!(sessions[0].id, keep_id);
-        assert_eq!(sessions[0].created_at, "2026-03-01T00:00:00Z");
-        assert_eq!(sessions[0].updated_at, "2026-03-04T00:00:00Z");
+    #[tokio::test]
+    async fn test_search_sessions_handles_fts_special_characters() {
+        let db = setup_test_db().await;
+        let session = Session {
+            title: Some("Fix \"quoted\" parser for glob* patterns".to_string()),
+            ..session(Source::Crush, "title-1")
+        };
+        db.insert_session_with_events(&session, &[]).await.unwrap();
 
-        let events = db.get_session_events(keep_id).await.unwrap();
-        assert_eq!(events.len(), 3);
-        assert!(events.iter().any(|e| e.content.as_deref() == Some("donor-unique")));
+        for query in ["\"quoted", "glob*", "quoted\" parser", "*", "patterns -- NOT"] {
+            let results = db.search_sessions(query, &SearchFacets::default(), 10, 0).await;
+            assert!(results.is_ok(), "query {:?} errored: {:?}", query, results.err());
+        }
+
+        let results = db
+            .search_sessions("\"quoted", &SearchFacets::default(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.external_id, "title-1");
+
+        let results = db
+            .search_sessions("glob*", &SearchFacets::default(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
     }
 
     #[tokio::test]
-    async fn test_prune_duplicate_sessions_does_not_merge_across_sources() {
+    async fn test_search_sessions_ranks_the_closest_title_first() {
         let db = setup_test_db().await;
-        let shared_external_id = "shared-id";
+        let titles = [
+            "Refactor the payment webhook retry queue",
+            "Fix flaky login test",
+            "Webhook docs typo",
+            "Investigate webhook retry storms after the payment outage",
+        ];
+        for (i, title) in titles.iter().enumerate() {
+            let session =
+                Session { title: Some(title.to_string()), ..session(Source::Claude, &format!("titled-{}", i)) };
+            db.insert_session_with_events(&session, &[]).await.unwrap();
+        }
 
-        let claude_session = Session {
+        let results = db
+            .search_sessions("payment webhook retry", &SearchFacets::default(), 10, 0)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = results.iter().map(|(s, _)| s.external_id.as_str()).collect();
+        assert_eq!(ids, ["titled-0", "titled-3"]);
+        assert!(results[0].1 <= results[1].1);
+
+        let login = db
+            .search_sessions("login", &SearchFacets::default(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(login.len(), 1);
+        assert_eq!(login[0].0.external_id, "titled-1");
+    }
+
+    #[tokio::test]
+    async fn test_session_timeline_one_entry_per_active_day() {
+        let db = setup_test_db().await;
+        let event = |session_id: Uuid, timestamp: &str| Event {
             id: Uuid::new_v4(),
-            source: Source::Claude,
-            external_id: shared_external_id.to_string(),
-            project: Some("p1".to_string()),
-            title: Some("Claude".to_string()),
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
+            session_id,
+            kind: EventKind::Message,
+            role: Some(Role::User),
+            content: Some("content".to_string()),
+            timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc),
             raw_payload: serde_json::json!({}),
+            parent_id: None,
         };
-        let codex_session = Session {
+
+        let claude = session(Source::Claude, "timeline-claude");
+        let codex = session(Source::Codex, "timeline-codex");
+        db.insert_session_with_events(
+            &claude,
+            &[
+                event(claude.id, "2024-06-03T09:00:00Z"),
+                event(claude.id, "2024-06-03T10:00:00Z"),
+                event(claude.id, "2024-06-04T10:00:00Z"),
+            ],
+        )
+        .await
+        .unwrap();
+        db.insert_session_with_events(&codex, &[event(codex.id, "2024-06-03T11:00:00Z")])
+            .await
+            .unwrap();
+
+        let timeline = db.get_session_timeline(None, None).await.unwrap();
+        assert_eq!(timeline.len(), 2);
+
+        let june_4 = &timeline[0];
+        assert_eq!(june_4.day, NaiveDate::from_ymd_opt(2024, 6, 4).unwrap());
+        assert_eq!(june_4.event_count, 1);
+        assert_eq!(june_4.session_count, 1);
+        assert_eq!(june_4.sources.len(), 1);
+
+        let june_3 = &timeline[1];
+        assert_eq!(june_3.day, NaiveDate::from_ymd_opt(2024, 6, 3).unwrap());
+        assert_eq!(june_3.event_count, 3);
+        assert_eq!(june_3.session_count, 2);
+        let claude_day = june_3.sources.iter().find(|s| s.source == "claude").unwrap();
+        assert_eq!(claude_day.event_count, 2);
+        assert_eq!(claude_day.session_count, 1);
+        let codex_day = june_3.sources.iter().find(|s| s.source == "codex").unwrap();
+        assert_eq!(codex_day.event_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_file_edits_produce_files_touched_rows() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "edits-1");
+        let event = Event {
             id: Uuid::new_v4(),
-            source: Source::Codex,
-            external_id: shared_external_id.to_string(),
-            project: Some("p2".to_string()),
-            title: Some("Codex".to_string()),
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-            raw_payload: serde_json::json!({}),
+            session_id: session.id,
+            kind: EventKind::ToolResult,
+            role: Some(Role::User),
+            content: Some("ok".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({
+                "file_edits": [{"path": "/repo/src/main.rs", "operation": "edit", "lines_added": 3, "lines_removed": 2}],
+            }),
+            parent_id: None,
         };
 
-        let claude_events = vec![Event {
+        db.insert_session_with_events(&session, std::slice::from_ref(&event))
+            .await
+            .unwrap();
+        db.insert_session_with_events(&session, std::slice::from_ref(&event))
+            .await
+            .unwrap();
+
+        let session_id = session.id.to_string();
+        let files = db.get_session_files_touched(&session_id).await.unwrap();
+        assert_eq!(files.len(), 1, "re-ingest should replace files touched");
+        assert_eq!(files[0].file_path, "/repo/src/main.rs");
+        assert_eq!(files[0].operation, "edit");
+        assert_eq!(files[0].lines_added, 3);
+        assert_eq!(files[0].lines_removed, 2);
+
+        db.compute_session_metrics(session.id.into()).await.unwrap();
+        let metrics = db.get_session_metrics(session.id.into()).await.unwrap().unwrap();
+        assert_eq!(metrics.files_touched, 1);
+        assert_eq!(metrics.lines_added, 3);
+        assert_eq!(metrics.lines_removed, 2);
+    }
+
+    #[tokio::test]
+    async fn test_tagged_search_results_from_older_epoch_are_stale() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "typing-1");
+        let event = Event {
             id: Uuid::new_v4(),
-            session_id: claude_session.id,
+            session_id: session.id,
             kind: EventKind::Message,
             role: Some(Role::User),
-            content: Some("claude".to_string()),
+            content: Some("refactor the parser".to_string()),
             timestamp: Utc::now(),
             raw_payload: serde_json::json!({}),
-        }];
-        let codex_events = vec![Event {
+            parent_id: None,
+        };
+        db.insert_session_with_events(&session, std::slice::from_ref(&event))
+            .await
+            .unwrap();
+
+        let generation = SearchGeneration::default();
+        let facets = SearchFacets::default();
+        let first = db
+            .search_events_tagged("pars*", &facets, 10, 0, &generation)
+            .await
+            .unwrap();
+        assert!(!first.is_stale(&generation));
+
+        let second = db
+            .search_events_tagged("parser", &facets, 10, 0, &generation.clone())
+            .await
+            .unwrap();
+        assert!(second.epoch > first.epoch);
+        assert!(first.is_stale(&generation), "older epoch should be superseded");
+        assert!(!second.is_stale(&generation));
+        assert_eq!(second.results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_tokens_per_line_metric() {
+        let db = setup_test_db().await;
+        let mut sessions = Vec::new();
+        for (external_id, file_edits) in [
+            (
+                "efficient-1",
+                serde_json::json!([
+                    {"path": "/repo/src/main.rs", "operation": "edit", "lines_added": 30, "lines_removed": 5},
+                    {"path": "/repo/src/lib.rs", "operation": "edit", "lines_added": 5, "lines_removed": 0},
+                ]),
+            ),
+            ("no-edits-1", serde_json::json!([])),
+        ] {
+            let session = session(Source::Claude, external_id);
+            let event = Event {
+                id: Uuid::new_v4(),
+                session_id: session.id,
+                kind: EventKind::ToolResult,
+                role: None,
+                content: None,
+                timestamp: Utc::now(),
+                raw_payload: serde_json::json!({
+                    "message": {"usage": {"input_tokens": 300, "output_tokens": 100}},
+                    "file_edits": file_edits,
+                }),
+                parent_id: None,
+            };
+            db.insert_session_with_events(&session, std::slice::from_ref(&event))
+                .await
+                .unwrap();
+            db.compute_session_metrics(session.id.into()).await.unwrap();
+            sessions.push(SessionId::from(session.id));
+        }
+
+        let metrics = db.get_session_metrics(sessions[0]).await.unwrap().unwrap();
+        assert_eq!(metrics.tokens_per_line, Some(10.0));
+        let metrics = db.get_session_metrics(sessions[1]).await.unwrap().unwrap();
+        assert_eq!(metrics.tokens_per_line, None);
+
+        let ranked = db.get_sessions_by_tokens_per_line(None, None, None, 10).await.unwrap();
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].external_id, "efficient-1");
+        assert_eq!(ranked[0].total_tokens, 400);
+        assert_eq!(ranked[0].lines_changed, 40);
+
+        let stats = db.get_efficiency_stats(None, None, None).await.unwrap();
+        assert_eq!(stats.avg_tokens_per_line, Some(10.0));
+    }
+
+    #[tokio::test]
+    async fn test_files_leaderboard_grouped_by_extension() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "grouped-1");
+        let event = Event {
             id: Uuid::new_v4(),
-            session_id: codex_session.id,
-            kind: EventKind::Message,
+            session_id: session.id,
+            kind: EventKind::ToolResult,
             role: Some(Role::User),
-            content: Some("codex".to_string()),
+            content: Some("ok".to_string()),
             timestamp: Utc::now(),
-            raw_payload: serde_json::json!({}),
-        }];
+            raw_payload: serde_json::json!({
+                "file_edits": [
+                    {"path": "/repo/src/main.rs", "operation": "edit", "lines_added": 3, "lines_removed": 1},
+                    {"path": "/repo/src/lib.rs", "operation": "edit", "lines_added": 2, "lines_removed": 0},
+                    {"path": "/repo/crates/store/db.rs", "operation": "write", "lines_added": 5, "lines_removed": 4},
+                    {"path": "/repo/web/app.ts", "operation": "edit", "lines_added": 1, "lines_removed": 1},
+                ],
+            }),
+            parent_id: None,
+        };
+        db.insert_session_with_events(&session, std::slice::from_ref(&event))
+            .await
+            .unwrap();
 
-        db.insert_session_with_events(&claude_session, &claude_events)
+        let by_ext = db
+            .get_files_leaderboard_grouped(None, None, 20, FileGrouping::Extension)
             .await
             .unwrap();
-        db.insert_session_with_events(&codex_session, &codex_events)
+        assert_eq!(by_ext.len(), 2);
+        assert_eq!(by_ext[0].file_path, ".rs");
+        assert_eq!(by_ext[0].touch_count, 3);
+        assert_eq!(by_ext[0].sessions, 1);
+        assert_eq!(by_ext[0].total_lines_added, 10);
+        assert_eq!(by_ext[0].total_lines_removed, 5);
+        assert_eq!(by_ext[1].file_path, ".ts");
+
+        let by_dir = db
+            .get_files_leaderboard_grouped(None, None, 20, FileGrouping::Directory)
             .await
             .unwrap();
+        let dirs: Vec<&str> = by_dir.iter().map(|e| e.file_path.as_str()).collect();
+        assert_eq!(dirs, vec!["src", "crates", "web"]);
+    }
 
-        let pruned = db.prune_duplicate_sessions().await.unwrap();
-        assert_eq!(pruned, 0);
+    #[tokio::test]
+    async fn test_reingest_adds_no_external_ids() {
+        let db = setup_test_db().await;
+        let session = session(Source::Codex, "rollout-1");
 
-        let sessions = db.list_sessions(10, 0).await.unwrap();
-        assert_eq!(sessions.len(), 2);
+        assert!(db.get_external_ids("codex").await.unwrap().is_empty());
+        db.insert_session_with_events(&session, &[]).await.unwrap();
+        let after_first = db.get_external_ids("codex").await.unwrap();
+        assert_eq!(after_first, HashSet::from(["rollout-1".to_string()]));
+
+        db.insert_session_with_events(&session, &[]).await.unwrap();
+        let after_second = db.get_external_ids("codex").await.unwrap();
+        assert_eq!(after_second, HashSet::from(["rollout-1".to_string()]));
+        assert!(db.get_external_ids("claude").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_session_removes_events_and_search_entries() {
+        let db = setup_test_db().await;
+        let (kept, doomed) = (Uuid::new_v4(), Uuid::new_v4());
+        for (id, external_id) in [(kept, "keep-1"), (doomed, "delete-1")] {
+            let session =
+                Session { id, title: Some("Flaky deploy".to_string()), ..session(Source::Claude, external_id) };
+            let events = vec![Event {
+                id: Uuid::new_v4(),
+                session_id: id,
+                kind: EventKind::Message,
+                role: Some(Role::User),
+                content: Some("why does the deploy keep failing".to_string()),
+                timestamp: Utc::now(),
+                raw_payload: serde_json::json!({}),
+                parent_id: None,
+            }];
+            db.insert_session_with_events(&session, &events).await.unwrap();
+        }
+
+        assert!(db.delete_session(doomed.into()).await.unwrap());
+
+        assert!(db.get_session(doomed.into()).await.unwrap().is_none());
+        assert!(db.get_session_events(doomed.into()).await.unwrap().is_empty());
+        assert!(db.get_session_metrics(doomed.into()).await.unwrap().is_none());
+        let hits = db
+            .search_events("deploy", &SearchFacets::default(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].event.session_id, kept.to_string());
+
+        assert!(!db.delete_session(doomed.into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_reingest_replaces_events_instead_of_duplicating() {
+        let db = setup_test_db().await;
+        let session = session(Source::Claude, "reingest-1");
+        // Each parse of the same file yields the same events under new IDs
+        let parse = || -> Vec<Event> {
+            ["fix the flaky retry test", "retry now backs off"]
+                .into_iter()
+                .map(|content| Event {
+                    id: Uuid::new_v4(),
+                    session_id: session.id,
+                    kind: EventKind::Message,
+                    role: Some(Role::User),
+                    content: Some(content.to_string()),
+                    timestamp: Utc::now(),
+                    raw_payload: serde_json::json!({}),
+                    parent_id: None,
+                })
+                .collect()
+        };
+
+        db.insert_session_with_events(&session, &parse()).await.unwrap();
+        db.insert_session_with_events(&session, &parse()).await.unwrap();
+
+        assert_eq!(db.get_session_events(session.id.into()).await.unwrap().len(), 2);
+        let metrics = db.get_session_metrics(session.id.into()).await.unwrap().unwrap();
+        assert_eq!(metrics.total_events, 2);
+        let hits = db
+            .search_events("retry", &SearchFacets::default(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_session_paths_reads_file_path_from_payload() {
+        let db = setup_test_db().await;
+        let sessions = [
+            (
+                Source::Claude,
+                "with-path",
+                serde_json::json!({"file_path": "/home/me/.claude/projects/p/a.jsonl"}),
+            ),
+            (Source::Claude, "without-path", serde_json::json!({})),
+            (Source::OpenCode, "opencode-1", serde_json::json!({"id": "ses_1"})),
+        ];
+        for (i, (source, external_id, raw_payload)) in sessions.into_iter().enumerate() {
+            let at = Utc::now() - chrono::Duration::minutes(10 - i as i64);
+            let session = Session { created_at: at, updated_at: at, raw_payload, ..session(source, external_id) };
+            db.insert_session(&session).await.unwrap();
+        }
+
+        let claude = db.list_session_paths_by_source(Some("claude")).await.unwrap();
+        let paths: Vec<(&str, Option<&str>)> = claude
+            .iter()
+            .map(|s| (s.external_id.as_str(), s.file_path.as_deref()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("with-path", Some("/home/me/.claude/projects/p/a.jsonl")),
+                ("without-path", None)
+            ]
+        );
+        assert_eq!(db.list_session_paths_by_source(None).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_tags_filter_sessions_and_search() {
+        let db = setup_test_db().await;
+        let (triage, spike) = (Uuid::new_v4(), Uuid::new_v4());
+        for (id, external_id) in [(triage, "tagged-1"), (spike, "tagged-2")] {
+            let session =
+                Session { id, title: Some("Login timeout".to_string()), ..session(Source::Claude, external_id) };
+            let events = vec![Event {
+                id: Uuid::new_v4(),
+                session_id: id,
+                kind: EventKind::Message,
+                role: Some(Role::User),
+                content: Some("the login request times out".to_string()),
+                timestamp: Utc::now(),
+                raw_payload: serde_json::json!({}),
+                parent_id: None,
+            }];
+            db.insert_session_with_events(&session, &events).await.unwrap();
+        }
+
+        assert!(db.add_tag(triage.into(), "bug-triage").await.unwrap());
+        assert!(db.add_tag(triage.into(), "auth").await.unwrap());
+        assert!(!db.add_tag(triage.into(), "auth").await.unwrap());
+        assert!(db.add_tag(spike.into(), "spike").await.unwrap());
+
+        assert_eq!(db.get_tags(triage.into()).await.unwrap(), ["auth", "bug-triage"]);
+        let tagged = db.list_sessions_by_tag("bug-triage").await.unwrap();
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].id, triage.to_string());
+        assert_eq!(
+            db.list_tags().await.unwrap(),
+            [
+                ("auth".to_string(), 1),
+                ("bug-triage".to_string(), 1),
+                ("spike".to_string(), 1)
+            ]
+        );
+
+        let facets = SearchFacets { tag: Some("spike".to_string()), ..Default::default() };
+        let hits = db.search_events("login", &facets, 10, 0).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].event.session_id, spike.to_string());
+        let titles = db.search_sessions("login", &facets, 10, 0).await.unwrap();
+        assert_eq!(titles.len(), 1);
+        assert_eq!(titles[0].0.id, spike.to_string());
+
+        assert!(db.remove_tag(triage.into(), "bug-triage").await.unwrap());
+        assert!(!db.remove_tag(triage.into(), "bug-triage").await.unwrap());
+        assert!(db.list_sessions_by_tag("bug-triage").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_session_facet_limits_search_to_one_session() {
+        let db = setup_test_db().await;
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        for (id, external_id) in [(first, "scoped-1"), (second, "scoped-2")] {
+            let session = Session { id, ..session(Source::Codex, external_id) };
+            let events: Vec<Event> = (0..2)
+                .map(|_| Event {
+                    id: Uuid::new_v4(),
+                    session_id: id,
+                    kind: EventKind::Message,
+                    role: Some(Role::User),
+                    content: Some("retry the flaky migration".to_string()),
+                    timestamp: Utc::now(),
+                    raw_payload: serde_json::json!({}),
+                    parent_id: None,
+                })
+                .collect();
+            db.insert_session_with_events(&session, &events).await.unwrap();
+        }
+
+        let everywhere = SearchFacets { session_id: Some(String::new()), ..Default::default() };
+        assert_eq!(db.search_events("flaky", &everywhere, 10, 0).await.unwrap().len(), 4);
+
+        let scoped = SearchFacets { session_id: Some(second.to_string()), ..Default::default() };
+        let hits = db.search_events("flaky", &scoped, 10, 0).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|hit| hit.event.session_id == second.to_string()));
+        assert_eq!(db.count_search_events("flaky", &scoped).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_since_and_until_bound_search_to_a_window() {
+        let db = setup_test_db().await;
+        let now = Utc::now();
+        let session_id = Uuid::new_v4();
+        let session = Session {
+            id: session_id,
+            created_at: now - chrono::Duration::days(40),
+            updated_at: now,
+            ..session(Source::Claude, "window-1")
+        };
+        let events: Vec<Event> = [40, 20, 10, 1]
+            .into_iter()
+            .map(|days_ago| Event {
+                id: Uuid::new_v4(),
+                session_id,
+                kind: EventKind::Message,
+                role: Some(Role::User),
+                content: Some(format!("deploy rollback {} days ago", days_ago)),
+                timestamp: now - chrono::Duration::days(days_ago),
+                raw_payload: serde_json::json!({}),
+                parent_id: None,
+            })
+            .collect();
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let window = SearchFacets {
+            since: Some(now - chrono::Duration::days(30)),
+            until: Some(now - chrono::Duration::days(7)),
+            ..Default::default()
+        };
+        let hits = db.search_events("rollback", &window, 10, 0).await.unwrap();
+        let mut days: Vec<&str> = hits.iter().filter_map(|h| h.event.content.as_deref()).collect();
+        days.sort();
+        assert_eq!(days, ["deploy rollback 10 days ago", "deploy rollback 20 days ago"]);
+        assert_eq!(db.count_search_events("rollback", &window).await.unwrap(), 2);
+        let pattern_hits = db.search_events_regex(r"rollback \d+", &window, 10).await.unwrap();
+        assert_eq!(pattern_hits.len(), 2);
+
+        let open_ended = SearchFacets { until: Some(now - chrono::Duration::days(7)), ..Default::default() };
+        assert_eq!(db.count_search_events("rollback", &open_ended).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_regex_search_matches_patterns_fts_cannot() {
+        let db = setup_test_db().await;
+        let session_id = Uuid::new_v4();
+        let session = Session { id: session_id, ..session(Source::Codex, "regex-1") };
+        let events: Vec<Event> = ["build failed: error code 137", "error code unknown", "all green"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, content)| Event {
+                id: Uuid::new_v4(),
+                session_id,
+                kind: EventKind::Message,
+                role: Some(Role::Assistant),
+                content: Some(content.to_string()),
+                timestamp: Utc::now() + chrono::Duration::seconds(i as i64),
+                raw_payload: serde_json::json!({}),
+                parent_id: None,
+            })
+            .collect();
+        db.insert_session_with_events(&session, &events).await.unwrap();
+
+        let hits = db
+            .search_events_regex(r"error code (\d+)", &SearchFacets::default(), 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].event.content.as_deref(), Some("build failed: error code 137"));
+        assert_eq!(hits[0].snippet.as_deref(), Some("build failed: [error code 137]"));
+
+        let any_error = db
+            .search_events_regex("(?i)ERROR", &SearchFacets::default(), 1)
+            .await
+            .unwrap();
+        assert_eq!(any_error.len(), 1);
+        assert_eq!(any_error[0].event.content.as_deref(), Some("error code unknown"));
+
+        let invalid = db.search_events_regex("TODO(", &SearchFacets::default(), 10).await;
+        assert!(matches!(invalid, Err(RegexSearchError::InvalidPattern(_))));
+        let huge = db
+            .search_events_regex(r"(?:\w{100}){100}", &SearchFacets::default(), 10)
+            .await;
+        assert!(matches!(huge, Err(RegexSearchError::TooComplex)));
+    }
+
+    #[tokio::test]
+    async fn test_claude_usage_sums_messages_and_keeps_the_main_model() {
+        let db = setup_test_db().await;
+        let session_id = Uuid::new_v4();
+        let session = Session { id: session_id, ..session(Source::Claude, "claude-usage-1") };
+        let assistant = |kind: EventKind, id: &str, model: &str, input: i64, output: i64| Event {
+            id: Uuid::new_v4(),
+            session_id,
+            kind,
+            role: Some(Role::Assistant),
+            content: Some("working on it".to_string()),
+            timestamp: Utc::now(),
+            raw_payload: serde_json::json!({"type": "assistant", "message": {
+                "id": id,
+                "model": model,
+                "usage": {"input_tokens": input, "output_tokens": output}
+            }}),
+            parent_id: None,
+        };
+        let sonnet = "claude-sonnet-4-5-20250929";
+        let events = vec![
+            // One API response split over a text line and a tool_use line
+            assistant(EventKind::Message, "msg_1", sonnet, 1000, 200),
+            assistant(EventKind::ToolCall, "msg_1", sonnet, 1000, 200),
+            assistant(EventKind::Message, "msg_2", "claude-haiku-4-5-20251001", 300, 50),
+            assistant(EventKind::Message, "msg_3", sonnet, 1500, 120),
+        ];
+
+        db.insert_session_with_events(&session, &events).await.unwrap();
+        db.compute_session_metrics(session_id.into()).await.unwrap();
+
+        let metrics = db.get_session_metrics(session_id.into()).await.unwrap().unwrap();
+        assert_eq!(metrics.model.as_deref(), Some(sonnet));
+        assert_eq!(metrics.provider.as_deref(), Some("anthropic"));
+        assert_eq!(metrics.input_tokens, Some(2800));
+        assert_eq!(metrics.output_tokens, Some(370));
+    }
+
+    #[tokio::test]
+    async fn test_large_session_inserts_all_or_nothing() {
+        let db = setup_test_db().await;
+        let events = |session_id: Uuid| -> Vec<Event> {
+            (0..5000)
+                .map(|i| Event {
+                    id: Uuid::new_v4(),
+                    session_id,
+                    kind: EventKind::Message,
+                    role: Some(Role::User),
+                    content: Some(format!("message {}", i)),
+                    timestamp: Utc::now() + chrono::Duration::milliseconds(i),
+                    raw_payload: serde_json::json!({}),
+                    parent_id: None,
+                })
+                .collect()
+        };
+
+        let complete = session(Source::Codex, "bulk-complete");
+        db.insert_session_with_events(&complete, &events(complete.id))
+            .await
+            .unwrap();
+        assert_eq!(db.get_session_events(complete.id.into()).await.unwrap().len(), 5000);
+
+        // A duplicate event ID halfway through fails the insert after 2500 rows were written
+        let broken = session(Source::Codex, "bulk-broken");
+        let mut broken_events = events(broken.id);
+        broken_events[2500].id = broken_events[0].id;
+        assert!(db.insert_session_with_events(&broken, &broken_events).await.is_err());
+        assert!(db.get_session(broken.id.into()).await.unwrap().is_none());
+        assert!(db.get_session_events(broken.id.into()).await.unwrap().is_empty());
+        assert_eq!(db.list_sessions(10, 0).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_token_timeline_sums_to_the_session_total() {
+        let db = setup_test_db().await;
+        let event = |session_id: Uuid, seconds: i64, raw_payload: serde_json::Value| Event {
+            id: Uuid::new_v4(),
+            session_id,
+            kind: EventKind::Message,
+            role: Some(Role::Assistant),
+            content: Some("done".to_string()),
+            timestamp: Utc::now() + chrono::Duration::seconds(seconds),
+            raw_payload,
+            parent_id: None,
+        };
+        let claude_line = |id: &str, input: i64, output: i64| serde_json::json!({"message": {"id": id, "usage": {"input_tokens": input, "output_tokens": output}}});
+
+        let claude_id = Uuid::new_v4();
+        let claude = Session { id: claude_id, ..session(Source::Claude, "claude-timeline") };
+        let first = vec![
+            event(claude_id, 0, claude_line("msg_1", 1000, 200)),
+            event(claude_id, 1, claude_line("msg_1", 1000, 200)),
+            event(claude_id, 2, serde_json::json!({"type": "user"})),
+        ];
+        db.insert_session_with_events(&claude, &first).await.unwrap();
+        // The rest of msg_1 arrives in a later append and must not be counted again
+        let later = vec![
+            event(claude_id, 3, claude_line("msg_1", 1000, 200)),
+            event(claude_id, 4, claude_line("msg_2", 1500, 120)),
+        ];
+        db.append_events(claude_id.into(), &later).await.unwrap();
+        db.compute_session_metrics(claude_id.into()).await.unwrap();
+
+        let timeline = db.get_token_timeline(claude_id.into()).await.unwrap();
+        assert_eq!(timeline.len(), 2);
+        assert_eq!((timeline[0].input_tokens, timeline[0].output_tokens), (1000, 200));
+        let metrics = db.get_session_metrics(claude_id.into()).await.unwrap().unwrap();
+        assert_eq!(
+            Some(timeline.iter().map(|p| p.input_tokens).sum::<i64>()),
+            metrics.input_tokens
+        );
+        assert_eq!(
+            Some(timeline.iter().map(|p| p.output_tokens).sum::<i64>()),
+            metrics.output_tokens
+        );
+
+        let opencode_id = Uuid::new_v4();
+        let opencode =
+            Session { id: opencode_id, source: Source::OpenCode, external_id: "ses_tokens".to_string(), ..claude };
+        let tokens =
+            |input: i64, output: i64| serde_json::json!({"tokens": {"input": input, "output": output, "reasoning": 0}});
+        let events = vec![
+            event(opencode_id, 0, tokens(400, 90)),
+            event(opencode_id, 1, tokens(650, 30)),
+        ];
+        db.insert_session_with_events(&opencode, &events).await.unwrap();
+        db.compute_session_metrics(opencode_id.into()).await.unwrap();
+
+        let timeline = db.get_token_timeline(opencode_id.into()).await.unwrap();
+        let metrics = db.get_session_metrics(opencode_id.into()).await.unwrap().unwrap();
+        assert_eq!(metrics.input_tokens, Some(1050));
+        assert_eq!(
+            Some(timeline.iter().map(|p| p.input_tokens).sum::<i64>()),
+            metrics.input_tokens
+        );
+        assert_eq!(
+            Some(timeline.iter().map(|p| p.output_tokens).sum::<i64>()),
+            metrics.output_tokens
+        );
     }
 }
@@ -0,0 +1,303 @@
+//! File touch extraction from stored event payloads.
+//!
+//! The `files_touched` table feeds the files leaderboard and patch churn. Its rows come from:
+//! - [`FileEdit`]s an adapter recorded in an event payload (Claude `toolUseResult` patches)
+//! - Claude `Edit`, `Write`, `MultiEdit`, and `NotebookEdit` tool calls, with churn estimated
+//!   from the call's arguments when no recorded edit arrives with the call's result
+//! - OpenCode session diffs, which already carry per-file additions and deletions
+//!
+//! Like [`crate::tool_calls`], this module is pure and leaves the database to [`crate::Database`].
+
+use crate::models::EventRow;
+use agent_v_core::FileEdit;
+use std::collections::{HashMap, HashSet};
+
+/// A row for the `files_touched` table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileTouch {
+    pub path: String,
+    /// Kind of change, e.g. `create`, `edit`, `write`, or `delete`
+    pub operation: String,
+    pub lines_added: i64,
+    pub lines_removed: i64,
+    pub touched_at: String,
+}
+
+impl FileTouch {
+    fn from_edit(edit: FileEdit, touched_at: &str) -> Self {
+        Self {
+            path: edit.path,
+            operation: edit.operation,
+            lines_added: edit.lines_added,
+            lines_removed: edit.lines_removed,
+            touched_at: touched_at.to_string(),
+        }
+    }
+}
+
+/// Build `files_touched` rows for a session's events (in timestamp order).
+///
+/// An edit recorded on a tool result replaces the estimate made from its call, so a Claude edit
+/// whose result carries a patch is counted once, with the patch's exact line counts.
+pub fn extract_file_touches(events: &[EventRow]) -> Vec<FileTouch> {
+    let mut touches: Vec<FileTouch> = Vec::new();
+    let mut estimates: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut superseded: HashSet<usize> = HashSet::new();
+
+    for event in events {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(&event.raw_payload) else {
+            continue;
+        };
+
+        let recorded = FileEdit::from_payload(&payload);
+        if !recorded.is_empty() {
+            for call_id in tool_result_ids(&payload) {
+                superseded.extend(estimates.remove(&call_id).unwrap_or_default());
+            }
+            touches.extend(
+                recorded
+                    .into_iter()
+                    .map(|edit| FileTouch::from_edit(edit, &event.timestamp)),
+            );
+        }
+
+        touches.extend(opencode_diffs(&payload, &event.timestamp));
+
+        for (call_id, edit) in claude_edit_calls(&payload) {
+            estimates.entry(call_id).or_default().push(touches.len());
+            touches.push(FileTouch::from_edit(edit, &event.timestamp));
+        }
+    }
+
+    touches
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| !superseded.contains(idx))
+        .map(|(_, touch)| touch)
+        .collect()
+}
+
+/// The estimated edit of each file-editing `tool_use` block in a Claude message, with its call ID
+fn claude_edit_calls(payload: &serde_json::Value) -> Vec<(String, FileEdit)> {
+    let Some(blocks) = payload.pointer("/message/content").and_then(|c| c.as_array()) else {
+        return Vec::new();
+    };
+
+    blocks
+        .iter()
+        .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+        .filter_map(|block| {
+            let call_id = block.get("id").and_then(|i| i.as_str()).unwrap_or_default();
+            let name = block.get("name").and_then(|n| n.as_str())?;
+            let edit = estimate_edit(name, block.get("input")?)?;
+            Some((call_id.to_string(), edit))
+        })
+        .collect()
+}
+
+/// Estimate the churn of an edit tool call from its arguments: replaced text counts as removed
+/// lines, new text as added lines
+fn estimate_edit(tool: &str, input: &serde_json::Value) -> Option<FileEdit> {
+    let text_lines = |key: &str| input.get(key).and_then(|v| v.as_str()).map_or(0, line_count);
+    let edit = |path: &str, operation: &str, lines_added: i64, lines_removed: i64| FileEdit {
+        path: path.to_string(),
+        operation: operation.to_string(),
+        lines_added,
+        lines_removed,
+    };
+
+    match tool {
+        "Edit" => {
+            let path = input.get("file_path")?.as_str()?;
+            Some(edit(path, "edit", text_lines("new_string"), text_lines("old_string")))
+        }
+        "MultiEdit" => {
+            let path = input.get("file_path")?.as_str()?;
+            let (added, removed) = input
+                .get("edits")
+                .and_then(|e| e.as_array())
+                .into_iter()
+                .flatten()
+                .fold((0, 0), |(added, removed), e| {
+                    let lines = |key: &str| e.get(key).and_then(|v| v.as_str()).map_or(0, line_count);
+                    (added + lines("new_string"), removed + lines("old_string"))
+                });
+            Some(edit(path, "edit", added, removed))
+        }
+        "Write" => {
+            let path = input.get("file_path")?.as_str()?;
+            Some(edit(path, "write", text_lines("content"), 0))
+        }
+        "NotebookEdit" => {
+            let path = input
+                .get("notebook_path")
+                .or_else(|| input.get("file_path"))?
+                .as_str()?;
+            Some(edit(path, "edit", text_lines("new_source"), 0))
+        }
+        _ => None,
+    }
+}
+
+/// Call IDs of the `tool_result` blocks in a Claude message
+fn tool_result_ids(payload: &serde_json::Value) -> Vec<String> {
+    let blocks = payload.pointer("/message/content").and_then(|c| c.as_array());
+    let mut ids: Vec<String> = blocks
+        .into_iter()
+        .flatten()
+        .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("tool_result"))
+        .filter_map(|block| block.get("tool_use_id")?.as_str().map(str::to_string))
+        .collect();
+    if ids.is_empty()
+        && let Some(id) = payload.get("tool_use_id").and_then(|i| i.as_str())
+    {
+        ids.push(id.to_string());
+    }
+    ids
+}
+
+/// Per-file rows from an OpenCode session diff event, whose payload is the `summary_diffs` list
+fn opencode_diffs(payload: &serde_json::Value, touched_at: &str) -> Vec<FileTouch> {
+    let Some(diffs) = payload.as_array() else {
+        return Vec::new();
+    };
+
+    diffs
+        .iter()
+        .filter_map(|diff| {
+            let path = diff.get("file")?.as_str()?;
+            let additions = diff.get("additions")?.as_i64()?;
+            let deletions = diff.get("deletions").and_then(|d| d.as_i64()).unwrap_or(0);
+            let operation = match diff.get("status").and_then(|s| s.as_str()) {
+                Some("added") => "create",
+                Some("deleted") => "delete",
+                _ => "edit",
+            };
+            Some(FileTouch {
+                path: path.to_string(),
+                operation: operation.to_string(),
+                lines_added: additions,
+                lines_removed: deletions,
+                touched_at: touched_at.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Number of lines in a string, counting a final line without a trailing newline
+fn line_count(text: &str) -> i64 {
+    text.lines().count() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, timestamp: &str, payload: serde_json::Value) -> EventRow {
+        EventRow {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            kind: "message".to_string(),
+            role: None,
+            content: None,
+            timestamp: timestamp.to_string(),
+            raw_payload: payload.to_string(),
+            category: None,
+            parent_id: None,
+        }
+    }
+
+    #[test]
+    fn test_claude_edit_call_estimates_churn_from_arguments() {
+        let events = vec![event(
+            "e1",
+            "2025-01-01T10:00:00Z",
+            serde_json::json!({"type": "assistant", "message": {"content": [
+                {"type": "tool_use", "id": "toolu_1", "name": "Edit", "input": {
+                    "file_path": "/repo/src/lib.rs",
+                    "old_string": "fn a() {}\n",
+                    "new_string": "fn a() {\n    b();\n}\n"
+                }},
+                {"type": "tool_use", "id": "toolu_2", "name": "MultiEdit", "input": {
+                    "file_path": "/repo/src/main.rs",
+                    "edits": [
+                        {"old_string": "x", "new_string": "y\nz"},
+                        {"old_string": "p\nq", "new_string": ""}
+                    ]
+                }},
+                {"type": "tool_use", "id": "toolu_3", "name": "Write", "input": {
+                    "file_path": "/repo/NOTES.md", "content": "one\ntwo\nthree"
+                }},
+                {"type": "tool_use", "id": "toolu_4", "name": "Bash", "input": {"command": "ls"}}
+            ]}}),
+        )];
+
+        let touches = extract_file_touches(&events);
+        let churn: Vec<(&str, &str, i64, i64)> = touches
+            .iter()
+            .map(|t| (t.path.as_str(), t.operation.as_str(), t.lines_added, t.lines_removed))
+            .collect();
+        assert_eq!(
+            churn,
+            vec![
+                ("/repo/src/lib.rs", "edit", 3, 1),
+                ("/repo/src/main.rs", "edit", 2, 3),
+                ("/repo/NOTES.md", "write", 3, 0),
+            ]
+        );
+        assert!(touches.iter().all(|t| t.touched_at == "2025-01-01T10:00:00Z"));
+    }
+
+    #[test]
+    fn test_recorded_patch_replaces_the_estimate_for_its_call() {
+        let events = vec![
+            event(
+                "e1",
+                "2025-01-01T10:00:00Z",
+                serde_json::json!({"message": {"content": [
+                    {"type": "tool_use", "id": "toolu_1", "name": "Edit", "input": {
+                        "file_path": "/repo/src/lib.rs", "old_string": "a\nb\nc", "new_string": "a\nc"
+                    }}
+                ]}}),
+            ),
+            event(
+                "e2",
+                "2025-01-01T10:00:01Z",
+                serde_json::json!({
+                    "message": {"content": [{"type": "tool_result", "tool_use_id": "toolu_1", "content": "ok"}]},
+                    "file_edits": [{"path": "/repo/src/lib.rs", "operation": "edit", "lines_added": 0, "lines_removed": 1}]
+                }),
+            ),
+        ];
+
+        let touches = extract_file_touches(&events);
+        assert_eq!(touches.len(), 1);
+        assert_eq!((touches[0].lines_added, touches[0].lines_removed), (0, 1));
+        assert_eq!(touches[0].touched_at, "2025-01-01T10:00:01Z");
+    }
+
+    #[test]
+    fn test_opencode_session_diffs_become_touches() {
+        let events = vec![event(
+            "e1",
+            "2025-01-01T10:00:00Z",
+            serde_json::json!([
+                {"file": "src/app.ts", "before": "", "after": "", "additions": 12, "deletions": 4, "status": "modified"},
+                {"file": "src/new.ts", "before": "", "after": "", "additions": 30, "deletions": 0, "status": "added"}
+            ]),
+        )];
+
+        let touches = extract_file_touches(&events);
+        assert_eq!(touches.len(), 2);
+        assert_eq!(
+            (
+                touches[0].path.as_str(),
+                touches[0].operation.as_str(),
+                touches[0].lines_added,
+                touches[0].lines_removed
+            ),
+            ("src/app.ts", "edit", 12, 4)
+        );
+        assert_eq!((touches[1].operation.as_str(), touches[1].lines_added), ("create", 30));
+    }
+}
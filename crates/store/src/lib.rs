@@ -1,12 +1,19 @@
+pub mod conversation_tree;
 pub mod db;
+pub mod file_touches;
 pub mod migrations;
 pub mod models;
 pub mod queries;
 pub mod session_merge;
+pub mod tool_calls;
 
+pub use conversation_tree::{ConversationNode, ConversationTree};
 pub use db::{
-    ActivityStats, CostStats, Database, ErrorStats, FileLeaderboardEntry, GroupedStats, LatencyDistribution,
-    LongRunningToolCall, ModelUsageStats, PatchChurnStats, SearchFacets, SearchResult, SessionCostStats,
-    ToolFrequencyStats, check_sources_health,
+    ActivityStats, BACKUP_KEEP, BackupError, CostStats, Database, ErrorStats, ExternalIdCollision, FileGrouping,
+    FileLeaderboardEntry, FileSessionMatch, GroupedStats, KindDistribution, LatencyDistribution, LongRunningToolCall,
+    ModelUsageStats, PatchChurnStats, RegexSearchError, SearchFacets, SearchGeneration, SearchResult, SessionCostStats,
+    SessionEfficiency, SourceActivity, StorageStats, TaggedSearchResults, TimelineDay, TokenUsagePoint,
+    ToolFrequencyStats, WeekdayActivity, check_sources_health, detect_source_in_dir, prune_backups, sanitize_fts_query,
+    sanitize_fts_query_any,
 };
 pub use models::*;
@@ -175,4 +175,189 @@ pub const MIGRATIONS: &[Migration] = &[
             CREATE INDEX IF NOT EXISTS idx_session_metrics_provider ON session_metrics(provider);
         "#,
     },
+    Migration {
+        name: "006_attachments",
+        sql: r#"
+            -- Local files (images, documents) referenced by events
+            CREATE TABLE IF NOT EXISTS attachments (
+                id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                event_id TEXT NOT NULL,
+                path TEXT NOT NULL,
+                mime_type TEXT,
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+                FOREIGN KEY (event_id) REFERENCES events(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_attachments_session ON attachments(session_id);
+            CREATE INDEX IF NOT EXISTS idx_attachments_event ON attachments(event_id);
+        "#,
+    },
+    Migration {
+        name: "007_reasoning_tokens",
+        sql: r#"
+            -- Reasoning tokens are billed as output but tracked separately so their cost can be excluded
+            ALTER TABLE session_metrics ADD COLUMN reasoning_tokens INTEGER;
+            ALTER TABLE session_metrics ADD COLUMN reasoning_cost REAL;
+        "#,
+    },
+    Migration {
+        name: "008_event_category",
+        sql: r#"
+            -- Source-specific sub-type (e.g. progress, file-history-snapshot) for events folded into a shared kind
+            ALTER TABLE events ADD COLUMN category TEXT;
+            CREATE INDEX IF NOT EXISTS idx_events_category ON events(category);
+        "#,
+    },
+    Migration {
+        name: "009_tokens_per_line",
+        sql: r#"
+            -- Tokens spent per changed line, NULL for sessions that touched no files
+            ALTER TABLE session_metrics ADD COLUMN tokens_per_line REAL;
+        "#,
+    },
+    Migration {
+        name: "010_event_thinking",
+        sql: r#"
+            -- Assistant reasoning text, indexed on its own so it stays out of regular content search
+            ALTER TABLE events ADD COLUMN thinking TEXT;
+
+            -- Backfill Claude thinking blocks and Codex agent_reasoning messages
+            UPDATE events SET thinking = (
+                SELECT GROUP_CONCAT(json_extract(block.value, '$.thinking'), char(10) || char(10))
+                FROM json_each(events.raw_payload, '$.message.content') AS block
+                WHERE json_extract(block.value, '$.type') = 'thinking'
+                    AND TRIM(COALESCE(json_extract(block.value, '$.thinking'), '')) != ''
+            )
+            WHERE json_valid(raw_payload) AND json_type(raw_payload, '$.message.content') = 'array';
+
+            UPDATE events SET thinking = json_extract(raw_payload, '$.payload.message')
+            WHERE category = 'agent_reasoning' AND json_valid(raw_payload);
+
+            CREATE VIRTUAL TABLE IF NOT EXISTS events_thinking_fts USING fts5(
+                thinking,
+                content='events',
+                content_rowid='rowid',
+                tokenize='porter'
+            );
+
+            INSERT INTO events_thinking_fts(events_thinking_fts) VALUES ('rebuild');
+
+            CREATE TRIGGER IF NOT EXISTS events_thinking_fts_insert AFTER INSERT ON events BEGIN
+                INSERT INTO events_thinking_fts(rowid, thinking) VALUES (new.rowid, new.thinking);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS events_thinking_fts_delete AFTER DELETE ON events BEGIN
+                INSERT INTO events_thinking_fts(events_thinking_fts, rowid, thinking)
+                VALUES ('delete', old.rowid, old.thinking);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS events_thinking_fts_update AFTER UPDATE ON events BEGIN
+                INSERT INTO events_thinking_fts(events_thinking_fts, rowid, thinking)
+                VALUES ('delete', old.rowid, old.thinking);
+                INSERT INTO events_thinking_fts(rowid, thinking) VALUES (new.rowid, new.thinking);
+            END;
+        "#,
+    },
+    Migration {
+        name: "011_event_raw_payload_z",
+        sql: r#"
+            -- zstd-compressed raw payload; when set, raw_payload is left empty
+            ALTER TABLE events ADD COLUMN raw_payload_z BLOB;
+        "#,
+    },
+    Migration {
+        name: "012_ingested_files",
+        sql: r#"
+            -- Session files as of their last successful ingest, so unchanged files can be skipped
+            CREATE TABLE IF NOT EXISTS ingested_files (
+                source TEXT NOT NULL,
+                path TEXT NOT NULL,
+                mtime INTEGER NOT NULL,
+                size INTEGER NOT NULL,
+                ingested_at TEXT NOT NULL,
+                PRIMARY KEY (source, path)
+            );
+        "#,
+    },
+    Migration {
+        name: "013_session_tags",
+        sql: r#"
+            -- Free-form labels users attach to sessions, e.g. bug-triage or spike
+            CREATE TABLE IF NOT EXISTS tags (
+                session_id TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (session_id, tag),
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_tags_tag ON tags(tag);
+        "#,
+    },
+    Migration {
+        name: "014_event_parent_id",
+        sql: r#"
+            -- Event this one follows from, for rebuilding branched conversations
+            ALTER TABLE events ADD COLUMN parent_id TEXT;
+
+            CREATE INDEX IF NOT EXISTS idx_events_parent_id ON events(parent_id);
+        "#,
+    },
+    Migration {
+        name: "015_event_error_signature",
+        sql: r#"
+            -- Normalized error message (paths, IDs, and positions replaced) so recurring errors group together
+            ALTER TABLE events ADD COLUMN signature TEXT;
+        "#,
+    },
+    Migration {
+        name: "016_event_tokens",
+        sql: r#"
+            -- Tokens reported for the API message behind an event, set once per message
+            ALTER TABLE events ADD COLUMN input_tokens INTEGER;
+            ALTER TABLE events ADD COLUMN output_tokens INTEGER;
+        "#,
+    },
+    Migration {
+        name: "017_session_external_id_index",
+        sql: r#"
+            -- Lookups by external ID alone (no source), which UNIQUE(source, external_id) can't serve
+            CREATE INDEX IF NOT EXISTS idx_sessions_external_id ON sessions(external_id);
+        "#,
+    },
+    Migration {
+        name: "018_session_merges",
+        sql: r#"
+            -- Sessions merged into another, so re-ingesting their files updates the session they joined
+            CREATE TABLE IF NOT EXISTS session_merges (
+                source TEXT NOT NULL,
+                external_id TEXT NOT NULL,
+                secondary_id TEXT NOT NULL,
+                primary_id TEXT NOT NULL,
+                merged_at TEXT NOT NULL,
+                PRIMARY KEY (source, external_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_session_merges_primary_id ON session_merges(primary_id);
+
+            -- Merged session an event came from, so a re-ingest replaces only its own session's events
+            ALTER TABLE events ADD COLUMN merged_from TEXT;
+        "#,
+    },
+    Migration {
+        name: "019_ingested_file_options",
+        sql: r#"
+            -- Ingest options that shape the stored events, so changing them re-ingests unchanged files
+            ALTER TABLE ingested_files ADD COLUMN options TEXT NOT NULL DEFAULT '';
+        "#,
+    },
+    Migration {
+        name: "020_null_empty_error_signatures",
+        sql: r#"
+            -- Content-less errors have no signature; earlier inserts stored an empty one. Errors stored
+            -- before 015 still lack one until `recompute` fills it in.
+            UPDATE events SET signature = NULL WHERE signature = '';
+        "#,
+    },
 ];
@@ -1,3 +1,4 @@
+use agent_v_core::SessionId;
 use serde::{Deserialize, Serialize};
 
 /// Database row for a session
@@ -13,16 +14,28 @@ pub struct SessionRow {
     pub raw_payload: String,
 }
 
+impl SessionRow {
+    /// Parse the stored internal ID
+    pub fn session_id(&self) -> Result<SessionId, String> {
+        self.id.parse()
+    }
+}
+
 /// Database row for an event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventRow {
     pub id: String,
     pub session_id: String,
     pub kind: String,
+    /// `user`, `assistant`, `system`, or `tool` for tool output; `None` when the source names no speaker
     pub role: Option<String>,
     pub content: Option<String>,
     pub timestamp: String,
     pub raw_payload: String,
+    /// Source sub-type the event was derived from, e.g. `progress`
+    pub category: Option<String>,
+    /// ID of the event this one follows from, when the source records one
+    pub parent_id: Option<String>,
 }
 
 /// Computed metrics for a session
@@ -50,6 +63,11 @@ pub struct SessionMetricsRow {
     pub avg_latency_ms: Option<f64>,
     pub p50_latency_ms: Option<i64>,
     pub p95_latency_ms: Option<i64>,
+    pub reasoning_tokens: Option<i64>,
+    /// Portion of `estimated_cost` spent on reasoning tokens
+    pub reasoning_cost: Option<f64>,
+    /// Input plus output tokens per line added or removed, `None` when no lines changed
+    pub tokens_per_line: Option<f64>,
 }
 
 /// Database row for a tool call with latency tracking
@@ -77,3 +95,24 @@ pub struct FileTouchedRow {
     pub lines_removed: i64,
     pub touched_at: String,
 }
+
+/// A session with the source file it was parsed from, when its source records one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPathRow {
+    pub id: String,
+    pub source: String,
+    pub external_id: String,
+    /// `file_path` from the session's raw payload (Claude, Codex, and Gemini sessions)
+    pub file_path: Option<String>,
+    pub updated_at: String,
+}
+
+/// Database row for a local file attached to an event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentRow {
+    pub id: String,
+    pub session_id: String,
+    pub event_id: String,
+    pub path: String,
+    pub mime_type: Option<String>,
+}
@@ -22,7 +22,7 @@ pub const LIST_SESSIONS: &str = r#"
         s.updated_at,
         s.raw_payload
     FROM sessions s
-    ORDER BY s.updated_at DESC
+    ORDER BY s.updated_at DESC, s.id
     LIMIT ?1 OFFSET ?2
 "#;
 
@@ -51,7 +51,7 @@ pub const LIST_SESSIONS_FILTERED: &str = r#"
         s.raw_payload
     FROM sessions s
     WHERE (?1 = '' OR s.source = ?1)
-    ORDER BY s.updated_at DESC
+    ORDER BY s.updated_at DESC, s.id
     LIMIT ?2 OFFSET ?3
 "#;
 
@@ -64,7 +64,10 @@ pub const GET_SESSION_EVENTS: &str = r#"
         role,
         content,
         timestamp,
-        raw_payload
+        raw_payload,
+        category,
+        raw_payload_z,
+        parent_id
     FROM events
     WHERE session_id = ?1
     ORDER BY timestamp ASC
@@ -118,12 +121,22 @@ pub const INSERT_SESSION: &str = r#"
 "#;
 
 /// Insert a new event
+///
+/// Event IDs are minted per parse, so a re-ingest deletes the session's old events before
+/// reinserting (see `Database::insert_session_with_events`); `APPEND_EVENTS` is the
+/// duplicate-tolerant variant for incremental updates. `?15` names the merged session the event
+/// came from, if any.
 pub const INSERT_EVENT: &str = r#"
-    INSERT INTO events (id, session_id, kind, role, content, timestamp, raw_payload)
-    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+    INSERT INTO events (id, session_id, kind, role, content, timestamp, raw_payload, category, thinking, raw_payload_z, parent_id, signature, input_tokens, output_tokens, merged_from)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
 "#;
 
 /// Search events using FTS5 with faceted filtering
+///
+/// Snippets wrap matched terms in `[` `]`. The snippet token budget is `?8` when positive,
+/// otherwise it scales with content length between `?9` and `?10` tokens (one token per `?11` chars).
+/// `?12` filters on the event's source category, `?13` on a session tag, `?14` on one session's ID,
+/// and `?15` keeps events before a timestamp.
 pub const SEARCH_EVENTS_FILTERED: &str = r#"
     SELECT
         e.id,
@@ -133,7 +146,14 @@ pub const SEARCH_EVENTS_FILTERED: &str = r#"
         e.content,
         e.timestamp,
         e.raw_payload,
-        rank
+        rank,
+        snippet(events_fts, 0, '[', ']', '…',
+            CASE WHEN ?8 > 0 THEN ?8
+                ELSE MIN(?10, MAX(?9, LENGTH(e.content) / ?11))
+            END),
+        e.category,
+        e.raw_payload_z,
+        e.parent_id
     FROM events_fts f
     JOIN events e ON e.rowid = f.rowid
     JOIN sessions s ON e.session_id = s.id
@@ -142,10 +162,111 @@ pub const SEARCH_EVENTS_FILTERED: &str = r#"
         AND (?3 = '' OR s.project = ?3)
         AND (?4 = '' OR e.kind = ?4)
         AND (?5 = '' OR e.timestamp >= ?5)
+        AND (?12 = '' OR e.category = ?12)
+        AND (?13 = '' OR e.session_id IN (SELECT session_id FROM tags WHERE tag = ?13))
+        AND (?14 = '' OR e.session_id = ?14)
+        AND (?15 = '' OR e.timestamp < ?15)
+    ORDER BY rank
+    LIMIT ?6 OFFSET ?7
+"#;
+
+/// Search assistant reasoning text with the same parameters as [`SEARCH_EVENTS_FILTERED`]
+pub const SEARCH_EVENTS_THINKING: &str = r#"
+    SELECT
+        e.id,
+        e.session_id,
+        e.kind,
+        e.role,
+        e.content,
+        e.timestamp,
+        e.raw_payload,
+        rank,
+        snippet(events_thinking_fts, 0, '[', ']', '…',
+            CASE WHEN ?8 > 0 THEN ?8
+                ELSE MIN(?10, MAX(?9, LENGTH(e.thinking) / ?11))
+            END),
+        e.category,
+        e.raw_payload_z,
+        e.parent_id
+    FROM events_thinking_fts f
+    JOIN events e ON e.rowid = f.rowid
+    JOIN sessions s ON e.session_id = s.id
+    WHERE events_thinking_fts MATCH ?1
+        AND (?2 = '' OR s.source = ?2)
+        AND (?3 = '' OR s.project = ?3)
+        AND (?4 = '' OR e.kind = ?4)
+        AND (?5 = '' OR e.timestamp >= ?5)
+        AND (?12 = '' OR e.category = ?12)
+        AND (?13 = '' OR e.session_id IN (SELECT session_id FROM tags WHERE tag = ?13))
+        AND (?14 = '' OR e.session_id = ?14)
+        AND (?15 = '' OR e.timestamp < ?15)
     ORDER BY rank
     LIMIT ?6 OFFSET ?7
 "#;
 
+/// Count every match of [`SEARCH_EVENTS_FILTERED`], ignoring paging.
+/// Parameters: ?1 query, ?2 source, ?3 project, ?4 kind, ?5 since, ?6 category, ?7 tag, ?8 session ID, ?9 until
+pub const COUNT_SEARCH_EVENTS_FILTERED: &str = r#"
+    SELECT COUNT(*)
+    FROM events_fts f
+    JOIN events e ON e.rowid = f.rowid
+    JOIN sessions s ON e.session_id = s.id
+    WHERE events_fts MATCH ?1
+        AND (?2 = '' OR s.source = ?2)
+        AND (?3 = '' OR s.project = ?3)
+        AND (?4 = '' OR e.kind = ?4)
+        AND (?5 = '' OR e.timestamp >= ?5)
+        AND (?6 = '' OR e.category = ?6)
+        AND (?7 = '' OR e.session_id IN (SELECT session_id FROM tags WHERE tag = ?7))
+        AND (?8 = '' OR e.session_id = ?8)
+        AND (?9 = '' OR e.timestamp < ?9)
+"#;
+
+/// Count every match of [`SEARCH_EVENTS_THINKING`], with the parameters of [`COUNT_SEARCH_EVENTS_FILTERED`]
+pub const COUNT_SEARCH_EVENTS_THINKING: &str = r#"
+    SELECT COUNT(*)
+    FROM events_thinking_fts f
+    JOIN events e ON e.rowid = f.rowid
+    JOIN sessions s ON e.session_id = s.id
+    WHERE events_thinking_fts MATCH ?1
+        AND (?2 = '' OR s.source = ?2)
+        AND (?3 = '' OR s.project = ?3)
+        AND (?4 = '' OR e.kind = ?4)
+        AND (?5 = '' OR e.timestamp >= ?5)
+        AND (?6 = '' OR e.category = ?6)
+        AND (?7 = '' OR e.session_id IN (SELECT session_id FROM tags WHERE tag = ?7))
+        AND (?8 = '' OR e.session_id = ?8)
+        AND (?9 = '' OR e.timestamp < ?9)
+"#;
+
+/// Events with content that pass the search facets, newest first, for scanning outside FTS.
+/// Parameters: ?1 source, ?2 project, ?3 kind, ?4 since, ?5 category, ?6 tag, ?7 session ID, ?8 until
+pub const SCAN_EVENTS_FILTERED: &str = r#"
+    SELECT
+        e.id,
+        e.session_id,
+        e.kind,
+        e.role,
+        e.content,
+        e.timestamp,
+        e.raw_payload,
+        e.category,
+        e.raw_payload_z,
+        e.parent_id
+    FROM events e
+    JOIN sessions s ON e.session_id = s.id
+    WHERE e.content IS NOT NULL AND e.content != ''
+        AND (?1 = '' OR s.source = ?1)
+        AND (?2 = '' OR s.project = ?2)
+        AND (?3 = '' OR e.kind = ?3)
+        AND (?4 = '' OR e.timestamp >= ?4)
+        AND (?5 = '' OR e.category = ?5)
+        AND (?6 = '' OR e.session_id IN (SELECT session_id FROM tags WHERE tag = ?6))
+        AND (?7 = '' OR e.session_id = ?7)
+        AND (?8 = '' OR e.timestamp < ?8)
+    ORDER BY e.timestamp DESC
+"#;
+
 /// Search sessions using FTS5 with faceted filtering
 pub const SEARCH_SESSIONS_FILTERED: &str = r#"
     SELECT
@@ -164,6 +285,9 @@ pub const SEARCH_SESSIONS_FILTERED: &str = r#"
         AND (?2 = '' OR s.source = ?2)
         AND (?3 = '' OR s.project = ?3)
         AND (?4 = '' OR s.created_at >= ?4)
+        AND (?7 = '' OR s.id IN (SELECT session_id FROM tags WHERE tag = ?7))
+        AND (?8 = '' OR s.id = ?8)
+        AND (?9 = '' OR s.created_at < ?9)
     ORDER BY rank
     LIMIT ?5 OFFSET ?6
 "#;
@@ -182,30 +306,70 @@ pub const ACTIVITY_BY_DAY: &str = r#"
     ORDER BY day DESC
 "#;
 
-/// Get error stats by day
+/// Get activity stats by day, broken down by source
+pub const ACTIVITY_BY_DAY_AND_SOURCE: &str = r#"
+    SELECT
+        DATE(e.timestamp) as day,
+        s.source,
+        COUNT(*) as event_count,
+        COUNT(DISTINCT e.session_id) as session_count
+    FROM events e
+    JOIN sessions s ON e.session_id = s.id
+    WHERE (?1 = '' OR e.timestamp >= ?1)
+        AND (?2 = '' OR e.timestamp < ?2)
+    GROUP BY DATE(e.timestamp), s.source
+    ORDER BY day DESC, s.source
+"#;
+
+/// Get activity stats by day of week (0 = Sunday)
+pub const ACTIVITY_BY_WEEKDAY: &str = r#"
+    SELECT
+        CAST(strftime('%w', timestamp) AS INTEGER) as weekday,
+        COUNT(*) as event_count,
+        COUNT(DISTINCT session_id) as session_count
+    FROM events
+    WHERE (?1 = '' OR timestamp >= ?1)
+        AND (?2 = '' OR timestamp < ?2)
+    GROUP BY weekday
+"#;
+
+/// Get error counts per day and signature.
+///
+/// Errors ingested before the `signature` column existed group by their full content instead.
 pub const ERRORS_BY_DAY: &str = r#"
     SELECT
         DATE(timestamp) as day,
         COUNT(*) as error_count,
-        content
+        COALESCE(signature, content) as signature
     FROM events
     WHERE kind = 'error'
         AND (?1 = '' OR timestamp >= ?1)
         AND (?2 = '' OR timestamp < ?2)
-    GROUP BY DATE(timestamp), content
+    GROUP BY DATE(timestamp), COALESCE(signature, content)
     ORDER BY day DESC, error_count DESC
 "#;
 
-/// Get top error signatures
+/// Get error events that have content but no signature yet
+pub const ERRORS_WITHOUT_SIGNATURE: &str = r#"
+    SELECT id, content FROM events
+    WHERE kind = 'error' AND signature IS NULL AND content IS NOT NULL AND content <> ''
+"#;
+
+/// Set an event's error signature
+pub const UPDATE_EVENT_SIGNATURE: &str = r#"
+    UPDATE events SET signature = ?2 WHERE id = ?1
+"#;
+
+/// Get the most common error signatures, falling back to content like [`ERRORS_BY_DAY`]
 pub const TOP_ERROR_SIGNATURES: &str = r#"
     SELECT
-        COALESCE(content, 'Unknown error') as signature,
+        COALESCE(signature, content, 'Unknown error') as signature,
         COUNT(*) as count
     FROM events
     WHERE kind = 'error'
         AND (?1 = '' OR timestamp >= ?1)
         AND (?2 = '' OR timestamp < ?2)
-    GROUP BY content
+    GROUP BY COALESCE(signature, content)
     ORDER BY count DESC
     LIMIT ?3
 "#;
@@ -248,6 +412,58 @@ pub const STATS_BY_TOOL: &str = r#"
     ORDER BY count DESC
 "#;
 
+/// Get event counts by source and kind
+pub const KIND_DISTRIBUTION_BY_SOURCE: &str = r#"
+    SELECT
+        s.source,
+        e.kind,
+        COUNT(*) as count
+    FROM events e
+    JOIN sessions s ON e.session_id = s.id
+    WHERE (?1 = '' OR e.timestamp >= ?1)
+        AND (?2 = '' OR e.timestamp < ?2)
+    GROUP BY s.source, e.kind
+    ORDER BY s.source, e.kind
+"#;
+
+/// Count a session's events by kind
+pub const SESSION_KIND_COUNTS: &str = r#"
+    SELECT kind, COUNT(*) as count
+    FROM events
+    WHERE session_id = ?1
+    GROUP BY kind
+"#;
+
+/// Get stored bytes per source; `CAST AS BLOB` makes `LENGTH` count bytes rather than characters,
+/// and compressed payloads count at their compressed size
+pub const STORAGE_BY_SOURCE: &str = r#"
+    SELECT
+        s.source,
+        COUNT(DISTINCT s.id) as session_count,
+        COUNT(e.id) as event_count,
+        COALESCE(SUM(LENGTH(CAST(e.content AS BLOB))), 0) as content_bytes,
+        COALESCE(SUM(LENGTH(CAST(e.raw_payload AS BLOB)) + COALESCE(LENGTH(e.raw_payload_z), 0)), 0) as raw_bytes
+    FROM sessions s
+    LEFT JOIN events e ON e.session_id = s.id
+    GROUP BY s.source
+    ORDER BY raw_bytes DESC
+"#;
+
+/// Get the sessions with the most stored bytes
+pub const STORAGE_BY_SESSION: &str = r#"
+    SELECT
+        s.id,
+        s.source,
+        COUNT(e.id) as event_count,
+        COALESCE(SUM(LENGTH(CAST(e.content AS BLOB))), 0) as content_bytes,
+        COALESCE(SUM(LENGTH(CAST(e.raw_payload AS BLOB)) + COALESCE(LENGTH(e.raw_payload_z), 0)), 0) as raw_bytes
+    FROM sessions s
+    LEFT JOIN events e ON e.session_id = s.id
+    GROUP BY s.id
+    ORDER BY raw_bytes DESC
+    LIMIT ?1
+"#;
+
 /// Get distinct sources for faceting
 pub const GET_SOURCES: &str = r#"
     SELECT DISTINCT source FROM sessions ORDER BY source
@@ -270,8 +486,12 @@ pub const UPSERT_SESSION_METRICS: &str = r#"
         error_count, user_messages, assistant_messages, duration_seconds,
         files_touched, lines_added, lines_removed, computed_at,
         model, provider, input_tokens, output_tokens, estimated_cost,
-        total_latency_ms, avg_latency_ms, p50_latency_ms, p95_latency_ms
-    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)
+        total_latency_ms, avg_latency_ms, p50_latency_ms, p95_latency_ms,
+        reasoning_tokens, reasoning_cost, tokens_per_line
+    ) VALUES (
+        ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24,
+        ?25
+    )
     ON CONFLICT(session_id) DO UPDATE SET
         total_events = excluded.total_events,
         message_count = excluded.message_count,
@@ -293,7 +513,10 @@ pub const UPSERT_SESSION_METRICS: &str = r#"
         total_latency_ms = excluded.total_latency_ms,
         avg_latency_ms = excluded.avg_latency_ms,
         p50_latency_ms = excluded.p50_latency_ms,
-        p95_latency_ms = excluded.p95_latency_ms
+        p95_latency_ms = excluded.p95_latency_ms,
+        reasoning_tokens = excluded.reasoning_tokens,
+        reasoning_cost = excluded.reasoning_cost,
+        tokens_per_line = excluded.tokens_per_line
 "#;
 
 /// Insert a tool call record
@@ -318,6 +541,53 @@ pub const INSERT_FILE_TOUCHED: &str = r#"
         lines_removed = excluded.lines_removed
 "#;
 
+/// Delete files touched for a session
+pub const DELETE_FILES_TOUCHED_BY_SESSION_ID: &str = r#"
+    DELETE FROM files_touched WHERE session_id = ?1
+"#;
+
+pub const DELETE_TOOL_CALLS_BY_SESSION_ID: &str = r#"
+    DELETE FROM tool_calls WHERE session_id = ?1
+"#;
+
+/// Get a session's tool calls in the order they were made
+pub const GET_SESSION_TOOL_CALLS: &str = r#"
+    SELECT id, session_id, event_id, tool_name, started_at, completed_at, duration_ms, success, error_message
+    FROM tool_calls
+    WHERE session_id = ?1
+    ORDER BY started_at ASC
+"#;
+
+/// Get files touched for a session in chronological order
+pub const GET_SESSION_FILES_TOUCHED: &str = r#"
+    SELECT id, session_id, file_path, operation, lines_added, lines_removed, touched_at
+    FROM files_touched
+    WHERE session_id = ?1
+    ORDER BY touched_at ASC, file_path ASC
+"#;
+
+/// Get sessions with files_touched rows matching a path pattern, with match counts and line totals.
+/// `?1` is a GLOB pattern when `?2` is 1, otherwise a LIKE pattern escaped with `\`.
+pub const SESSIONS_BY_FILE: &str = r#"
+    SELECT
+        s.id,
+        s.source,
+        s.project,
+        s.title,
+        s.updated_at,
+        COUNT(*) as match_count,
+        COUNT(DISTINCT f.file_path) as file_count,
+        COALESCE(SUM(f.lines_added), 0) as lines_added,
+        COALESCE(SUM(f.lines_removed), 0) as lines_removed
+    FROM files_touched f
+    JOIN sessions s ON s.id = f.session_id
+    WHERE (?2 = 1 AND f.file_path GLOB ?1)
+        OR (?2 = 0 AND f.file_path LIKE ?1 ESCAPE '\')
+    GROUP BY s.id
+    ORDER BY s.updated_at DESC
+    LIMIT ?3
+"#;
+
 /// Get tool call frequency stats
 pub const TOOL_CALL_FREQUENCY: &str = r#"
     SELECT
@@ -349,6 +619,14 @@ pub const FILES_TOUCHED_LEADERBOARD: &str = r#"
     LIMIT ?3
 "#;
 
+/// Get individual file touches in a date range, for leaderboards grouped outside SQL
+pub const FILES_TOUCHED_IN_RANGE: &str = r#"
+    SELECT file_path, session_id, lines_added, lines_removed
+    FROM files_touched
+    WHERE (?1 = '' OR touched_at >= ?1)
+        AND (?2 = '' OR touched_at < ?2)
+"#;
+
 /// Get patch churn stats (lines added/removed) by day
 pub const PATCH_CHURN_BY_DAY: &str = r#"
     SELECT
@@ -390,7 +668,8 @@ pub const GET_SESSION_METRICS: &str = r#"
         error_count, user_messages, assistant_messages, duration_seconds,
         files_touched, lines_added, lines_removed, computed_at,
         model, provider, input_tokens, output_tokens, estimated_cost,
-        total_latency_ms, avg_latency_ms, p50_latency_ms, p95_latency_ms
+        total_latency_ms, avg_latency_ms, p50_latency_ms, p95_latency_ms,
+        reasoning_tokens, reasoning_cost, tokens_per_line
     FROM session_metrics
     WHERE session_id = ?1
 "#;
@@ -414,11 +693,207 @@ pub const GET_SESSION_ID_BY_SOURCE_AND_EXTERNAL_ID: &str = r#"
     SELECT id FROM sessions WHERE source = ?1 AND external_id = ?2
 "#;
 
+/// Get every stored external ID for a source
+pub const GET_EXTERNAL_IDS_BY_SOURCE: &str = r#"
+    SELECT external_id FROM sessions WHERE source = ?1
+"#;
+
+/// Find sessions by external ID, optionally scoped to a source
+pub const FIND_SESSIONS_BY_EXTERNAL_ID: &str = r#"
+    SELECT id, source, external_id, project, title, created_at, updated_at, raw_payload
+    FROM sessions
+    WHERE external_id = ?1
+        AND (?2 = '' OR source = ?2)
+    ORDER BY source
+"#;
+
+/// Sessions by internal ID or external ID, optionally scoped to a source, internal ID match first
+pub const FIND_SESSIONS_BY_ID_OR_EXTERNAL_ID: &str = r#"
+    SELECT id, source, external_id, project, title, created_at, updated_at, raw_payload
+    FROM sessions
+    WHERE (id = ?1 OR external_id = ?1)
+        AND (?2 = '' OR source = ?2)
+    ORDER BY id = ?1 DESC, source
+"#;
+
+/// External IDs stored under more than one source
+pub const EXTERNAL_ID_COLLISIONS: &str = r#"
+    SELECT external_id, GROUP_CONCAT(source, ',')
+    FROM (SELECT DISTINCT external_id, source FROM sessions ORDER BY source)
+    GROUP BY external_id
+    HAVING COUNT(*) > 1
+    ORDER BY external_id
+"#;
+
 /// Delete events for a session
 pub const DELETE_EVENTS_BY_SESSION_ID: &str = r#"
     DELETE FROM events WHERE session_id = ?1
 "#;
 
+/// Delete the events session `?1` holds from one origin: its own parse when `?2` is NULL,
+/// otherwise those of the merged session `?2`
+pub const DELETE_EVENTS_BY_ORIGIN: &str = r#"
+    DELETE FROM events WHERE session_id = ?1 AND merged_from IS ?2
+"#;
+
+/// Delete computed metrics for a session
+pub const DELETE_SESSION_METRICS_BY_SESSION_ID: &str = r#"
+    DELETE FROM session_metrics WHERE session_id = ?1
+"#;
+
+/// List sessions with the source file recorded in their raw payload, oldest update first.
+/// `?1` filters on source when non-empty.
+pub const LIST_SESSION_PATHS: &str = r#"
+    SELECT
+        id,
+        source,
+        external_id,
+        CASE WHEN json_valid(raw_payload) THEN json_extract(raw_payload, '$.file_path') END,
+        updated_at
+    FROM sessions
+    WHERE ?1 = '' OR source = ?1
+    ORDER BY updated_at ASC, id
+"#;
+
+/// Tag a session; tagging it twice with the same tag is a no-op
+pub const INSERT_TAG: &str = r#"
+    INSERT OR IGNORE INTO tags (session_id, tag) VALUES (?1, ?2)
+"#;
+
+/// Remove one tag from a session
+pub const DELETE_TAG: &str = r#"
+    DELETE FROM tags WHERE session_id = ?1 AND tag = ?2
+"#;
+
+/// Delete every tag of a session
+pub const DELETE_TAGS_BY_SESSION_ID: &str = r#"
+    DELETE FROM tags WHERE session_id = ?1
+"#;
+
+/// Get a session's tags in alphabetical order
+pub const GET_SESSION_TAGS: &str = r#"
+    SELECT tag FROM tags WHERE session_id = ?1 ORDER BY tag
+"#;
+
+/// List every tag in use with the number of sessions carrying it
+pub const LIST_TAGS: &str = r#"
+    SELECT tag, COUNT(*) FROM tags GROUP BY tag ORDER BY tag
+"#;
+
+/// List sessions carrying a tag, most recently updated first
+pub const LIST_SESSIONS_BY_TAG: &str = r#"
+    SELECT s.id, s.source, s.external_id, s.project, s.title, s.created_at, s.updated_at, s.raw_payload
+    FROM sessions s
+    JOIN tags t ON t.session_id = s.id
+    WHERE t.tag = ?1
+    ORDER BY s.updated_at DESC, s.id
+"#;
+
+/// Delete a session row
+pub const DELETE_SESSION: &str = r#"
+    DELETE FROM sessions WHERE id = ?1
+"#;
+
+/// Move every event of session `?2` into session `?1`, marking the ones `?2` parsed itself as
+/// merged from it
+pub const MOVE_EVENTS_TO_SESSION: &str = r#"
+    UPDATE events SET session_id = ?1, merged_from = COALESCE(merged_from, ?2) WHERE session_id = ?2
+"#;
+
+/// Move every attachment of session `?2` into session `?1`
+pub const MOVE_ATTACHMENTS_TO_SESSION: &str = r#"
+    UPDATE attachments SET session_id = ?1 WHERE session_id = ?2
+"#;
+
+/// Give session `?1` every tag of session `?2` it doesn't already have
+pub const COPY_TAGS_TO_SESSION: &str = r#"
+    INSERT OR IGNORE INTO tags (session_id, tag, created_at)
+    SELECT ?1, tag, created_at FROM tags WHERE session_id = ?2
+"#;
+
+/// Widen session `?1`'s time span to cover session `?2`'s
+pub const WIDEN_SESSION_SPAN: &str = r#"
+    UPDATE sessions SET
+        created_at = MIN(created_at, (SELECT created_at FROM sessions WHERE id = ?2)),
+        updated_at = MAX(updated_at, (SELECT updated_at FROM sessions WHERE id = ?2))
+    WHERE id = ?1
+"#;
+
+/// Widen session `?1`'s time span to cover `?2` through `?3`
+pub const WIDEN_SESSION_SPAN_TO: &str = r#"
+    UPDATE sessions SET created_at = MIN(created_at, ?2), updated_at = MAX(updated_at, ?3) WHERE id = ?1
+"#;
+
+/// Record that session `?2` was merged into `?1` at `?3`, keyed by `?2`'s source and external ID
+pub const RECORD_SESSION_MERGE: &str = r#"
+    INSERT INTO session_merges (source, external_id, secondary_id, primary_id, merged_at)
+    SELECT source, external_id, id, ?1, ?3 FROM sessions WHERE id = ?2
+    ON CONFLICT(source, external_id) DO UPDATE SET
+        secondary_id = excluded.secondary_id,
+        primary_id = excluded.primary_id,
+        merged_at = excluded.merged_at
+"#;
+
+/// Point the merges into session `?2` at session `?1` instead
+pub const REDIRECT_SESSION_MERGES: &str = r#"
+    UPDATE session_merges SET primary_id = ?1 WHERE primary_id = ?2
+"#;
+
+/// Get the session a `(source, external_id)` was merged into, with the merged session's old ID,
+/// provided the session it joined still exists
+pub const GET_SESSION_MERGE: &str = r#"
+    SELECT m.secondary_id, m.primary_id
+    FROM session_merges m
+    JOIN sessions s ON s.id = m.primary_id
+    WHERE m.source = ?1 AND m.external_id = ?2
+"#;
+
+/// Forget the merges into a session
+pub const DELETE_SESSION_MERGES_BY_PRIMARY: &str = r#"
+    DELETE FROM session_merges WHERE primary_id = ?1
+"#;
+
+/// Forget the ingest stamp of session `?1`'s source file, so the next ingest parses it again.
+/// Sessions read from a shared database (Crush) are stamped as `<db_path>#<session_id>` instead.
+pub const DELETE_INGESTED_FILE_BY_SESSION_ID: &str = r#"
+    DELETE FROM ingested_files
+    WHERE (source, path) IN (
+        SELECT source, json_extract(raw_payload, '$.file_path')
+        FROM sessions
+        WHERE id = ?1 AND json_valid(raw_payload)
+        UNION ALL
+        SELECT source, json_extract(raw_payload, '$.db_path') || '#' || json_extract(raw_payload, '$.session_id')
+        FROM sessions
+        WHERE id = ?1 AND json_valid(raw_payload)
+    )
+"#;
+
+/// Insert an attachment reference for an event
+pub const INSERT_ATTACHMENT: &str = r#"
+    INSERT INTO attachments (id, session_id, event_id, path, mime_type)
+    VALUES (?1, ?2, ?3, ?4, ?5)
+"#;
+
+/// Delete attachments for a session
+pub const DELETE_ATTACHMENTS_BY_SESSION_ID: &str = r#"
+    DELETE FROM attachments WHERE session_id = ?1
+"#;
+
+/// Delete the attachments of the events `DELETE_EVENTS_BY_ORIGIN` removes
+pub const DELETE_ATTACHMENTS_BY_ORIGIN: &str = r#"
+    DELETE FROM attachments
+    WHERE event_id IN (SELECT id FROM events WHERE session_id = ?1 AND merged_from IS ?2)
+"#;
+
+/// Get attachments for a session in event order
+pub const GET_SESSION_ATTACHMENTS: &str = r#"
+    SELECT a.id, a.session_id, a.event_id, a.path, a.mime_type
+    FROM attachments a
+    JOIN events e ON a.event_id = e.id
+    WHERE a.session_id = ?1
+    ORDER BY e.timestamp ASC, a.path ASC
+"#;
+
 /// Get sessions with their metrics for export
 pub const GET_SESSIONS_WITH_METRICS: &str = r#"
     SELECT
@@ -427,20 +902,21 @@ pub const GET_SESSIONS_WITH_METRICS: &str = r#"
         m.error_count, m.user_messages, m.assistant_messages, m.duration_seconds,
         m.files_touched, m.lines_added, m.lines_removed, m.computed_at,
         m.model, m.provider, m.input_tokens, m.output_tokens, m.estimated_cost,
-        m.total_latency_ms, m.avg_latency_ms, m.p50_latency_ms, m.p95_latency_ms
+        m.total_latency_ms, m.avg_latency_ms, m.p50_latency_ms, m.p95_latency_ms,
+        m.reasoning_tokens, m.reasoning_cost, m.tokens_per_line
     FROM sessions s
     LEFT JOIN session_metrics m ON s.id = m.session_id
     ORDER BY s.updated_at DESC
     LIMIT ?1 OFFSET ?2
 "#;
 
-/// Get cost stats by source
+/// Get cost stats by source; when `?4` is true, reasoning-token cost is left out; `?5` is a JSON array of models
 pub const COST_STATS_BY_SOURCE: &str = r#"
     SELECT
         s.source,
         COUNT(DISTINCT m.session_id) as session_count,
-        SUM(m.estimated_cost) as total_cost,
-        AVG(m.estimated_cost) as avg_cost_per_session,
+        SUM(m.estimated_cost - CASE WHEN ?4 THEN COALESCE(m.reasoning_cost, 0) ELSE 0 END) as total_cost,
+        AVG(m.estimated_cost - CASE WHEN ?4 THEN COALESCE(m.reasoning_cost, 0) ELSE 0 END) as avg_cost_per_session,
         SUM(m.input_tokens) as total_input_tokens,
         SUM(m.output_tokens) as total_output_tokens,
         AVG(m.avg_latency_ms) as avg_latency_ms,
@@ -451,17 +927,18 @@ pub const COST_STATS_BY_SOURCE: &str = r#"
     WHERE (?1 = '' OR s.source = ?1)
         AND (?2 = '' OR m.computed_at >= ?2)
         AND (?3 = '' OR m.computed_at < ?3)
+        AND (?5 = '' OR m.model IN (SELECT value FROM json_each(?5)))
     GROUP BY s.source
     ORDER BY total_cost DESC
 "#;
 
-/// Get cost stats by project
+/// Get cost stats by project; when `?4` is true, reasoning-token cost is left out; `?5` is a JSON array of models
 pub const COST_STATS_BY_PROJECT: &str = r#"
     SELECT
         COALESCE(s.project, 'Unknown') as project,
         COUNT(DISTINCT m.session_id) as session_count,
-        SUM(m.estimated_cost) as total_cost,
-        AVG(m.estimated_cost) as avg_cost_per_session,
+        SUM(m.estimated_cost - CASE WHEN ?4 THEN COALESCE(m.reasoning_cost, 0) ELSE 0 END) as total_cost,
+        AVG(m.estimated_cost - CASE WHEN ?4 THEN COALESCE(m.reasoning_cost, 0) ELSE 0 END) as avg_cost_per_session,
         SUM(m.input_tokens) as total_input_tokens,
         SUM(m.output_tokens) as total_output_tokens,
         AVG(m.avg_latency_ms) as avg_latency_ms,
@@ -472,6 +949,7 @@ pub const COST_STATS_BY_PROJECT: &str = r#"
     WHERE (?1 = '' OR s.source = ?1)
         AND (?2 = '' OR m.computed_at >= ?2)
         AND (?3 = '' OR m.computed_at < ?3)
+        AND (?5 = '' OR m.model IN (SELECT value FROM json_each(?5)))
     GROUP BY s.project
     ORDER BY total_cost DESC
 "#;
@@ -500,6 +978,26 @@ pub const COST_STATS_BY_SESSION: &str = r#"
     LIMIT ?4 OFFSET ?5
 "#;
 
+/// Get sessions by tokens spent per changed line, most economical first
+pub const SESSIONS_BY_TOKENS_PER_LINE: &str = r#"
+    SELECT
+        s.id,
+        s.external_id,
+        s.project,
+        s.source,
+        COALESCE(m.input_tokens, 0) + COALESCE(m.output_tokens, 0) as total_tokens,
+        m.lines_added + m.lines_removed as lines_changed,
+        m.tokens_per_line
+    FROM session_metrics m
+    JOIN sessions s ON m.session_id = s.id
+    WHERE m.tokens_per_line IS NOT NULL
+        AND (?1 = '' OR s.source = ?1)
+        AND (?2 = '' OR m.computed_at >= ?2)
+        AND (?3 = '' OR m.computed_at < ?3)
+    ORDER BY m.tokens_per_line ASC
+    LIMIT ?4
+"#;
+
 /// Get latency distribution stats (p50, p95, p99)
 pub const LATENCY_DISTRIBUTION: &str = r#"
     SELECT
@@ -516,7 +1014,7 @@ pub const LATENCY_DISTRIBUTION: &str = r#"
         AND m.p95_latency_ms IS NOT NULL
 "#;
 
-/// Get model/provider usage stats
+/// Get model/provider usage stats; `?4` is a JSON array of models to keep
 pub const MODEL_USAGE_STATS: &str = r#"
     SELECT
         COALESCE(m.model, 'unknown') as model,
@@ -531,14 +1029,34 @@ pub const MODEL_USAGE_STATS: &str = r#"
     WHERE (?1 = '' OR s.source = ?1)
         AND (?2 = '' OR m.computed_at >= ?2)
         AND (?3 = '' OR m.computed_at < ?3)
+        AND (?4 = '' OR m.model IN (SELECT value FROM json_each(?4)))
     GROUP BY m.model, m.provider
     ORDER BY total_cost DESC NULLS LAST
 "#;
 
+/// Get every distinct model recorded in session metrics
+pub const DISTINCT_METRIC_MODELS: &str = r#"
+    SELECT DISTINCT model FROM session_metrics WHERE model IS NOT NULL
+"#;
+
 /// Append events to an existing session (without deleting existing events)
 pub const APPEND_EVENTS: &str = r#"
-    INSERT OR IGNORE INTO events (id, session_id, kind, role, content, timestamp, raw_payload)
-    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+    INSERT OR IGNORE INTO events (id, session_id, kind, role, content, timestamp, raw_payload, category, thinking, raw_payload_z, parent_id, signature, input_tokens, output_tokens)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+"#;
+
+/// Payloads of a session's events that carry token counts
+pub const TOKEN_EVENT_PAYLOADS: &str = r#"
+    SELECT raw_payload, raw_payload_z FROM events
+    WHERE session_id = ?1 AND (input_tokens IS NOT NULL OR output_tokens IS NOT NULL)
+"#;
+
+/// Token counts of a session's events in order
+pub const GET_TOKEN_TIMELINE: &str = r#"
+    SELECT id, timestamp, COALESCE(input_tokens, 0), COALESCE(output_tokens, 0)
+    FROM events
+    WHERE session_id = ?1 AND (input_tokens IS NOT NULL OR output_tokens IS NOT NULL)
+    ORDER BY timestamp ASC, rowid ASC
 "#;
 
 /// Update a session's updated_at timestamp
@@ -571,10 +1089,27 @@ pub const EFFICIENCY_STATS: &str = r#"
          AND (?2 = '' OR m2.computed_at >= ?2)
          AND (?3 = '' OR m2.computed_at < ?3)) as retry_loops,
         COALESCE(AVG(m.p50_latency_ms), 0.0) as p50_latency_ms,
-        COALESCE(AVG(m.p95_latency_ms), 0.0) as p95_latency_ms
+        COALESCE(AVG(m.p95_latency_ms), 0.0) as p95_latency_ms,
+        AVG(m.tokens_per_line) as avg_tokens_per_line
     FROM session_metrics m
     JOIN sessions s ON m.session_id = s.id
     WHERE (?1 = '' OR s.source = ?1)
         AND (?2 = '' OR m.computed_at >= ?2)
         AND (?3 = '' OR m.computed_at < ?3)
 "#;
+
+/// Get the modification time (ms), size, and ingest options recorded for an ingested session file
+pub const GET_INGESTED_FILE: &str = r#"
+    SELECT mtime, size, options FROM ingested_files WHERE source = ?1 AND path = ?2
+"#;
+
+/// Record a session file's modification time, size, and ingest options after it was ingested
+pub const UPSERT_INGESTED_FILE: &str = r#"
+    INSERT INTO ingested_files (source, path, mtime, size, ingested_at, options)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+    ON CONFLICT(source, path) DO UPDATE SET
+        mtime = excluded.mtime,
+        size = excluded.size,
+        ingested_at = excluded.ingested_at,
+        options = excluded.options
+"#;
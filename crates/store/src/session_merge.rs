@@ -25,7 +25,7 @@
 //! This is designed for historical data repair where prior schema versions
 //! or import bugs may have produced duplicate session rows.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 /// Session candidate metadata used by the merge planner.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -45,6 +45,7 @@ pub struct MergeEvent {
     pub content: Option<String>,
     pub timestamp: String,
     pub raw_payload: String,
+    pub parent_id: Option<String>,
 }
 
 /// Event that should be inserted into the canonical session.
@@ -56,6 +57,8 @@ pub struct MergeEventInsert {
     pub content: Option<String>,
     pub timestamp: String,
     pub raw_payload: String,
+    /// Parent event, pointed at the merged copy when the parent was a donor event
+    pub parent_id: Option<String>,
 }
 
 /// Transaction-ready merge plan for one duplicate `(source, external_id)` group.
@@ -98,9 +101,14 @@ pub fn build_merge_plan(
         .to_string();
 
     let keep_events = events_by_session.get(&keep.id).cloned().unwrap_or_default();
-    let mut seen_fingerprints: HashSet<String> = keep_events.iter().map(event_fingerprint).collect();
+    let mut seen_fingerprints: HashMap<String, String> = keep_events
+        .iter()
+        .map(|e| (event_fingerprint(e), e.id.clone()))
+        .collect();
 
     let mut events_to_insert: Vec<MergeEventInsert> = Vec::new();
+    // Donor event ID -> ID of the event that stands in for it after the merge
+    let mut renamed: HashMap<String, String> = HashMap::new();
 
     for donor in &donors {
         let mut donor_events = events_by_session.get(&donor.id).cloned().unwrap_or_default();
@@ -112,20 +120,30 @@ pub fn build_merge_plan(
 
         for donor_event in donor_events {
             let fingerprint = event_fingerprint(&donor_event);
-            if !seen_fingerprints.insert(fingerprint) {
+            if let Some(existing) = seen_fingerprints.get(&fingerprint) {
+                renamed.insert(donor_event.id, existing.clone());
                 continue;
             }
 
+            let id = uuid::Uuid::new_v4().to_string();
+            seen_fingerprints.insert(fingerprint, id.clone());
+            renamed.insert(donor_event.id, id.clone());
             events_to_insert.push(MergeEventInsert {
-                id: uuid::Uuid::new_v4().to_string(),
+                id,
                 kind: donor_event.kind,
                 role: donor_event.role,
                 content: donor_event.content,
                 timestamp: donor_event.timestamp,
                 raw_payload: donor_event.raw_payload,
+                parent_id: donor_event.parent_id,
             });
         }
     }
+    for event in &mut events_to_insert {
+        if let Some(parent) = event.parent_id.as_ref().and_then(|p| renamed.get(p)) {
+            event.parent_id = Some(parent.clone());
+        }
+    }
 
     Some(SessionMergePlan {
         keep_session_id: keep.id,
@@ -208,6 +226,7 @@ mod tests {
             content: Some(content.to_string()),
             timestamp: ts.to_string(),
             raw_payload: raw.to_string(),
+            parent_id: None,
         }
     }
 
@@ -330,6 +349,40 @@ mod tests {
         assert_ne!(plan.events_to_insert[0].id, donor_event_id);
     }
 
+    #[test]
+    fn donor_parent_links_follow_their_parents_into_the_kept_session() {
+        let sessions = vec![
+            session("keep", "2026-03-01T00:00:00Z", "2026-03-02T00:00:00Z", 4),
+            session("dup", "2026-03-01T00:00:00Z", "2026-03-02T00:00:00Z", 3),
+        ];
+        let child = |id: &str, content: &str, ts: &str, parent: &str| MergeEvent {
+            parent_id: Some(parent.to_string()),
+            ..event(id, "message", content, ts, "{}")
+        };
+
+        let mut events_by_session = HashMap::new();
+        events_by_session.insert(
+            "keep".to_string(),
+            vec![event("keep-1", "message", "hello", "2026-03-01T00:00:00Z", "{}")],
+        );
+        events_by_session.insert(
+            "dup".to_string(),
+            vec![
+                event("dup-1", "message", "hello", "2026-03-01T00:00:00Z", "{}"),
+                child("dup-2", "reply", "2026-03-01T00:01:00Z", "dup-1"),
+                child("dup-3", "follow-up", "2026-03-01T00:02:00Z", "dup-2"),
+            ],
+        );
+
+        let plan = build_merge_plan(&sessions, &events_by_session).expect("plan should exist");
+        assert_eq!(plan.events_to_insert.len(), 2);
+        assert_eq!(plan.events_to_insert[0].parent_id.as_deref(), Some("keep-1"));
+        assert_eq!(
+            plan.events_to_insert[1].parent_id.as_deref(),
+            Some(plan.events_to_insert[0].id.as_str())
+        );
+    }
+
     #[test]
     fn falls_back_to_raw_payload_string_when_json_is_invalid() {
         let sessions = vec![
@@ -0,0 +1,366 @@
+//! Tool call extraction from stored event payloads.
+//!
+//! Each source records tool use differently, and only the raw payloads keep the IDs that tie a
+//! call to its result:
+//! - Claude: `tool_use` / `tool_result` blocks in `message.content`, paired by `tool_use_id`
+//! - Codex: `function_call` / `function_call_output` response items, paired by `call_id`
+//! - Crush: `tool_use` / `tool_result` entries in the JSON-encoded `parts`, paired by `tool_use_id`
+//! - OpenCode: a single `tool` part carrying the call, its status, and its start/end times
+//!
+//! Like [`crate::session_merge`], this module is pure: it turns a session's events into
+//! `tool_calls` rows and leaves reading and writing the database to [`crate::Database`].
+
+use crate::models::EventRow;
+use agent_v_core::ToolOutcome;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// A row for the `tool_calls` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCallRecord {
+    pub id: String,
+    /// Event that made the call
+    pub event_id: String,
+    pub tool_name: String,
+    pub started_at: String,
+    pub completed_at: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub success: Option<bool>,
+    pub error_message: Option<String>,
+}
+
+/// A tool invocation found in an event payload
+struct ToolUse {
+    call_id: String,
+    name: String,
+}
+
+/// A tool result found in an event payload
+struct ToolResult {
+    call_id: String,
+    is_error: bool,
+}
+
+/// Build `tool_calls` rows for a session's events (in timestamp order), pairing each call with
+/// the first later result that carries its call ID.
+///
+/// Results whose call isn't in the session still get a row when the adapter recorded a
+/// [`ToolOutcome`] for them, so shell exit codes and durations are never lost.
+pub fn extract_tool_calls(events: &[EventRow]) -> Vec<ToolCallRecord> {
+    let mut records: Vec<ToolCallRecord> = Vec::new();
+    let mut pending: HashMap<String, usize> = HashMap::new();
+
+    for event in events {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(&event.raw_payload) else {
+            continue;
+        };
+
+        if let Some(record) = opencode_tool_part(event, &payload) {
+            records.push(record);
+            continue;
+        }
+
+        for tool_use in tool_uses(&payload) {
+            pending.insert(tool_use.call_id.clone(), records.len());
+            records.push(ToolCallRecord {
+                id: format!("{}:{}", event.id, tool_use.call_id),
+                event_id: event.id.clone(),
+                tool_name: tool_use.name,
+                started_at: event.timestamp.clone(),
+                completed_at: None,
+                duration_ms: None,
+                success: None,
+                error_message: None,
+            });
+        }
+
+        let outcome = ToolOutcome::from_payload(&payload);
+        let mut paired = false;
+        for result in tool_results(&payload) {
+            if let Some(idx) = pending.remove(&result.call_id) {
+                complete(&mut records[idx], event, &result, outcome.as_ref());
+                paired = true;
+            }
+        }
+        if !paired && let Some(outcome) = &outcome {
+            records.push(unpaired_outcome(event, outcome));
+        }
+    }
+
+    records
+}
+
+/// Fill in a call's completion from its result event
+fn complete(record: &mut ToolCallRecord, result_event: &EventRow, result: &ToolResult, outcome: Option<&ToolOutcome>) {
+    let elapsed = match (
+        parse_timestamp(&record.started_at),
+        parse_timestamp(&result_event.timestamp),
+    ) {
+        (Some(started), Some(completed)) => Some((completed - started).num_milliseconds().max(0)),
+        _ => None,
+    };
+
+    record.completed_at = Some(result_event.timestamp.clone());
+    record.duration_ms = outcome.and_then(|o| o.duration_ms).or(elapsed);
+    record.success = Some(outcome.map_or(!result.is_error, |o| o.success && !result.is_error));
+    record.error_message = outcome.and_then(|o| o.error_message.clone());
+}
+
+/// A row for a result whose call isn't in the session, timed by its recorded outcome
+fn unpaired_outcome(event: &EventRow, outcome: &ToolOutcome) -> ToolCallRecord {
+    let started_at = match (parse_timestamp(&event.timestamp), outcome.duration_ms) {
+        (Some(completed), Some(ms)) => (completed - chrono::Duration::milliseconds(ms)).to_rfc3339(),
+        _ => event.timestamp.clone(),
+    };
+    ToolCallRecord {
+        id: event.id.clone(),
+        event_id: event.id.clone(),
+        tool_name: outcome.tool_name.clone().unwrap_or_else(|| "unknown".to_string()),
+        started_at,
+        completed_at: Some(event.timestamp.clone()),
+        duration_ms: outcome.duration_ms,
+        success: Some(outcome.success),
+        error_message: outcome.error_message.clone(),
+    }
+}
+
+/// Tool calls made in a Claude, Codex, or Crush payload
+fn tool_uses(payload: &serde_json::Value) -> Vec<ToolUse> {
+    if let Some(item) = payload.get("payload")
+        && item.get("type").and_then(|t| t.as_str()) == Some("function_call")
+    {
+        return item
+            .get("call_id")
+            .and_then(|id| id.as_str())
+            .map(|call_id| ToolUse {
+                call_id: call_id.to_string(),
+                name: str_field(item, "name").unwrap_or("unknown").to_string(),
+            })
+            .into_iter()
+            .collect();
+    }
+
+    blocks(payload)
+        .into_iter()
+        .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+        .filter_map(|block| {
+            let data = block.get("data").unwrap_or(&block);
+            Some(ToolUse {
+                call_id: str_field(data, "id")?.to_string(),
+                name: str_field(data, "name").unwrap_or("unknown").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Tool results reported in a Claude, Codex, or Crush payload
+fn tool_results(payload: &serde_json::Value) -> Vec<ToolResult> {
+    if let Some(item) = payload.get("payload")
+        && item.get("type").and_then(|t| t.as_str()) == Some("function_call_output")
+    {
+        return str_field(item, "call_id")
+            .map(|call_id| ToolResult { call_id: call_id.to_string(), is_error: false })
+            .into_iter()
+            .collect();
+    }
+
+    blocks(payload)
+        .into_iter()
+        .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("tool_result"))
+        .filter_map(|block| {
+            let data = block.get("data").unwrap_or(&block);
+            Some(ToolResult {
+                call_id: str_field(data, "tool_use_id")?.to_string(),
+                is_error: data.get("is_error").and_then(|e| e.as_bool()).unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+/// Content blocks of a Claude message or the parts of a Crush message
+fn blocks(payload: &serde_json::Value) -> Vec<serde_json::Value> {
+    if let Some(content) = payload.pointer("/message/content").and_then(|c| c.as_array()) {
+        return content.clone();
+    }
+    match payload.get("parts") {
+        Some(serde_json::Value::String(parts)) => serde_json::from_str(parts).unwrap_or_default(),
+        Some(serde_json::Value::Array(parts)) => parts.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// An OpenCode `tool` part, which carries the call and its outcome together
+fn opencode_tool_part(event: &EventRow, payload: &serde_json::Value) -> Option<ToolCallRecord> {
+    if payload.get("type").and_then(|t| t.as_str()) != Some("tool") {
+        return None;
+    }
+    let state = payload.get("state");
+    let status = state.and_then(|s| str_field(s, "status")).unwrap_or_default();
+    let start = state.and_then(|s| s.pointer("/time/start")).and_then(|t| t.as_i64());
+    let end = state.and_then(|s| s.pointer("/time/end")).and_then(|t| t.as_i64());
+    let finished = matches!(status, "completed" | "error");
+
+    Some(ToolCallRecord {
+        id: event.id.clone(),
+        event_id: event.id.clone(),
+        tool_name: str_field(payload, "tool").unwrap_or("unknown").to_string(),
+        started_at: start
+            .and_then(millis_to_rfc3339)
+            .unwrap_or_else(|| event.timestamp.clone()),
+        completed_at: end.and_then(millis_to_rfc3339),
+        duration_ms: start.zip(end).map(|(start, end)| (end - start).max(0)),
+        success: finished.then_some(status == "completed"),
+        error_message: state
+            .and_then(|s| str_field(s, "error"))
+            .filter(|_| status == "error")
+            .map(str::to_string),
+    })
+}
+
+fn str_field<'a>(value: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(|v| v.as_str())
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn millis_to_rfc3339(ms: i64) -> Option<String> {
+    DateTime::from_timestamp_millis(ms).map(|t| t.to_rfc3339())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, timestamp: &str, raw_payload: serde_json::Value) -> EventRow {
+        EventRow {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            kind: "message".to_string(),
+            role: None,
+            content: None,
+            timestamp: timestamp.to_string(),
+            raw_payload: raw_payload.to_string(),
+            category: None,
+            parent_id: None,
+        }
+    }
+
+    #[test]
+    fn test_claude_tool_use_pairs_with_tool_result() {
+        let events = vec![
+            event(
+                "e1",
+                "2025-01-01T10:00:00Z",
+                serde_json::json!({"type": "assistant", "message": {"role": "assistant", "content": [
+                    {"type": "text", "text": "Reading both."},
+                    {"type": "tool_use", "id": "toolu_1", "name": "Read", "input": {}},
+                    {"type": "tool_use", "id": "toolu_2", "name": "Bash", "input": {}}
+                ]}}),
+            ),
+            event(
+                "e2",
+                "2025-01-01T10:00:02.500Z",
+                serde_json::json!({"type": "user", "message": {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_2", "content": "boom", "is_error": true},
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "ok"}
+                ]}}),
+            ),
+        ];
+
+        let calls = extract_tool_calls(&events);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].tool_name, "Read");
+        assert_eq!(calls[0].duration_ms, Some(2500));
+        assert_eq!(calls[0].success, Some(true));
+        assert_eq!(calls[1].tool_name, "Bash");
+        assert_eq!(calls[1].success, Some(false));
+        assert_eq!(calls[1].completed_at.as_deref(), Some("2025-01-01T10:00:02.500Z"));
+        assert_ne!(calls[0].id, calls[1].id);
+    }
+
+    #[test]
+    fn test_codex_function_call_pairs_by_call_id_and_keeps_outcome() {
+        let events = vec![
+            event(
+                "e1",
+                "2025-01-01T10:00:00Z",
+                serde_json::json!({"type": "response_item", "payload": {
+                    "type": "function_call", "name": "shell", "call_id": "call_1", "arguments": "{}"
+                }}),
+            ),
+            event(
+                "e2",
+                "2025-01-01T10:00:05Z",
+                serde_json::json!({
+                    "type": "response_item",
+                    "payload": {"type": "function_call_output", "call_id": "call_1", "output": "exit 1"},
+                    "tool_outcome": ToolOutcome::from_exit_code(1, Some(4200)),
+                }),
+            ),
+        ];
+
+        let calls = extract_tool_calls(&events);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].tool_name, "shell");
+        assert_eq!(calls[0].event_id, "e1");
+        assert_eq!(calls[0].duration_ms, Some(4200));
+        assert_eq!(calls[0].success, Some(false));
+        assert_eq!(calls[0].error_message.as_deref(), Some("exit code 1"));
+    }
+
+    #[test]
+    fn test_crush_parts_pair_tool_use_with_tool_result() {
+        let parts = |parts: serde_json::Value| serde_json::json!({"role": "assistant", "parts": parts.to_string()});
+        let events = vec![
+            event(
+                "e1",
+                "2025-01-01T10:00:00Z",
+                parts(serde_json::json!([{"type": "tool_use", "data": {"id": "tc_1", "name": "ls", "input": "{}"}}])),
+            ),
+            event(
+                "e2",
+                "2025-01-01T10:00:01Z",
+                parts(serde_json::json!([
+                    {"type": "tool_result", "data": {"tool_use_id": "tc_1", "content": "src", "is_error": false}}
+                ])),
+            ),
+            event(
+                "e3",
+                "2025-01-01T10:00:02Z",
+                parts(serde_json::json!([{"type": "tool_use", "data": {"id": "tc_2", "name": "view", "input": "{}"}}])),
+            ),
+        ];
+
+        let calls = extract_tool_calls(&events);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].tool_name, "ls");
+        assert_eq!(calls[0].duration_ms, Some(1000));
+        assert_eq!(calls[0].success, Some(true));
+        assert_eq!(calls[1].tool_name, "view");
+        assert_eq!(calls[1].completed_at, None);
+        assert_eq!(calls[1].success, None);
+    }
+
+    #[test]
+    fn test_opencode_tool_part_is_timed_from_its_state() {
+        let events = vec![event(
+            "e1",
+            "2024-01-01T00:00:00Z",
+            serde_json::json!({
+                "type": "tool", "tool": "bash", "callID": "call_1",
+                "state": {"status": "error", "error": "command not found", "time": {"start": 1704067200000i64, "end": 1704067203250i64}}
+            }),
+        )];
+
+        let calls = extract_tool_calls(&events);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].tool_name, "bash");
+        assert_eq!(calls[0].duration_ms, Some(3250));
+        assert_eq!(calls[0].success, Some(false));
+        assert_eq!(calls[0].error_message.as_deref(), Some("command not found"));
+    }
+}